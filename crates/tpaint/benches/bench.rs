@@ -1,11 +1,21 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use criterion::black_box;
 use criterion::{criterion_group, criterion_main, Criterion};
 use dioxus::prelude::*;
-use tpaint::DomEventLoop;
-use tpaint::{prelude::*, RendererDescriptor};
-use winit::{event_loop::EventLoopBuilder, window::WindowBuilder};
+use tpaint::prelude::*;
+use tpaint::host::{HeadlessHost, PhysicalSize};
+use tpaint::{DomEventLoop, RendererDescriptor, StyleState, Tailwind};
+
+/// Class strings like the ones in ``example_ui``, from a bare container to a styled button with variants
+const CLASSES: &[&str] = &[
+    "flex-col w-full h-full",
+    "flex-row items-center justify-between gap-x-10 px-20 py-10",
+    "w-full p-10 bg-blue-900 rounded-md border border-slate-400 text-white text-sm",
+    "grid grid-cols-3 gap-4 overflow-y-scroll scrollbar-thin scrollbar-thumb-slate-500",
+    "px-12 py-6 rounded-lg bg-slate-800 hover:bg-slate-700 active:bg-slate-900 focus:border-blue-500 disabled:bg-slate-500 transition-layout duration-200",
+];
 
 fn app(cx: Scope) -> Element {
     render! {
@@ -21,54 +31,119 @@ fn app(cx: Scope) -> Element {
     }
 }
 
+/// 10k rows with two cells each, mounting it takes well over 10k mutations
+fn rows_app(cx: Scope) -> Element {
+    render! {
+      view {
+        class: "flex-col w-full overflow-y-scroll",
+
+        (0..10_000).map(|i| rsx! {
+          view {
+            key: "{i}",
+            class: "flex-row w-full h-20 gap-x-10 bg-slate-200",
+            view { class: "w-50", "{i}" }
+            view { class: "w-full", "row" }
+          }
+        })
+      }
+    }
+}
+
+/// 1250 cards of four nodes each, with borders, rounded corners and text to tessellate
+fn cards_app(cx: Scope) -> Element {
+    render! {
+      view {
+        class: "flex-row flex-wrap w-full gap-10 p-10 overflow-y-scroll",
+
+        (0..1_250).map(|i| rsx! {
+          view {
+            key: "{i}",
+            class: "flex-col w-150 p-10 gap-y-5 rounded-md border border-slate-400 bg-white",
+            view { class: "w-full h-40 rounded-sm bg-blue-500" }
+            view { class: "text-sm text-slate-900", "card {i}" }
+          }
+        })
+      }
+    }
+}
+
+/// Replaces the dom with ``app``, so every benchmark starts from the same tree
+fn mount(event_loop: &mut DomEventLoop, app: fn(Scope) -> Element) {
+    event_loop.dom.reset();
+    let mut vdom = VirtualDom::new(app);
+    event_loop.dom.apply_mutations(vdom.rebuild());
+}
+
 pub fn run_calculate_layout(app: &mut DomEventLoop) {
-    let mut dom = app.dom.lock().unwrap();
-    app.renderer.calculate_layout(&mut dom);
+    app.renderer.calculate_layout(&mut app.dom);
+}
+
+/// Layout of the whole tree, not just what changed since the last frame
+pub fn run_full_layout(app: &mut DomEventLoop) {
+    app.dom.invalidate_all_layouts();
+    app.renderer.calculate_layout(&mut app.dom);
 }
 
 pub fn run_paint_info(app: &mut DomEventLoop) {
-    let mut dom = app.dom.lock().unwrap();
-    let _ = app.renderer.get_paint_info(&mut dom);
+    let (primitives, _, _) = app.renderer.get_paint_info(&mut app.dom);
+    black_box(primitives);
 }
 
-pub fn apply_mutations(event_loop: &mut DomEventLoop) {
-    let mut dom = event_loop.dom.lock().unwrap();
-    let mut vdom = VirtualDom::new(app);
-    dom.apply_mutations(vdom.rebuild());
+pub fn parse_classes() {
+    for class in CLASSES {
+        let mut tailwind = Tailwind::default();
+        black_box(tailwind.set_styling(black_box(class), &StyleState::default()));
+    }
 }
 
 pub fn criterion_benchmark(c: &mut Criterion) {
-    let event_loop = EventLoopBuilder::with_user_event().build().unwrap();
-    let window = Arc::new(
-        WindowBuilder::new()
-            .with_inner_size(winit::dpi::LogicalSize::new(800, 600))
-            .build(&event_loop)
-            .unwrap(),
-    );
-
-    let mut event_loop = DomEventLoop::spawn(
+    // no window and no VirtualDom thread, so nothing else writes to the dom while measuring
+    let size = PhysicalSize::new(800, 600);
+    let mut event_loop = DomEventLoop::new_single_threaded(
         app,
-        window.clone(),
+        Arc::new(HeadlessHost::new(size)),
         RendererDescriptor {
             font_definitions: Default::default(),
-            pixels_per_point: window.scale_factor() as f32,
-            window_size: window.inner_size(),
+            pixels_per_point: 1.0,
+            window_size: size,
         },
-        event_loop.create_proxy(),
-        (),
         (),
     );
 
+    c.bench_function("tailwind_parse", |b| b.iter(parse_classes));
+
+    mount(&mut event_loop, app);
     c.bench_function("calculate_layout", |b| {
         b.iter(|| run_calculate_layout(black_box(&mut event_loop)))
     });
-
     c.bench_function("get_paint_info", |b| {
         b.iter(|| run_paint_info(black_box(&mut event_loop)))
     });
 
-    c.bench_function("apply_mutations", |b| {
-        b.iter(|| apply_mutations(black_box(&mut event_loop)))
+    mount(&mut event_loop, cards_app);
+    run_calculate_layout(&mut event_loop);
+    c.bench_function("full_layout_5k_nodes", |b| {
+        b.iter(|| run_full_layout(black_box(&mut event_loop)))
+    });
+    c.bench_function("tessellate_5k_nodes", |b| {
+        b.iter(|| run_paint_info(black_box(&mut event_loop)))
+    });
+
+    // only the mutations are timed, building them with the VirtualDom is left out
+    c.bench_function("apply_10k_mutations", |b| {
+        b.iter_custom(|iters| {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                let dom = &mut event_loop.dom;
+                dom.reset();
+                let mut vdom = VirtualDom::new(rows_app);
+                let mutations = vdom.rebuild();
+                let start = Instant::now();
+                dom.apply_mutations(mutations);
+                total += start.elapsed();
+            }
+            total
+        })
     });
 }
 
@@ -0,0 +1,200 @@
+#![no_main]
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use arbitrary::Arbitrary;
+use dioxus::core::{
+    BorrowedAttributeValue, ElementId, Mutation, Mutations, Template, TemplateAttribute,
+    TemplateNode,
+};
+use libfuzzer_sys::fuzz_target;
+use tpaint::host::{HeadlessHost, PhysicalSize};
+use tpaint::{prelude::*, DomEventLoop, RendererDescriptor};
+
+/// Ids are kept small so most edits refer to nodes that were created before them
+const MAX_ID: u8 = 16;
+/// Same as the most nodes an edit moves off the stack in practice
+const MAX_M: u8 = 4;
+
+const TEMPLATES: &[Template<'static>] = &[
+    Template {
+        name: "fuzz:0",
+        roots: &[TemplateNode::Element {
+            tag: "view",
+            namespace: None,
+            attrs: &[
+                TemplateAttribute::Static { name: "class", value: "flex-col p-4", namespace: None },
+                TemplateAttribute::Dynamic { id: 0 },
+            ],
+            children: &[TemplateNode::Dynamic { id: 0 }, TemplateNode::DynamicText { id: 1 }],
+        }],
+        node_paths: &[&[0, 0], &[0, 1]],
+        attr_paths: &[&[0]],
+    },
+    Template {
+        name: "fuzz:1",
+        roots: &[
+            TemplateNode::Text { text: "static" },
+            TemplateNode::Element {
+                tag: "input",
+                namespace: None,
+                attrs: &[TemplateAttribute::Static { name: "id", value: "field", namespace: None }],
+                children: &[],
+            },
+        ],
+        node_paths: &[],
+        attr_paths: &[],
+    },
+    Template {
+        name: "fuzz:2",
+        roots: &[TemplateNode::Dynamic { id: 0 }],
+        node_paths: &[&[0]],
+        attr_paths: &[],
+    },
+];
+
+const PATHS: &[&[u8]] = &[&[0], &[0, 0], &[0, 1], &[1], &[2, 5]];
+
+const ATTRIBUTES: &[&str] = &[
+    "class", "id", "style", "src", "value", "disabled", "tabindex", "role", "portal", "dir",
+    "draggable", "drag_data", "text_decorations", "text_cursor", "is_active", "bg-image",
+    "border_image", "anchor", "placement",
+];
+
+const EVENTS: &[&str] = &["click", "mousedown", "input", "keydown", "scroll", "mounted"];
+
+#[derive(Arbitrary, Debug)]
+enum Value {
+    Text(String),
+    Float(f64),
+    Int(i64),
+    Bool(bool),
+    None,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Edit {
+    AppendChildren { id: u8, m: u8 },
+    AssignId { path: u8, id: u8 },
+    CreatePlaceholder { id: u8 },
+    CreateTextNode { value: String, id: u8 },
+    HydrateText { path: u8, value: String, id: u8 },
+    LoadTemplate { template: u8, index: u8, id: u8 },
+    ReplaceWith { id: u8, m: u8 },
+    ReplacePlaceholder { path: u8, m: u8 },
+    InsertAfter { id: u8, m: u8 },
+    InsertBefore { id: u8, m: u8 },
+    SetAttribute { name: u8, value: Value, id: u8 },
+    SetText { value: String, id: u8 },
+    NewEventListener { name: u8, id: u8 },
+    RemoveEventListener { name: u8, id: u8 },
+    Remove { id: u8 },
+    PushRoot { id: u8 },
+}
+
+fn id(id: u8) -> ElementId {
+    ElementId((id % MAX_ID) as usize)
+}
+
+fn m(m: u8) -> usize {
+    (m % MAX_M) as usize
+}
+
+fn pick<T: Copy>(options: &[T], index: u8) -> T {
+    options[index as usize % options.len()]
+}
+
+impl Edit {
+    fn to_mutation(&self) -> Mutation<'_> {
+        match self {
+            Edit::AppendChildren { id: i, m: n } => Mutation::AppendChildren { id: id(*i), m: m(*n) },
+            Edit::AssignId { path, id: i } => Mutation::AssignId { path: pick(PATHS, *path), id: id(*i) },
+            Edit::CreatePlaceholder { id: i } => Mutation::CreatePlaceholder { id: id(*i) },
+            Edit::CreateTextNode { value, id: i } => Mutation::CreateTextNode { value, id: id(*i) },
+            Edit::HydrateText { path, value, id: i } => Mutation::HydrateText {
+                path: pick(PATHS, *path),
+                value,
+                id: id(*i),
+            },
+            Edit::LoadTemplate { template, index, id: i } => {
+                let template = pick(TEMPLATES, *template);
+                Mutation::LoadTemplate {
+                    name: template.name,
+                    index: *index as usize % template.roots.len(),
+                    id: id(*i),
+                }
+            }
+            Edit::ReplaceWith { id: i, m: n } => Mutation::ReplaceWith { id: id(*i), m: m(*n) },
+            Edit::ReplacePlaceholder { path, m: n } => Mutation::ReplacePlaceholder {
+                path: pick(PATHS, *path),
+                m: m(*n),
+            },
+            Edit::InsertAfter { id: i, m: n } => Mutation::InsertAfter { id: id(*i), m: m(*n) },
+            Edit::InsertBefore { id: i, m: n } => Mutation::InsertBefore { id: id(*i), m: m(*n) },
+            Edit::SetAttribute { name, value, id: i } => Mutation::SetAttribute {
+                name: pick(ATTRIBUTES, *name),
+                value: match value {
+                    Value::Text(text) => BorrowedAttributeValue::Text(text),
+                    Value::Float(float) => BorrowedAttributeValue::Float(*float),
+                    Value::Int(int) => BorrowedAttributeValue::Int(*int),
+                    Value::Bool(bool) => BorrowedAttributeValue::Bool(*bool),
+                    Value::None => BorrowedAttributeValue::None,
+                },
+                id: id(*i),
+                ns: None,
+            },
+            Edit::SetText { value, id: i } => Mutation::SetText { value, id: id(*i) },
+            Edit::NewEventListener { name, id: i } => Mutation::NewEventListener {
+                name: pick(EVENTS, *name),
+                id: id(*i),
+            },
+            Edit::RemoveEventListener { name, id: i } => Mutation::RemoveEventListener {
+                name: pick(EVENTS, *name),
+                id: id(*i),
+            },
+            Edit::Remove { id: i } => Mutation::Remove { id: id(*i) },
+            Edit::PushRoot { id: i } => Mutation::PushRoot { id: id(*i) },
+        }
+    }
+}
+
+fn app(_cx: Scope) -> Element {
+    None
+}
+
+thread_local! {
+    /// Created once without a window, the dom is reset for every input
+    static EVENT_LOOP: RefCell<DomEventLoop> = RefCell::new({
+        let size = PhysicalSize::new(800, 600);
+        DomEventLoop::new_single_threaded(
+            app,
+            Arc::new(HeadlessHost::new(size)),
+            RendererDescriptor {
+                font_definitions: Default::default(),
+                pixels_per_point: 1.0,
+                window_size: size,
+            },
+            (),
+        )
+    });
+}
+
+fuzz_target!(|batches: Vec<Vec<Edit>>| {
+    EVENT_LOOP.with(|event_loop| {
+        let event_loop = &mut *event_loop.borrow_mut();
+        let dom = &mut event_loop.dom;
+        dom.reset();
+        for (index, batch) in batches.iter().enumerate() {
+            let mut mutations = Mutations::default();
+            // templates are registered by the first batch that uses them, like the VirtualDom does
+            if index == 0 {
+                mutations.templates.extend_from_slice(TEMPLATES);
+            }
+            mutations.edits = batch.iter().map(Edit::to_mutation).collect();
+            dom.apply_mutations(mutations);
+        }
+        // styling, layout and painting of whatever tree came out of it
+        event_loop.renderer.get_paint_info(dom);
+    });
+});
@@ -0,0 +1,31 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use tpaint::{StyleState, Tailwind};
+
+#[derive(Arbitrary, Debug)]
+struct Input<'a> {
+    class: &'a str,
+    inline_style: &'a str,
+    hovered: bool,
+    focused: bool,
+    active: bool,
+    disabled: bool,
+    group_hovered: bool,
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let state = StyleState {
+        hovered: input.hovered,
+        focused: input.focused,
+        active: input.active,
+        disabled: input.disabled,
+        group_hovered: input.group_hovered,
+    };
+    let mut tailwind = Tailwind::default();
+    let mut style = tailwind.get_style(input.class, &state);
+    tailwind.apply_inline_style(input.inline_style, &mut style);
+    // parsing again starts from the styling of the first pass
+    tailwind.set_styling(input.class, &state);
+});
@@ -0,0 +1,83 @@
+//! Places a floating node next to an anchor node, the base for tooltips, selects and dropdowns.
+//! A node with the ``anchor`` attribute is moved after every layout so it sits on the ``placement`` side of the node
+//! with that ``id``, flipped to the other side when it doesn't fit and shifted to stay inside the window.
+
+use epaint::{pos2, Pos2, Rect, Vec2};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Placement {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Placement {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "top" => Some(Self::Top),
+            "bottom" => Some(Self::Bottom),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    pub fn flipped(self) -> Self {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Bottom => Self::Top,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    /// Centered on the side of the anchor, ``gap`` points away from it
+    fn position(self, anchor: Rect, size: Vec2, gap: f32) -> Pos2 {
+        match self {
+            Self::Top => pos2(anchor.center().x - size.x / 2.0, anchor.min.y - gap - size.y),
+            Self::Bottom => pos2(anchor.center().x - size.x / 2.0, anchor.max.y + gap),
+            Self::Left => pos2(anchor.min.x - gap - size.x, anchor.center().y - size.y / 2.0),
+            Self::Right => pos2(anchor.max.x + gap, anchor.center().y - size.y / 2.0),
+        }
+    }
+
+    /// How far the rect sticks out of the viewport on the side it was placed
+    fn overflow(self, rect: Rect, viewport: Rect) -> f32 {
+        let overflow = match self {
+            Self::Top => viewport.min.y - rect.min.y,
+            Self::Bottom => rect.max.y - viewport.max.y,
+            Self::Left => viewport.min.x - rect.min.x,
+            Self::Right => rect.max.x - viewport.max.x,
+        };
+        overflow.max(0.0)
+    }
+}
+
+/// Where a floating rect of ``size`` goes next to ``anchor``, and the side it ended up on after flipping
+pub fn place(anchor: Rect, size: Vec2, placement: Placement, gap: f32, viewport: Rect) -> (Pos2, Placement) {
+    let overflow = |placement: Placement| {
+        placement.overflow(
+            Rect::from_min_size(placement.position(anchor, size, gap), size),
+            viewport,
+        )
+    };
+    let placement = if overflow(placement) > 0.0 && overflow(placement.flipped()) < overflow(placement) {
+        placement.flipped()
+    } else {
+        placement
+    };
+
+    // shift along the side of the anchor, the start edge wins when it doesn't fit at all
+    let mut min = placement.position(anchor, size, gap);
+    match placement {
+        Placement::Top | Placement::Bottom => {
+            min.x = min.x.min(viewport.max.x - size.x).max(viewport.min.x);
+        }
+        Placement::Left | Placement::Right => {
+            min.y = min.y.min(viewport.max.y - size.y).max(viewport.min.y);
+        }
+    }
+    (min, placement)
+}
@@ -0,0 +1,264 @@
+use epaint::{
+    textures::TextureOptions, Color32, ColorImage, ImageData, ImageDelta, Rect, TextureId, TextureManager,
+};
+
+/// Width and height of a page in pixels
+pub const ATLAS_PAGE_SIZE: usize = 1024;
+/// Images up to this size in both directions are packed into a page, bigger ones get a texture of their own
+pub const ATLAS_MAX_SIDE: usize = 128;
+/// Transparent pixels around every region, so linear filtering doesn't bleed in the neighbours
+const PADDING: usize = 1;
+
+/// Where an image ended up in the atlas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRegion {
+    pub page: TextureId,
+    /// The part of the page the image covers, normalized
+    pub uv: Rect,
+}
+
+impl AtlasRegion {
+    /// What goes in the ``src`` attribute to show the region
+    pub fn src(&self) -> String {
+        let id = match self.page {
+            TextureId::Managed(id) | TextureId::User(id) => id,
+        };
+        let Rect { min, max } = self.uv;
+        format!("texture://{id}#{},{},{},{}", min.x, min.y, max.x, max.y)
+    }
+}
+
+/// How full the atlas is, to tune ``ATLAS_MAX_SIDE`` against the number of pages
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AtlasStats {
+    pub pages: usize,
+    pub regions: usize,
+    /// Pixels taken by regions, padding included
+    pub used_pixels: usize,
+    pub capacity_pixels: usize,
+}
+
+impl AtlasStats {
+    /// Share of the pages that is taken, from 0 to 1
+    pub fn fill_ratio(&self) -> f32 {
+        if self.capacity_pixels == 0 {
+            return 0.0;
+        }
+        self.used_pixels as f32 / self.capacity_pixels as f32
+    }
+}
+
+/// A row of regions with the height of the tallest one that started it
+struct Shelf {
+    y: usize,
+    height: usize,
+    next_x: usize,
+    /// Spans of freed regions as their x and width, sorted and merged with their neighbours
+    free: Vec<[usize; 2]>,
+}
+
+struct Page {
+    texture_id: TextureId,
+    shelves: Vec<Shelf>,
+    next_y: usize,
+    regions: usize,
+    used_pixels: usize,
+}
+
+impl Page {
+    /// The top left of a free spot of ``width`` by ``height``, padding included
+    fn allocate(&mut self, width: usize, height: usize) -> Option<[usize; 2]> {
+        // freed spans first, on the lowest shelf that has one that fits
+        let freed = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height)
+            .filter_map(|shelf| Some((shelf.free.iter().position(|[_, free_width]| *free_width >= width)?, shelf)))
+            .min_by_key(|(_, shelf)| shelf.height);
+        if let Some((span, shelf)) = freed {
+            let [x, free_width] = shelf.free[span];
+            if free_width == width {
+                shelf.free.remove(span);
+            } else {
+                shelf.free[span] = [x + width, free_width - width];
+            }
+            self.regions += 1;
+            self.used_pixels += width * height;
+            return Some([x, shelf.y]);
+        }
+
+        // the lowest shelf that fits wastes the least space
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && ATLAS_PAGE_SIZE - shelf.next_x >= width)
+            .min_by_key(|shelf| shelf.height);
+        let position = match shelf {
+            Some(shelf) => {
+                let position = [shelf.next_x, shelf.y];
+                shelf.next_x += width;
+                position
+            }
+            None if ATLAS_PAGE_SIZE - self.next_y >= height => {
+                let position = [0, self.next_y];
+                self.shelves.push(Shelf {
+                    y: self.next_y,
+                    height,
+                    next_x: width,
+                    free: vec![],
+                });
+                self.next_y += height;
+                position
+            }
+            None => return None,
+        };
+        self.regions += 1;
+        self.used_pixels += width * height;
+        Some(position)
+    }
+
+    /// Gives the spot at ``position`` back to its shelf, padding included
+    fn free(&mut self, [x, y]: [usize; 2], width: usize, height: usize) {
+        self.regions = self.regions.saturating_sub(1);
+        self.used_pixels = self.used_pixels.saturating_sub(width * height);
+        let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == y) else {
+            return;
+        };
+
+        let index = shelf.free.partition_point(|[free_x, _]| *free_x < x);
+        shelf.free.insert(index, [x, width]);
+        // merge with the spans right after and before it
+        if index + 1 < shelf.free.len() && x + width == shelf.free[index + 1][0] {
+            let [_, next_width] = shelf.free.remove(index + 1);
+            shelf.free[index][1] += next_width;
+        }
+        if index > 0 && shelf.free[index - 1][0] + shelf.free[index - 1][1] == x {
+            let [_, width] = shelf.free.remove(index);
+            shelf.free[index - 1][1] += width;
+        }
+        // a span at the end of the shelf goes back to the space after it
+        if let Some([x, width]) = shelf.free.last().copied() {
+            if x + width == shelf.next_x {
+                shelf.next_x = x;
+                shelf.free.pop();
+            }
+        }
+    }
+}
+
+/// Packs small images like icons into shared textures, so drawing many of them doesn't need a texture each.
+/// Regions are placed on shelves and never move. The spot of a freed region is reused by images that fit in it,
+/// a page is freed once every region on it is
+#[derive(Default)]
+pub struct TextureAtlas {
+    pages: Vec<Page>,
+}
+
+impl TextureAtlas {
+    /// ``None`` when the image is bigger than ``ATLAS_MAX_SIDE``, it should get a texture of its own then
+    pub fn alloc(&mut self, image: &ColorImage, texture_manager: &mut TextureManager) -> Option<AtlasRegion> {
+        let [width, height] = image.size;
+        if width > ATLAS_MAX_SIDE || height > ATLAS_MAX_SIDE || width == 0 || height == 0 {
+            return None;
+        }
+        let (padded_width, padded_height) = (width + PADDING * 2, height + PADDING * 2);
+
+        let found = self
+            .pages
+            .iter_mut()
+            .find_map(|page| Some((page.texture_id, page.allocate(padded_width, padded_height)?)));
+        let (page, [x, y]) = match found {
+            Some(found) => found,
+            None => {
+                let texture_id = texture_manager.alloc(
+                    "atlas".into(),
+                    ImageData::Color(
+                        ColorImage::new([ATLAS_PAGE_SIZE, ATLAS_PAGE_SIZE], Color32::TRANSPARENT).into(),
+                    ),
+                    TextureOptions::LINEAR,
+                );
+                let mut page = Page {
+                    texture_id,
+                    shelves: vec![],
+                    next_y: 0,
+                    regions: 0,
+                    used_pixels: 0,
+                };
+                let position = page.allocate(padded_width, padded_height).unwrap();
+                self.pages.push(page);
+                (texture_id, position)
+            }
+        };
+
+        let (x, y) = (x + PADDING, y + PADDING);
+        texture_manager.set(page, ImageDelta::partial([x, y], image.clone(), TextureOptions::LINEAR));
+
+        let size = ATLAS_PAGE_SIZE as f32;
+        Some(AtlasRegion {
+            page,
+            uv: Rect::from_min_max(
+                epaint::pos2(x as f32 / size, y as f32 / size),
+                epaint::pos2((x + width) as f32 / size, (y + height) as f32 / size),
+            ),
+        })
+    }
+
+    /// The spot of the region can be taken by the next image that fits, its page is freed along with the last region on it
+    pub fn free(&mut self, region: &AtlasRegion, texture_manager: &mut TextureManager) {
+        let Some(index) = self.pages.iter().position(|page| page.texture_id == region.page) else {
+            return;
+        };
+        let size = ATLAS_PAGE_SIZE as f32;
+        let [x, y] = [region.uv.min.x, region.uv.min.y].map(|min| (min * size).round() as usize - PADDING);
+        let [width, height] = [region.uv.width(), region.uv.height()].map(|side| (side * size).round() as usize + PADDING * 2);
+
+        let page = &mut self.pages[index];
+        page.free([x, y], width, height);
+        if page.regions == 0 {
+            texture_manager.free(page.texture_id);
+            self.pages.remove(index);
+            return;
+        }
+        // the next image might be smaller, what's left of this one would show up in its padding
+        texture_manager.set(
+            page.texture_id,
+            ImageDelta::partial([x, y], ColorImage::new([width, height], Color32::TRANSPARENT), TextureOptions::LINEAR),
+        );
+    }
+
+    pub fn stats(&self) -> AtlasStats {
+        AtlasStats {
+            pages: self.pages.len(),
+            regions: self.pages.iter().map(|page| page.regions).sum(),
+            used_pixels: self.pages.iter().map(|page| page.used_pixels).sum(),
+            capacity_pixels: self.pages.len() * ATLAS_PAGE_SIZE * ATLAS_PAGE_SIZE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freed_region_is_reused() {
+        let mut atlas = TextureAtlas::default();
+        let mut texture_manager = TextureManager::default();
+        let icon = ColorImage::new([32, 32], Color32::WHITE);
+
+        let first = atlas.alloc(&icon, &mut texture_manager).unwrap();
+        let second = atlas.alloc(&icon, &mut texture_manager).unwrap();
+        let used_pixels = atlas.stats().used_pixels;
+
+        atlas.free(&first, &mut texture_manager);
+        assert_eq!(atlas.stats().used_pixels, used_pixels / 2);
+        let smaller = atlas.alloc(&ColorImage::new([16, 16], Color32::WHITE), &mut texture_manager).unwrap();
+        assert_eq!(smaller.uv.min, first.uv.min);
+
+        // the last region on the shelf gives its space back, the page stays for ``smaller``
+        atlas.free(&second, &mut texture_manager);
+        let third = atlas.alloc(&icon, &mut texture_manager).unwrap();
+        assert_eq!(atlas.stats().pages, 1);
+        assert_eq!(third.uv.min.y, first.uv.min.y);
+    }
+}
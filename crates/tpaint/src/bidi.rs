@@ -0,0 +1,307 @@
+//! Bidirectional text. epaint lays glyphs out left to right in the order of the string, afterwards every row is put
+//! in its visual order with the unicode bidi algorithm, and right aligned in right-to-left paragraphs.
+//! The glyphs stay in the order of the string, only their positions change, so cursors keep indexing into the text.
+//! Shaping is up to a [`TextShaper`], without one arabic letters are drawn in their isolated forms.
+
+use std::{borrow::Cow, ops::Range, sync::Arc};
+
+use epaint::{
+    pos2,
+    text::cursor::{Cursor, RCursor},
+    vec2, Galley, Rect, Vec2,
+};
+use unicode_bidi::{bidi_class, BidiClass, BidiInfo, Level};
+
+/// The ``dir`` attribute, inherited by the children of the node
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TextDirection {
+    /// Goes by the first strong character of the text
+    #[default]
+    Auto,
+    Ltr,
+    /// Also mirrors ``flex-row``
+    Rtl,
+}
+
+impl TextDirection {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(Self::Auto),
+            "ltr" => Some(Self::Ltr),
+            "rtl" => Some(Self::Rtl),
+            _ => None,
+        }
+    }
+}
+
+/// Swaps characters for the forms they take next to their neighbours before the text is laid out, e.g. the joined
+/// forms of arabic letters from the presentation form blocks, worked out with ``cosmic-text`` or ``rustybuzz``.
+/// epaint draws a glyph per char, so the shaped text has to have as many chars as the text, cursors and selections
+/// keep indexing into the text. Set it with [`crate::DomEventLoop::set_text_shaper`]
+pub trait TextShaper: Send + Sync + 'static {
+    fn shape<'a>(&self, text: &'a str, direction: TextDirection) -> Cow<'a, str>;
+}
+
+/// The text laid out for a node, shaped text that changed the number of chars is ignored
+pub(crate) fn shape(shaper: Option<&dyn TextShaper>, text: String, direction: TextDirection) -> String {
+    let Some(shaper) = shaper else {
+        return text;
+    };
+    match shaper.shape(&text, direction) {
+        Cow::Borrowed(_) => text,
+        Cow::Owned(shaped) if shaped.chars().count() == text.chars().count() => shaped,
+        Cow::Owned(_) => {
+            log::warn!("The text shaper changed the number of chars of {:?}, it's laid out unshaped", text);
+            text
+        }
+    }
+}
+
+/// Text without right-to-left characters in a left-to-right or auto paragraph looks the same after reordering
+fn needs_reorder(text: &str, direction: TextDirection) -> bool {
+    direction == TextDirection::Rtl
+        || text.chars().any(|c| {
+            matches!(
+                bidi_class(c),
+                BidiClass::R | BidiClass::AL | BidiClass::RLE | BidiClass::RLO | BidiClass::RLI
+            )
+        })
+}
+
+fn paragraph_level(text: &str, direction: TextDirection) -> Level {
+    match direction {
+        TextDirection::Ltr => Level::ltr(),
+        TextDirection::Rtl => Level::rtl(),
+        TextDirection::Auto => BidiInfo::new(text, None)
+            .paragraphs
+            .first()
+            .map(|paragraph| paragraph.level)
+            .unwrap_or_else(Level::ltr),
+    }
+}
+
+/// The indices of ``chars`` in visual order from left to right, and which of them run right to left
+fn visual_order(chars: &[char], level: Level) -> (Vec<usize>, Vec<bool>) {
+    let text: String = chars.iter().collect();
+    let info = BidiInfo::new(&text, Some(level));
+    let starts: Vec<usize> = text.char_indices().map(|(start, _)| start).collect();
+    let char_at = |byte: usize| starts.partition_point(|start| *start < byte);
+
+    let rtl = starts.iter().map(|start| info.levels[*start].is_rtl()).collect();
+    let mut order = Vec::with_capacity(chars.len());
+    for paragraph in &info.paragraphs {
+        let (_, runs) = info.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            let chars = char_at(run.start)..char_at(run.end);
+            if info.levels[run.start].is_rtl() {
+                order.extend(chars.rev());
+            } else {
+                order.extend(chars);
+            }
+        }
+    }
+    (order, rtl)
+}
+
+fn row_direction(galley: &Galley, row: usize, level: Level) -> Vec<bool> {
+    let chars: Vec<char> = galley.rows[row].glyphs.iter().map(|glyph| glyph.chr).collect();
+    visual_order(&chars, level).1
+}
+
+/// Moves the glyphs of every row to their visual position, galleys without right-to-left text are returned as they are
+pub(crate) fn reorder(galley: Arc<Galley>, direction: TextDirection) -> Arc<Galley> {
+    if !needs_reorder(galley.text(), direction) {
+        return galley;
+    }
+    let level = paragraph_level(galley.text(), direction);
+    let mut galley = (*galley).clone();
+    let right = galley.rect.max.x;
+
+    for row in &mut galley.rows {
+        let chars: Vec<char> = row.glyphs.iter().map(|glyph| glyph.chr).collect();
+        let (order, _) = visual_order(&chars, level);
+
+        // every glyph with a texture is a quad in the mesh, in the order of the glyphs
+        let vertices = row.visuals.glyph_vertex_range.clone();
+        let mut next_vertex = vertices.start;
+        let first_vertices: Vec<Option<usize>> = row
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                (!glyph.uv_rect.is_nothing()).then(|| {
+                    next_vertex += 4;
+                    next_vertex - 4
+                })
+            })
+            .collect();
+        if next_vertex != vertices.end {
+            log::error!("Can't reorder a row of text, its mesh doesn't have a quad per glyph");
+            continue;
+        }
+
+        let mut x = row.rect.min.x;
+        for index in order {
+            let glyph = &mut row.glyphs[index];
+            let offset = x - glyph.pos.x;
+            glyph.pos.x = x;
+            x += glyph.size.x;
+            if let Some(first) = first_vertices[index] {
+                for vertex in &mut row.visuals.mesh.vertices[first..first + 4] {
+                    vertex.pos.x += offset;
+                }
+            }
+        }
+
+        if level.is_rtl() {
+            let offset = vec2(right - row.rect.max.x, 0.0);
+            row.rect = row.rect.translate(offset);
+            for glyph in &mut row.glyphs {
+                glyph.pos.x += offset.x;
+            }
+            row.visuals.mesh.translate(offset);
+            row.visuals.mesh_bounds = row.visuals.mesh_bounds.translate(offset);
+        }
+    }
+    galley.mesh_bounds = galley
+        .rows
+        .iter()
+        .fold(Rect::NOTHING, |bounds, row| bounds.union(row.visuals.mesh_bounds));
+
+    Arc::new(galley)
+}
+
+/// The caret sits on the leading edge of the glyph after it, which is the right edge in right-to-left runs
+fn caret_x(galley: &Galley, rtl: &[bool], level: Level, rcursor: RCursor) -> f32 {
+    let row = &galley.rows[rcursor.row];
+    if let Some(glyph) = row.glyphs.get(rcursor.column) {
+        return if rtl[rcursor.column] { glyph.pos.x + glyph.size.x } else { glyph.pos.x };
+    }
+    match row.glyphs.last() {
+        Some(glyph) if rtl[row.glyphs.len() - 1] => glyph.pos.x,
+        Some(glyph) => glyph.pos.x + glyph.size.x,
+        None if level.is_rtl() => row.rect.max.x,
+        None => row.rect.min.x,
+    }
+}
+
+/// Same as [`Galley::pos_from_cursor`], for galleys from [`reorder`]
+pub(crate) fn pos_from_cursor(galley: &Galley, direction: TextDirection, cursor: &Cursor) -> Rect {
+    if !needs_reorder(galley.text(), direction) {
+        return galley.pos_from_cursor(cursor);
+    }
+    let level = paragraph_level(galley.text(), direction);
+    let cursor = galley.from_pcursor(cursor.pcursor);
+    let rtl = row_direction(galley, cursor.rcursor.row, level);
+    let x = caret_x(galley, &rtl, level, cursor.rcursor);
+    let row = &galley.rows[cursor.rcursor.row];
+    Rect::from_min_max(pos2(x, row.min_y()), pos2(x, row.max_y()))
+}
+
+/// Same as [`Galley::cursor_from_pos`], for galleys from [`reorder`]
+pub(crate) fn cursor_from_pos(galley: &Galley, direction: TextDirection, pos: Vec2) -> Cursor {
+    let cursor = galley.cursor_from_pos(pos);
+    if !needs_reorder(galley.text(), direction) {
+        return cursor;
+    }
+    let level = paragraph_level(galley.text(), direction);
+    let row_index = cursor.rcursor.row;
+    let rtl = row_direction(galley, row_index, level);
+    let glyphs = &galley.rows[row_index].glyphs;
+    if glyphs.is_empty() {
+        return cursor;
+    }
+
+    // the glyph under the pointer, or the closest one on the side the pointer is at
+    let hovered = glyphs
+        .iter()
+        .position(|glyph| pos.x >= glyph.pos.x && pos.x < glyph.pos.x + glyph.size.x);
+    let (index, before_center) = match hovered {
+        Some(index) => (index, pos.x < glyphs[index].pos.x + glyphs[index].size.x / 2.0),
+        None => {
+            let leftmost = (0..glyphs.len())
+                .min_by(|a, b| glyphs[*a].pos.x.total_cmp(&glyphs[*b].pos.x))
+                .unwrap();
+            let rightmost = (0..glyphs.len())
+                .max_by(|a, b| glyphs[*a].pos.x.total_cmp(&glyphs[*b].pos.x))
+                .unwrap();
+            if pos.x < glyphs[leftmost].pos.x {
+                (leftmost, true)
+            } else {
+                (rightmost, false)
+            }
+        }
+    };
+    // the half of the glyph that comes first in reading order puts the caret before it
+    let column = if before_center != rtl[index] { index } else { index + 1 };
+
+    galley.from_rcursor(RCursor {
+        row: row_index,
+        column,
+    })
+}
+
+/// The horizontal extents of the selected glyphs of a row, a selection across direction changes can be in pieces.
+/// ``None`` when the galley wasn't reordered and the selection is a single span
+pub(crate) fn selection_spans(
+    galley: &Galley,
+    direction: TextDirection,
+    row: usize,
+    columns: Range<usize>,
+) -> Option<Vec<Range<f32>>> {
+    if !needs_reorder(galley.text(), direction) {
+        return None;
+    }
+    let glyphs = &galley.rows[row].glyphs;
+    let mut spans: Vec<Range<f32>> = glyphs[columns.start.min(glyphs.len())..columns.end.min(glyphs.len())]
+        .iter()
+        .map(|glyph| glyph.pos.x..glyph.pos.x + glyph.size.x)
+        .collect();
+    spans.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let mut merged: Vec<Range<f32>> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end + 0.5 => last.end = last.end.max(span.end),
+            _ => merged.push(span),
+        }
+    }
+    Some(merged)
+}
+
+/// The char index the caret ends up at after moving one step to the left or right on screen, for single line text.
+/// In right-to-left runs that's the opposite of moving through the string
+pub fn move_caret(text: &str, direction: TextDirection, cursor: usize, left: bool) -> usize {
+    let len = text.chars().count();
+    if !needs_reorder(text, direction) {
+        return if left {
+            crate::text::prev_grapheme(text, cursor)
+        } else {
+            crate::text::next_grapheme(text, cursor)
+        };
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let (order, rtl) = visual_order(&chars, paragraph_level(text, direction));
+    let mut slots = vec![0; len];
+    for (slot, index) in order.into_iter().enumerate() {
+        slots[index] = slot;
+    }
+    // every glyph is one unit wide, same rules as for painting the caret
+    let x = |position: usize| {
+        if position < len {
+            slots[position] + usize::from(rtl[position])
+        } else if let Some(last) = len.checked_sub(1) {
+            slots[last] + usize::from(!rtl[last])
+        } else {
+            0
+        }
+    };
+
+    let current = x(cursor.min(len));
+    (0..=len)
+        // never between the chars of a grapheme
+        .filter(|position| crate::text::is_grapheme_boundary(text, *position))
+        .filter(|position| if left { x(*position) < current } else { x(*position) > current })
+        .min_by_key(|position| (x(*position).abs_diff(current), position.abs_diff(cursor)))
+        .unwrap_or(cursor)
+}
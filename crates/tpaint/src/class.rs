@@ -0,0 +1,752 @@
+//! A typed alternative to writing class strings by hand. Every method adds one of the utilities the tailwind parser
+//! understands, so a misspelled utility is a compile error instead of a class that's silently ignored.
+//!
+//! ```ignore
+//! use tpaint::class::{Align, Palette, Shade};
+//! use tpaint::tw;
+//!
+//! let class = tw![flex_col, gap(4.0), items(Align::Center), bg(Palette::Red.shade(Shade::S500))];
+//! rsx! { view { class: "{class}" } }
+//! ```
+
+use std::fmt::{self, Display, Write as _};
+
+pub use crate::tailwind::BackgroundSize;
+
+/// Builds a [`Class`] from a comma separated list of its methods, ``tw![flex_col, p(8.0)]``
+#[macro_export]
+macro_rules! tw {
+    ($($method:ident $(($($arg:expr),* $(,)?))?),* $(,)?) => {
+        $crate::class::Class::new()$(.$method($($($arg),*)?))*
+    };
+}
+
+/// The colors that come with the parser, see [`Palette::shade`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Palette {
+    Slate,
+    Gray,
+    Zinc,
+    Neutral,
+    Stone,
+    Red,
+    Orange,
+    Amber,
+    Yellow,
+    Lime,
+    Green,
+    Emerald,
+    Teal,
+    Cyan,
+    Sky,
+    Blue,
+    Indigo,
+    Violet,
+    Purple,
+    Fuchsia,
+    Pink,
+    Rose,
+}
+
+impl Palette {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Slate => "slate",
+            Self::Gray => "gray",
+            Self::Zinc => "zinc",
+            Self::Neutral => "neutral",
+            Self::Stone => "stone",
+            Self::Red => "red",
+            Self::Orange => "orange",
+            Self::Amber => "amber",
+            Self::Yellow => "yellow",
+            Self::Lime => "lime",
+            Self::Green => "green",
+            Self::Emerald => "emerald",
+            Self::Teal => "teal",
+            Self::Cyan => "cyan",
+            Self::Sky => "sky",
+            Self::Blue => "blue",
+            Self::Indigo => "indigo",
+            Self::Violet => "violet",
+            Self::Purple => "purple",
+            Self::Fuchsia => "fuchsia",
+            Self::Pink => "pink",
+            Self::Rose => "rose",
+        }
+    }
+
+    pub fn shade(self, shade: Shade) -> Color {
+        Color {
+            name: self.name(),
+            shade: Some(shade),
+            opacity: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shade {
+    S50,
+    S100,
+    S200,
+    S300,
+    S400,
+    S500,
+    S600,
+    S700,
+    S800,
+    S900,
+    S950,
+}
+
+impl Shade {
+    fn value(self) -> u16 {
+        match self {
+            Self::S50 => 50,
+            Self::S100 => 100,
+            Self::S200 => 200,
+            Self::S300 => 300,
+            Self::S400 => 400,
+            Self::S500 => 500,
+            Self::S600 => 600,
+            Self::S700 => 700,
+            Self::S800 => 800,
+            Self::S900 => 900,
+            Self::S950 => 950,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Color {
+    name: &'static str,
+    shade: Option<Shade>,
+    opacity: Option<u8>,
+}
+
+impl Color {
+    pub const TRANSPARENT: Self = Self::special("transparent");
+    pub const WHITE: Self = Self::special("white");
+    pub const BLACK: Self = Self::special("black");
+
+    const fn special(name: &'static str) -> Self {
+        Self {
+            name,
+            shade: None,
+            opacity: None,
+        }
+    }
+
+    /// ``red-500/50``, from 0 to 100 percent
+    pub fn opacity(self, percent: u8) -> Self {
+        Self {
+            opacity: Some(percent.min(100)),
+            ..self
+        }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)?;
+        if let Some(shade) = self.shade {
+            write!(f, "-{}", shade.value())?;
+        }
+        if let Some(opacity) = self.opacity {
+            write!(f, "/{opacity}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The values of ``w-*`` and ``h-*``, see [`Length`] for their min and max variants
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Size {
+    Points(f32),
+    /// From 0 to 100
+    Percent(f32),
+    Full,
+    Auto,
+    /// Only for ``w-*`` and ``h-*``, measured from the content before every layout
+    Min,
+    Max,
+    Fit,
+}
+
+impl Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Points(points) => write!(f, "{points}"),
+            Self::Percent(percent) => write!(f, "{percent}%"),
+            Self::Full => f.write_str("full"),
+            Self::Auto => f.write_str("auto"),
+            Self::Min => f.write_str("min"),
+            Self::Max => f.write_str("max"),
+            Self::Fit => f.write_str("fit"),
+        }
+    }
+}
+
+/// The values of ``min-*``, ``max-*`` and ``basis-*``, which have no content sizes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Length {
+    Points(f32),
+    /// From 0 to 100
+    Percent(f32),
+    Full,
+    Auto,
+}
+
+impl Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Size::from(*self).fmt(f)
+    }
+}
+
+impl From<Length> for Size {
+    fn from(length: Length) -> Self {
+        match length {
+            Length::Points(points) => Self::Points(points),
+            Length::Percent(percent) => Self::Percent(percent),
+            Length::Full => Self::Full,
+            Length::Auto => Self::Auto,
+        }
+    }
+}
+
+/// ``justify-*`` and ``place-content-*``
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Justify {
+    Start,
+    End,
+    Center,
+    Between,
+    Around,
+    Evenly,
+    Stretch,
+}
+
+impl Justify {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::End => "end",
+            Self::Center => "center",
+            Self::Between => "between",
+            Self::Around => "around",
+            Self::Evenly => "evenly",
+            Self::Stretch => "stretch",
+        }
+    }
+}
+
+/// ``items-*``, ``self-*``, ``justify-items-*``, ``justify-self-*``, ``place-items-*`` and ``place-self-*``
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    Start,
+    End,
+    Center,
+    Baseline,
+    Stretch,
+}
+
+impl Align {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::End => "end",
+            Self::Center => "center",
+            Self::Baseline => "baseline",
+            Self::Stretch => "stretch",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Overflow {
+    Visible,
+    Hidden,
+    Scroll,
+}
+
+impl Overflow {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Visible => "visible",
+            Self::Hidden => "hidden",
+            Self::Scroll => "scroll",
+        }
+    }
+}
+
+/// A class string built from typed utilities, displays as the string the ``class`` attribute takes
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Class {
+    classes: String,
+}
+
+impl Class {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(mut self, class: impl Display) -> Self {
+        if !self.classes.is_empty() {
+            self.classes.push(' ');
+        }
+        write!(self.classes, "{class}").unwrap();
+        self
+    }
+
+    /// Negative values get the ``-`` prefix, ``-mt-4``
+    fn push_signed(self, prefix: &str, value: f32) -> Self {
+        if value < 0.0 {
+            self.push(format_args!("-{prefix}-{}", -value))
+        } else {
+            self.push(format_args!("{prefix}-{value}"))
+        }
+    }
+
+    fn push_variant(mut self, variant: &str, classes: Class) -> Self {
+        for class in classes.classes.split_whitespace() {
+            self = self.push(format_args!("{variant}:{class}"));
+        }
+        self
+    }
+
+    /// Escape hatch for classes the builder doesn't know about, these aren't checked
+    pub fn raw(self, classes: &str) -> Self {
+        if classes.trim().is_empty() {
+            return self;
+        }
+        self.push(classes.trim())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.classes
+    }
+
+    pub fn hover(self, classes: Class) -> Self {
+        self.push_variant("hover", classes)
+    }
+
+    pub fn focus(self, classes: Class) -> Self {
+        self.push_variant("focus", classes)
+    }
+
+    pub fn active(self, classes: Class) -> Self {
+        self.push_variant("active", classes)
+    }
+
+    pub fn disabled(self, classes: Class) -> Self {
+        self.push_variant("disabled", classes)
+    }
+
+    /// Applies when an ancestor with [`Class::group`] is hovered
+    pub fn group_hover(self, classes: Class) -> Self {
+        self.push_variant("group-hover", classes)
+    }
+
+    pub fn group(self) -> Self {
+        self.push("group")
+    }
+
+    pub fn flex_col(self) -> Self {
+        self.push("flex-col")
+    }
+
+    pub fn flex_row(self) -> Self {
+        self.push("flex-row")
+    }
+
+    pub fn flex_wrap(self) -> Self {
+        self.push("flex-wrap")
+    }
+
+    pub fn flex_wrap_reverse(self) -> Self {
+        self.push("flex-wrap-reverse")
+    }
+
+    pub fn flex_nowrap(self) -> Self {
+        self.push("flex-nowrap")
+    }
+
+    pub fn flex_none(self) -> Self {
+        self.push("flex-none")
+    }
+
+    pub fn grow(self) -> Self {
+        self.push("grow")
+    }
+
+    pub fn grow_0(self) -> Self {
+        self.push("grow-0")
+    }
+
+    pub fn shrink(self) -> Self {
+        self.push("shrink")
+    }
+
+    pub fn shrink_0(self) -> Self {
+        self.push("shrink-0")
+    }
+
+    pub fn basis(self, size: Length) -> Self {
+        self.push(format_args!("basis-{size}"))
+    }
+
+    pub fn grid(self) -> Self {
+        self.push("grid")
+    }
+
+    pub fn grid_cols(self, columns: usize) -> Self {
+        self.push(format_args!("grid-cols-{columns}"))
+    }
+
+    pub fn col_span(self, span: u16) -> Self {
+        self.push(format_args!("col-span-{span}"))
+    }
+
+    pub fn row_span(self, span: u16) -> Self {
+        self.push(format_args!("row-span-{span}"))
+    }
+
+    pub fn w(self, size: Size) -> Self {
+        self.push(format_args!("w-{size}"))
+    }
+
+    pub fn h(self, size: Size) -> Self {
+        self.push(format_args!("h-{size}"))
+    }
+
+    pub fn min_w(self, size: Length) -> Self {
+        self.push(format_args!("min-w-{size}"))
+    }
+
+    pub fn min_h(self, size: Length) -> Self {
+        self.push(format_args!("min-h-{size}"))
+    }
+
+    pub fn max_w(self, size: Length) -> Self {
+        self.push(format_args!("max-w-{size}"))
+    }
+
+    pub fn max_h(self, size: Length) -> Self {
+        self.push(format_args!("max-h-{size}"))
+    }
+
+    pub fn bg(self, color: Color) -> Self {
+        self.push(format_args!("bg-{color}"))
+    }
+
+    pub fn bg_size(self, size: BackgroundSize) -> Self {
+        self.push(match size {
+            BackgroundSize::Auto => "bg-auto",
+            BackgroundSize::Cover => "bg-cover",
+            BackgroundSize::Contain => "bg-contain",
+        })
+    }
+
+    pub fn bg_repeat(self, repeat: bool) -> Self {
+        self.push(if repeat { "bg-repeat" } else { "bg-no-repeat" })
+    }
+
+    pub fn text(self, color: Color) -> Self {
+        self.push(format_args!("text-{color}"))
+    }
+
+    pub fn text_size(self, size: f32) -> Self {
+        self.push(format_args!("text-{size}"))
+    }
+
+    pub fn selection(self, color: Color) -> Self {
+        self.push(format_args!("selection-{color}"))
+    }
+
+    pub fn font_sans(self) -> Self {
+        self.push("font-sans")
+    }
+
+    pub fn font_mono(self) -> Self {
+        self.push("font-mono")
+    }
+
+    pub fn p(self, padding: f32) -> Self {
+        self.push(format_args!("p-{padding}"))
+    }
+
+    pub fn px(self, padding: f32) -> Self {
+        self.push(format_args!("px-{padding}"))
+    }
+
+    pub fn py(self, padding: f32) -> Self {
+        self.push(format_args!("py-{padding}"))
+    }
+
+    pub fn pt(self, padding: f32) -> Self {
+        self.push(format_args!("pt-{padding}"))
+    }
+
+    pub fn pb(self, padding: f32) -> Self {
+        self.push(format_args!("pb-{padding}"))
+    }
+
+    pub fn pl(self, padding: f32) -> Self {
+        self.push(format_args!("pl-{padding}"))
+    }
+
+    pub fn pr(self, padding: f32) -> Self {
+        self.push(format_args!("pr-{padding}"))
+    }
+
+    pub fn m(self, margin: f32) -> Self {
+        self.push_signed("m", margin)
+    }
+
+    pub fn mx(self, margin: f32) -> Self {
+        self.push_signed("mx", margin)
+    }
+
+    pub fn my(self, margin: f32) -> Self {
+        self.push_signed("my", margin)
+    }
+
+    pub fn mt(self, margin: f32) -> Self {
+        self.push_signed("mt", margin)
+    }
+
+    pub fn mb(self, margin: f32) -> Self {
+        self.push_signed("mb", margin)
+    }
+
+    pub fn ml(self, margin: f32) -> Self {
+        self.push_signed("ml", margin)
+    }
+
+    pub fn mr(self, margin: f32) -> Self {
+        self.push_signed("mr", margin)
+    }
+
+    pub fn gap(self, gap: f32) -> Self {
+        self.push(format_args!("gap-{gap}"))
+    }
+
+    pub fn gap_x(self, gap: f32) -> Self {
+        self.push(format_args!("gap-x-{gap}"))
+    }
+
+    pub fn gap_y(self, gap: f32) -> Self {
+        self.push(format_args!("gap-y-{gap}"))
+    }
+
+    pub fn space_x(self, space: f32) -> Self {
+        self.push_signed("space-x", space)
+    }
+
+    pub fn space_y(self, space: f32) -> Self {
+        self.push_signed("space-y", space)
+    }
+
+    pub fn rounded(self, radius: f32) -> Self {
+        self.push(format_args!("rounded-{radius}"))
+    }
+
+    pub fn rounded_t(self, radius: f32) -> Self {
+        self.push(format_args!("rounded-t-{radius}"))
+    }
+
+    pub fn rounded_b(self, radius: f32) -> Self {
+        self.push(format_args!("rounded-b-{radius}"))
+    }
+
+    pub fn rounded_tl(self, radius: f32) -> Self {
+        self.push(format_args!("rounded-tl-{radius}"))
+    }
+
+    pub fn rounded_tr(self, radius: f32) -> Self {
+        self.push(format_args!("rounded-tr-{radius}"))
+    }
+
+    pub fn rounded_bl(self, radius: f32) -> Self {
+        self.push(format_args!("rounded-bl-{radius}"))
+    }
+
+    pub fn rounded_br(self, radius: f32) -> Self {
+        self.push(format_args!("rounded-br-{radius}"))
+    }
+
+    pub fn border(self, width: f32) -> Self {
+        self.push(format_args!("border-{width}"))
+    }
+
+    pub fn border_color(self, color: Color) -> Self {
+        self.push(format_args!("border-{color}"))
+    }
+
+    pub fn justify(self, justify: Justify) -> Self {
+        self.push(format_args!("justify-{}", justify.name()))
+    }
+
+    pub fn justify_items(self, align: Align) -> Self {
+        self.push(format_args!("justify-items-{}", align.name()))
+    }
+
+    pub fn justify_self(self, align: Align) -> Self {
+        self.push(format_args!("justify-self-{}", align.name()))
+    }
+
+    pub fn items(self, align: Align) -> Self {
+        self.push(format_args!("items-{}", align.name()))
+    }
+
+    pub fn align_self(self, align: Align) -> Self {
+        self.push(format_args!("self-{}", align.name()))
+    }
+
+    pub fn place_content(self, justify: Justify) -> Self {
+        self.push(format_args!("place-content-{}", justify.name()))
+    }
+
+    pub fn place_items(self, align: Align) -> Self {
+        self.push(format_args!("place-items-{}", align.name()))
+    }
+
+    pub fn place_self(self, align: Align) -> Self {
+        self.push(format_args!("place-self-{}", align.name()))
+    }
+
+    pub fn order(self, order: i32) -> Self {
+        if order < 0 {
+            self.push(format_args!("-order-{}", -order))
+        } else {
+            self.push(format_args!("order-{order}"))
+        }
+    }
+
+    pub fn aspect_ratio(self, width: f32, height: f32) -> Self {
+        self.push(format_args!("aspect-[{width}/{height}]"))
+    }
+
+    pub fn relative(self) -> Self {
+        self.push("relative")
+    }
+
+    pub fn absolute(self) -> Self {
+        self.push("absolute")
+    }
+
+    pub fn left(self, inset: f32) -> Self {
+        self.push(format_args!("left-{inset}"))
+    }
+
+    pub fn right(self, inset: f32) -> Self {
+        self.push(format_args!("right-{inset}"))
+    }
+
+    pub fn top(self, inset: f32) -> Self {
+        self.push(format_args!("top-{inset}"))
+    }
+
+    pub fn bottom(self, inset: f32) -> Self {
+        self.push(format_args!("bottom-{inset}"))
+    }
+
+    pub fn hidden(self) -> Self {
+        self.push("hidden")
+    }
+
+    pub fn z(self, z_layer: crate::ZLayer) -> Self {
+        self.push(format_args!("z-{}", z_layer.name()))
+    }
+
+    pub fn layer(self) -> Self {
+        self.push("layer")
+    }
+
+    pub fn transition_layout(self) -> Self {
+        self.push("transition-layout")
+    }
+
+    pub fn duration(self, millis: u32) -> Self {
+        self.push(format_args!("duration-{millis}"))
+    }
+
+    pub fn grayscale(self, grayscale: bool) -> Self {
+        self.push(if grayscale { "grayscale" } else { "grayscale-0" })
+    }
+
+    /// In percent, 100 leaves the colors as they are
+    pub fn brightness(self, percent: f32) -> Self {
+        self.push(format_args!("brightness-{percent}"))
+    }
+
+    /// In percent, 100 leaves the colors as they are
+    pub fn saturate(self, percent: f32) -> Self {
+        self.push(format_args!("saturate-{percent}"))
+    }
+
+    pub fn hue_rotate(self, degrees: f32) -> Self {
+        self.push_signed("hue-rotate", degrees)
+    }
+
+    pub fn backdrop_blur(self, radius: f32) -> Self {
+        self.push(format_args!("backdrop-blur-{radius}"))
+    }
+
+    pub fn overflow(self, overflow: Overflow) -> Self {
+        self.push(format_args!("overflow-{}", overflow.name()))
+    }
+
+    pub fn overflow_x(self, overflow: Overflow) -> Self {
+        self.push(format_args!("overflow-x-{}", overflow.name()))
+    }
+
+    pub fn overflow_y(self, overflow: Overflow) -> Self {
+        self.push(format_args!("overflow-y-{}", overflow.name()))
+    }
+
+    pub fn invisible(self) -> Self {
+        self.push("invisible")
+    }
+
+    pub fn visible(self) -> Self {
+        self.push("visible")
+    }
+
+    pub fn pointer_events_none(self) -> Self {
+        self.push("pointer-events-none")
+    }
+
+    pub fn pointer_events_auto(self) -> Self {
+        self.push("pointer-events-auto")
+    }
+
+    pub fn scrollbar_default(self) -> Self {
+        self.push("scrollbar-default")
+    }
+
+    pub fn scrollbar_none(self) -> Self {
+        self.push("scrollbar-none")
+    }
+
+    pub fn scrollbar_bg(self, color: Color) -> Self {
+        self.push(format_args!("scrollbar-bg-{color}"))
+    }
+
+    pub fn scrollbar_thumb_bg(self, color: Color) -> Self {
+        self.push(format_args!("scrollbar-thumb-bg-{color}"))
+    }
+}
+
+impl Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.classes)
+    }
+}
+
+impl From<Class> for String {
+    fn from(class: Class) -> Self {
+        class.classes
+    }
+}
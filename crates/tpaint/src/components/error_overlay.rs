@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+pub(crate) struct ErrorOverlayProps {
+    pub message: String,
+    pub restart: tokio::sync::mpsc::UnboundedSender<()>,
+}
+
+/// Replaces the app after it panicked when using [`crate::PanicPolicy::Restart`]
+pub(crate) fn ErrorOverlay(cx: Scope<ErrorOverlayProps>) -> Element {
+    render! {
+        view {
+            class: "flex-col items-start w-full h-full p-20 gap-y-10 bg-red-900 text-white",
+
+            view {
+                class: "text-24",
+                "The app crashed"
+            }
+
+            view {
+                class: "font-mono",
+                "{cx.props.message}"
+            }
+
+            button {
+                class: "px-20 py-10 rounded-4 bg-white text-black hover:bg-slate-200",
+                onclick: move |_| {
+                    let _ = cx.props.restart.send(());
+                },
+                "Restart app"
+            }
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::{
+    components::{input::Input, theme::use_component_theme},
+    events::{ClickEvent, KeyInput},
+    prelude::*,
+};
+pub use regex::Regex;
+use rustc_hash::FxHashMap;
+use winit::{
+    event::ElementState,
+    keyboard::{Key, NamedKey},
+};
+
+/// A check on the value of a field, the message is shown under it when the check fails
+#[derive(Clone)]
+pub enum Rule {
+    Required(String),
+    /// Empty values pass, combine it with ``Required`` for fields that can't be empty
+    Pattern(Regex, String),
+    /// Gets the value of the field and the values of the whole form, e.g. to compare two passwords
+    Custom(Rc<dyn Fn(&str, &FormValues) -> Result<(), String>>),
+}
+
+impl Rule {
+    pub fn required(message: impl Into<String>) -> Self {
+        Self::Required(message.into())
+    }
+
+    pub fn pattern(regex: Regex, message: impl Into<String>) -> Self {
+        Self::Pattern(regex, message.into())
+    }
+
+    pub fn custom(check: impl Fn(&str, &FormValues) -> Result<(), String> + 'static) -> Self {
+        Self::Custom(Rc::new(check))
+    }
+
+    fn check(&self, value: &str, values: &FormValues) -> Result<(), String> {
+        match self {
+            Self::Required(message) if value.trim().is_empty() => Err(message.clone()),
+            Self::Pattern(regex, message) if !value.is_empty() && !regex.is_match(value) => Err(message.clone()),
+            Self::Custom(check) => check(value, values),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The values of every field by name, what ``onsubmit`` gets
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct FormValues(pub BTreeMap<String, String>);
+
+impl FormValues {
+    /// Empty for fields that were never set
+    pub fn get(&self, name: &str) -> &str {
+        self.0.get(name).map(String::as_str).unwrap_or("")
+    }
+
+    /// ``None`` when the value doesn't parse as ``T``, e.g. ``values.parse::<f64>("age")``
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name).trim().parse().ok()
+    }
+}
+
+#[derive(Default)]
+struct FormState {
+    values: FormValues,
+    rules: BTreeMap<String, Vec<Rule>>,
+    errors: BTreeMap<String, String>,
+    /// Fields that were changed, their errors show right away. The others wait for a submit
+    touched: BTreeSet<String>,
+    submitted: bool,
+    /// A ``SubmitButton`` was pressed, the ``Form`` handles it when it renders
+    submit_requested: bool,
+    /// Scopes that read the form and re-render when it changes
+    subscribers: FxHashMap<ScopeId, Arc<dyn Fn()>>,
+}
+
+/// The state of a form, from [`use_form`]. Pass it to a ``Form``, the fields inside of it find it by themselves
+#[derive(Clone, Default)]
+pub struct UseForm {
+    state: Rc<RefCell<FormState>>,
+}
+
+impl UseForm {
+    pub fn value(&self, name: &str) -> String {
+        self.state.borrow().values.get(name).to_string()
+    }
+
+    pub fn values(&self) -> FormValues {
+        self.state.borrow().values.clone()
+    }
+
+    pub fn set(&self, name: &str, value: impl Into<String>) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.values.0.insert(name.to_string(), value.into());
+            state.touched.insert(name.to_string());
+        }
+        self.validate_field(name);
+        self.notify();
+    }
+
+    /// Adds the rules of a field, fields register themselves when they mount
+    pub fn register(&self, name: &str, rules: Vec<Rule>) {
+        let mut state = self.state.borrow_mut();
+        state.values.0.entry(name.to_string()).or_default();
+        state.rules.insert(name.to_string(), rules);
+    }
+
+    /// The message of the first failing rule of the field, once it was changed or the form was submitted
+    pub fn error(&self, name: &str) -> Option<String> {
+        let state = self.state.borrow();
+        if !state.submitted && !state.touched.contains(name) {
+            return None;
+        }
+        state.errors.get(name).cloned()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.state.borrow().errors.is_empty()
+    }
+
+    /// Checks every field and shows all errors, returns whether the form can be submitted
+    pub fn validate(&self) -> bool {
+        let names: Vec<String> = self.state.borrow().rules.keys().cloned().collect();
+        for name in &names {
+            self.validate_field(name);
+        }
+        self.state.borrow_mut().submitted = true;
+        self.notify();
+        self.is_valid()
+    }
+
+    /// Clears the values and errors, the rules stay
+    pub fn reset(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            for value in state.values.0.values_mut() {
+                value.clear();
+            }
+            state.errors.clear();
+            state.touched.clear();
+            state.submitted = false;
+        }
+        self.notify();
+    }
+
+    /// Lets the surrounding ``Form`` submit, like pressing Enter in one of its fields
+    pub fn request_submit(&self) {
+        self.state.borrow_mut().submit_requested = true;
+        self.notify();
+    }
+
+    fn validate_field(&self, name: &str) {
+        let mut state = self.state.borrow_mut();
+        let value = state.values.get(name);
+        let error = state
+            .rules
+            .get(name)
+            .and_then(|rules| rules.iter().find_map(|rule| rule.check(value, &state.values).err()));
+        match error {
+            Some(error) => state.errors.insert(name.to_string(), error),
+            None => state.errors.remove(name),
+        };
+    }
+
+    fn notify(&self) {
+        let subscribers: Vec<_> = self.state.borrow().subscribers.values().cloned().collect();
+        for update in subscribers {
+            update();
+        }
+    }
+
+    /// Re-renders the scope whenever the form changes, until it unmounts
+    fn subscribe(&self, cx: &ScopeState) {
+        cx.use_hook(|| {
+            self.state
+                .borrow_mut()
+                .subscribers
+                .insert(cx.scope_id(), cx.schedule_update());
+            Subscription {
+                form: self.clone(),
+                scope_id: cx.scope_id(),
+            }
+        });
+    }
+}
+
+struct Subscription {
+    form: UseForm,
+    scope_id: ScopeId,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.form.state.borrow_mut().subscribers.remove(&self.scope_id);
+    }
+}
+
+/// Creates the state of a form, the component re-renders when it changes
+pub fn use_form(cx: &ScopeState) -> &UseForm {
+    let form = cx.use_hook(UseForm::default);
+    form.subscribe(cx);
+    form
+}
+
+/// The form of the surrounding ``Form``, the scope re-renders when it changes
+fn use_form_context(cx: &ScopeState) -> Option<UseForm> {
+    let form = use_context::<UseForm>(cx).cloned()?;
+    form.subscribe(cx);
+    Some(form)
+}
+
+#[derive(Props)]
+pub struct FormProps<'a> {
+    pub form: &'a UseForm,
+    #[props(default = "", into)]
+    pub class: &'a str,
+    /// Only called when every rule passes
+    pub onsubmit: Option<EventHandler<'a, FormValues>>,
+    pub children: Element<'a>,
+}
+
+/// Groups fields like ``FormInput`` and ``FormField``. Enter in one of them or a ``SubmitButton`` validates the form
+/// and submits it if everything passes
+pub fn Form<'a>(cx: Scope<'a, FormProps<'a>>) -> Element<'a> {
+    use_context_provider(cx, || cx.props.form.clone());
+    let form = cx.props.form;
+
+    let submit = move || {
+        if form.validate() {
+            if let Some(onsubmit) = &cx.props.onsubmit {
+                onsubmit.call(form.values());
+            }
+        }
+    };
+
+    if std::mem::take(&mut form.state.borrow_mut().submit_requested) {
+        submit();
+    }
+
+    render! {
+        view {
+            class: "flex-col {cx.props.class}",
+            onkeydown: move |event: Event<KeyInput>| {
+                if event.logical_key == Key::Named(NamedKey::Enter) {
+                    submit();
+                }
+            },
+            &cx.props.children
+        }
+    }
+}
+
+#[derive(Props)]
+pub struct FormFieldProps<'a> {
+    pub name: &'a str,
+    #[props(default = "", into)]
+    pub label: &'a str,
+    #[props(default)]
+    pub rules: Vec<Rule>,
+    #[props(default = "", into)]
+    pub class: &'a str,
+    /// The control, which calls ``UseForm::set`` with the name of the field when it changes
+    pub children: Element<'a>,
+}
+
+/// A label, any control and the error of the field under it, e.g. for a ``NumberInput``
+pub fn FormField<'a>(cx: Scope<'a, FormFieldProps<'a>>) -> Element<'a> {
+    let theme = use_component_theme(cx);
+    let Some(form) = use_form_context(cx) else {
+        log::error!("FormField has to be inside of Form");
+        return None;
+    };
+    cx.use_hook(|| form.register(cx.props.name, cx.props.rules.clone()));
+    let label = (!cx.props.label.is_empty()).then_some(cx.props.label);
+
+    render! {
+        view {
+            class: "flex-col {cx.props.class}",
+            label.map(|label| rsx! { view { class: "{theme.form_label}", "{label}" } }),
+            &cx.props.children,
+            FormError { name: cx.props.name }
+        }
+    }
+}
+
+#[derive(Props)]
+pub struct FormInputProps<'a> {
+    pub name: &'a str,
+    #[props(default = "", into)]
+    pub label: &'a str,
+    #[props(default)]
+    pub rules: Vec<Rule>,
+    #[props(default = "", into)]
+    pub class: &'a str,
+}
+
+/// A text ``Input`` bound to a field of the surrounding ``Form``
+pub fn FormInput<'a>(cx: Scope<'a, FormInputProps<'a>>) -> Element<'a> {
+    let Some(form) = use_form_context(cx) else {
+        log::error!("FormInput has to be inside of Form");
+        return None;
+    };
+    let value: &'a str = cx.bump().alloc_str(&form.value(cx.props.name));
+    let name = cx.props.name;
+
+    render! {
+        FormField {
+            name: name,
+            label: cx.props.label,
+            rules: cx.props.rules.clone(),
+            class: cx.props.class,
+            Input {
+                value: value,
+                onchange: move |value: Rc<String>| form.set(name, value.as_str()),
+            }
+        }
+    }
+}
+
+#[derive(Props)]
+pub struct FormErrorProps<'a> {
+    pub name: &'a str,
+    #[props(default = "", into)]
+    pub class: &'a str,
+}
+
+/// The error of a field, for layouts that put it somewhere else than ``FormField`` does
+pub fn FormError<'a>(cx: Scope<'a, FormErrorProps<'a>>) -> Element<'a> {
+    let theme = use_component_theme(cx);
+    let form = use_form_context(cx)?;
+    let error = form.error(cx.props.name)?;
+
+    render! {
+        view { class: "{theme.form_error} {cx.props.class}", "{error}" }
+    }
+}
+
+#[derive(Props)]
+pub struct SubmitButtonProps<'a> {
+    #[props(default = "", into)]
+    pub class: &'a str,
+    pub children: Element<'a>,
+}
+
+pub fn SubmitButton<'a>(cx: Scope<'a, SubmitButtonProps<'a>>) -> Element<'a> {
+    let theme = use_component_theme(cx);
+    let form = use_context::<UseForm>(cx).cloned();
+
+    render! {
+        button {
+            class: "{theme.submit_button} {cx.props.class}",
+            onclick: move |event: Event<ClickEvent>| {
+                if event.element_state == ElementState::Pressed {
+                    if let Some(form) = &form {
+                        form.request_submit();
+                    }
+                }
+            },
+            &cx.props.children
+        }
+    }
+}
@@ -1,135 +1,552 @@
-use std::sync::Arc;
+use std::{
+    cell::RefCell,
+    io::Cursor,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
 
-use crate::{event_loop::DomContext, prelude::*};
-use epaint::{textures::TextureOptions, ColorImage, TextureId};
+use crate::{
+    atlas::{AtlasRegion, TextureAtlas},
+    event_loop::DomContext,
+    events::{LayoutEvent, MountedEvent},
+    platform,
+    prelude::*,
+};
+use epaint::{textures::TextureOptions, ColorImage, TextureId, TextureManager};
+use image::imageops::FilterType;
 use resvg::usvg::TreeParsing;
+use rustc_hash::FxHashMap;
 
-#[derive(Props, PartialEq, Clone, Debug)]
+/// How many bytes of pixels image textures take up by default before unused ones are freed
+pub const DEFAULT_IMAGE_BUDGET: usize = 256 * 1024 * 1024;
+
+/// The source and the size in pixels it was decoded at, ``None`` for the full size
+type ImageKey = (String, Option<[u32; 2]>);
+
+/// Small images share a page of the [`TextureAtlas`], bigger ones get a texture of their own
+#[derive(Clone, Copy)]
+enum ImageTexture {
+    Own(TextureId),
+    Atlas(AtlasRegion),
+}
+
+impl ImageTexture {
+    fn src(&self) -> String {
+        match self {
+            Self::Own(TextureId::Managed(id) | TextureId::User(id)) => format!("texture://{id}"),
+            Self::Atlas(region) => region.src(),
+        }
+    }
+
+    fn free(self, atlas: &mut TextureAtlas, texture_manager: &mut TextureManager) {
+        match self {
+            Self::Own(texture_id) => texture_manager.free(texture_id),
+            Self::Atlas(region) => atlas.free(&region, texture_manager),
+        }
+    }
+}
+
+struct CachedImage {
+    texture: ImageTexture,
+    load: ImageLoad,
+    bytes: usize,
+    /// Mounted ``Image``s showing the texture, it's only freed while there are none
+    users: usize,
+    last_used: u64,
+}
+
+/// Textures of decoded images, shared by every ``Image`` that shows the same source at the same size. Textures that
+/// no ``Image`` shows anymore stay around for when they're shown again, the least recently used ones are freed once
+/// all of them together take up more than the budget. A budget of 0 frees them as soon as the last ``Image`` unmounts
+pub struct ImageCache {
+    entries: FxHashMap<ImageKey, CachedImage>,
+    budget: usize,
+    used: usize,
+    clock: u64,
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self {
+            entries: FxHashMap::default(),
+            budget: DEFAULT_IMAGE_BUDGET,
+            used: 0,
+            clock: 0,
+        }
+    }
+}
+
+impl ImageCache {
+    /// Bytes of pixels the textures may take up, textures in use are never freed so it can be exceeded
+    pub fn set_budget(&mut self, budget: usize, atlas: &mut TextureAtlas, texture_manager: &mut TextureManager) {
+        self.budget = budget;
+        self.evict(atlas, texture_manager);
+    }
+
+    /// Bytes of pixels the cached textures take up right now
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// The texture for ``key`` if it's cached, it counts as used until it's released
+    fn acquire(&mut self, key: &ImageKey) -> Option<(ImageTexture, ImageLoad)> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.users += 1;
+        entry.last_used = self.clock;
+        Some((entry.texture, entry.load))
+    }
+
+    /// Caches a texture that was just allocated and counts it as used. When another ``Image`` decoded the same
+    /// source in the meantime the new texture is freed and that one is used instead
+    fn insert(
+        &mut self,
+        key: ImageKey,
+        texture: ImageTexture,
+        load: ImageLoad,
+        bytes: usize,
+        atlas: &mut TextureAtlas,
+        texture_manager: &mut TextureManager,
+    ) -> ImageTexture {
+        if let Some((existing, _)) = self.acquire(&key) {
+            texture.free(atlas, texture_manager);
+            return existing;
+        }
+        self.entries.insert(
+            key,
+            CachedImage {
+                texture,
+                load,
+                bytes,
+                users: 1,
+                last_used: self.clock,
+            },
+        );
+        self.used += bytes;
+        self.evict(atlas, texture_manager);
+        texture
+    }
+
+    fn release(&mut self, key: &ImageKey, atlas: &mut TextureAtlas, texture_manager: &mut TextureManager) {
+        self.clock += 1;
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.users = entry.users.saturating_sub(1);
+            entry.last_used = self.clock;
+        }
+        self.evict(atlas, texture_manager);
+    }
+
+    /// Frees unused textures, the least recently used first, until the cache fits in the budget
+    fn evict(&mut self, atlas: &mut TextureAtlas, texture_manager: &mut TextureManager) {
+        while self.used > self.budget {
+            let Some(key) = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.users == 0)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            let entry = self.entries.remove(&key).unwrap();
+            self.used -= entry.bytes;
+            entry.texture.free(atlas, texture_manager);
+        }
+    }
+}
+
+/// The texture an ``Image`` shows, released when it shows another one and when the ``Image`` unmounts.
+/// The cache is always locked before the atlas, and the atlas before the texture manager
+struct TextureLease {
+    cache: Arc<Mutex<ImageCache>>,
+    atlas: Arc<Mutex<TextureAtlas>>,
+    texture_manager: Arc<Mutex<TextureManager>>,
+    key: RefCell<Option<ImageKey>>,
+}
+
+impl TextureLease {
+    /// Takes over a texture that was acquired for ``key``, releasing the previous one
+    fn replace(&self, key: ImageKey) {
+        if let Some(previous) = self.key.borrow_mut().replace(key) {
+            let mut cache = self.cache.lock().unwrap();
+            let mut atlas = self.atlas.lock().unwrap();
+            cache.release(&previous, &mut atlas, &mut self.texture_manager.lock().unwrap());
+        }
+    }
+}
+
+impl Drop for TextureLease {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.get_mut().take() {
+            let mut cache = self.cache.lock().unwrap();
+            let mut atlas = self.atlas.lock().unwrap();
+            cache.release(&key, &mut atlas, &mut self.texture_manager.lock().unwrap());
+        }
+    }
+}
+
+/// The natural size of an image in pixels, sent through ``onload`` once it's decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLoad {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Props)]
 pub struct ImageProps<'a> {
     #[props(default = "", into)]
     pub class: &'a str,
+    /// Used at a density of ``1x``, or when nothing in ``srcset`` matches
     pub src: String,
+    /// Variants per display density like ``"logo.png 1x, logo@2x.png 2x"``, the smallest one that covers the scale factor of the window is used
+    pub srcset: Option<&'a str>,
+    /// Shrinks the texture to the displayed size times the scale factor before it's uploaded
+    #[props(default = true)]
+    pub downscale: bool,
+    /// Called from the task that loaded the image, once per natural size, so it's not bound to a render
+    pub onload: Option<Rc<dyn Fn(ImageLoad)>>,
 }
 
-/// TODO: cache images in a global cache
+/// Images are decoded off the VirtualDom thread, until then a placeholder with the size from the image header is shown.
+/// With ``downscale`` the pixels are only decoded once the first layout tells how big the image is on screen,
+/// at the natural size when it's laid out empty, e.g. while it's hidden.
+/// Textures are shared through the [`ImageCache`], so showing the same image again doesn't decode it again.
+/// Small images like icons are packed into the [`TextureAtlas`] instead of getting a texture each
 pub fn Image<'a>(cx: Scope<'a, ImageProps<'a>>) -> Element {
     let dom_context = use_context::<DomContext>(cx).unwrap();
-    let texture_id_state = use_state::<Option<TextureId>>(cx, || None);
-
-    use_effect(cx, (&cx.props.src,), |(src,)| {
-        to_owned![texture_id_state, dom_context];
-        async move {
-            let handle_png = |src: String, bytes: &[u8]| {
-                let img = match image::load_from_memory(&bytes) {
-                    Ok(img) => img,
-                    Err(e) => {
-                        log::error!("Failed to load image in memory: {}", e);
-                        return;
-                    }
-                };
+    let lease = cx.use_hook(|| {
+        Rc::new(TextureLease {
+            cache: dom_context.image_cache.clone(),
+            atlas: dom_context.atlas.clone(),
+            texture_manager: dom_context.texture_manager.clone(),
+            key: RefCell::new(None),
+        })
+    });
+    let texture_src = use_state::<Option<String>>(cx, || None);
+    let natural_size = use_state::<Option<ImageLoad>>(cx, || None);
+    let reported_load = use_ref::<Option<ImageLoad>>(cx, || None);
+    // the effect reads the handler of the latest render when the image is in
+    let onload: &Rc<RefCell<Option<Rc<dyn Fn(ImageLoad)>>>> = cx.use_hook(|| Rc::new(RefCell::new(None)));
+    *onload.borrow_mut() = cx.props.onload.clone();
+    // the size in pixels the texture has to cover, it only grows so resizing the window doesn't decode over and over
+    let target_size = use_state::<Option<[u32; 2]>>(cx, || None);
+    let fetched = use_ref::<Option<(String, Arc<Vec<u8>>, bool)>>(cx, || None);
 
-                let size = [img.width() as usize, img.height() as usize];
-                let rgba = img.to_rgba8();
-
-                let id = dom_context.texture_manager.lock().unwrap().alloc(
-                    src,
-                    epaint::ImageData::Color(Arc::new(ColorImage::from_rgba_unmultiplied(
-                        size, &rgba,
-                    ))),
-                    TextureOptions::LINEAR,
-                );
-                texture_id_state.set(Some(id));
-            };
+    let scale_factor = dom_context.host.scale_factor() as f32;
+    let (src, density) = pick_source(&cx.props.src, cx.props.srcset, scale_factor);
 
-            let handle_svg = |src: String, bytes: &[u8]| {
-                let opt = resvg::usvg::Options::default();
-                let rtree = resvg::usvg::Tree::from_data(&bytes, &opt)
-                    .map_err(|err| err.to_string())
-                    .expect("Failed to parse SVG file");
-
-                let rtree = resvg::Tree::from_usvg(&rtree);
-                let pixmap_size = rtree.size.to_int_size();
-                let mut pixmap =
-                    resvg::tiny_skia::Pixmap::new(pixmap_size.width(), pixmap_size.height())
-                        .unwrap();
-                rtree.render(resvg::tiny_skia::Transform::default(), &mut pixmap.as_mut());
-
-                let texture_id = dom_context.texture_manager.lock().unwrap().alloc(
-                    src,
-                    epaint::ImageData::Color(Arc::new(ColorImage::from_rgba_unmultiplied(
-                        [pixmap_size.width() as usize, pixmap_size.height() as usize],
-                        pixmap.data(),
-                    ))),
-                    TextureOptions::LINEAR,
-                );
-                texture_id_state.set(Some(texture_id));
+    use_effect(
+        cx,
+        (&src, target_size.get(), &cx.props.downscale),
+        |(src, target, downscale)| {
+            to_owned![texture_src, natural_size, reported_load, onload, dom_context, fetched, lease];
+            let report = move |load: ImageLoad| {
+                if *reported_load.read() == Some(load) {
+                    return;
+                }
+                *reported_load.write_silent() = Some(load);
+                let handler = onload.borrow().clone();
+                if let Some(handler) = handler {
+                    handler(load);
+                }
             };
+            async move {
+                let key = (src.clone(), if downscale { target } else { None });
+                let hit = dom_context.image_cache.lock().unwrap().acquire(&key);
+                if let Some((texture, load)) = hit {
+                    lease.replace(key);
+                    texture_src.set(Some(texture.src()));
+                    natural_size.set(Some(load));
+                    report(load);
+                    return;
+                }
 
-            // todo: be more specific about what we accept and what we don't and show this to the user
-            let (bytes, is_svg) = if src.starts_with("http://") || src.starts_with("https://") {
-                let req = dom_context.client.get(&src).build().unwrap();
-                let res = match dom_context.client.execute(req).await {
-                    Ok(res) => res,
-                    Err(e) => {
-                        log::error!("Failed to fetch URL inside image: {}", e);
-                        return;
+                let cached = fetched
+                    .read()
+                    .as_ref()
+                    .filter(|(cached_src, ..)| *cached_src == src)
+                    .map(|(_, bytes, is_svg)| (bytes.clone(), *is_svg));
+                let (bytes, is_svg) = match cached {
+                    Some(cached) => cached,
+                    None => {
+                        let Some((bytes, is_svg)) = fetch(&dom_context, &src).await else {
+                            return;
+                        };
+                        let bytes = Arc::new(bytes);
+                        *fetched.write_silent() = Some((src.clone(), bytes.clone(), is_svg));
+                        (bytes, is_svg)
                     }
                 };
 
-                let is_svg = res
-                    .headers()
-                    .get("content-type")
-                    .map(|ct| ct.as_bytes().starts_with(b"image/svg+xml"))
-                    .unwrap_or(false);
-
-                let bytes = match res.bytes().await {
-                    Ok(bytes) => bytes,
-                    Err(e) => {
-                        log::error!("Failed to decode body inside image: {}", e);
-                        return;
-                    }
+                // only the header is read here, so the layout doesn't jump once the pixels are in
+                let probed = if is_svg {
+                    probe_svg_size(&bytes)
+                } else {
+                    probe_size(&bytes)
                 };
+                natural_size.set(probed);
 
-                (bytes.to_vec(), is_svg)
-            } else {
-                let bytes = match tokio::fs::read(&src).await {
-                    Ok(bytes) => bytes,
-                    Err(e) => {
-                        log::error!("Failed to read file inside image: {}", e);
-                        return;
+                // wait for the placeholder to be laid out, unless there's no placeholder to lay out
+                if downscale && target.is_none() && probed.is_some() {
+                    return;
+                }
+
+                let image = platform::spawn_blocking({
+                    let src = src.clone();
+                    let target = if downscale { target } else { None };
+                    move || {
+                        if is_svg {
+                            decode_svg(&src, &bytes, target)
+                        } else {
+                            decode_raster(&src, &bytes, target)
+                        }
                     }
+                })
+                .await
+                .flatten();
+                let Some(image) = image else {
+                    return;
                 };
 
-                let is_svg = src.ends_with(".svg");
+                let load = probed.unwrap_or(ImageLoad {
+                    width: image.size[0] as u32,
+                    height: image.size[1] as u32,
+                });
+                let bytes = image.pixels.len() * 4;
+                let texture = {
+                    let mut cache = dom_context.image_cache.lock().unwrap();
+                    let mut atlas = dom_context.atlas.lock().unwrap();
+                    let mut texture_manager = dom_context.texture_manager.lock().unwrap();
+                    let texture = match atlas.alloc(&image, &mut texture_manager) {
+                        Some(region) => ImageTexture::Atlas(region),
+                        None => ImageTexture::Own(texture_manager.alloc(
+                            src,
+                            epaint::ImageData::Color(Arc::new(image)),
+                            TextureOptions::LINEAR,
+                        )),
+                    };
+                    cache.insert(key.clone(), texture, load, bytes, &mut atlas, &mut texture_manager)
+                };
+                lease.replace(key);
 
-                (bytes, is_svg)
-            };
+                texture_src.set(Some(texture.src()));
+                natural_size.set(Some(load));
+                report(load);
+            }
+        },
+    );
 
-            if is_svg {
-                handle_svg(src, &bytes);
-            } else {
-                handle_png(src, &bytes);
+    let ImageLoad { width, height } = (*natural_size.get())?;
+    let cover = move |rect: epaint::Rect| {
+        if !cx.props.downscale {
+            return;
+        }
+        let size = rect.size() * scale_factor;
+        if size.x <= 0.0 || size.y <= 0.0 {
+            // nothing to shrink to, decode at the natural size so a hidden image is ready once it's shown
+            if target_size.get().is_none() {
+                target_size.set(Some([width, height]));
             }
+            return;
+        }
+        let needed = [
+            (size.x.ceil() as u32).min(width),
+            (size.y.ceil() as u32).min(height),
+        ];
+        let covered = target_size
+            .get()
+            .map(|[w, h]| w >= needed[0] && h >= needed[1])
+            .unwrap_or(false);
+        if !covered {
+            let current = target_size.get().unwrap_or([0, 0]);
+            target_size.set(Some([current[0].max(needed[0]), current[1].max(needed[1])]));
         }
-    });
-
-    let src = if let Some(texture_id) = texture_id_state.get() {
-        Some(match texture_id {
-            TextureId::Managed(uint) => uint,
-            TextureId::User(uint) => uint,
-        })
-    } else {
-        None
     };
+    let on_layout = move |event: Event<LayoutEvent>| cover(event.rect);
+    let on_mounted = move |event: Event<MountedEvent>| cover(event.rect);
+    let natural_width = width as f32 / density;
+    let natural_height = height as f32 / density;
 
-    if let Some(src) = src {
+    if let Some(texture_src) = texture_src.get() {
         render! {
           view {
             class: "{cx.props.class}",
-            src: "texture://{src}"
+            src: "{texture_src}",
+            natural_size: "{natural_width}x{natural_height}",
+            onlayout: on_layout,
+            onmounted: on_mounted,
           }
         }
     } else {
-        None
+        render! {
+          view {
+            class: "{cx.props.class}",
+            natural_size: "{natural_width}x{natural_height}",
+            onlayout: on_layout,
+            onmounted: on_mounted,
+          }
+        }
+    }
+}
+
+/// Picks ``src`` or one of the ``srcset`` candidates for the scale factor, together with its density
+fn pick_source(src: &str, srcset: Option<&str>, scale_factor: f32) -> (String, f32) {
+    let mut candidates = vec![(src, 1.0f32)];
+    for candidate in srcset.unwrap_or_default().split(',') {
+        let mut parts = candidate.split_whitespace();
+        let Some(url) = parts.next() else {
+            continue;
+        };
+        let density = match parts.next() {
+            Some(descriptor) => match descriptor.strip_suffix('x').and_then(|d| d.parse().ok()) {
+                Some(density) if density > 0.0 => density,
+                _ => {
+                    log::warn!("Unsupported srcset descriptor {:?}, only densities like 2x are", descriptor);
+                    continue;
+                }
+            },
+            None => 1.0,
+        };
+        // later candidates win over ``src`` for the same density
+        candidates.retain(|(_, existing)| *existing != density);
+        candidates.push((url, density));
+    }
+
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let (url, density) = candidates
+        .iter()
+        .find(|(_, density)| *density >= scale_factor)
+        .or(candidates.last())
+        .copied()
+        .unwrap();
+    (url.to_string(), density)
+}
+
+async fn fetch(dom_context: &DomContext, src: &str) -> Option<(Vec<u8>, bool)> {
+    // todo: be more specific about what we accept and what we don't and show this to the user
+    if src.starts_with("http://") || src.starts_with("https://") {
+        let req = dom_context.client.get(src).build().unwrap();
+        let res = match dom_context.client.execute(req).await {
+            Ok(res) => res,
+            Err(e) => {
+                log::error!("Failed to fetch URL inside image: {}", e);
+                return None;
+            }
+        };
+
+        let is_svg = res
+            .headers()
+            .get("content-type")
+            .map(|ct| ct.as_bytes().starts_with(b"image/svg+xml"))
+            .unwrap_or(false);
+
+        let bytes = match res.bytes().await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::error!("Failed to decode body inside image: {}", e);
+                return None;
+            }
+        };
+
+        Some((bytes.to_vec(), is_svg))
+    } else {
+        let bytes = match read_file(src).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::error!("Failed to read file inside image: {}", e);
+                return None;
+            }
+        };
+
+        Some((bytes, src.ends_with(".svg")))
+    }
+}
+
+/// The size ``[width, height]`` has to be scaled by to cover ``target``, never above 1
+fn downscale_factor(width: f32, height: f32, target: Option<[u32; 2]>) -> f32 {
+    let Some([target_width, target_height]) = target else {
+        return 1.0;
+    };
+    (target_width as f32 / width)
+        .max(target_height as f32 / height)
+        .min(1.0)
+}
+
+fn probe_size(bytes: &[u8]) -> Option<ImageLoad> {
+    let (width, height) = image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()?;
+    Some(ImageLoad { width, height })
+}
+
+fn decode_raster(src: &str, bytes: &[u8], target: Option<[u32; 2]>) -> Option<ColorImage> {
+    let mut img = match image::load_from_memory(bytes) {
+        Ok(img) => img,
+        Err(e) => {
+            log::error!("Failed to load image {} in memory: {}", src, e);
+            return None;
+        }
+    };
+
+    let scale = downscale_factor(img.width() as f32, img.height() as f32, target);
+    if scale < 1.0 {
+        img = img.resize_exact(
+            ((img.width() as f32 * scale).ceil() as u32).max(1),
+            ((img.height() as f32 * scale).ceil() as u32).max(1),
+            FilterType::Triangle,
+        );
     }
+
+    let size = [img.width() as usize, img.height() as usize];
+    Some(ColorImage::from_rgba_unmultiplied(size, &img.to_rgba8()))
+}
+
+fn probe_svg_size(bytes: &[u8]) -> Option<ImageLoad> {
+    let rtree = resvg::usvg::Tree::from_data(bytes, &resvg::usvg::Options::default()).ok()?;
+    let size = rtree.size.to_int_size();
+    Some(ImageLoad {
+        width: size.width(),
+        height: size.height(),
+    })
+}
+
+fn decode_svg(src: &str, bytes: &[u8], target: Option<[u32; 2]>) -> Option<ColorImage> {
+    let opt = resvg::usvg::Options::default();
+    let rtree = match resvg::usvg::Tree::from_data(bytes, &opt) {
+        Ok(rtree) => rtree,
+        Err(e) => {
+            log::error!("Failed to parse SVG file {}: {}", src, e);
+            return None;
+        }
+    };
+
+    let rtree = resvg::Tree::from_usvg(&rtree);
+    let svg_size = rtree.size.to_int_size();
+    // rasterizing straight at the smaller size is sharper than shrinking the pixels afterwards
+    let scale = downscale_factor(svg_size.width() as f32, svg_size.height() as f32, target);
+    let width = ((svg_size.width() as f32 * scale).ceil() as u32).max(1);
+    let height = ((svg_size.height() as f32 * scale).ceil() as u32).max(1);
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)?;
+    rtree.render(
+        resvg::tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    Some(ColorImage::from_rgba_unmultiplied(
+        [width as usize, height as usize],
+        pixmap.data(),
+    ))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_file(path: &str) -> std::io::Result<Vec<u8>> {
+    tokio::fs::read(path).await
+}
+
+/// There's no filesystem on the web, images have to be fetched over http(s) there
+#[cfg(target_arch = "wasm32")]
+async fn read_file(_path: &str) -> std::io::Result<Vec<u8>> {
+    Err(std::io::ErrorKind::Unsupported.into())
 }
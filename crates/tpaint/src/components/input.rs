@@ -1,8 +1,12 @@
 use std::rc::Rc;
 
 use crate::{
+    bidi::{self, TextDirection},
+    components::theme::use_component_theme,
     events::{ClickEvent, InputEvent},
+    platform,
     prelude::*,
+    text::{byte_range, char_count, next_grapheme, next_word, prev_grapheme, prev_word},
 };
 use copypasta::{ClipboardContext, ClipboardProvider};
 
@@ -13,11 +17,69 @@ pub struct InputProps<'a> {
     pub onchange: Option<EventHandler<'a, Rc<String>>>,
     pub default_value: Option<&'a str>,
     pub value: Option<&'a str>,
+    /// ``ltr``, ``rtl`` or ``auto``, the arrow keys move the cursor the way it looks on screen.
+    /// Without it the direction is inherited, and the arrow keys go by the first strong character
+    pub dir: Option<&'a str>,
+    /// Spell-check or syntax markers, see [`crate::TextDecorations`]
+    #[props(default = "", into)]
+    pub decorations: &'a str,
+    /// Shows the value differently than it's typed, like ``(555) 123-4567`` for ``5551234567``.
+    /// Editing and ``onchange`` still go by the raw value
+    pub formatter: Option<&'a dyn Fn(&str) -> FormattedValue>,
+}
+
+/// What an [`Input`] with a ``formatter`` shows for its raw value
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormattedValue {
+    pub text: String,
+    /// The char index in ``text`` of every char of the raw value
+    pub positions: Vec<usize>,
+}
+
+impl FormattedValue {
+    /// The raw value as is
+    pub fn plain(raw: &str) -> Self {
+        Self {
+            text: raw.to_string(),
+            positions: (0..char_count(raw)).collect(),
+        }
+    }
+
+    /// For formatters that only add chars around the raw ones, like separators, prefixes and suffixes.
+    /// Raw chars the formatter left out or changed stay with the char before them
+    pub fn inserted(raw: &str, text: String) -> Self {
+        let mut positions = Vec::with_capacity(raw.len());
+        let mut display = text.chars().enumerate();
+        for char in raw.chars() {
+            match display.by_ref().find(|(_, display_char)| *display_char == char) {
+                Some((index, _)) => positions.push(index),
+                None => positions.push(positions.last().copied().unwrap_or(0)),
+            }
+        }
+        Self { text, positions }
+    }
+
+    /// Where the caret shows for a raw char index, right after the raw char before it
+    pub fn to_display(&self, raw: usize) -> usize {
+        match raw.min(self.positions.len()).checked_sub(1) {
+            Some(before) => self.positions[before] + 1,
+            None => self.positions.first().copied().unwrap_or(char_count(&self.text)),
+        }
+    }
+
+    /// The raw char index for a char index in ``text``, e.g. where a click lands
+    pub fn to_raw(&self, display: usize) -> usize {
+        self.positions.iter().filter(|position| **position < display).count()
+    }
 }
 
 pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
+    let theme = use_component_theme(cx);
     let text = use_state(cx, || cx.props.default_value.unwrap_or("").to_string());
+    // char indices, like the cursors of the galley
     let cursor_pos = use_state(cx, || 0);
+    // at a line wrap the caret shows at the start of the next line, unless it was put at the end of the wrapped one
+    let cursor_prefer_next_row = use_state(cx, || true);
     let cursor_visible = use_state(cx, || false);
     let is_focused = use_state(cx, || false);
     let selection_start = use_state(cx, || 0);
@@ -28,11 +90,12 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
         if value != *text.current() {
             text.set(value.clone());
 
-            if *cursor_pos.get() > value.len() {
-                cursor_pos.set(value.len());
+            let len = char_count(&value);
+            if *cursor_pos.get() > len {
+                cursor_pos.set(len);
             }
-            if *selection_start.get() > value.len() {
-                selection_start.set(value.len());
+            if *selection_start.get() > len {
+                selection_start.set(len);
             }
         }
         text
@@ -43,9 +106,15 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
     // TODO: clean up
     let handle_input = move |event: Event<InputEvent>| {
         let mut text = text.make_mut();
+        // typing and moving the caret go by the text, like native fields
+        if !*cursor_prefer_next_row.get() {
+            cursor_prefer_next_row.set(true);
+        }
 
         let range = *selection_start.get()..*cursor_pos.get();
         let is_selecting = range.start != range.end;
+        let bytes = byte_range(&text, range.clone());
+        let range = range.start.min(range.end)..range.start.max(range.end);
 
         // println!("is_selected {} range: {:?}", is_selecting, range);
 
@@ -56,7 +125,7 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
                 match c.as_str() {
                     "c" => {
                         if is_selecting && event.state.state().command() {
-                            let text = text[range].to_string();
+                            let text = text[bytes].to_string();
                             let mut ctx = ClipboardContext::new().unwrap();
                             println!("copying: {:?}", text);
                             ctx.set_contents(text).unwrap();
@@ -65,12 +134,13 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
                     }
                     "x" => {
                         if is_selecting && event.state.state().command() {
-                            let selected_text = text[range.clone()].to_string();
+                            let selected_text = text[bytes.clone()].to_string();
                             let mut ctx = ClipboardContext::new().unwrap();
                             ctx.set_contents(selected_text).unwrap();
 
-                            text.replace_range(range.clone(), &"".to_string());
+                            text.replace_range(bytes, "");
                             cursor_pos.set(range.start);
+                            selection_start.set(range.start);
                             return;
                         }
                     }
@@ -84,45 +154,54 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
                     _ => {}
                 }
 
-                text.replace_range(range.clone(), &c.to_string());
-                cursor_pos.set(range.start + 1);
-                selection_start.set(range.start + 1);
+                text.replace_range(bytes, &c);
+                let end = range.start + c.chars().count();
+                cursor_pos.set(end);
+                selection_start.set(end);
             }
             winit::keyboard::Key::Named(named_key) => match named_key {
                 winit::keyboard::NamedKey::Delete => {
-                    if *cursor_pos.get() < text.len() {
-                        if is_selecting {
-                            text.replace_range(range.clone(), &"".to_string());
-                            cursor_pos.set(range.start);
-                            selection_start.set(range.start);
-                        } else {
-                            text.remove(*cursor_pos.get());
-                            cursor_pos.set(*cursor_pos.get());
-                            selection_start.set(*cursor_pos.get());
-                        }
+                    if is_selecting {
+                        text.replace_range(bytes, "");
+                        cursor_pos.set(range.start);
+                        selection_start.set(range.start);
+                    } else if *cursor_pos.get() < char_count(&text) {
+                        // the whole grapheme, not just its first char
+                        let cursor = *cursor_pos.get();
+                        let bytes = byte_range(&text, cursor..next_grapheme(&text, cursor));
+                        text.replace_range(bytes, "");
+                        selection_start.set(cursor);
                     }
                 }
                 winit::keyboard::NamedKey::Home => {
                     cursor_pos.set(0);
                 }
                 winit::keyboard::NamedKey::End => {
-                    cursor_pos.set(text.len());
+                    cursor_pos.set(char_count(&text));
                 }
                 winit::keyboard::NamedKey::ArrowLeft => {
+                    let direction = cx.props.dir.and_then(TextDirection::parse).unwrap_or_default();
+                    let by_word = event.state.state().modifiers().state().control_key();
                     cursor_pos.with_mut(|cursor_pos| {
-                        if *cursor_pos > 0 {
-                            *cursor_pos -= 1;
-                        }
+                        *cursor_pos = if by_word {
+                            prev_word(&text, *cursor_pos)
+                        } else {
+                            bidi::move_caret(&text, direction, *cursor_pos, true)
+                        };
                         selection_start.set(*cursor_pos);
                     });
 
                     // if !event.state.state().shift() {}
                 }
                 winit::keyboard::NamedKey::ArrowRight => {
+                    let direction = cx.props.dir.and_then(TextDirection::parse).unwrap_or_default();
+                    let by_word = event.state.state().modifiers().state().control_key();
                     cursor_pos.with_mut(|cursor_pos| {
-                        if *cursor_pos < text.len() {
-                            *cursor_pos += 1;
-                        }
+                        *cursor_pos = if by_word {
+                            next_word(&text, *cursor_pos)
+                        } else {
+                            bidi::move_caret(&text, direction, *cursor_pos, false)
+                        };
                         selection_start.set(*cursor_pos);
                     });
 
@@ -130,22 +209,22 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
                     // }
                 }
                 winit::keyboard::NamedKey::Backspace => {
-                    if *cursor_pos.get() > 0 {
-                        if is_selecting {
-                            text.replace_range(range.clone(), &"".to_string());
-                            cursor_pos.set(range.start);
-                            selection_start.set(range.start);
-                        } else {
-                            text.remove(*cursor_pos.get() - 1);
-                            cursor_pos.set(*cursor_pos.get() - 1);
-                            selection_start.set(*cursor_pos.get() - 1);
-                        }
+                    if is_selecting {
+                        text.replace_range(bytes, "");
+                        cursor_pos.set(range.start);
+                        selection_start.set(range.start);
+                    } else if *cursor_pos.get() > 0 {
+                        let cursor = *cursor_pos.get();
+                        let start = prev_grapheme(&text, cursor);
+                        text.replace_range(byte_range(&text, start..cursor), "");
+                        cursor_pos.set(start);
+                        selection_start.set(start);
                     }
                 }
                 winit::keyboard::NamedKey::Space => {
-                    text.insert(*cursor_pos.get(), ' ');
-                    cursor_pos.set(*cursor_pos.get() + 1);
-                    selection_start.set(*cursor_pos.get() + 1);
+                    text.replace_range(bytes, " ");
+                    cursor_pos.set(range.start + 1);
+                    selection_start.set(range.start + 1);
                 }
                 _ => {}
             },
@@ -159,15 +238,28 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
         }
     };
 
+    let formatted = cx.props.formatter.map(|formatter| formatter(text.get()));
+    let to_raw = {
+        let formatted = formatted.clone();
+        move |display: usize| formatted.as_ref().map_or(display, |formatted| formatted.to_raw(display))
+    };
+    let to_raw_on_select = to_raw.clone();
+
     let handle_click = move |event: Event<ClickEvent>| {
         if let Some(f_cursor_pos) = event.text_cursor_position {
-            cursor_pos.set(f_cursor_pos);
+            cursor_pos.set(to_raw(f_cursor_pos));
+            cursor_prefer_next_row.set(event.text_cursor_prefer_next_row);
         } else {
             cursor_pos.set(0);
             cursor_visible.set(false);
         }
     };
 
+    let display = formatted.as_ref().map_or(text.get().as_str(), |formatted| formatted.text.as_str());
+    let text_cursor = formatted
+        .as_ref()
+        .map_or(*cursor_pos.get(), |formatted| formatted.to_display(*cursor_pos.get()));
+
     let cursor_blinking = use_future(
         cx,
         (cursor_visible, is_focused),
@@ -176,11 +268,8 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
                 return;
             }
 
-            let mut interval = tokio::time::interval(std::time::Duration::from_millis(500));
-            interval.tick().await;
-
             loop {
-                interval.tick().await;
+                platform::sleep(std::time::Duration::from_millis(500)).await;
                 cursor_visible.set(!*cursor_visible.get());
             }
         },
@@ -188,8 +277,9 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
 
     render! {
       view {
-        class: "focus:border-2 border-1 p-5 min-w-100 border-gray-300 flex-col text-black focus:border-black bg-white cursor-text {cx.props.class}",
+        class: "flex-col {theme.input} {cx.props.class}",
         tabindex: 0,
+        dir: cx.props.dir,
         oninput: handle_input,
         onclick: handle_click,
         onfocus: move |_| {
@@ -202,13 +292,16 @@ pub fn Input<'a>(cx: Scope<'a, InputProps<'a>>) -> Element {
             is_focused.set(false);
         },
         onselect: move |event| {
-            selection_start.set(event.start_cursor.ccursor.index);
-            cursor_pos.set(event.end_cursor.ccursor.index);
+            selection_start.set(to_raw_on_select(event.start_cursor.ccursor.index));
+            cursor_pos.set(to_raw_on_select(event.end_cursor.ccursor.index));
+            cursor_prefer_next_row.set(event.end_cursor.pcursor.prefer_next_row);
         },
-        text_cursor: *cursor_pos.get() as i64,
+        text_cursor: text_cursor as i64,
+        text_cursor_prefer_next_row: *cursor_prefer_next_row.get(),
+        text_decorations: cx.props.decorations,
         text_cursor_visible: *cursor_visible.get() && *is_focused.get(),
 
-        "{text}"
+        "{display}"
       }
     }
 }
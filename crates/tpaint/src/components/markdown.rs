@@ -0,0 +1,379 @@
+#[cfg(feature = "images")]
+use crate::components::image::Image;
+use crate::{
+    components::theme::{use_component_theme, ComponentTheme},
+    prelude::*,
+};
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+#[derive(Props)]
+pub struct MarkdownProps<'a> {
+    #[props(default = "", into)]
+    pub class: &'a str,
+    /// CommonMark, with ``~~strikethrough~~`` and task lists
+    pub src: &'a str,
+    /// Called with the url of a clicked link, links don't do anything without it
+    pub onlink: Option<EventHandler<'a, String>>,
+}
+
+/// Renders markdown with views and text, e.g. for docs or chat messages. Raw html and footnotes are left out, and
+/// there are no bold or italic fonts, so strong and emphasized text only differ in color
+pub fn Markdown<'a>(cx: Scope<'a, MarkdownProps<'a>>) -> Element<'a> {
+    let blocks = use_memo(cx, (&cx.props.src.to_string(),), |(src,)| parse(&src));
+    let onlink = cx.props.onlink.as_ref();
+    let theme = use_component_theme(cx);
+
+    render! {
+        view {
+            class: "flex-col gap-y-10 {cx.props.class}",
+            blocks.iter().map(|block| render_block(block, "", onlink, theme))
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+struct SpanStyle {
+    strong: bool,
+    emphasis: bool,
+    strikethrough: bool,
+    code: bool,
+}
+
+#[derive(PartialEq, Debug)]
+enum Inline {
+    Text {
+        text: String,
+        style: SpanStyle,
+        link: Option<String>,
+    },
+    Image {
+        src: String,
+        alt: String,
+    },
+    Break,
+}
+
+#[derive(PartialEq, Debug)]
+enum Block {
+    Heading(u8, Vec<Inline>),
+    Paragraph(Vec<Inline>),
+    List {
+        start: Option<u64>,
+        items: Vec<Vec<Block>>,
+    },
+    Quote(Vec<Block>),
+    Code(String),
+    Rule,
+}
+
+/// Blocks that hold other blocks, while they're being parsed
+enum Container {
+    Blocks(Vec<Block>),
+    Quote(Vec<Block>),
+    List {
+        start: Option<u64>,
+        items: Vec<Vec<Block>>,
+    },
+    Item(Vec<Block>),
+}
+
+#[derive(Default)]
+struct Builder {
+    containers: Vec<Container>,
+    inlines: Vec<Inline>,
+    style: SpanStyle,
+    links: Vec<String>,
+    image: Option<(String, String)>,
+    code: Option<String>,
+}
+
+impl Builder {
+    fn push_block(&mut self, block: Block) {
+        match self.containers.last_mut() {
+            Some(Container::Blocks(blocks) | Container::Quote(blocks) | Container::Item(blocks)) => blocks.push(block),
+            // text directly in a list without an item isn't valid markdown
+            Some(Container::List { .. }) | None => {}
+        }
+    }
+
+    /// Ends the paragraph that is being built, tight list items have text without a paragraph around it
+    fn flush(&mut self) {
+        if !self.inlines.is_empty() {
+            let inlines = std::mem::take(&mut self.inlines);
+            self.push_block(Block::Paragraph(inlines));
+        }
+    }
+
+    fn text(&mut self, text: &str, code: bool) {
+        self.inlines.push(Inline::Text {
+            text: text.to_string(),
+            style: SpanStyle { code, ..self.style },
+            link: self.links.last().cloned(),
+        });
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::Heading(..) => self.flush(),
+            Tag::BlockQuote => {
+                self.flush();
+                self.containers.push(Container::Quote(vec![]));
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.containers.push(Container::List { start, items: vec![] });
+            }
+            Tag::Item => {
+                self.flush();
+                self.containers.push(Container::Item(vec![]));
+            }
+            Tag::CodeBlock(_) => {
+                self.flush();
+                self.code = Some(String::new());
+            }
+            Tag::Emphasis => self.style.emphasis = true,
+            Tag::Strong => self.style.strong = true,
+            Tag::Strikethrough => self.style.strikethrough = true,
+            Tag::Link(_, url, _) => self.links.push(url.to_string()),
+            Tag::Image(_, url, _) => self.image = Some((url.to_string(), String::new())),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.flush(),
+            Tag::Heading(level, ..) => {
+                let inlines = std::mem::take(&mut self.inlines);
+                self.push_block(Block::Heading(level as u8, inlines));
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                if let Some(Container::Quote(blocks)) = self.containers.pop() {
+                    self.push_block(Block::Quote(blocks));
+                }
+            }
+            Tag::List(_) => {
+                self.flush();
+                if let Some(Container::List { start, items }) = self.containers.pop() {
+                    self.push_block(Block::List { start, items });
+                }
+            }
+            Tag::Item => {
+                self.flush();
+                if let Some(Container::Item(blocks)) = self.containers.pop() {
+                    if let Some(Container::List { items, .. }) = self.containers.last_mut() {
+                        items.push(blocks);
+                    }
+                }
+            }
+            Tag::CodeBlock(_) => {
+                if let Some(code) = self.code.take() {
+                    self.push_block(Block::Code(code.trim_end_matches('\n').to_string()));
+                }
+            }
+            Tag::Emphasis => self.style.emphasis = false,
+            Tag::Strong => self.style.strong = false,
+            Tag::Strikethrough => self.style.strikethrough = false,
+            Tag::Link(..) => {
+                self.links.pop();
+            }
+            Tag::Image(..) => {
+                if let Some((src, alt)) = self.image.take() {
+                    self.inlines.push(Inline::Image { src, alt });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse(src: &str) -> Vec<Block> {
+    let mut builder = Builder {
+        containers: vec![Container::Blocks(vec![])],
+        ..Default::default()
+    };
+
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(src, options) {
+        match event {
+            Event::Start(tag) => builder.start(tag),
+            Event::End(tag) => builder.end(tag),
+            Event::Text(text) => {
+                if let Some(code) = &mut builder.code {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = &mut builder.image {
+                    alt.push_str(&text);
+                } else {
+                    builder.text(&text, false);
+                }
+            }
+            Event::Code(text) => builder.text(&text, true),
+            Event::SoftBreak => builder.text(" ", false),
+            Event::HardBreak => builder.inlines.push(Inline::Break),
+            Event::Rule => {
+                builder.flush();
+                builder.push_block(Block::Rule);
+            }
+            Event::TaskListMarker(checked) => builder.text(if checked { "[x] " } else { "[ ] " }, true),
+            // raw html and footnotes
+            _ => {}
+        }
+    }
+
+    builder.flush();
+    match builder.containers.into_iter().next() {
+        Some(Container::Blocks(blocks)) => blocks,
+        _ => vec![],
+    }
+}
+
+/// Text styling isn't inherited by views, so every word gets the classes of the block it's in
+fn span_class(base: &str, style: SpanStyle, link: bool, theme: &ComponentTheme) -> String {
+    let mut class = base.to_string();
+    if link {
+        class.push(' ');
+        class.push_str(&theme.markdown_link);
+    } else if style.strong {
+        class.push_str(" text-black");
+    } else if style.emphasis {
+        class.push_str(" text-slate-600");
+    }
+    if style.strikethrough {
+        class.push_str(" text-slate-400");
+    }
+    if style.code {
+        class.push(' ');
+        class.push_str(&theme.markdown_code);
+    }
+    class
+}
+
+fn heading_class(level: u8) -> &'static str {
+    match level {
+        1 => "text-32",
+        2 => "text-26",
+        3 => "text-22",
+        4 => "text-18",
+        _ => "text-16",
+    }
+}
+
+/// ``base`` holds the text classes of the blocks around it, e.g. the color of a quote
+fn render_block<'a>(
+    block: &'a Block,
+    base: &'static str,
+    onlink: Option<&'a EventHandler<'a, String>>,
+    theme: &'a ComponentTheme,
+) -> LazyNodes<'a, 'a> {
+    match block {
+        Block::Heading(level, inlines) => {
+            let base = format!("{} {}", base, heading_class(*level));
+            rsx! {
+                view {
+                    class: "flex-row flex-wrap items-end",
+                    render_inlines(inlines, base, onlink, theme)
+                }
+            }
+        }
+        Block::Paragraph(inlines) => rsx! {
+            view {
+                class: "flex-row flex-wrap items-end",
+                render_inlines(inlines, base.to_string(), onlink, theme)
+            }
+        },
+        Block::List { start, items } => rsx! {
+            view {
+                class: "flex-col gap-y-4",
+                items.iter().enumerate().map(move |(index, blocks)| {
+                    let marker = match start {
+                        Some(start) => format!("{}.", start + index as u64),
+                        None => "•".to_string(),
+                    };
+                    rsx! {
+                        view {
+                            class: "flex-row gap-x-6",
+                            view { class: "{base}", "{marker}" }
+                            view {
+                                class: "flex-col grow gap-y-4",
+                                blocks.iter().map(|block| render_block(block, base, onlink, theme))
+                            }
+                        }
+                    }
+                })
+            }
+        },
+        Block::Quote(blocks) => rsx! {
+            view {
+                class: "flex-row gap-x-10",
+                view { class: "w-4 bg-slate-300" }
+                view {
+                    class: "flex-col grow gap-y-10",
+                    blocks.iter().map(|block| render_block(block, "text-slate-600", onlink, theme))
+                }
+            }
+        },
+        Block::Code(code) => rsx! {
+            view {
+                class: "flex-col {theme.markdown_code_block} {base}",
+                "{code}"
+            }
+        },
+        Block::Rule => rsx! {
+            view { class: "w-full h-1 bg-slate-300" }
+        },
+    }
+}
+
+/// Every word is its own node, so the line wraps between words of differently styled spans
+fn render_inlines<'a>(
+    inlines: &'a [Inline],
+    base: String,
+    onlink: Option<&'a EventHandler<'a, String>>,
+    theme: &'a ComponentTheme,
+) -> impl Iterator<Item = LazyNodes<'a, 'a>> {
+    inlines.iter().flat_map(move |inline| -> Vec<LazyNodes<'a, 'a>> {
+        match inline {
+            Inline::Text { text, style, link } => text
+                .split_inclusive(' ')
+                .map(|word| {
+                    let class = span_class(&base, *style, link.is_some(), theme);
+                    match link {
+                        Some(link) => rsx! {
+                            view {
+                                class: "{class}",
+                                onclick: move |_| {
+                                    if let Some(onlink) = onlink {
+                                        onlink.call(link.clone());
+                                    }
+                                },
+                                "{word}"
+                            }
+                        },
+                        None => rsx! {
+                            view { class: "{class}", "{word}" }
+                        },
+                    }
+                })
+                .collect(),
+            Inline::Image { src, alt } => vec![render_image(src, alt)],
+            // a full width node pushes the rest of the paragraph onto the next line
+            Inline::Break => vec![rsx! { view { class: "w-full" } }],
+        }
+    })
+}
+
+#[cfg(feature = "images")]
+fn render_image<'a>(src: &'a str, _alt: &'a str) -> LazyNodes<'a, 'a> {
+    rsx! {
+        Image { class: "max-w-full", src: src.to_string() }
+    }
+}
+
+/// Without the ``images`` feature the alt text is shown instead
+#[cfg(not(feature = "images"))]
+fn render_image<'a>(_src: &'a str, alt: &'a str) -> LazyNodes<'a, 'a> {
+    rsx! {
+        view { class: "text-slate-500", "{alt}" }
+    }
+}
@@ -1,4 +1,14 @@
+pub(crate) mod error_overlay;
+pub mod form;
 #[cfg(feature = "images")]
 pub mod image;
 pub mod input;
+#[cfg(feature = "markdown")]
+pub mod markdown;
 pub mod motion;
+pub mod number_input;
+pub mod portal;
+pub mod progress;
+pub mod tabs;
+pub mod theme;
+pub mod tree;
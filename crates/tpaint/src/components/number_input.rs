@@ -0,0 +1,184 @@
+use std::rc::Rc;
+
+use crate::{
+    components::{input::Input, theme::use_component_theme},
+    events::{ClickEvent, DragEvent, EventState, KeyInput},
+    prelude::*,
+};
+use winit::{
+    event::ElementState,
+    keyboard::{Key, NamedKey},
+};
+
+/// How far in points the label has to be dragged for one ``step``
+const SCRUB_POINTS_PER_STEP: f32 = 4.0;
+
+#[derive(Props)]
+pub struct NumberInputProps<'a> {
+    #[props(default = "", into)]
+    pub class: &'a str,
+    /// Makes the value controlled by the parent, ``onchange`` tells when it should change
+    pub value: Option<f64>,
+    #[props(default = 0.0)]
+    pub default_value: f64,
+    #[props(default = f64::NEG_INFINITY)]
+    pub min: f64,
+    #[props(default = f64::INFINITY)]
+    pub max: f64,
+    /// Values snap to multiples of it, counted from ``min`` when there is one. The decimals it has are the ones shown
+    #[props(default = 1.0)]
+    pub step: f64,
+    /// Drag it sideways to change the value, Shift moves ten times as fast and Ctrl ten times as slow
+    #[props(default = "⇔", into)]
+    pub label: &'a str,
+    pub onchange: Option<EventHandler<'a, f64>>,
+}
+
+/// Decimals of ``step``, so 0.25 shows two
+fn precision(step: f64) -> usize {
+    let step = step.to_string();
+    step.split_once('.').map(|(_, decimals)| decimals.len()).unwrap_or(0)
+}
+
+/// ``min`` and ``max`` that can be clamped to, a NaN bound is no bound and an inverted range is swapped
+fn bounds(min: f64, max: f64) -> (f64, f64) {
+    let min = if min.is_nan() { f64::NEG_INFINITY } else { min };
+    let max = if max.is_nan() { f64::INFINITY } else { max };
+    if min > max {
+        (max, min)
+    } else {
+        (min, max)
+    }
+}
+
+/// A number field with buttons to step it. Up and Down step it as well, typed values are taken on Enter
+pub fn NumberInput<'a>(cx: Scope<'a, NumberInputProps<'a>>) -> Element<'a> {
+    let theme = use_component_theme(cx);
+    let value = use_state(cx, || cx.props.value.unwrap_or(cx.props.default_value));
+    // what is being typed, until it's taken
+    let draft = use_state(cx, || None::<String>);
+    // the unsnapped value while scrubbing, so small moves add up
+    let scrub = use_ref(cx, || None::<f64>);
+
+    // a controlled value that changed outside of the input
+    if let Some(controlled) = cx.props.value {
+        if *value.get() != controlled {
+            value.set(controlled);
+        }
+    }
+
+    let (min, max) = bounds(cx.props.min, cx.props.max);
+    let step = cx.props.step.abs();
+    let precision = precision(step);
+    let snap = move |raw: f64| {
+        let base = if min.is_finite() { min } else { 0.0 };
+        let snapped = if step > 0.0 { ((raw - base) / step).round() * step + base } else { raw };
+        // steps like 0.1 don't add up exactly
+        let factor = 10f64.powi(precision as i32);
+        ((snapped * factor).round() / factor).clamp(min, max)
+    };
+
+    let set_value = move |raw: f64| {
+        let next = snap(raw);
+        draft.set(None);
+        if next == *value.get() {
+            return;
+        }
+        if cx.props.value.is_none() {
+            value.set(next);
+        }
+        if let Some(onchange) = &cx.props.onchange {
+            onchange.call(next);
+        }
+    };
+
+    // Shift and Ctrl speed up and slow down stepping and scrubbing
+    let multiplier = |event_state: &EventState| {
+        let modifiers = event_state.state().modifiers().state();
+        if modifiers.shift_key() {
+            10.0
+        } else if modifiers.control_key() {
+            0.1
+        } else {
+            1.0
+        }
+    };
+
+    let handle_key = move |event: Event<KeyInput>| {
+        let steps = match event.logical_key {
+            Key::Named(NamedKey::ArrowUp) => 1.0,
+            Key::Named(NamedKey::ArrowDown) => -1.0,
+            Key::Named(NamedKey::PageUp) => 10.0,
+            Key::Named(NamedKey::PageDown) => -10.0,
+            Key::Named(NamedKey::Enter) => {
+                if let Some(typed) = draft.get().as_ref().and_then(|typed| typed.trim().parse::<f64>().ok()) {
+                    set_value(typed);
+                } else {
+                    draft.set(None);
+                }
+                return;
+            }
+            _ => return,
+        };
+        set_value(*value.get() + steps * step * multiplier(&event.state));
+    };
+
+    let text = match draft.get() {
+        Some(typed) => typed.clone(),
+        None => format!("{:.*}", precision, value.get()),
+    };
+    let text: &'a str = cx.bump().alloc_str(&text);
+    let at_min = *value.get() <= min;
+    let at_max = *value.get() >= max;
+
+    render! {
+        view {
+            class: "flex-row {cx.props.class}",
+            onkeydown: handle_key,
+
+            view {
+                class: "flex-row {theme.number_input_scrub}",
+                tabindex: 0,
+                onclick: move |event: Event<ClickEvent>| {
+                    *scrub.write() = (event.element_state == ElementState::Pressed).then(|| *value.get());
+                },
+                ondrag: move |event: Event<DragEvent>| {
+                    let Some(raw) = *scrub.read() else {
+                        return;
+                    };
+                    let raw = raw + (event.delta.x / SCRUB_POINTS_PER_STEP) as f64 * step * multiplier(&event.state);
+                    *scrub.write() = Some(raw.clamp(min, max));
+                    set_value(raw);
+                },
+                "{cx.props.label}"
+            }
+
+            Input {
+                class: "grow",
+                value: text,
+                onchange: move |typed: Rc<String>| draft.set(Some(typed.to_string())),
+            }
+
+            button {
+                class: "flex-row {theme.number_input_button}",
+                disabled: at_min,
+                onclick: move |event: Event<ClickEvent>| {
+                    if event.element_state == ElementState::Pressed {
+                        set_value(*value.get() - step * multiplier(&event.state));
+                    }
+                },
+                "−"
+            }
+            button {
+                class: "flex-row {theme.number_input_button}",
+                disabled: at_max,
+                onclick: move |event: Event<ClickEvent>| {
+                    if event.element_state == ElementState::Pressed {
+                        set_value(*value.get() + step * multiplier(&event.state));
+                    }
+                },
+                "+"
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::prelude::*;
+
+#[derive(Props)]
+pub struct PortalProps<'a> {
+    /// ``id`` of the node the children are mounted in, the root when empty
+    #[props(default = "", into)]
+    pub host: &'a str,
+    #[props(default = "", into)]
+    pub class: &'a str,
+    pub children: Element<'a>,
+}
+
+/// Renders its children under another node, so modals and popovers escape the clipping and stacking of the
+/// components they're written in. The children stay part of this component, and their events bubble up through it
+/// as if they were rendered in place. A host that's removed takes the portal with it, so it should outlive the portal
+pub fn Portal<'a>(cx: Scope<'a, PortalProps<'a>>) -> Element<'a> {
+    render! {
+        // the placeholder stays where the portal was rendered, Dioxus keeps inserting its siblings next to it
+        view {
+            class: "hidden",
+            view {
+                portal: "{cx.props.host}",
+                class: "flex-col {cx.props.class}",
+                &cx.props.children
+            }
+        }
+    }
+}
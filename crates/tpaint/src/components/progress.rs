@@ -0,0 +1,53 @@
+use crate::{components::theme::use_component_theme, prelude::*};
+
+#[derive(Props)]
+pub struct ProgressBarProps<'a> {
+    /// Added to the ``progress_track`` class of the [`ComponentTheme`](crate::components::theme::ComponentTheme)
+    #[props(default = "", into)]
+    pub class: &'a str,
+    /// From 0 to 1, without a value the bar slides back and forth until there is one
+    pub value: Option<f32>,
+    /// Replaces the ``progress_bar`` class of the theme, its text color is the color of the sliding bar
+    pub bar_class: Option<&'a str>,
+}
+
+/// Shows how far along a task is. The indeterminate bar is animated by the renderer, so it keeps moving while the app
+/// is idle and doesn't cause re-renders
+pub fn ProgressBar<'a>(cx: Scope<'a, ProgressBarProps<'a>>) -> Element<'a> {
+    let theme = use_component_theme(cx);
+    let track_class = &theme.progress_track;
+    let bar_class = cx.props.bar_class.unwrap_or(&theme.progress_bar);
+
+    let Some(value) = cx.props.value else {
+        // the bar is painted in the text color, the background of the track stays visible behind it
+        return render! {
+            view {
+                class: "flex-row {track_class} {cx.props.class}",
+                view { class: "h-full w-full animate-indeterminate {bar_class} bg-transparent" }
+            }
+        };
+    };
+    let percentage = (value.clamp(0.0, 1.0) * 100.0).round();
+
+    render! {
+        view {
+            class: "flex-row {track_class} {cx.props.class}",
+            view { class: "h-full w-{percentage}% {bar_class}" }
+        }
+    }
+}
+
+#[derive(Props)]
+pub struct SpinnerProps<'a> {
+    /// Added to the ``spinner`` class of the theme, the text color is the color of the arc
+    #[props(default = "", into)]
+    pub class: &'a str,
+}
+
+/// A turning arc for work of unknown length, animated by the renderer like the indeterminate ``ProgressBar``
+pub fn Spinner<'a>(cx: Scope<'a, SpinnerProps<'a>>) -> Element<'a> {
+    let theme = use_component_theme(cx);
+    render! {
+        view { class: "shrink-0 animate-spin {theme.spinner} {cx.props.class}" }
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+
+use crate::{
+    components::theme::use_component_theme,
+    events::{ClickEvent, KeyInput},
+    handle::DomHandle,
+    prelude::*,
+};
+use winit::keyboard::{Key, NamedKey};
+
+/// Shared with the ``TabPanel`` children of a ``Tabs``
+struct TabsState {
+    active: usize,
+    /// Panels that were active at some point, the others haven't been mounted yet
+    visited: HashSet<usize>,
+    unmount_inactive: bool,
+}
+
+#[derive(Props)]
+pub struct TabsProps<'a> {
+    #[props(default = "", into)]
+    pub class: &'a str,
+    /// The label of every tab, in the order of the ``index`` of the panels
+    pub labels: &'a [&'a str],
+    #[props(default = 0)]
+    pub default_index: usize,
+    /// Makes the active tab controlled by the parent, ``onchange`` tells when it should change
+    pub index: Option<usize>,
+    pub onchange: Option<EventHandler<'a, usize>>,
+    /// Panels that were opened once stay mounted and hidden, so they keep their state. This unmounts them instead
+    #[props(default = false)]
+    pub unmount_inactive: bool,
+    /// Replace the ``tab_list``, ``tab`` and ``active_tab`` classes of the [`ComponentTheme`](crate::components::theme::ComponentTheme)
+    pub list_class: Option<&'a str>,
+    pub tab_class: Option<&'a str>,
+    pub active_tab_class: Option<&'a str>,
+    pub children: Element<'a>,
+}
+
+/// A row of tabs above the ``TabPanel`` children. The arrow keys, Home and End switch tabs while one is focused.
+/// Panels are only mounted once their tab is opened, so tabs nobody looks at cost no layout
+pub fn Tabs<'a>(cx: Scope<'a, TabsProps<'a>>) -> Element<'a> {
+    let dom_handle = use_context::<DomHandle>(cx).cloned();
+    let theme = use_component_theme(cx);
+    let tabs_state = {
+        use_shared_state_provider(cx, || {
+            let active = cx.props.index.unwrap_or(cx.props.default_index);
+            TabsState {
+                active,
+                visited: HashSet::from([active]),
+                unmount_inactive: cx.props.unmount_inactive,
+            }
+        });
+        use_shared_state::<TabsState>(cx).unwrap()
+    };
+
+    // a controlled index that changed outside of the tabs
+    if let Some(index) = cx.props.index {
+        if tabs_state.read().active != index {
+            let mut state = tabs_state.write_silent();
+            state.active = index;
+            state.visited.insert(index);
+        }
+    }
+    tabs_state.write_silent().unmount_inactive = cx.props.unmount_inactive;
+
+    let scope = cx.scope_id().0;
+    let tab_id = move |index: usize| format!("tabs-{}-{}", scope, index);
+    let count = cx.props.labels.len();
+
+    let select = move |index: usize, focus: bool| {
+        if focus {
+            if let Some(dom_handle) = &dom_handle {
+                dom_handle.focus(&tab_id(index));
+            }
+        }
+        if tabs_state.read().active == index {
+            return;
+        }
+        if cx.props.index.is_none() {
+            let mut state = tabs_state.write();
+            state.active = index;
+            state.visited.insert(index);
+        }
+        if let Some(onchange) = &cx.props.onchange {
+            onchange.call(index);
+        }
+    };
+
+    let active = tabs_state.read().active;
+    let list_class = cx.props.list_class.unwrap_or(&theme.tab_list);
+    let tab_class = cx.props.tab_class.unwrap_or(&theme.tab);
+    let active_tab_class = cx.props.active_tab_class.unwrap_or(&theme.active_tab);
+    render! {
+        view {
+            class: "flex-col {cx.props.class}",
+
+            view {
+                class: "flex-row {list_class}",
+                cx.props.labels.iter().enumerate().map(|(index, label)| {
+                    let class = if index == active { active_tab_class } else { tab_class };
+                    let id = tab_id(index);
+                    let select = select.clone();
+                    let select_key = select.clone();
+                    rsx! {
+                        button {
+                            id: "{id}",
+                            class: "{class}",
+                            tabindex: 0,
+                            onclick: move |_: Event<ClickEvent>| select(index, false),
+                            onkeydown: move |event: Event<KeyInput>| {
+                                let next = match event.logical_key {
+                                    Key::Named(NamedKey::ArrowLeft) => (index + count - 1) % count,
+                                    Key::Named(NamedKey::ArrowRight) => (index + 1) % count,
+                                    Key::Named(NamedKey::Home) => 0,
+                                    Key::Named(NamedKey::End) => count - 1,
+                                    _ => return,
+                                };
+                                select_key(next, true);
+                            },
+                            "{label}"
+                        }
+                    }
+                })
+            }
+
+            &cx.props.children
+        }
+    }
+}
+
+#[derive(Props)]
+pub struct TabPanelProps<'a> {
+    /// Which of the ``labels`` of the surrounding ``Tabs`` this panel belongs to
+    pub index: usize,
+    #[props(default = "", into)]
+    pub class: &'a str,
+    pub children: Element<'a>,
+}
+
+pub fn TabPanel<'a>(cx: Scope<'a, TabPanelProps<'a>>) -> Element<'a> {
+    let Some(tabs_state) = use_shared_state::<TabsState>(cx) else {
+        log::error!("TabPanel has to be inside of Tabs");
+        return None;
+    };
+    let state = tabs_state.read();
+    let is_active = state.active == cx.props.index;
+    if !is_active && (state.unmount_inactive || !state.visited.contains(&cx.props.index)) {
+        return None;
+    }
+    let hidden = if is_active { "" } else { "hidden" };
+
+    render! {
+        view {
+            class: "{hidden} {cx.props.class}",
+            &cx.props.children
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::prelude::*;
+
+/// The classes the built-in components start from, per part of a component. Provide one near the root with
+/// ``use_context_provider(cx, || ComponentTheme { input: "...".into(), ..Default::default() })`` and every component
+/// below it picks it up. The ``class`` prop of a component is added after its theme classes, so it still wins
+#[derive(Clone, PartialEq, Debug)]
+pub struct ComponentTheme {
+    pub input: String,
+    /// The increment and decrement buttons of a ``NumberInput``
+    pub number_input_button: String,
+    /// The label of a ``NumberInput`` that changes the value when dragged sideways
+    pub number_input_scrub: String,
+    pub form_label: String,
+    /// The message of a failing rule under a field
+    pub form_error: String,
+    pub submit_button: String,
+    /// Scroll containers of the components, e.g. ``scrollbar-thin scrollbar-thumb-gray-400 scrollbar-thumb-hover-gray-500``
+    pub scrollbar: String,
+    pub tab_list: String,
+    pub tab: String,
+    pub active_tab: String,
+    pub tree_row: String,
+    pub tree_row_selected: String,
+    /// The background of a ``ProgressBar``
+    pub progress_track: String,
+    /// The filled part of a ``ProgressBar``, its text color is the color of the indeterminate bar
+    pub progress_bar: String,
+    /// The text color is the color of the arc
+    pub spinner: String,
+    pub markdown_link: String,
+    /// Inline ``code``
+    pub markdown_code: String,
+    pub markdown_code_block: String,
+}
+
+impl Default for ComponentTheme {
+    fn default() -> Self {
+        Self {
+            input: "focus:border-2 border-1 p-5 min-w-100 border-gray-300 text-black focus:border-black bg-white cursor-text"
+                .into(),
+            number_input_button: "px-8 items-center bg-gray-100 hover:bg-gray-200 text-gray-700".into(),
+            number_input_scrub: "px-6 items-center text-gray-600 cursor-ew-resize".into(),
+            form_label: "text-14 text-gray-700 pb-4".into(),
+            form_error: "text-13 text-red-600 pt-4".into(),
+            submit_button: "px-16 py-8 rounded-4 bg-blue-500 hover:bg-blue-600 text-white".into(),
+            scrollbar: "".into(),
+            tab_list: "gap-x-2".into(),
+            tab: "px-16 py-8 text-gray-600 hover:bg-gray-100 focus:bg-gray-100".into(),
+            active_tab: "px-16 py-8 text-black bg-gray-200 focus:bg-gray-300".into(),
+            tree_row: "hover:bg-gray-100".into(),
+            tree_row_selected: "bg-blue-100".into(),
+            progress_track: "h-6 w-full rounded-3 bg-gray-200".into(),
+            progress_bar: "rounded-3 bg-blue-500 text-blue-500".into(),
+            spinner: "w-24 h-24 text-blue-500".into(),
+            markdown_link: "text-blue-600 cursor-pointer hover:text-blue-800".into(),
+            markdown_code: "font-mono bg-slate-100".into(),
+            markdown_code_block: "p-10 rounded-4 bg-slate-100 font-mono".into(),
+        }
+    }
+}
+
+/// The provided ``ComponentTheme``, or the default one when there is none
+pub fn use_component_theme(cx: &ScopeState) -> &ComponentTheme {
+    let fallback: &ComponentTheme = cx.use_hook(ComponentTheme::default);
+    use_context::<ComponentTheme>(cx).unwrap_or(fallback)
+}
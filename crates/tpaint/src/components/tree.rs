@@ -0,0 +1,329 @@
+use std::collections::HashSet;
+
+use crate::{
+    components::theme::use_component_theme,
+    events::{ClickEvent, KeyInput, LayoutEvent, ScrollEvent},
+    handle::DomHandle,
+    prelude::*,
+};
+use epaint::Vec2;
+use winit::{
+    event::ElementState,
+    keyboard::{Key, NamedKey},
+};
+
+/// Rows above and below the visible ones that are rendered as well, so fast scrolling doesn't show gaps
+const OVERSCAN: usize = 4;
+/// Width of one level of indentation in points
+const INDENT: f32 = 16.0;
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TreeNode {
+    /// Unique in the whole tree, it's what ``onselect`` and ``onactivate`` report
+    pub id: String,
+    pub label: String,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            children: vec![],
+        }
+    }
+
+    pub fn with_children(mut self, children: Vec<TreeNode>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+#[derive(Props)]
+pub struct TreeViewProps<'a> {
+    #[props(default = "", into)]
+    pub class: &'a str,
+    pub nodes: &'a [TreeNode],
+    /// Every row is this high, so only the rows in view have to be rendered
+    #[props(default = 24.0)]
+    pub row_height: f32,
+    /// Ids of the nodes that start out expanded
+    #[props(default)]
+    pub default_expanded: Vec<String>,
+    /// The selected ids in the order they're shown
+    pub onselect: Option<EventHandler<'a, Vec<String>>>,
+    /// Enter on the row with the keyboard cursor, e.g. to open a file
+    pub onactivate: Option<EventHandler<'a, String>>,
+    /// Replace the ``tree_row`` and ``tree_row_selected`` classes of the [`ComponentTheme`](crate::components::theme::ComponentTheme)
+    pub row_class: Option<&'a str>,
+    pub selected_class: Option<&'a str>,
+}
+
+#[derive(Default)]
+struct TreeState {
+    expanded: HashSet<String>,
+    selected: HashSet<String>,
+    /// Where Shift selections start from
+    anchor: Option<String>,
+    /// The row the arrow keys move from
+    cursor: Option<String>,
+}
+
+/// A row of the expanded tree
+struct Row<'a> {
+    node: &'a TreeNode,
+    depth: usize,
+    parent: Option<&'a str>,
+}
+
+fn flatten<'a>(nodes: &'a [TreeNode], expanded: &HashSet<String>) -> Vec<Row<'a>> {
+    fn visit<'a>(
+        nodes: &'a [TreeNode],
+        depth: usize,
+        parent: Option<&'a str>,
+        expanded: &HashSet<String>,
+        rows: &mut Vec<Row<'a>>,
+    ) {
+        for node in nodes {
+            rows.push(Row { node, depth, parent });
+            if expanded.contains(&node.id) {
+                visit(&node.children, depth + 1, Some(&node.id), expanded, rows);
+            }
+        }
+    }
+
+    let mut rows = vec![];
+    visit(nodes, 0, None, expanded, &mut rows);
+    rows
+}
+
+/// Hierarchical data like a file explorer. Chevrons expand and collapse, Ctrl and Shift select multiple rows, and
+/// while focused the arrow keys move through the rows, Left and Right collapse and expand. Only the rows in view are
+/// rendered, so the tree needs a fixed height, e.g. through ``h-full`` or ``h-400`` in ``class``
+pub fn TreeView<'a>(cx: Scope<'a, TreeViewProps<'a>>) -> Element<'a> {
+    let dom_handle = use_context::<DomHandle>(cx).cloned();
+    let theme = use_component_theme(cx);
+    let tree_state = use_ref(cx, || TreeState {
+        expanded: cx.props.default_expanded.iter().cloned().collect(),
+        ..Default::default()
+    });
+    let scroll_offset = use_state(cx, || 0.0f32);
+    let viewport_height = use_state(cx, || 0.0f32);
+
+    let row_height = cx.props.row_height;
+    let container_id = format!("tree-{}", cx.scope_id().0);
+    let rows = flatten(cx.props.nodes, &tree_state.read().expanded);
+    let ids: Vec<String> = rows.iter().map(|row| row.node.id.clone()).collect();
+
+    let report_selection = {
+        let ids = ids.clone();
+        move || {
+            if let Some(onselect) = &cx.props.onselect {
+                let state = tree_state.read();
+                let selected = ids.iter().filter(|id| state.selected.contains(*id)).cloned().collect();
+                onselect.call(selected);
+            }
+        }
+    };
+
+    // ``extend`` selects from the anchor up to the row, ``toggle`` adds or removes the row, otherwise only the row is selected
+    let select = {
+        let ids = ids.clone();
+        let report_selection = report_selection.clone();
+        move |id: &str, extend: bool, toggle: bool| {
+            {
+                let mut state = tree_state.write();
+                let anchor = state.anchor.clone().filter(|anchor| ids.contains(anchor));
+                match (extend, anchor) {
+                    (true, Some(anchor)) => {
+                        let from = ids.iter().position(|row| *row == anchor).unwrap();
+                        let to = ids.iter().position(|row| row == id).unwrap_or(from);
+                        let range = from.min(to)..=from.max(to);
+                        state.selected = ids[range].iter().cloned().collect();
+                    }
+                    _ if toggle => {
+                        if !state.selected.remove(id) {
+                            state.selected.insert(id.to_string());
+                        }
+                        state.anchor = Some(id.to_string());
+                    }
+                    _ => {
+                        state.selected = HashSet::from([id.to_string()]);
+                        state.anchor = Some(id.to_string());
+                    }
+                }
+                state.cursor = Some(id.to_string());
+            }
+            report_selection();
+        }
+    };
+
+    let toggle_expanded = move |id: &str| {
+        let mut state = tree_state.write();
+        if !state.expanded.remove(id) {
+            state.expanded.insert(id.to_string());
+        }
+    };
+
+    // keeps the row with the keyboard cursor in view
+    let scroll_to = {
+        let container_id = container_id.clone();
+        move |index: usize| {
+            let top = index as f32 * row_height;
+            let offset = *scroll_offset.get();
+            let offset = if top < offset {
+                top
+            } else if top + row_height > offset + *viewport_height.get() {
+                top + row_height - *viewport_height.get()
+            } else {
+                return;
+            };
+            if let Some(dom_handle) = &dom_handle {
+                dom_handle.set_scroll_offset(&container_id, Vec2::new(0.0, offset));
+            }
+        }
+    };
+
+    let handle_key = {
+        let select = select.clone();
+        let rows: Vec<(String, bool, bool, Option<String>)> = rows
+            .iter()
+            .map(|row| {
+                (
+                    row.node.id.clone(),
+                    !row.node.children.is_empty(),
+                    tree_state.read().expanded.contains(&row.node.id),
+                    row.parent.map(str::to_string),
+                )
+            })
+            .collect();
+        move |event: Event<KeyInput>| {
+            if rows.is_empty() {
+                return;
+            }
+            let cursor = tree_state
+                .read()
+                .cursor
+                .as_ref()
+                .and_then(|cursor| rows.iter().position(|(id, ..)| id == cursor));
+            let shift = event.state.state().modifiers().state().shift_key();
+            let (id, has_children, is_expanded, parent) = &rows[cursor.unwrap_or(0)];
+
+            let next = match &event.logical_key {
+                Key::Named(NamedKey::ArrowUp) => cursor.map(|cursor| cursor.saturating_sub(1)).unwrap_or(0),
+                Key::Named(NamedKey::ArrowDown) => cursor.map(|cursor| (cursor + 1).min(rows.len() - 1)).unwrap_or(0),
+                Key::Named(NamedKey::Home) => 0,
+                Key::Named(NamedKey::End) => rows.len() - 1,
+                Key::Named(NamedKey::ArrowRight) => {
+                    if *has_children && !is_expanded {
+                        toggle_expanded(id.as_str());
+                        return;
+                    }
+                    // the first child
+                    (cursor.unwrap_or(0) + usize::from(*has_children)).min(rows.len() - 1)
+                }
+                Key::Named(NamedKey::ArrowLeft) => {
+                    if *has_children && *is_expanded {
+                        toggle_expanded(id.as_str());
+                        return;
+                    }
+                    match parent {
+                        Some(parent) => rows.iter().position(|(id, ..)| id == parent).unwrap_or(0),
+                        None => return,
+                    }
+                }
+                Key::Named(NamedKey::Space) => {
+                    select(id.as_str(), false, true);
+                    return;
+                }
+                Key::Named(NamedKey::Enter) => {
+                    if let Some(onactivate) = &cx.props.onactivate {
+                        onactivate.call(id.clone());
+                    }
+                    return;
+                }
+                _ => return,
+            };
+            select(rows[next].0.as_str(), shift, false);
+            scroll_to(next);
+        }
+    };
+
+    // the rows in view, and a few around them
+    let first = ((*scroll_offset.get() / row_height) as usize).saturating_sub(OVERSCAN);
+    let last = (((*scroll_offset.get() + *viewport_height.get()) / row_height).ceil() as usize + OVERSCAN).min(rows.len());
+    let first = first.min(last);
+    let total_height = rows.len() as f32 * row_height;
+    let top = first as f32 * row_height;
+
+    let state = tree_state.read();
+    let indent = INDENT;
+    let row_class = cx.props.row_class.unwrap_or(&theme.tree_row);
+    let selected_class = cx.props.selected_class.unwrap_or(&theme.tree_row_selected);
+    let visible = rows[first..last].iter().map(|row| {
+        let (node, depth) = (row.node, row.depth);
+        let id = node.id.as_str();
+        let has_children = !node.children.is_empty();
+        let chevron = match (has_children, state.expanded.contains(id)) {
+            (false, _) => "",
+            (true, true) => "⏷",
+            (true, false) => "⏵",
+        };
+        let selected_class = if state.selected.contains(id) { selected_class } else { "" };
+        let select = select.clone();
+        rsx! {
+            view {
+                class: "flex-row items-center shrink-0 h-{row_height} {row_class} {selected_class}",
+                onclick: move |event: Event<ClickEvent>| {
+                    if event.element_state != ElementState::Pressed {
+                        return;
+                    }
+                    let modifiers = event.state.state().modifiers();
+                    select(id, modifiers.state().shift_key(), event.state.state().command());
+                },
+
+                // a guide for every level of indentation
+                (0..depth).map(|_| rsx! {
+                    view {
+                        class: "flex-row justify-center shrink-0 w-{indent} h-full",
+                        view { class: "w-1 h-full bg-gray-200" }
+                    }
+                }),
+
+                view {
+                    class: "flex-row justify-center items-center shrink-0 w-{indent} h-full",
+                    onclick: move |event: Event<ClickEvent>| {
+                        if has_children && event.element_state == ElementState::Pressed {
+                            toggle_expanded(id);
+                        }
+                    },
+                    "{chevron}"
+                }
+
+                view { class: "pl-4", "{node.label}" }
+            }
+        }
+    });
+
+    render! {
+        view {
+            id: "{container_id}",
+            class: "flex-col overflow-y-scroll {theme.scrollbar} {cx.props.class}",
+            tabindex: 0,
+            onkeydown: handle_key,
+            onscroll: move |event: Event<ScrollEvent>| scroll_offset.set(event.offset.y),
+            onlayout: move |event: Event<LayoutEvent>| {
+                if *viewport_height.get() != event.rect.height() {
+                    viewport_height.set(event.rect.height());
+                }
+            },
+
+            view {
+                class: "flex-col shrink-0 w-full h-{total_height}",
+                view { class: "shrink-0 h-{top}" }
+                visible
+            }
+        }
+    }
+}
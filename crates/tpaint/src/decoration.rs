@@ -0,0 +1,212 @@
+//! Decorations over ranges of text, like the squiggles of a spell checker or the markers of a linter. They are set on
+//! the parent of the text through the ``text_decorations`` attribute, which [`TextDecorations`] builds, and drawn as
+//! extra shapes lined up with the rows of the galley, so they follow wrapping and right-to-left runs.
+
+use std::{fmt, ops::Range};
+
+use epaint::{pos2, text::cursor::CCursor, Color32, Galley, Pos2, Rect, Shape, Stroke};
+
+use crate::bidi::{self, TextDirection};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecorationKind {
+    /// A straight line under the text, e.g. for the text that is still being composed
+    Underline,
+    /// A wavy line under the text, e.g. for spelling mistakes
+    Squiggle,
+    /// Fills the rows behind the text, e.g. for search results
+    Background,
+}
+
+impl DecorationKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Underline => "underline",
+            Self::Squiggle => "squiggle",
+            Self::Background => "background",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "underline" => Some(Self::Underline),
+            "squiggle" => Some(Self::Squiggle),
+            "background" => Some(Self::Background),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextDecoration {
+    pub kind: DecorationKind,
+    /// Char indices into the text, like the ``text_cursor`` attribute
+    pub range: Range<usize>,
+    pub color: Color32,
+}
+
+/// Builds the value of the ``text_decorations`` attribute, e.g.
+/// ``text_decorations: TextDecorations::new().squiggle(4..9, Color32::RED).to_string()``
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TextDecorations(pub Vec<TextDecoration>);
+
+impl TextDecorations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, kind: DecorationKind, range: Range<usize>, color: Color32) -> Self {
+        self.0.push(TextDecoration { kind, range, color });
+        self
+    }
+
+    pub fn underline(self, range: Range<usize>, color: Color32) -> Self {
+        self.with(DecorationKind::Underline, range, color)
+    }
+
+    pub fn squiggle(self, range: Range<usize>, color: Color32) -> Self {
+        self.with(DecorationKind::Squiggle, range, color)
+    }
+
+    pub fn background(self, range: Range<usize>, color: Color32) -> Self {
+        self.with(DecorationKind::Background, range, color)
+    }
+
+    /// Reads the attribute back, entries that don't make sense are skipped
+    pub fn parse(value: &str) -> Self {
+        Self(
+            value
+                .split(';')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .filter_map(|entry| {
+                    let decoration = parse_decoration(entry);
+                    if decoration.is_none() {
+                        log::warn!("Invalid text decoration: {}", entry);
+                    }
+                    decoration
+                })
+                .collect(),
+        )
+    }
+}
+
+/// ``{kind} {start}..{end} #{rrggbbaa}``
+fn parse_decoration(entry: &str) -> Option<TextDecoration> {
+    let mut parts = entry.split_whitespace();
+    let kind = DecorationKind::parse(parts.next()?)?;
+    let (start, end) = parts.next()?.split_once("..")?;
+    let color = parts.next()?.strip_prefix('#')?;
+    if color.len() != 8 || parts.next().is_some() {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(color.get(index * 2..index * 2 + 2)?, 16).ok();
+
+    Some(TextDecoration {
+        kind,
+        range: start.parse().ok()?..end.parse().ok()?,
+        color: Color32::from_rgba_unmultiplied(channel(0)?, channel(1)?, channel(2)?, channel(3)?),
+    })
+}
+
+impl fmt::Display for TextDecorations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, decoration) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            let [r, g, b, a] = decoration.color.to_srgba_unmultiplied();
+            write!(
+                f,
+                "{} {}..{} #{:02x}{:02x}{:02x}{:02x}",
+                decoration.kind.name(),
+                decoration.range.start,
+                decoration.range.end,
+                r,
+                g,
+                b,
+                a
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl From<TextDecorations> for String {
+    fn from(decorations: TextDecorations) -> Self {
+        decorations.to_string()
+    }
+}
+
+/// The horizontal extents of ``chars`` on every row they cover, relative to the galley
+fn row_spans(galley: &Galley, direction: TextDirection, chars: Range<usize>) -> Vec<(usize, Range<f32>)> {
+    let min = galley.from_ccursor(CCursor::new(chars.start.min(chars.end))).rcursor;
+    let max = galley.from_ccursor(CCursor::new(chars.start.max(chars.end))).rcursor;
+
+    let mut spans = vec![];
+    for index in min.row..=max.row.min(galley.rows.len().saturating_sub(1)) {
+        let row = &galley.rows[index];
+        let start = if index == min.row { min.column } else { 0 };
+        let end = if index == max.row { max.column } else { row.glyphs.len() };
+        if start >= end {
+            continue;
+        }
+        match bidi::selection_spans(galley, direction, index, start..end) {
+            Some(pieces) => spans.extend(pieces.into_iter().map(|piece| (index, piece))),
+            None => spans.push((index, row.x_offset(start)..row.x_offset(end))),
+        }
+    }
+    spans
+}
+
+/// A zigzag along ``y`` from ``left`` to ``right``
+fn squiggle_points(left: f32, right: f32, y: f32) -> Vec<Pos2> {
+    const STEP: f32 = 2.0;
+    const HEIGHT: f32 = 1.5;
+
+    let mut points = vec![];
+    let mut x = left;
+    let mut up = false;
+    while x < right {
+        points.push(pos2(x, if up { y - HEIGHT } else { y }));
+        x += STEP;
+        up = !up;
+    }
+    points.push(pos2(right, if up { y - HEIGHT } else { y }));
+    points
+}
+
+/// Shapes for the decorations of a galley drawn at ``origin``, the backgrounds go behind the text and the lines on top
+pub(crate) fn shapes(
+    galley: &Galley,
+    direction: TextDirection,
+    origin: Pos2,
+    decorations: &TextDecorations,
+) -> (Vec<Shape>, Vec<Shape>) {
+    let mut backgrounds = vec![];
+    let mut lines = vec![];
+    for decoration in &decorations.0 {
+        for (index, span) in row_spans(galley, direction, decoration.range.clone()) {
+            let row = &galley.rows[index];
+            let left = origin.x + span.start;
+            let right = origin.x + span.end;
+            let bottom = origin.y + row.max_y() - 1.0;
+            match decoration.kind {
+                DecorationKind::Background => backgrounds.push(Shape::rect_filled(
+                    Rect::from_min_max(pos2(left, origin.y + row.min_y()), pos2(right, origin.y + row.max_y())),
+                    0.0,
+                    decoration.color,
+                )),
+                DecorationKind::Underline => lines.push(Shape::line_segment(
+                    [pos2(left, bottom), pos2(right, bottom)],
+                    Stroke::new(1.0, decoration.color),
+                )),
+                DecorationKind::Squiggle => lines.push(Shape::line(
+                    squiggle_points(left, right, bottom),
+                    Stroke::new(1.0, decoration.color),
+                )),
+            }
+        }
+    }
+    (backgrounds, lines)
+}
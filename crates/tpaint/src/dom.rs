@@ -1,31 +1,118 @@
-use std::{sync::Arc, time::Instant};
+use std::{rc::Rc, sync::Arc};
 
+use copypasta::{ClipboardContext, ClipboardProvider};
 use dioxus::{
-    core::{BorrowedAttributeValue, ElementId, Mutations},
+    core::{ElementId, Mutations},
     prelude::{TemplateAttribute, TemplateNode},
 };
-use epaint::{text::cursor::Cursor, Pos2, Vec2};
+use epaint::{
+    text::cursor::{CCursor, Cursor},
+    Pos2, Vec2,
+};
 use rustc_hash::{FxHashMap, FxHashSet};
 use taffy::{prelude::*, Overflow};
 use winit::{
-    dpi::PhysicalPosition,
-    event::{ElementState, KeyEvent, Modifiers, MouseScrollDelta},
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, Force, Modifiers, MouseButton, MouseScrollDelta, TouchPhase},
+    keyboard::{Key, NamedKey, NativeKeyCode, PhysicalKey, SmolStr},
     window::CursorIcon,
 };
 
 use crate::{
+    anchor::{self, Placement},
+    bidi::{self, TextDirection},
+    drag::{DragPayload, DragState, AUTO_SCROLL_EDGE, AUTO_SCROLL_SPEED, DRAG_THRESHOLD},
+    element::{CustomElement, Element, ElementNode},
+    error::DomError,
     event_loop::DomContext,
+    event_queue::EventMetrics,
     events::{self, DomEvent, EventState, LayoutEvent},
+    filter::ColorMatrix,
+    handle::{DomSnapshot, SnapshotNode},
+    host::Host,
+    mutations::{AttributeValue, DomMutation, MutationBatch},
+    platform::Instant,
     renderer::{Renderer, ScreenDescriptor},
+    text,
 };
 
-use super::tailwind::{StyleState, Tailwind};
+use super::tailwind::{Overscroll, StyleState, Tailwind, ZLayer};
+
+/// Clickable nodes smaller than this, in points, are hit-tested as if they were this big for touches
+const MIN_TOUCH_TARGET: f32 = 48.0;
+
+/// How far in points the rubber band lets a scroll container be pulled past one of its ends
+const RUBBER_BAND_LIMIT: f32 = 120.0;
+/// The share of the pull that is left after every frame once the scrolling stops
+const RUBBER_BAND_RELAX: f32 = 0.8;
+
+/// How soon a press has to follow the previous one to count as a double, triple or quadruple click
+const MULTI_CLICK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// How much the visible share of a node has to change before ``visibilitychange`` is sent again
+const VISIBILITY_STEP: f32 = 0.05;
+
+/// Events that are suppressed on ``disabled`` elements
+const INTERACTION_EVENTS: &[&str] = &[
+    "click",
+    "mousedown",
+    "mouseup",
+    "mousemove",
+    "input",
+    "keydown",
+    "keyup",
+    "focus",
+    "drag",
+    "select",
+];
 
 pub struct Computed {
     /// The computed rect of the node, ready to be drawn
     pub rect: epaint::Rect,
     /// The computed galley of the text node, ready to be drawn
     pub galley: Option<Arc<epaint::Galley>>,
+    /// Where the subtree of a node with the ``layer`` class was painted to its texture
+    pub layer: Option<LayerCache>,
+    /// How far a node with the ``anchor`` attribute was moved from its laid out position, see [`Dom::apply_anchors`]
+    pub anchor_offset: Vec2,
+    /// The side of its anchor the node ended up on after flipping
+    pub placement: Option<Placement>,
+    /// Set on nodes with the ``transition-layout`` class once they have been laid out
+    pub layout_transition: Option<LayoutTransition>,
+    /// How far the rubber band pulled a scroll container past its ends, its content is moved along
+    pub overscroll: Vec2,
+    /// See [`Dom::set_animated_style`]
+    pub animated_style: AnimatedStyle,
+}
+
+/// Values an animation writes every frame, applied over the classes and the ``style`` attribute without parsing either
+/// of them again. Unset values leave what the classes say
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct AnimatedStyle {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    /// Moves the node and its children after layout, like ``translate`` in the ``style`` attribute
+    pub translate: Option<Vec2>,
+}
+
+impl AnimatedStyle {
+    pub(crate) fn apply(&self, style: &mut Style) {
+        if let Some(width) = self.width {
+            style.size.width = Dimension::Length(width);
+        }
+        if let Some(height) = self.height {
+            style.size.height = Dimension::Length(height);
+        }
+    }
+}
+
+/// A ``transition-layout`` node moving between two rects, ``rect`` of [`Computed`] is the painted one in between
+#[derive(Clone, Copy, Debug)]
+pub struct LayoutTransition {
+    pub from: epaint::Rect,
+    /// Where layout put the node
+    pub to: epaint::Rect,
+    pub started: Instant,
+    pub painted: epaint::Rect,
 }
 
 impl Default for Computed {
@@ -33,7 +120,96 @@ impl Default for Computed {
         Self {
             rect: epaint::Rect::from_min_size(epaint::Pos2::ZERO, epaint::Vec2::ZERO),
             galley: None,
+            layer: None,
+            anchor_offset: Vec2::ZERO,
+            placement: None,
+            layout_transition: None,
+            overscroll: Vec2::ZERO,
+            animated_style: AnimatedStyle::default(),
+        }
+    }
+}
+
+/// The texture itself is owned by the renderer, it's kept when the cache is dropped and painted over the next time
+pub struct LayerCache {
+    /// The rect of the layer node at the time it was painted
+    pub rect: epaint::Rect,
+    /// The area the texture covers in points, children and shadows can reach past the node. Empty when nothing was painted
+    pub bounds: epaint::Rect,
+    pub pixels_per_point: f32,
+    /// The filters of the layer and its ancestors it was painted with
+    pub color_matrix: Option<ColorMatrix>,
+}
+
+/// A node in the flat index [`Dom::hit_test`] walks, in pre-order so the entries of a subtree follow its root
+struct HitEntry {
+    node_id: NodeId,
+    rect: epaint::Rect,
+    /// Buttons and nodes listening for clicks, touches hit them in at least ``MIN_TOUCH_TARGET``
+    touch_target: bool,
+    /// Everything in the subtree could be hit within this, children can stick out of their parent
+    bounds: epaint::Rect,
+    /// Index of the first entry after the subtree
+    subtree_end: usize,
+    /// See [`Dom::z_layer`], inherited while the index is built
+    z_layer: ZLayer,
+}
+
+/// Grows small touch targets to ``MIN_TOUCH_TARGET`` around their center
+fn touch_target_rect(rect: epaint::Rect) -> epaint::Rect {
+    let grow = (Vec2::splat(MIN_TOUCH_TARGET) - rect.size()).max(Vec2::ZERO) * 0.5;
+    rect.expand2(grow)
+}
+
+/// A template root flattened in pre-order, instantiating it doesn't need to parse any classes
+/// and every parent gets all of its children attached at once
+struct BakedTemplate {
+    nodes: Vec<BakedNode>,
+}
+
+struct BakedNode {
+    tag: Tag,
+    attrs: FxHashMap<Arc<str>, Arc<str>>,
+    styling: Tailwind,
+    style: Style,
+    /// Index into ``BakedTemplate::nodes``, ``None`` for the root
+    parent: Option<usize>,
+}
+
+impl BakedTemplate {
+    fn instantiate(&self, tree: &mut TaffyTree<NodeContext>, parent_id: NodeId) -> NodeId {
+        let mut ids: Vec<NodeId> = Vec::with_capacity(self.nodes.len());
+        let mut children: Vec<Vec<NodeId>> = vec![Vec::new(); self.nodes.len()];
+
+        for node in &self.nodes {
+            let id = tree
+                .new_leaf_with_context(
+                    node.style.clone(),
+                    NodeContext {
+                        parent_id: Some(node.parent.map_or(parent_id, |index| ids[index])),
+                        tag: node.tag.clone(),
+                        attrs: node.attrs.clone(),
+                        styling: node.styling.clone(),
+                        scroll: Vec2::ZERO,
+                        computed: Default::default(),
+                        listeners: Default::default(),
+                    },
+                )
+                .unwrap();
+
+            if let Some(parent) = node.parent {
+                children[parent].push(id);
+            }
+            ids.push(id);
+        }
+
+        for (index, children) in children.iter().enumerate() {
+            if !children.is_empty() {
+                tree.set_children(ids[index], children).unwrap();
+            }
         }
+
+        ids[0]
     }
 }
 
@@ -41,6 +217,25 @@ impl Default for Computed {
 pub enum Tag {
     View,
     Text,
+    /// A view that is focusable and clickable with Enter and Space by default
+    Button,
+    /// A view with the behavior of a registered [`Element`]
+    Custom(CustomElement),
+}
+
+/// A copy of what the dom knows about a node, handed to event handlers through [`crate::events::EventState`]
+#[derive(Clone, Debug)]
+pub struct NodeInfo {
+    pub node_id: NodeId,
+    pub tag: Tag,
+    pub attrs: FxHashMap<Arc<str>, Arc<str>>,
+    /// Where the node was painted last, in points from the top left of the window
+    pub rect: epaint::Rect,
+    pub scroll: Vec2,
+    /// The classes that apply right now, ``hover:`` and the other variants only while they're active
+    pub classes: Vec<Arc<str>>,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
 }
 
 pub struct NodeContext {
@@ -60,8 +255,21 @@ impl NodeContext {
         }
 
         let galley = self.computed.galley.as_ref()?;
-        let cursor = galley.cursor_from_pos(pick_position - self.computed.rect.min.to_vec2());
-        Some(cursor)
+        let cursor = bidi::cursor_from_pos(
+            galley,
+            self.styling.text.direction,
+            pick_position - self.computed.rect.min.to_vec2(),
+        );
+        // clicking the second half of an emoji shouldn't put the cursor inside of it
+        let index = text::snap_to_grapheme(galley.text(), cursor.ccursor.index);
+        if index == cursor.ccursor.index {
+            return Some(cursor);
+        }
+        // keeps the row the pointer is on when the snapped index is at a line wrap
+        Some(galley.from_ccursor(CCursor {
+            index,
+            prefer_next_row: cursor.ccursor.prefer_next_row,
+        }))
     }
 }
 
@@ -77,6 +285,26 @@ pub struct CursorState {
     pub drag_end_position: Option<Pos2>,
 }
 
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerType {
+    #[default]
+    Mouse,
+    Touch,
+    Pen,
+}
+
+/// Details about the device behind the latest pointer event
+#[derive(Default, Debug, Clone, Copy)]
+pub struct PointerState {
+    pub pointer_type: PointerType,
+    /// Normalized between 0.0 and 1.0, mice report 0.5 while a button is held
+    pub pressure: f32,
+    /// Angle between the pen and the surface in radians, where 0 is parallel and PI/2 perpendicular
+    pub tilt: Option<f32>,
+    /// The touch that's currently used as the pointer
+    pub touch_id: Option<u64>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SelectedNode {
     pub node_id: NodeId,
@@ -93,6 +321,22 @@ pub struct FocusedNode {
     pub text_child_id: Option<NodeId>,
 }
 
+/// A press on the scrollbar of a scroll container, dragging the thumb moves the scroll offset along with the pointer
+#[derive(Debug, Clone, Copy)]
+pub struct ScrollbarDrag {
+    pub horizontal: bool,
+    /// Presses on the track page once and don't follow the pointer
+    pub on_thumb: bool,
+    /// Where the track starts along its axis
+    track_start: f32,
+    /// How far the thumb moves from one end of the track to the other
+    thumb_travel: f32,
+    /// Where the thumb was grabbed, from its start
+    grab_offset: f32,
+    /// The scroll offset at the end of the track
+    scroll_max: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct DomState {
     pub window_position: PhysicalPosition<i32>,
@@ -101,17 +345,121 @@ pub struct DomState {
     pub selection: Vec<SelectedNode>,
     pub keyboard_state: KeyboardState,
     pub cursor_state: CursorState,
+    /// The button that's currently held down with the pointer or keyboard
+    pub pressed: Option<NodeId>,
+    pub pointer: PointerState,
     pub last_clicked: Option<(Instant, Option<NodeId>)>,
+    /// Presses in a row on the same text node, 2 selects a word, 3 the paragraph and 4 all of the text
+    pub click_count: u32,
+    /// Scroll containers whose scrollbar is held down
+    pub scrollbar_drags: FxHashMap<NodeId, ScrollbarDrag>,
 }
 
 pub struct Dom {
     pub tree: TaffyTree<NodeContext>,
-    templates: FxHashMap<String, Vec<NodeId>>,
+    templates: FxHashMap<String, Vec<BakedTemplate>>,
     stack: Vec<NodeId>,
     pub element_id_mapping: FxHashMap<ElementId, NodeId>,
+    /// Reverse of ``element_id_mapping``, kept in sync by [`Dom::map_element`]
+    pub node_id_mapping: FxHashMap<NodeId, ElementId>,
     common_tags_and_attr_keys: FxHashSet<Arc<str>>,
     pub state: DomState,
     context: DomContext,
+    cursor_overrides: Vec<(usize, CursorIcon)>,
+    /// Scroll offsets of removed nodes with an ``id``, restored when a node with the same ``id`` mounts
+    saved_scroll_offsets: FxHashMap<Arc<str>, Vec2>,
+    /// The ``id`` of the focused node if it was removed, it's focused again when a node with that ``id`` mounts in the same batch
+    saved_focus: Option<Arc<str>>,
+    /// Document order of the children of parents that have ``order-*`` children, taffy keeps them in visual order
+    children_in_dom_order: FxHashMap<NodeId, Vec<NodeId>>,
+    /// Parents whose children have to be sorted by ``order-*`` again, see [`Dom::apply_child_order`]
+    pub(crate) reorder_queue: FxHashSet<NodeId>,
+    /// Nodes to render on their own after the next frame, see [`crate::DomHandle::capture_node`]
+    #[cfg(feature = "export")]
+    pub(crate) capture_requests: Vec<(NodeId, tokio::sync::oneshot::Sender<epaint::ColorImage>)>,
+    pub move_coalescing: MoveCoalescing,
+    /// Outlines nodes with classes the parser doesn't know in red, they're always logged to the ``tpaint::class`` target
+    pub show_unknown_classes: bool,
+    /// When the event being replayed was recorded, see [`Dom::now`]
+    pub(crate) replay_time: Option<Instant>,
+    /// Trackpads pull scroll containers past their ends, they bounce back once the scrolling stops
+    pub rubber_band: bool,
+    pub keyboard_scroll: KeyboardScroll,
+    /// Space in points kept between a node and the edges of its scroll containers when focusing scrolls it into view
+    pub focus_scroll_margin: f32,
+    /// Tags registered through [`crate::DomEventLoop::register_element`]
+    pub elements: FxHashMap<Arc<str>, Arc<dyn Element>>,
+    /// ``mousemove`` and ``drag`` events waiting for the next frame, see [`Dom::flush_moves`]
+    pending_moves: Vec<PendingMove>,
+    /// Nodes that scrolled since the last frame and their offset before that, see [`Dom::flush_scrolls`]
+    pending_scrolls: FxHashMap<NodeId, Vec2>,
+    /// What the last ``scroll`` event of a node was sent with, for its velocity and ``reachstart`` / ``reachend``
+    scroll_tracking: FxHashMap<NodeId, ScrollTracking>,
+    /// The press on a draggable node or the drag it turned into
+    pub(crate) drag: Option<DragState>,
+    /// Scroll containers with a rubber band that hasn't bounced back yet, see [`Dom::relax_overscroll`]
+    overscrolled: FxHashSet<NodeId>,
+    /// Nodes listening for ``mounted`` that haven't been laid out yet
+    pending_mounts: FxHashSet<NodeId>,
+    /// Nodes listening for ``visibilitychange`` and the ratio they were last sent, see [`Dom::update_visibility`]
+    visibility: FxHashMap<NodeId, Option<f32>>,
+    /// Nodes by their ``id`` attribute, in the order they got it when several share one
+    ids: FxHashMap<Arc<str>, Vec<NodeId>>,
+    /// The tree in paint order for [`Dom::hit_test`]
+    hit_index: Vec<HitEntry>,
+    /// Set when nodes were added, removed or moved since the index was built, or their [`ZLayer`] changed
+    pub(crate) hit_index_dirty: bool,
+    /// Nodes with a ``portal`` attribute by the node they were rendered in, see [`Dom::mount_portals`]
+    portals: FxHashMap<NodeId, Vec<NodeId>>,
+    /// Nodes whose ``portal`` attribute changed or whose host didn't exist yet
+    pending_portals: FxHashSet<NodeId>,
+}
+
+/// How pointer moves are sent to the VirtualDom thread, high polling rate mice can send a thousand moves a second
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveCoalescing {
+    /// Every move is sent right away
+    None,
+    /// Moves to the same node are merged into one event per frame, ``delta`` adds up all of them
+    #[default]
+    PerFrame,
+}
+
+/// How far the keys scroll the container around the focused node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyboardScroll {
+    /// In points, for the arrow keys
+    pub line: f32,
+    /// The part of what the container shows that PageUp, PageDown and Space scroll by
+    pub page: f32,
+}
+
+impl Default for KeyboardScroll {
+    fn default() -> Self {
+        Self { line: 40.0, page: 0.9 }
+    }
+}
+
+/// A node in view of a scrolled container, the container follows it when layout moves it
+pub(crate) struct ScrollAnchor {
+    container: NodeId,
+    node: NodeId,
+    /// From the top of the content of the container, before layout
+    offset: f32,
+}
+
+struct ScrollTracking {
+    sent_at: Instant,
+    near_start: bool,
+    near_end: bool,
+}
+
+struct PendingMove {
+    node_id: NodeId,
+    listener: &'static str,
+    delta: Vec2,
+    timestamp: Instant,
+    coalesced: usize,
 }
 
 impl Dom {
@@ -135,9 +483,12 @@ impl Dom {
 
         let mut element_id_mapping = FxHashMap::default();
         element_id_mapping.insert(ElementId(0), root_id);
+        let mut node_id_mapping = FxHashMap::default();
+        node_id_mapping.insert(root_id, ElementId(0));
 
         let mut common_tags_and_attr_keys = FxHashSet::default();
         common_tags_and_attr_keys.insert("view".into());
+        common_tags_and_attr_keys.insert("button".into());
         common_tags_and_attr_keys.insert("class".into());
         common_tags_and_attr_keys.insert("value".into());
         common_tags_and_attr_keys.insert("image".into());
@@ -147,6 +498,7 @@ impl Dom {
             templates: Default::default(),
             stack: Default::default(),
             element_id_mapping,
+            node_id_mapping,
             common_tags_and_attr_keys,
             state: DomState {
                 window_position: Default::default(),
@@ -155,73 +507,190 @@ impl Dom {
                 selection: vec![],
                 keyboard_state: Default::default(),
                 cursor_state: Default::default(),
+                pressed: None,
+                pointer: Default::default(),
                 last_clicked: None,
+                click_count: 0,
+                scrollbar_drags: Default::default(),
             },
             context,
+            cursor_overrides: vec![],
+            saved_scroll_offsets: Default::default(),
+            saved_focus: None,
+            children_in_dom_order: Default::default(),
+            reorder_queue: Default::default(),
+            #[cfg(feature = "export")]
+            capture_requests: Vec::new(),
+            move_coalescing: Default::default(),
+            show_unknown_classes: false,
+            replay_time: None,
+            rubber_band: false,
+            keyboard_scroll: Default::default(),
+            focus_scroll_margin: 8.0,
+            elements: FxHashMap::default(),
+            pending_moves: vec![],
+            pending_scrolls: Default::default(),
+            scroll_tracking: Default::default(),
+            drag: None,
+            overscrolled: Default::default(),
+            pending_mounts: Default::default(),
+            visibility: Default::default(),
+            ids: Default::default(),
+            portals: Default::default(),
+            pending_portals: Default::default(),
+            hit_index: vec![],
+            hit_index_dirty: true,
         }
     }
 
-    pub fn insert_node_before(&mut self, old_node_id: NodeId, new_id: NodeId) {
+    /// Starts over with an empty tree, for when the VirtualDom gets replaced
+    pub fn reset(&mut self) {
+        let move_coalescing = self.move_coalescing;
+        let show_unknown_classes = self.show_unknown_classes;
+        let rubber_band = self.rubber_band;
+        let keyboard_scroll = self.keyboard_scroll;
+        let focus_scroll_margin = self.focus_scroll_margin;
+        let elements = std::mem::take(&mut self.elements);
+        *self = Dom::new(self.context.clone());
+        self.move_coalescing = move_coalescing;
+        self.show_unknown_classes = show_unknown_classes;
+        self.rubber_band = rubber_band;
+        self.keyboard_scroll = keyboard_scroll;
+        self.focus_scroll_margin = focus_scroll_margin;
+        self.elements = elements;
+        self.check_and_set_cursor_icon();
+    }
+
+    /// Fails when ``old_node_id`` isn't among the children of its parent, the tree is left as it was
+    pub fn insert_node_before(&mut self, old_node_id: NodeId, new_id: NodeId) -> Result<(), DomError> {
+        self.insert_node_next_to(old_node_id, new_id, 0)
+    }
+
+    /// Fails when ``old_node_id`` isn't among the children of its parent, the tree is left as it was
+    pub fn insert_node_after(&mut self, old_node_id: NodeId, new_id: NodeId) -> Result<(), DomError> {
+        self.insert_node_next_to(old_node_id, new_id, 1)
+    }
+
+    /// The parent nodes can be inserted into next to ``old_node_id``, checked before anything moves.
+    /// A portal is laid out under its host instead of its parent, so it has no siblings to insert next to
+    fn sibling_parent(&self, old_node_id: NodeId) -> Result<NodeId, DomError> {
+        let missing = || DomError::MissingSibling(old_node_id);
         let parent_id = self
             .tree
-            .get_node_context_mut(old_node_id)
-            .unwrap()
-            .parent_id
-            .unwrap();
+            .get_node_context(old_node_id)
+            .and_then(|node| node.parent_id)
+            .ok_or_else(missing)?;
+        let is_child = self.tree.children(parent_id).is_ok_and(|children| children.contains(&old_node_id));
+        let missing_in_dom_order = self
+            .children_in_dom_order
+            .get(&parent_id)
+            .is_some_and(|children| !children.contains(&old_node_id));
+        if !is_child || missing_in_dom_order {
+            return Err(missing());
+        }
+        Ok(parent_id)
+    }
 
-        {
-            let node = self.tree.get_node_context_mut(new_id).unwrap();
-            node.parent_id = Some(parent_id);
+    /// ``offset`` is 0 to insert before ``old_node_id`` and 1 to insert after it
+    fn insert_node_next_to(&mut self, old_node_id: NodeId, new_id: NodeId, offset: usize) -> Result<(), DomError> {
+        let parent_id = self.sibling_parent(old_node_id)?;
+        if new_id == old_node_id {
+            return Err(DomError::MissingSibling(old_node_id));
         }
 
-        // let parent = self.nodes.get_mut(parent_id).unwrap();
-        let children = self.tree.children(parent_id).unwrap();
-        let index = children
+        self.detach_node(new_id);
+        self.tree.get_node_context_mut(new_id).unwrap().parent_id = Some(parent_id);
+
+        let index = self
+            .tree
+            .children(parent_id)
+            .unwrap()
             .iter()
             .position(|child| *child == old_node_id)
-            .unwrap();
-
+            .ok_or_else(missing)?;
         self.tree
-            .insert_child_at_index(parent_id, index, new_id)
+            .insert_child_at_index(parent_id, index + offset, new_id)
             .unwrap();
+
+        if let Some(children) = self.children_in_dom_order.get_mut(&parent_id) {
+            let index = children
+                .iter()
+                .position(|child| *child == old_node_id)
+                .ok_or_else(missing)?;
+            children.insert(index + offset, new_id);
+            self.reorder_queue.insert(parent_id);
+        }
+        Ok(())
     }
 
-    pub fn insert_node_after(&mut self, old_node_id: NodeId, new_id: NodeId) {
-        let parent_id = self
-            .tree
-            .get_node_context_mut(old_node_id)
-            .unwrap()
-            .parent_id
-            .unwrap();
+    /// Keyed lists move existing nodes with ``PushRoot`` followed by an insert, unhook them from their old
+    /// parent so their state (scroll, focus, layers) survives the move instead of ending up with two parents
+    fn detach_node(&mut self, node_id: NodeId) {
+        let Some(parent_id) = self.tree.parent(node_id) else {
+            return;
+        };
+        self.invalidate_layers(parent_id);
+        self.tree.remove_child(parent_id, node_id).unwrap();
+        if let Some(children) = self.children_in_dom_order.get_mut(&parent_id) {
+            children.retain(|child| *child != node_id);
+        }
+    }
 
-        {
-            let node = self.tree.get_node_context_mut(new_id).unwrap();
-            node.parent_id = Some(parent_id);
+    /// The children of a node in the order they're in the VirtualDom, which is what template paths index into
+    fn dom_children(&self, node_id: NodeId) -> Vec<NodeId> {
+        match self.children_in_dom_order.get(&node_id) {
+            Some(children) => children.clone(),
+            None => self.tree.children(node_id).unwrap(),
         }
+    }
 
-        let children = self.tree.children(parent_id).unwrap();
-        let index = children
-            .iter()
-            .position(|child| *child == old_node_id)
-            .unwrap();
+    /// Sorts the children of the queued parents by their ``order-*`` class, stable so equal orders keep the document order.
+    /// Taffy has no ``order`` of its own, so the children are handed to it in visual order.
+    pub fn apply_child_order(&mut self) {
+        for parent_id in std::mem::take(&mut self.reorder_queue) {
+            if self.tree.get_node_context(parent_id).is_none() {
+                continue;
+            }
 
-        self.tree
-            .insert_child_at_index(parent_id, index + 1, new_id)
-            .unwrap();
+            let dom_children = self.dom_children(parent_id);
+            let mut sorted = dom_children.clone();
+            sorted.sort_by_key(|child| {
+                self.tree
+                    .get_node_context(*child)
+                    .map(|node| node.styling.order)
+                    .unwrap_or_default()
+            });
+
+            if sorted != self.tree.children(parent_id).unwrap() {
+                self.tree.set_children(parent_id, &sorted).unwrap();
+                self.invalidate_layers(parent_id);
+            }
+            if sorted == dom_children {
+                self.children_in_dom_order.remove(&parent_id);
+            } else {
+                self.children_in_dom_order.insert(parent_id, dom_children);
+            }
+        }
     }
 
-    fn load_path(&self, path: &[u8]) -> NodeId {
-        let mut current_node_id = *self.stack.last().unwrap();
+    fn load_path(&self, path: &[u8]) -> Result<NodeId, DomError> {
+        let root_id = *self.stack.last().ok_or(DomError::StackUnderflow {
+            expected: 1,
+            len: 0,
+        })?;
+        self.load_path_from(root_id, path)
+    }
 
+    fn load_path_from(&self, root_id: NodeId, path: &[u8]) -> Result<NodeId, DomError> {
+        let mut current_node_id = root_id;
         for index in path {
-            let new_id = self
-                .tree
-                .child_at_index(current_node_id, *index as usize)
-                .unwrap();
-            current_node_id = new_id;
+            current_node_id = *self
+                .dom_children(current_node_id)
+                .get(*index as usize)
+                .ok_or_else(|| DomError::InvalidPath(path.to_vec()))?;
         }
 
-        current_node_id
+        Ok(current_node_id)
     }
 
     pub fn get_tag_or_attr_key(&mut self, key: &str) -> Arc<str> {
@@ -235,7 +704,14 @@ impl Dom {
         }
     }
 
-    fn create_template_node(&mut self, node: &TemplateNode, parent_id: Option<NodeId>) -> NodeId {
+    /// Flattens a template in pre-order with its classes already parsed, see [`BakedTemplate`]
+    fn bake_template_node(
+        &mut self,
+        node: &TemplateNode,
+        parent: Option<usize>,
+        nodes: &mut Vec<BakedNode>,
+    ) {
+        let index = nodes.len();
         match *node {
             TemplateNode::Element {
                 tag,
@@ -243,342 +719,1350 @@ impl Dom {
                 children,
                 ..
             } => {
-                let mut node = NodeContext {
-                    parent_id,
-                    tag: if tag == "text" { Tag::Text } else { Tag::View },
-                    attrs: attrs
-                        .iter()
-                        .filter_map(|val| {
-                            if let TemplateAttribute::Static { name, value, .. } = val {
-                                Some((self.get_tag_or_attr_key(name), (*value).into()))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect(),
-                    styling: Tailwind::default(),
-                    scroll: Vec2::ZERO,
-                    computed: Default::default(),
-                    listeners: Default::default(),
+                let attrs: FxHashMap<Arc<str>, Arc<str>> = attrs
+                    .iter()
+                    .filter_map(|val| {
+                        if let TemplateAttribute::Static { name, value, .. } = val {
+                            Some((self.get_tag_or_attr_key(name), self.get_tag_or_attr_key(value)))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                let mut styling = Tailwind::default();
+                let style = match attrs.get("class") {
+                    Some(class) => styling.get_style(class, &StyleState::default()),
+                    None => Style::default(),
                 };
-                let style = self.get_initial_styling(&mut node);
-                let node_id = self.tree.new_leaf_with_context(style, node).unwrap();
 
-                for child in children {
-                    let child = self.create_template_node(child, Some(node_id));
+                nodes.push(BakedNode {
+                    tag: match tag {
+                        "text" => Tag::Text,
+                        "button" => Tag::Button,
+                        _ => match self.elements.get(tag) {
+                            Some(element) => Tag::Custom(CustomElement::new(self.get_tag_or_attr_key(tag), element.clone())),
+                            None => Tag::View,
+                        },
+                    },
+                    attrs,
+                    styling,
+                    style,
+                    parent,
+                });
 
-                    self.tree.add_child(node_id, child).unwrap();
+                for child in children {
+                    self.bake_template_node(child, Some(index), nodes);
                 }
-
-                node_id
             }
             TemplateNode::Text { text } => {
                 let mut attrs = FxHashMap::default();
                 attrs.insert(self.get_tag_or_attr_key("value"), text.into());
-                attrs.insert(self.get_tag_or_attr_key("class"), "".into());
+                attrs.insert(self.get_tag_or_attr_key("class"), self.get_tag_or_attr_key(""));
 
-                let mut node = NodeContext {
-                    parent_id,
+                nodes.push(BakedNode {
                     tag: Tag::Text,
                     attrs,
                     styling: Tailwind::default(),
-                    scroll: Vec2::ZERO,
-                    computed: Default::default(),
-                    listeners: Default::default(),
-                };
-                let style = self.get_initial_styling(&mut node);
-                let node_id = self.tree.new_leaf_with_context(style, node).unwrap();
-
-                node_id
+                    style: Style::default(),
+                    parent,
+                });
             }
-
             TemplateNode::Dynamic { .. } => {
-                let node_id = self
-                    .tree
-                    .new_leaf_with_context(
-                        Style::default(),
-                        NodeContext {
-                            parent_id,
-                            tag: Tag::View,
-                            attrs: FxHashMap::default(),
-                            styling: Tailwind::default(),
-                            scroll: Vec2::ZERO,
-                            computed: Default::default(),
-                            listeners: Default::default(),
-                        },
-                    )
-                    .unwrap();
-
-                node_id
+                nodes.push(BakedNode {
+                    tag: Tag::View,
+                    attrs: FxHashMap::default(),
+                    styling: Tailwind::default(),
+                    style: Style::default(),
+                    parent,
+                });
             }
-
             TemplateNode::DynamicText { .. } => {
                 let mut attrs = FxHashMap::default();
-                attrs.insert(self.get_tag_or_attr_key("class"), "".into());
-                let node_id = self
-                    .tree
-                    .new_leaf_with_context(
-                        Style::default(),
-                        NodeContext {
-                            parent_id,
-                            tag: Tag::Text,
-                            attrs,
-                            styling: Tailwind::default(),
-                            scroll: Vec2::ZERO,
-                            computed: Default::default(),
-                            listeners: Default::default(),
-                        },
-                    )
-                    .unwrap();
+                attrs.insert(self.get_tag_or_attr_key("class"), self.get_tag_or_attr_key(""));
 
-                node_id
+                nodes.push(BakedNode {
+                    tag: Tag::Text,
+                    attrs,
+                    styling: Tailwind::default(),
+                    style: Style::default(),
+                    parent,
+                });
             }
         }
     }
 
-    #[tracing::instrument(skip_all, name = "Dom::apply_mutations")]
     pub fn apply_mutations(&mut self, mutations: Mutations) {
+        self.apply_batch(MutationBatch::from(mutations));
+    }
+
+    /// Named after [`Dom::apply_mutations`], which only converts the mutations, so the batches the event loop queues
+    /// show up under the same span
+    #[tracing::instrument(skip_all, name = "Dom::apply_mutations")]
+    pub(crate) fn apply_batch(&mut self, mutations: MutationBatch) {
         for template in mutations.templates {
-            let mut children = Vec::with_capacity(template.roots.len());
+            let mut roots = Vec::with_capacity(template.roots.len());
             for root in template.roots {
-                let id: NodeId =
-                    self.create_template_node(root, Some(self.element_id_mapping[&ElementId(0)]));
-                children.push(id);
+                let mut nodes = vec![];
+                self.bake_template_node(root, None, &mut nodes);
+                roots.push(BakedTemplate { nodes });
             }
-            self.templates.insert(template.name.to_string(), children);
+            self.templates.insert(template.name.to_string(), roots);
         }
 
+        let mut mounted = vec![];
         for edit in mutations.edits {
-            match edit {
-                dioxus::core::Mutation::LoadTemplate { name, index, id } => {
-                    let template_id = self.templates[name][index];
-                    let new_id =
-                        self.clone_node(template_id, self.element_id_mapping[&ElementId(0)]);
-                    self.stack.push(new_id);
-                    self.element_id_mapping.insert(id, new_id);
+            if let Err(error) = self.apply_mutation(edit, &mut mounted) {
+                self.report_error(error);
+            }
+        }
+        // moved once the batch is done, template paths that are loaded in it still point into the place they were rendered
+        self.mount_portals();
+        self.hit_index_dirty = true;
+
+        // restore once the whole batch is applied, replaced nodes are only removed after their replacement is inserted
+        for node_id in &mounted {
+            self.restore_scroll_offsets(*node_id);
+        }
+        self.restore_focus(&mounted);
+
+        self.check_and_set_cursor_icon();
+
+        if mutations.hot_reloaded {
+            self.send_document_event("hotreload", |state| {
+                events::Event::HotReload(events::HotReloadEvent { state })
+            });
+        }
+    }
+
+    fn restore_focus(&mut self, mounted: &[NodeId]) {
+        let Some(key) = self.saved_focus.take() else {
+            return;
+        };
+
+        let mut restored = None;
+        for node_id in mounted {
+            if self.tree.get_node_context(*node_id).is_none() {
+                continue;
+            }
+            self.traverse_tree(*node_id, &mut |dom, id| {
+                if restored.is_none() && dom.tree.get_node_context(id).unwrap().attrs.get("id") == Some(&key) {
+                    restored = Some(id);
                 }
-                dioxus::core::Mutation::AssignId { path, id } => {
-                    let node_id = self.load_path(path);
-                    self.element_id_mapping.insert(id, node_id);
+                restored.is_none()
+            });
+        }
+
+        // set directly, the node that had focus is gone so there's nothing to blur
+        if let Some(node_id) = restored {
+            self.state.focused = Some(FocusedNode {
+                node_id,
+                text_child_id: None,
+            });
+            self.context.host.set_ime_allowed(self.wants_text_input());
+        }
+    }
+
+    /// Errors leave the tree as it was before the mutation, so the rest of the batch can still be applied
+    fn apply_mutation(
+        &mut self,
+        edit: DomMutation,
+        mounted: &mut Vec<NodeId>,
+    ) -> Result<(), DomError> {
+        match edit {
+            DomMutation::LoadTemplate { name, index, id } => {
+                let root_id = self.get_root_id();
+                let template = self
+                    .templates
+                    .get(name)
+                    .and_then(|roots| roots.get(index))
+                    .ok_or_else(|| DomError::MissingTemplate {
+                        name: name.to_string(),
+                        index,
+                    })?;
+                let needs_walk = template
+                    .nodes
+                    .iter()
+                    .any(|node| node.attrs.contains_key("portal") || node.attrs.contains_key("id"));
+                let new_id = template.instantiate(&mut self.tree, root_id);
+                if needs_walk {
+                    self.traverse_tree(new_id, &mut |dom, id| {
+                        let attrs = &dom.tree.get_node_context(id).unwrap().attrs;
+                        if attrs.contains_key("portal") {
+                            dom.pending_portals.insert(id);
+                        }
+                        if let Some(value) = attrs.get("id").cloned() {
+                            dom.ids.entry(value).or_default().push(id);
+                        }
+                        true
+                    });
                 }
+                self.stack.push(new_id);
+                self.map_element(id, new_id);
+            }
+            DomMutation::AssignId { path, id } => {
+                let node_id = self.load_path(path)?;
+                self.map_element(id, node_id);
+            }
 
-                dioxus::core::Mutation::CreatePlaceholder { id } => {
-                    let node = NodeContext {
-                        parent_id: None,
-                        attrs: FxHashMap::default(),
-                        computed: Default::default(),
-                        listeners: Default::default(),
-                        scroll: Vec2::ZERO,
-                        styling: Tailwind::default(),
-                        tag: Tag::View,
-                    };
+            DomMutation::CreatePlaceholder { id } => {
+                let node = NodeContext {
+                    parent_id: None,
+                    attrs: FxHashMap::default(),
+                    computed: Default::default(),
+                    listeners: Default::default(),
+                    scroll: Vec2::ZERO,
+                    styling: Tailwind::default(),
+                    tag: Tag::View,
+                };
 
-                    let node_id = self
-                        .tree
-                        .new_leaf_with_context(Style::default(), node)
-                        .unwrap();
+                let node_id = self
+                    .tree
+                    .new_leaf_with_context(Style::default(), node)
+                    .unwrap();
 
-                    self.element_id_mapping.insert(id, node_id);
-                    self.stack.push(node_id);
-                }
+                self.map_element(id, node_id);
+                self.stack.push(node_id);
+            }
 
-                dioxus::core::Mutation::AppendChildren { m, id } => {
-                    let children = self.stack.split_off(self.stack.len() - m);
-                    let parent = self.element_id_mapping[&id];
-                    for child in children {
-                        self.tree.add_child(parent, child).unwrap();
+            DomMutation::AppendChildren { m, id } => {
+                let parent = self.get_node_id(id)?;
+                let children = self.pop_stack(m)?;
+                self.invalidate_layers(parent);
+                for child in children {
+                    self.detach_node(child);
+                    self.tree.get_node_context_mut(child).unwrap().parent_id = Some(parent);
+                    self.tree.add_child(parent, child).unwrap();
+                    if let Some(children) = self.children_in_dom_order.get_mut(&parent) {
+                        children.push(child);
+                        self.reorder_queue.insert(parent);
                     }
+                    mounted.push(child);
                 }
-                dioxus::core::Mutation::NewEventListener { name, id } => {
-                    let name = self.get_tag_or_attr_key(name);
-                    let node_id = self.element_id_mapping[&id];
-                    let node = self.tree.get_node_context_mut(node_id).unwrap();
-                    node.listeners.insert(name);
-                }
-                dioxus::core::Mutation::RemoveEventListener { name, id } => {
-                    let name = self.get_tag_or_attr_key(name);
-                    let node_id = self.element_id_mapping[&id];
-                    let node = self.tree.get_node_context_mut(node_id).unwrap();
-                    node.listeners.remove(&name);
-                }
-                dioxus::core::Mutation::SetAttribute {
-                    name, value, id, ..
-                } => {
-                    let node_id = self.element_id_mapping[&id];
-                    if let BorrowedAttributeValue::None = &value {
-                        let node = self.tree.get_node_context_mut(node_id).unwrap();
-                        node.attrs.remove(name);
-                    } else {
-                        let key = self.get_tag_or_attr_key(name);
-                        let node = self.tree.get_node_context_mut(node_id).unwrap();
-                        node.attrs.insert(
-                            key,
-                            match value {
-                                BorrowedAttributeValue::Int(val) => (val.to_string()).into(),
-                                BorrowedAttributeValue::Bool(val) => (val.to_string()).into(),
-                                BorrowedAttributeValue::Float(val) => (val.to_string()).into(),
-                                BorrowedAttributeValue::Text(val) => val.into(),
-                                BorrowedAttributeValue::None => "".into(),
-                                BorrowedAttributeValue::Any(_) => unimplemented!(),
-                            },
-                        );
+            }
+            DomMutation::NewEventListener { name, id } => {
+                let name = self.get_tag_or_attr_key(&name);
+                let node_id = self.get_node_id(id)?;
+                match &*name {
+                    "mounted" => {
+                        self.pending_mounts.insert(node_id);
+                    }
+                    "visibilitychange" => {
+                        self.visibility.insert(node_id, None);
                     }
+                    _ => {}
                 }
-                dioxus::core::Mutation::CreateTextNode { value, id } => {
-                    let mut attrs = FxHashMap::default();
-                    attrs.insert(self.get_tag_or_attr_key("value"), value.into());
-                    attrs.insert(self.get_tag_or_attr_key("class"), "".into());
-
-                    let node = NodeContext {
-                        parent_id: None,
-                        attrs,
-                        computed: Default::default(),
-                        listeners: Default::default(),
-                        scroll: Vec2::ZERO,
-                        styling: Tailwind::default(),
-                        tag: Tag::Text,
-                    };
-                    let node_id = self
-                        .tree
-                        .new_leaf_with_context(Style::default(), node)
-                        .unwrap();
-
-                    self.element_id_mapping.insert(id, node_id);
-                    self.stack.push(node_id);
+                let node = self.tree.get_node_context_mut(node_id).unwrap();
+                node.listeners.insert(name);
+            }
+            DomMutation::RemoveEventListener { name, id } => {
+                let name = self.get_tag_or_attr_key(&name);
+                let node_id = self.get_node_id(id)?;
+                if &*name == "visibilitychange" {
+                    self.visibility.remove(&node_id);
                 }
-                dioxus::core::Mutation::HydrateText { path, value, id } => {
-                    let node_id = self.load_path(path);
-                    let key = self.get_tag_or_attr_key("value");
-                    self.element_id_mapping.insert(id, node_id);
-                    let node = self.tree.get_node_context_mut(node_id).unwrap();
-                    node.attrs.insert(key, value.into());
+                let node = self.tree.get_node_context_mut(node_id).unwrap();
+                node.listeners.remove(&name);
+            }
+            DomMutation::SetAttribute { name, value, id } => {
+                let node_id = self.get_node_id(id)?;
+                if let AttributeValue::Unsupported = &value {
+                    return Err(DomError::UnsupportedAttributeValue {
+                        name: name.to_string(),
+                    });
                 }
-                dioxus::core::Mutation::SetText { value, id } => {
-                    let node_id = self.element_id_mapping[&id];
-                    let key = self.get_tag_or_attr_key("value");
-                    let node = self.tree.get_node_context_mut(node_id).unwrap();
-                    node.attrs.insert(key, value.into());
+                self.invalidate_layers(node_id);
+                // the measure function reads this one, taffy has to ask for it again
+                if &*name == "natural_size" {
                     self.tree.mark_dirty(node_id).unwrap();
-                    self.state
-                        .selection
-                        .retain(|range| range.node_id != node_id);
                 }
-                dioxus::core::Mutation::ReplaceWith { id, m } => {
-                    let new_nodes = self.stack.split_off(self.stack.len() - m);
-                    let old_node_id = self.element_id_mapping[&id];
-                    for new_id in new_nodes {
-                        self.insert_node_before(old_node_id, new_id);
-                    }
-                    self.remove_node(old_node_id);
+                if &*name == "portal" {
+                    self.pending_portals.insert(node_id);
                 }
-                dioxus::core::Mutation::ReplacePlaceholder { path, m } => {
-                    let new_nodes = self.stack.split_off(self.stack.len() - m);
-                    let old_node_id = self.load_path(path);
-
-                    for new_id in new_nodes {
-                        self.insert_node_before(old_node_id, new_id);
+                if &*name == "id" {
+                    self.unindex_id(node_id);
+                    if let AttributeValue::Text(value) = &value {
+                        self.ids.entry(value.clone()).or_default().push(node_id);
                     }
-
-                    self.remove_node(old_node_id);
                 }
-
-                dioxus::core::Mutation::InsertAfter { id, m } => {
-                    let new_nodes = self.stack.split_off(self.stack.len() - m);
-                    let old_node_id = self.element_id_mapping[&id];
-                    for new_id in new_nodes.into_iter().rev() {
-                        self.insert_node_after(old_node_id, new_id);
+                match value {
+                    AttributeValue::None => {
+                        let node = self.tree.get_node_context_mut(node_id).unwrap();
+                        node.attrs.remove(&*name);
                     }
-                }
-
-                dioxus::core::Mutation::InsertBefore { id, m } => {
-                    let new_nodes = self.stack.split_off(self.stack.len() - m);
-                    let old_node_id = self.element_id_mapping[&id];
-                    for new_id in new_nodes {
-                        self.insert_node_before(old_node_id, new_id);
+                    AttributeValue::Text(_) | AttributeValue::Unsupported => {
+                        let key = self.get_tag_or_attr_key(&name);
+                        let value = match value {
+                            AttributeValue::Text(value) => value,
+                            _ => "".into(),
+                        };
+                        let node = self.tree.get_node_context_mut(node_id).unwrap();
+                        node.attrs.insert(key, value);
                     }
                 }
+            }
+            DomMutation::CreateTextNode { value, id } => {
+                let mut attrs = FxHashMap::default();
+                attrs.insert(self.get_tag_or_attr_key("value"), value);
+                attrs.insert(self.get_tag_or_attr_key("class"), "".into());
 
-                dioxus::core::Mutation::Remove { id } => {
-                    let node_id = self.element_id_mapping[&id];
-                    self.remove_node(node_id);
-                }
+                let node = NodeContext {
+                    parent_id: None,
+                    attrs,
+                    computed: Default::default(),
+                    listeners: Default::default(),
+                    scroll: Vec2::ZERO,
+                    styling: Tailwind::default(),
+                    tag: Tag::Text,
+                };
+                let node_id = self
+                    .tree
+                    .new_leaf_with_context(Style::default(), node)
+                    .unwrap();
 
-                dioxus::core::Mutation::PushRoot { id } => {
-                    let node_id = self.element_id_mapping[&id];
-                    self.stack.push(node_id);
+                self.map_element(id, node_id);
+                self.stack.push(node_id);
+            }
+            DomMutation::HydrateText { path, value, id } => {
+                let node_id = self.load_path(path)?;
+                let key = self.get_tag_or_attr_key("value");
+                self.map_element(id, node_id);
+                let node = self.tree.get_node_context_mut(node_id).unwrap();
+                node.attrs.insert(key, value);
+            }
+            DomMutation::SetText { value, id } => {
+                let node_id = self.get_node_id(id)?;
+                self.invalidate_layers(node_id);
+                let key = self.get_tag_or_attr_key("value");
+                let node = self.tree.get_node_context_mut(node_id).unwrap();
+                node.attrs.insert(key, value);
+                self.tree.mark_dirty(node_id).unwrap();
+                self.state
+                    .selection
+                    .retain(|range| range.node_id != node_id);
+            }
+            DomMutation::ReplaceWith { id, m } => {
+                let old_node_id = self.get_node_id(id)?;
+                let new_nodes = self.pop_siblings(old_node_id, m)?;
+                self.invalidate_layers(old_node_id);
+                for new_id in new_nodes {
+                    self.insert_node_before(old_node_id, new_id)?;
+                    mounted.push(new_id);
                 }
+                self.remove_node(old_node_id);
             }
-        }
-
-        self.check_and_set_cursor_icon();
-    }
+            DomMutation::ReplacePlaceholder { path, m } => {
+                // the path points into the template under the new nodes, it's resolved before they're popped so
+                // a bad path leaves the stack as it was
+                let template_id = self
+                    .stack
+                    .len()
+                    .checked_sub(m + 1)
+                    .map(|index| self.stack[index])
+                    .ok_or(DomError::StackUnderflow {
+                        expected: m + 1,
+                        len: self.stack.len(),
+                    })?;
+                let old_node_id = self.load_path_from(template_id, path)?;
+                let new_nodes = self.pop_siblings(old_node_id, m)?;
+                self.invalidate_layers(old_node_id);
+
+                for new_id in new_nodes {
+                    self.insert_node_before(old_node_id, new_id)?;
+                    mounted.push(new_id);
+                }
 
-    /// Clone node and its children, they all get new ids
-    pub fn clone_node(&mut self, node_id: NodeId, parent_id: NodeId) -> NodeId {
-        let (tag, attrs, styling) = {
-            let ctx = self.tree.get_node_context_mut(node_id).unwrap();
+                self.remove_node(old_node_id);
+            }
 
-            (ctx.tag.clone(), ctx.attrs.clone(), ctx.styling.clone())
-        };
+            DomMutation::InsertAfter { id, m } => {
+                let old_node_id = self.get_node_id(id)?;
+                let new_nodes = self.pop_siblings(old_node_id, m)?;
+                self.invalidate_layers(old_node_id);
+                for new_id in new_nodes.into_iter().rev() {
+                    self.insert_node_after(old_node_id, new_id)?;
+                    mounted.push(new_id);
+                }
+            }
 
-        let mut node = NodeContext {
-            parent_id: Some(parent_id),
-            tag,
-            attrs,
-            styling,
-            scroll: Vec2::ZERO,
-            computed: Default::default(),
-            listeners: Default::default(),
-        };
-        let style = self.get_initial_styling(&mut node);
+            DomMutation::InsertBefore { id, m } => {
+                let old_node_id = self.get_node_id(id)?;
+                let new_nodes = self.pop_siblings(old_node_id, m)?;
+                self.invalidate_layers(old_node_id);
+                for new_id in new_nodes {
+                    self.insert_node_before(old_node_id, new_id)?;
+                    mounted.push(new_id);
+                }
+            }
 
-        let cloned_node = self.tree.new_leaf_with_context(style, node).unwrap();
+            DomMutation::Remove { id } => {
+                let node_id = self.get_node_id(id)?;
+                self.invalidate_layers(node_id);
+                self.remove_node(node_id);
+            }
 
-        for children in self.tree.children(node_id).unwrap().iter() {
-            let new_id = self.clone_node(*children, cloned_node);
-            self.tree.add_child(cloned_node, new_id).unwrap();
+            DomMutation::PushRoot { id } => {
+                let node_id = self.get_node_id(id)?;
+                self.stack.push(node_id);
+            }
         }
 
-        cloned_node
+        Ok(())
     }
 
-    pub fn get_root_id(&self) -> NodeId {
-        self.element_id_mapping[&ElementId(0)]
+    fn get_node_id(&self, id: ElementId) -> Result<NodeId, DomError> {
+        self.element_id_mapping
+            .get(&id)
+            .copied()
+            .ok_or(DomError::MissingElement(id))
     }
 
-    pub fn remove_node(&mut self, id: NodeId) {
-        // remove children recursively
-        for child in self.tree.children(id).unwrap().iter() {
-            self.remove_node(*child);
+    /// Pops the ``m`` nodes that go next to ``old_node_id``, only once it's certain they can be inserted there,
+    /// so a detached node leaves the stack as it was
+    fn pop_siblings(&mut self, old_node_id: NodeId, m: usize) -> Result<Vec<NodeId>, DomError> {
+        self.sibling_parent(old_node_id)?;
+        let start = self.stack.len().checked_sub(m).ok_or(DomError::StackUnderflow {
+            expected: m,
+            len: self.stack.len(),
+        })?;
+        if self.stack[start..].contains(&old_node_id) {
+            return Err(DomError::MissingSibling(old_node_id));
         }
-        self.tree.remove(id).unwrap();
+        self.pop_stack(m)
     }
 
-    pub fn print_tree(&mut self) {
-        self.tree.print_tree(self.get_root_id());
+    fn pop_stack(&mut self, m: usize) -> Result<Vec<NodeId>, DomError> {
+        if m > self.stack.len() {
+            return Err(DomError::StackUnderflow {
+                expected: m,
+                len: self.stack.len(),
+            });
+        }
+        Ok(self.stack.split_off(self.stack.len() - m))
     }
 
-    pub fn get_initial_styling(&mut self, node_context: &mut NodeContext) -> Style {
-        let Some(class) = node_context.attrs.get(&self.get_tag_or_attr_key("class")) else {
-            return Style::default();
-        };
-        node_context
-            .styling
-            .get_style(class, &StyleState::default())
+    /// Logs the error and passes it on to the handler set with [`crate::DomEventLoop::on_error`]
+    pub fn report_error(&self, error: DomError) {
+        self.context.report_error(error);
     }
 
-    /// Return true to continue traversal, false to stop
-    pub fn traverse_tree(
+    pub fn set_error_handler(&self, on_error: impl FnMut(DomError) + Send + 'static) {
+        *self.context.on_error.lock().unwrap() = Some(Box::new(on_error));
+    }
+
+    /// Dioxus re-uses element ids, so the previous node of the element id loses its mapping
+    fn map_element(&mut self, element_id: ElementId, node_id: NodeId) {
+        if let Some(old_node_id) = self.element_id_mapping.insert(element_id, node_id) {
+            if self.node_id_mapping.get(&old_node_id) == Some(&element_id) {
+                self.node_id_mapping.remove(&old_node_id);
+            }
+        }
+        if let Some(old_element_id) = self.node_id_mapping.insert(node_id, element_id) {
+            if old_element_id != element_id
+                && self.element_id_mapping.get(&old_element_id) == Some(&node_id)
+            {
+                self.element_id_mapping.remove(&old_element_id);
+            }
+        }
+    }
+
+    pub fn get_root_id(&self) -> NodeId {
+        self.element_id_mapping[&ElementId(0)]
+    }
+
+    /// Removes the node and everything under it. The subtree is collected with an explicit stack, so very deep trees
+    /// don't overflow the call stack
+    pub fn remove_node(&mut self, id: NodeId) {
+        let mut subtree = vec![];
+        let mut stack = vec![id];
+        while let Some(node_id) = stack.pop() {
+            subtree.push(node_id);
+            stack.extend(self.tree.children(node_id).unwrap());
+            // portals are laid out under their host, but they go away with the node that rendered them
+            stack.extend(self.portals.remove(&node_id).unwrap_or_default());
+        }
+
+        // children before their parents
+        for node_id in subtree.into_iter().rev() {
+            self.forget_node(node_id);
+        }
+    }
+
+    /// Removes a single node from the tree and every bookkeeping it's in
+    fn forget_node(&mut self, id: NodeId) {
+        // a portal is reached through its host and the node that rendered it
+        if self.tree.get_node_context(id).is_none() {
+            return;
+        }
+        if let Some(portals) = self
+            .tree
+            .get_node_context(id)
+            .and_then(|node| node.parent_id)
+            .and_then(|owner| self.portals.get_mut(&owner))
+        {
+            portals.retain(|portal| *portal != id);
+        }
+        self.pending_portals.remove(&id);
+
+        self.children_in_dom_order.remove(&id);
+        self.pending_scrolls.remove(&id);
+        self.scroll_tracking.remove(&id);
+        self.overscrolled.remove(&id);
+        self.pending_mounts.remove(&id);
+        self.visibility.remove(&id);
+        self.unindex_id(id);
+        if self.drag.as_ref().is_some_and(|drag| drag.source == id) {
+            self.drag = None;
+        } else if let Some(drag) = self.drag.as_mut().filter(|drag| drag.target == Some(id)) {
+            drag.target = None;
+        }
+        if let Some(children) = self
+            .tree
+            .parent(id)
+            .and_then(|parent_id| self.children_in_dom_order.get_mut(&parent_id))
+        {
+            children.retain(|child| *child != id);
+        }
+
+        let node = self.tree.get_node_context(id).unwrap();
+        if let Some(key) = node.attrs.get("id") {
+            if node.scroll != Vec2::ZERO {
+                self.saved_scroll_offsets.insert(key.clone(), node.scroll);
+            }
+            if self.state.focused.is_some_and(|focused| focused.node_id == id) {
+                self.saved_focus = Some(key.clone());
+            }
+        }
+
+        if let Some(element_id) = self.node_id_mapping.remove(&id) {
+            if self.element_id_mapping.get(&element_id) == Some(&id) {
+                self.element_id_mapping.remove(&element_id);
+            }
+        }
+
+        self.tree.remove(id).unwrap();
+    }
+
+    fn restore_scroll_offsets(&mut self, node_id: NodeId) {
+        if self.saved_scroll_offsets.is_empty() || self.tree.get_node_context(node_id).is_none() {
+            return;
+        }
+
+        self.traverse_tree(node_id, &mut |dom, id| {
+            let node = dom.tree.get_node_context_mut(id).unwrap();
+            if let Some(offset) = node
+                .attrs
+                .get("id")
+                .and_then(|key| dom.saved_scroll_offsets.remove(key))
+            {
+                node.scroll = offset;
+            }
+            true
+        });
+    }
+
+    pub fn get_scroll_offset(&self, node_id: NodeId) -> Option<Vec2> {
+        self.tree.get_node_context(node_id).map(|node| node.scroll)
+    }
+
+    /// Grabs the thumb of the topmost scrollbar under the pointer, or pages towards the pointer when the track
+    /// was pressed. Returns whether a scrollbar was hit
+    fn press_scrollbar(&mut self, renderer: &Renderer) -> bool {
+        let position = self.state.cursor_state.current_position;
+        let hit = self.state.hovered.iter().rev().find_map(|id| {
+            let node = self.tree.get_node_context(*id)?;
+            let style = self.tree.style(*id).ok()?;
+            let layout = self.tree.layout(*id).ok()?;
+            if style.scrollbar_width <= 0.0 {
+                return None;
+            }
+            let are_both_scrollbars_visible =
+                style.overflow.x == Overflow::Scroll && style.overflow.y == Overflow::Scroll;
+            [false, true].into_iter().find_map(|horizontal| {
+                let overflow = if horizontal { style.overflow.x } else { style.overflow.y };
+                if overflow != Overflow::Scroll {
+                    return None;
+                }
+                let track =
+                    renderer.get_scrollbar_rect(node, style.scrollbar_width, horizontal, are_both_scrollbars_visible);
+                if !track.contains(position) {
+                    return None;
+                }
+                let thumb = renderer.get_scroll_thumb_rect(
+                    node,
+                    layout,
+                    style.scrollbar_width,
+                    horizontal,
+                    are_both_scrollbars_visible,
+                );
+                let along = |pos: Pos2| if horizontal { pos.x } else { pos.y };
+                let pointer = along(position);
+                let drag = ScrollbarDrag {
+                    horizontal,
+                    on_thumb: pointer >= along(thumb.min) && pointer <= along(thumb.max),
+                    track_start: along(track.min),
+                    thumb_travel: (along(track.max) - along(track.min)) - (along(thumb.max) - along(thumb.min)),
+                    grab_offset: pointer - along(thumb.min),
+                    scroll_max: if horizontal { layout.scroll_width() } else { layout.scroll_height() },
+                };
+                // a page is what the container shows, towards the side of the thumb the pointer is on
+                let page = if horizontal { node.computed.rect.width() } else { node.computed.rect.height() };
+                let page = if pointer < along(thumb.min) { -page } else { page };
+                let page = if horizontal { Vec2::new(page, 0.0) } else { Vec2::new(0.0, page) };
+                Some((*id, drag, node.scroll + page))
+            })
+        });
+
+        let Some((node_id, drag, paged_offset)) = hit else {
+            return false;
+        };
+        if !drag.on_thumb {
+            self.set_scroll_offset(node_id, paged_offset);
+        }
+        self.state.scrollbar_drags.insert(node_id, drag);
+        true
+    }
+
+    /// Moves the scroll offset of every container whose thumb is held to where the pointer put the thumb
+    fn drag_scrollbars(&mut self) {
+        let position = self.state.cursor_state.current_position;
+        for (node_id, drag) in self.state.scrollbar_drags.clone() {
+            let Some(mut offset) = self.get_scroll_offset(node_id) else {
+                continue;
+            };
+            if !drag.on_thumb {
+                continue;
+            }
+            let along = if drag.horizontal { position.x } else { position.y };
+            let fraction = if drag.thumb_travel > 0.0 {
+                ((along - drag.grab_offset - drag.track_start) / drag.thumb_travel).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            if drag.horizontal {
+                offset.x = fraction * drag.scroll_max;
+            } else {
+                offset.y = fraction * drag.scroll_max;
+            }
+            self.set_scroll_offset(node_id, offset);
+        }
+    }
+
+    /// Clamped to the scrollable area of the last computed layout
+    pub fn set_scroll_offset(&mut self, node_id: NodeId, offset: Vec2) {
+        let Ok(layout) = self.tree.layout(node_id) else {
+            return;
+        };
+        let offset = Vec2::new(
+            offset.x.max(0.0).min(layout.scroll_width()),
+            offset.y.max(0.0).min(layout.scroll_height()),
+        );
+
+        self.invalidate_layers(node_id);
+        if let Some(node) = self.tree.get_node_context_mut(node_id) {
+            let previous = node.scroll;
+            node.scroll = offset;
+            self.queue_scroll(node_id, previous);
+        }
+    }
+
+    /// The node with a matching ``id`` attribute, the one that got it first when several share it
+    pub fn get_node_by_id(&self, id: &str) -> Option<NodeId> {
+        self.ids.get(id)?.first().copied()
+    }
+
+    /// Sends an event to the node like the dom does for input, e.g. from tests or tooling
+    pub fn dispatch_event(&mut self, node_id: NodeId, name: &str, event: events::Event, bubbles: bool) {
+        self.send_event_to_element(node_id, name, Arc::new(event), bubbles);
+    }
+
+    /// Takes the node out of [`Dom::get_node_by_id`] under the ``id`` it has now
+    fn unindex_id(&mut self, node_id: NodeId) {
+        let Some(id) = self
+            .tree
+            .get_node_context(node_id)
+            .and_then(|node| node.attrs.get("id"))
+        else {
+            return;
+        };
+        if let Some(nodes) = self.ids.get_mut(id) {
+            nodes.retain(|node| *node != node_id);
+            if nodes.is_empty() {
+                self.ids.remove(id);
+            }
+        }
+    }
+
+    /// The fast path for animations, see [`AnimatedStyle`]. Only the taffy style of the node is touched, the parsed
+    /// classes stay cached. Values that get unset make the classes of the node apply again on the next frame
+    pub fn set_animated_style(&mut self, node_id: NodeId, animated_style: AnimatedStyle) {
+        let Some(node) = self.tree.get_node_context_mut(node_id) else {
+            return;
+        };
+        let previous = std::mem::replace(&mut node.computed.animated_style, animated_style);
+        if previous == animated_style {
+            return;
+        }
+        let unset = (previous.width.is_some() && animated_style.width.is_none())
+            || (previous.height.is_some() && animated_style.height.is_none());
+        if unset {
+            node.styling.cache = Default::default();
+        } else if previous.width != animated_style.width || previous.height != animated_style.height {
+            let mut style = self.tree.style(node_id).unwrap().clone();
+            animated_style.apply(&mut style);
+            self.tree.set_style(node_id, style).unwrap();
+        }
+        self.invalidate_layers(node_id);
+    }
+
+    /// A copy of a node, [`crate::DomHandle::node_info`] uses it to walk the tree from an event target
+    pub fn node_info(&self, node_id: NodeId) -> Option<NodeInfo> {
+        let node = self.tree.get_node_context(node_id)?;
+        let class = node.attrs.get("class").map(|class| &**class).unwrap_or("");
+        Some(NodeInfo {
+            node_id,
+            tag: node.tag.clone(),
+            attrs: node.attrs.clone(),
+            rect: node.computed.rect,
+            scroll: node.scroll,
+            classes: node.styling.cache.state.active_classes(class).into_iter().map(Arc::from).collect(),
+            parent: node.parent_id,
+            children: self.tree.children(node_id).unwrap_or_default(),
+        })
+    }
+
+    /// Brings a snapshot from an earlier frame up to date, nodes that didn't change keep their allocations
+    pub(crate) fn update_snapshot(&self, snapshot: &mut DomSnapshot) {
+        snapshot.frame += 1;
+        let frame = snapshot.frame;
+
+        snapshot.ids.clear();
+        snapshot.ids.extend(self.ids.iter().filter_map(|(id, nodes)| Some((id.clone(), *nodes.first()?))));
+        snapshot.selected_text.clear();
+        if !self.state.selection.is_empty() {
+            snapshot.selected_text.push_str(&self.selected_text());
+        }
+
+        let mut stack = vec![self.get_root_id()];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = self.tree.get_node_context(node_id) else {
+                continue;
+            };
+            let children = self.tree.children(node_id).unwrap_or_default();
+            match snapshot.nodes.get_mut(&node_id) {
+                Some(entry) => entry.update(frame, node, &children),
+                None => {
+                    snapshot.nodes.insert(node_id, SnapshotNode::new(frame, node, children.clone()));
+                }
+            }
+            stack.extend(children.into_iter().rev());
+        }
+        // removed nodes weren't visited
+        snapshot.nodes.retain(|_, node| node.frame == frame);
+    }
+
+    /// Picks a node to keep still in every vertically scrolled container, runs before every layout. It's the first node
+    /// that starts inside of the viewport, nodes that are cut off by the top are searched for one first.
+    /// Containers at the very top aren't anchored, so new items show up there like they would without anchoring
+    pub(crate) fn capture_scroll_anchors(&mut self) -> Vec<ScrollAnchor> {
+        let mut anchors = vec![];
+        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
+            let node = dom.tree.get_node_context(id).unwrap();
+            if dom.tree.style(id).unwrap().overflow.y != Overflow::Scroll
+                || node.scroll.y <= 0.0
+                || node.styling.scroll_anchor_disabled
+            {
+                return true;
+            }
+            let viewport = node.computed.rect;
+
+            let mut parent = id;
+            let anchor = loop {
+                let visible = dom.tree.children(parent).unwrap().into_iter().find(|child| {
+                    let rect = dom.tree.get_node_context(*child).unwrap().computed.rect;
+                    rect.height() > 0.0 && rect.max.y > viewport.min.y && rect.min.y < viewport.max.y
+                });
+                match visible {
+                    Some(child)
+                        if dom.tree.get_node_context(child).unwrap().computed.rect.min.y < viewport.min.y
+                            && !dom.tree.children(child).unwrap().is_empty() =>
+                    {
+                        parent = child
+                    }
+                    visible => break visible,
+                }
+            };
+            if let Some(anchor) = anchor {
+                anchors.push(ScrollAnchor {
+                    container: id,
+                    node: anchor,
+                    offset: dom.content_offset(anchor, id),
+                });
+            }
+            true
+        });
+        anchors
+    }
+
+    /// Scrolls the containers by as much as their anchor moved in layout, so what's on screen doesn't jump when items
+    /// are added above it, e.g. older messages of a chat
+    pub(crate) fn apply_scroll_anchors(&mut self, anchors: Vec<ScrollAnchor>) {
+        for anchor in anchors {
+            if !self.tree.get_node_context(anchor.node).is_some_and(|node| node.parent_id.is_some()) {
+                continue;
+            }
+            let delta = self.content_offset(anchor.node, anchor.container) - anchor.offset;
+            if delta.abs() < f32::EPSILON {
+                continue;
+            }
+            let max = self.tree.layout(anchor.container).unwrap().scroll_height();
+            let node = self.tree.get_node_context_mut(anchor.container).unwrap();
+            let previous = node.scroll;
+            node.scroll.y = (node.scroll.y + delta).clamp(0.0, max.max(0.0));
+            self.invalidate_layers(anchor.container);
+            self.queue_scroll(anchor.container, previous);
+        }
+    }
+
+    /// Where ``node_id`` is in the content of ``container``, scroll offsets of containers in between are left out
+    fn content_offset(&self, node_id: NodeId, container: NodeId) -> f32 {
+        let mut offset = 0.0;
+        let mut current = Some(node_id);
+        while let Some(id) = current.filter(|id| *id != container) {
+            offset += self.tree.layout(id).unwrap().location.y;
+            current = self.tree.get_node_context(id).unwrap().parent_id;
+        }
+        offset
+    }
+
+    /// Moves every node with an ``anchor`` attribute next to the node with that ``id``, runs after every layout.
+    /// The offset is kept so the next layout starts from the same spot, returns the nodes that moved.
+    pub fn apply_anchors(&mut self, viewport: epaint::Rect) -> Vec<NodeId> {
+        let mut floating_nodes = vec![];
+        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
+            if dom.tree.get_node_context(id).unwrap().attrs.contains_key("anchor") {
+                floating_nodes.push(id);
+            }
+            true
+        });
+
+        let mut moved_nodes = vec![];
+        for id in floating_nodes {
+            let node = self.tree.get_node_context(id).unwrap();
+            let placement = node
+                .attrs
+                .get("placement")
+                .and_then(|placement| Placement::parse(placement))
+                .unwrap_or_default();
+            let gap = node
+                .attrs
+                .get("anchor_gap")
+                .and_then(|gap| gap.parse::<f32>().ok())
+                .unwrap_or(0.0);
+            let rect = node.computed.rect;
+            let anchor_offset = node.computed.anchor_offset;
+            let anchor_rect = self
+                .get_node_by_id(&node.attrs["anchor"])
+                .map(|anchor_id| self.tree.get_node_context(anchor_id).unwrap().computed.rect);
+
+            // without its anchor the node goes back to where it was laid out
+            let (min, placement) = match anchor_rect {
+                Some(anchor_rect) => {
+                    let (min, placement) = anchor::place(anchor_rect, rect.size(), placement, gap, viewport);
+                    (min, Some(placement))
+                }
+                None => (rect.min - anchor_offset, None),
+            };
+
+            let node = self.tree.get_node_context_mut(id).unwrap();
+            node.computed.placement = placement;
+            let delta = min - rect.min;
+            if delta == Vec2::ZERO {
+                continue;
+            }
+            node.computed.anchor_offset += delta;
+
+            self.traverse_tree(id, &mut |dom, id| {
+                let node = dom.tree.get_node_context_mut(id).unwrap();
+                node.computed.rect = node.computed.rect.translate(delta);
+                moved_nodes.push(id);
+                true
+            });
+            self.invalidate_layers(id);
+        }
+
+        moved_nodes
+    }
+
+    /// Moves nodes with the ``transition-layout`` class from their painted rect towards the one layout gave them,
+    /// their children follow along. Runs after every layout, returns the nodes that moved and whether any is still moving.
+    pub fn apply_layout_transitions(&mut self, now: Instant) -> (Vec<NodeId>, bool) {
+        let mut transitioning_nodes = vec![];
+        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
+            if dom.tree.get_node_context(id).unwrap().styling.layout_transition {
+                transitioning_nodes.push(id);
+            }
+            true
+        });
+
+        let mut moved_nodes = vec![];
+        let mut is_animating = false;
+        // children first, their targets must not include the offset of a moving parent
+        for id in transitioning_nodes.into_iter().rev() {
+            let node = self.tree.get_node_context_mut(id).unwrap();
+            let target = node.computed.rect;
+            let duration = node.styling.transition_duration;
+            let transition = node.computed.layout_transition.get_or_insert(LayoutTransition {
+                from: target,
+                to: target,
+                started: now,
+                painted: target,
+            });
+            if transition.to != target {
+                transition.from = transition.painted;
+                transition.to = target;
+                transition.started = now;
+            }
+
+            let t = if duration > 0.0 {
+                (now.duration_since(transition.started).as_secs_f32() / duration).min(1.0)
+            } else {
+                1.0
+            };
+            // ease in out, like tailwind's default timing function
+            let eased = t * t * (3.0 - 2.0 * t);
+            let painted = epaint::Rect::from_min_max(
+                transition.from.min.lerp(transition.to.min, eased),
+                transition.from.max.lerp(transition.to.max, eased),
+            );
+            transition.painted = painted;
+            if t >= 1.0 {
+                transition.from = target;
+            } else {
+                is_animating = true;
+            }
+
+            if painted == target {
+                continue;
+            }
+            node.computed.rect = painted;
+            moved_nodes.push(id);
+
+            let offset = painted.min - target.min;
+            for child in self.tree.children(id).unwrap() {
+                self.traverse_tree(child, &mut |dom, id| {
+                    let node = dom.tree.get_node_context_mut(id).unwrap();
+                    node.computed.rect = node.computed.rect.translate(offset);
+                    moved_nodes.push(id);
+                    true
+                });
+            }
+            self.invalidate_layers(id);
+        }
+
+        (moved_nodes, is_animating)
+    }
+
+    /// Drops the cached painting of every ``layer`` the node is part of, so they get repainted next frame.
+    /// Follows the layout tree, a portal is painted as part of its host
+    pub fn invalidate_layers(&mut self, node_id: NodeId) {
+        let mut current_node_id = Some(node_id);
+        while let Some(id) = current_node_id {
+            let Some(node) = self.tree.get_node_context_mut(id) else {
+                break;
+            };
+            node.computed.layer = None;
+            current_node_id = self.tree.parent(id);
+        }
+    }
+
+    /// Moves the nodes with a ``portal`` attribute under their host, the node with that ``id`` or the root when it's
+    /// empty. Their ``parent_id`` stays the node they were rendered in, so events bubble through the component
+    /// tree while layout, clipping and painting follow the host. Nodes whose attribute was removed move back
+    pub fn mount_portals(&mut self) {
+        let mut waiting = FxHashSet::default();
+        for node_id in std::mem::take(&mut self.pending_portals) {
+            let Some(node) = self.tree.get_node_context(node_id) else {
+                continue;
+            };
+            let Some(owner) = node.parent_id else {
+                continue;
+            };
+            let host = match node.attrs.get("portal") {
+                None => owner,
+                Some(host) if host.is_empty() || &**host == "root" => self.get_root_id(),
+                Some(host) => match self.get_node_by_id(host) {
+                    Some(host) => host,
+                    None => {
+                        log::warn!("portal host {host} doesn't exist yet");
+                        waiting.insert(node_id);
+                        continue;
+                    }
+                },
+            };
+            if self.tree.parent(node_id) == Some(host) {
+                continue;
+            }
+
+            self.detach_node(node_id);
+            self.invalidate_layers(host);
+            self.tree.add_child(host, node_id).unwrap();
+            if let Some(children) = self.children_in_dom_order.get_mut(&host) {
+                children.push(node_id);
+                self.reorder_queue.insert(host);
+            }
+
+            let portals = self.portals.entry(owner).or_default();
+            portals.retain(|portal| *portal != node_id);
+            if host != owner {
+                portals.push(node_id);
+            }
+        }
+        self.pending_portals = waiting;
+    }
+
+    /// The current time, or when the event being replayed was recorded. Timing like double clicks and the coalescing of
+    /// moves goes by this, so a replay behaves like the recording did however fast it runs
+    pub fn now(&self) -> Instant {
+        self.replay_time.unwrap_or_else(Instant::now)
+    }
+
+    /// Measures the node again on the next layout and repaints the ``layer`` nodes it's in, for nodes whose size or
+    /// shapes changed without their attributes changing, like a custom [`crate::element::Element`]
+    pub fn mark_dirty(&mut self, node_id: NodeId) {
+        // the node might've been removed since the id was handed out
+        if self.tree.get_node_context(node_id).is_none() {
+            return;
+        }
+        self.tree.mark_dirty(node_id).unwrap();
+        self.invalidate_layers(node_id);
+    }
+
+    /// Forces every node to be measured and painted again
+    pub fn invalidate_all_layouts(&mut self) {
+        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
+            dom.tree.mark_dirty(id).unwrap();
+            dom.tree.get_node_context_mut(id).unwrap().computed.layer = None;
+            true
+        });
+    }
+
+    /// False for nodes under ``pointer-events-none``, unless a closer ancestor or the node itself has
+    /// ``pointer-events-auto``. Their children are still hit tested.
+    /// Nodes under ``hidden`` or ``invisible`` never get them, their rects are stale or not shown
+    pub fn receives_pointer_events(&self, node_id: NodeId) -> bool {
+        let mut pointer_events = None;
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let Some(node) = self.tree.get_node_context(id) else {
+                break;
+            };
+            if node.styling.invisible || self.tree.style(id).is_ok_and(|style| style.display == Display::None) {
+                return false;
+            }
+            pointer_events = pointer_events.or(node.styling.pointer_events);
+            current = node.parent_id;
+        }
+        pointer_events.unwrap_or(true)
+    }
+
+    /// Whether the node or one of its ancestors has ``disabled`` set
+    pub fn is_disabled(&self, node_id: NodeId) -> bool {
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let Some(node) = self.tree.get_node_context(id) else {
+                return false;
+            };
+            if node.attrs.get("disabled").is_some_and(|value| &**value == "true") {
+                return true;
+            }
+            current = node.parent_id;
+        }
+        false
+    }
+
+    /// The color filters of the node and its ancestors folded together, ``None`` when there are none
+    pub fn color_matrix(&self, node_id: NodeId) -> Option<ColorMatrix> {
+        let mut matrix: Option<ColorMatrix> = None;
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let node = self.tree.get_node_context(id)?;
+            if !node.styling.filter.is_identity() {
+                let own = node.styling.filter.matrix();
+                // the filter of a child runs before the one of its parent
+                matrix = Some(match matrix {
+                    Some(inner) => inner.followed_by(&own),
+                    None => own,
+                });
+            }
+            current = self.tree.parent(id);
+        }
+        matrix
+    }
+
+    /// Logs the classes of the node the parser didn't understand, called whenever its ``class`` changes
+    pub(crate) fn report_unknown_classes(&self, node_id: NodeId) {
+        let node = self.tree.get_node_context(node_id).unwrap();
+        if node.styling.unknown_classes.is_empty() {
+            return;
+        }
+        log::warn!(
+            target: "tpaint::class",
+            "unknown classes {} on {:?} {:?}{}",
+            node.styling.unknown_classes.join(", "),
+            node.tag,
+            node_id,
+            node.attrs.get("id").map(|id| format!(" with id {id}")).unwrap_or_default(),
+        );
+    }
+
+    /// The ``dir`` attribute of the node or its closest ancestor that has one
+    pub fn text_direction(&self, node_id: NodeId) -> TextDirection {
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let Some(node) = self.tree.get_node_context(id) else {
+                break;
+            };
+            if let Some(direction) = node.attrs.get("dir").and_then(|dir| TextDirection::parse(dir)) {
+                return direction;
+            }
+            current = node.parent_id;
+        }
+        TextDirection::Auto
+    }
+
+    /// Whether an ancestor with the ``group`` class is hovered
+    pub fn is_group_hovered(&self, node_id: NodeId) -> bool {
+        let mut current = self.tree.get_node_context(node_id).and_then(|node| node.parent_id);
+        while let Some(id) = current {
+            let Some(node) = self.tree.get_node_context(id) else {
+                return false;
+            };
+            let is_group = node
+                .attrs
+                .get("class")
+                .is_some_and(|class| class.split_whitespace().any(|class| class == "group"));
+            if is_group && self.state.hovered.contains(&id) {
+                return true;
+            }
+            current = node.parent_id;
+        }
+        false
+    }
+
+    /// Enter and Space click the focused element if it acts like a button
+    fn is_activation_key(&self, node_id: NodeId, logical_key: &Key) -> bool {
+        let Some(node) = self.tree.get_node_context(node_id) else {
+            return false;
+        };
+
+        (node.tag == Tag::Button || node.attrs.get("role").is_some_and(|role| &**role == "button"))
+            && matches!(logical_key, Key::Named(NamedKey::Enter | NamedKey::Space))
+    }
+
+    /// The nodes under ``position`` in paint order, so the last one is on top, see [`ZLayer`]. Subtrees that can't contain the
+    /// position are skipped, which keeps pointer moves cheap in large trees
+    pub fn hit_test(&mut self, position: Pos2, is_touch: bool) -> Vec<NodeId> {
+        if self.hit_index_dirty {
+            self.rebuild_hit_index();
+        }
+
+        let mut hits = vec![];
+        let mut index = 0;
+        while let Some(entry) = self.hit_index.get(index) {
+            if !entry.bounds.contains(position) {
+                index = entry.subtree_end;
+                continue;
+            }
+            let rect = if is_touch && entry.touch_target {
+                touch_target_rect(entry.rect)
+            } else {
+                entry.rect
+            };
+            if rect.contains(position)
+                && self.tree.get_node_context(entry.node_id).is_some()
+                && self.receives_pointer_events(entry.node_id)
+            {
+                hits.push((entry.node_id, entry.z_layer));
+            }
+            index += 1;
+        }
+        // stable, so the nodes of a layer stay in the order of the tree
+        hits.sort_by_key(|(_, z_layer)| *z_layer);
+        hits.into_iter().map(|(node_id, _)| node_id).collect()
+    }
+
+    /// The layer the node is painted in, the highest one set on it or its ancestors. Inside of a ``layer`` node the
+    /// subtree is painted at once, so its nodes are in the layer of the ``layer`` node whatever they set themselves
+    pub fn z_layer(&self, node_id: NodeId) -> ZLayer {
+        let mut z_layer = ZLayer::Base;
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let Some(node) = self.tree.get_node_context(id) else {
+                break;
+            };
+            if node.styling.layer && id != node_id {
+                z_layer = ZLayer::Base;
+            }
+            z_layer = z_layer.max(node.styling.z_layer.unwrap_or_default());
+            current = self.tree.parent(id);
+        }
+        z_layer
+    }
+
+    /// Flattens the tree into [`HitEntry`]s, happens on the first hit test after the tree or a rect changed
+    fn rebuild_hit_index(&mut self) {
+        let mut entries = Vec::with_capacity(self.hit_index.len());
+        self.push_hit_entries(self.get_root_id(), &mut entries);
+        self.hit_index = entries;
+        self.hit_index_dirty = false;
+    }
+
+    /// Pushes the tree in pre-order, then folds the bounds of every entry into its parent from the back
+    fn push_hit_entries(&self, root_id: NodeId, entries: &mut Vec<HitEntry>) {
+        let first = entries.len();
+        // the index of the parent entry of every entry
+        let mut parents: Vec<Option<usize>> = vec![];
+        // the layer of the parent and whether it's in a ``layer`` node, see [`Dom::z_layer`]
+        let mut stack = vec![(root_id, None, (ZLayer::Base, false))];
+        while let Some((node_id, parent, (parent_z_layer, in_layer))) = stack.pop() {
+            let node = self.tree.get_node_context(node_id).unwrap();
+            let rect = node.computed.rect;
+            let touch_target = node.tag == Tag::Button || node.listeners.contains("click");
+            let z_layer = if in_layer {
+                parent_z_layer
+            } else {
+                parent_z_layer.max(node.styling.z_layer.unwrap_or_default())
+            };
+            let in_layer = in_layer || node.styling.layer;
+            let index = entries.len();
+            entries.push(HitEntry {
+                node_id,
+                rect,
+                touch_target,
+                bounds: if touch_target { touch_target_rect(rect) } else { rect },
+                subtree_end: 0,
+                z_layer,
+            });
+            parents.push(parent);
+            stack.extend(
+                self.tree.children(node_id).unwrap().into_iter().rev().map(|child| (child, Some(index), (z_layer, in_layer))),
+            );
+        }
+
+        // children come after their parent, so walking backwards finishes every subtree before its parent
+        for index in (first..entries.len()).rev() {
+            if entries[index].subtree_end == 0 {
+                entries[index].subtree_end = index + 1;
+            }
+            if let Some(parent) = parents[index - first] {
+                let (bounds, subtree_end) = (entries[index].bounds, entries[index].subtree_end);
+                let parent = &mut entries[parent];
+                parent.bounds = parent.bounds.union(bounds);
+                parent.subtree_end = parent.subtree_end.max(subtree_end);
+            }
+        }
+    }
+
+    /// Selects the text from ``from`` to ``to`` in document order. The first and last node are selected from and up to
+    /// the nearest character, every text node in between entirely, whichever way the pointer went
+    pub fn select_between(&mut self, from: Pos2, to: Pos2) {
+        self.clear_selection();
+        let nodes = self.selectable_text_nodes();
+        let (Some(anchor), Some(focus)) = (self.text_position_at(&nodes, from), self.text_position_at(&nodes, to)) else {
+            return;
+        };
+        let (start, end) = if (anchor.0, anchor.1.ccursor.index) <= (focus.0, focus.1.ccursor.index) {
+            (anchor, focus)
+        } else {
+            (focus, anchor)
+        };
+        if start.0 == end.0 && start.1.ccursor.index == end.1.ccursor.index {
+            return;
+        }
+
+        for (index, node_id) in nodes.iter().enumerate().take(end.0 + 1).skip(start.0) {
+            let node = self.tree.get_node_context(*node_id).unwrap();
+            let galley = node.computed.galley.as_ref().unwrap();
+            let start_cursor = if index == start.0 { start.1 } else { galley.from_ccursor(CCursor::new(0)) };
+            let end_cursor = if index == end.0 { end.1 } else { galley.end() };
+            self.set_selection(*node_id, start_cursor, end_cursor, false);
+        }
+    }
+
+    /// Laid out text nodes in document order, leaving out the ones under ``global_selection_mode: "off"``
+    fn selectable_text_nodes(&mut self) -> Vec<NodeId> {
+        let mut nodes = vec![];
+        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
+            let node = dom.tree.get_node_context(id).unwrap();
+            if node.attrs.get("global_selection_mode").is_some_and(|mode| &**mode == "off") {
+                return false;
+            }
+            if node.tag == Tag::Text && node.computed.galley.is_some() {
+                nodes.push(id);
+            }
+            true
+        });
+        nodes
+    }
+
+    /// The text node closest to ``position`` as an index into ``nodes``, and the cursor in it
+    fn text_position_at(&self, nodes: &[NodeId], position: Pos2) -> Option<(usize, Cursor)> {
+        let (index, node) = nodes
+            .iter()
+            .map(|id| self.tree.get_node_context(*id).unwrap())
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                let a = a.computed.rect.distance_sq_to_pos(position);
+                let b = b.computed.rect.distance_sq_to_pos(position);
+                a.total_cmp(&b)
+            })?;
+        Some((index, node.get_text_cursor(position.to_vec2())?))
+    }
+
+    /// The selected text in document order, with a line break between text nodes of different parents
+    pub fn selected_text(&self) -> String {
+        let mut selected_text = String::new();
+        let mut previous_parent = None;
+        for selected in &self.state.selection {
+            let Some(galley) = self
+                .tree
+                .get_node_context(selected.node_id)
+                .and_then(|node| node.computed.galley.as_ref())
+            else {
+                continue;
+            };
+            let (start, end) = (selected.start_cursor.ccursor.index, selected.end_cursor.ccursor.index);
+            if previous_parent.is_some_and(|parent| parent != selected.parent_id) {
+                selected_text.push('\n');
+            }
+            selected_text.push_str(&galley.text()[text::byte_range(galley.text(), start..end)]);
+            previous_parent = Some(selected.parent_id);
+        }
+        selected_text
+    }
+
+    pub fn clear_selection(&mut self) {
+        for selected in std::mem::take(&mut self.state.selection) {
+            self.invalidate_layers(selected.node_id);
+        }
+    }
+
+    pub fn print_tree(&mut self) {
+        self.tree.print_tree(self.get_root_id());
+    }
+
+    /// Return true to continue traversal, false to stop. Visits in pre-order with an explicit stack, the children of
+    /// a node are read after its callback ran
+    pub fn traverse_tree(
         &mut self,
         id: NodeId,
         callback: &mut impl FnMut(&mut Dom, NodeId) -> bool,
     ) {
-        let should_continue = callback(self, id);
-        if !should_continue {
-            return;
-        }
-        for child in self.tree.children(id).unwrap().iter() {
-            self.traverse_tree(*child, callback);
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if callback(self, id) {
+                stack.extend(self.tree.children(id).unwrap().into_iter().rev());
+            }
         }
     }
 
@@ -588,23 +2072,15 @@ impl Dom {
         parent_id: Option<NodeId>,
         callback: &mut impl FnMut(&mut Dom, NodeId, Option<NodeId>) -> bool,
     ) {
-        if let Some(parent_id) = parent_id {
-            let should_continue = callback(self, id, Some(parent_id));
-            if !should_continue {
-                return;
+        let mut stack = vec![(id, parent_id)];
+        while let Some((id, parent_id)) = stack.pop() {
+            if callback(self, id, parent_id) {
+                stack.extend(self.tree.children(id).unwrap().into_iter().rev().map(|child| (child, Some(id))));
             }
-        } else {
-            let should_continue = callback(self, id, None);
-            if !should_continue {
-                return;
-            }
-        };
-
-        for child in self.tree.children(id).unwrap().iter() {
-            self.traverse_tree_with_parent(*child, Some(id), callback);
         }
     }
 
+    /// Every node gets the data its parent returned, shared between siblings
     pub fn traverse_tree_mut_with_parent_and_data<T>(
         &mut self,
         id: NodeId,
@@ -612,24 +2088,21 @@ impl Dom {
         data: &T,
         callback: &mut impl FnMut(&mut Dom, NodeId, Option<NodeId>, &T) -> (bool, T),
     ) {
-        let data = if let Some(parent_id) = parent_id {
-            let (should_continue, new_data) = callback(self, id, Some(parent_id), data);
-            if !should_continue {
-                return;
-            }
+        let (should_continue, data) = callback(self, id, parent_id, data);
+        if !should_continue {
+            return;
+        }
 
-            new_data
-        } else {
-            let (should_continue, new_data) = callback(self, id, None, data);
+        let mut stack: Vec<(NodeId, NodeId, Rc<T>)> = vec![];
+        let data = Rc::new(data);
+        stack.extend(self.tree.children(id).unwrap().into_iter().rev().map(|child| (child, id, data.clone())));
+        while let Some((id, parent_id, data)) = stack.pop() {
+            let (should_continue, data) = callback(self, id, Some(parent_id), &data);
             if !should_continue {
-                return;
+                continue;
             }
-
-            new_data
-        };
-
-        for child in self.tree.children(id).unwrap().iter() {
-            self.traverse_tree_mut_with_parent_and_data(*child, Some(id), &data, callback);
+            let data = Rc::new(data);
+            stack.extend(self.tree.children(id).unwrap().into_iter().rev().map(|child| (child, id, data.clone())));
         }
     }
 
@@ -640,6 +2113,19 @@ impl Dom {
         event: Arc<events::Event>,
         bubbles: bool,
     ) {
+        // disabled elements don't react to the user
+        if INTERACTION_EVENTS.contains(&listener) && self.is_disabled(node_id) {
+            return;
+        }
+
+        if let Some(node) = self.tree.get_node_context(node_id) {
+            if let Tag::Custom(custom) = &node.tag {
+                if custom.element().on_event(ElementNode::new(node_id, node), listener, &event) {
+                    return;
+                }
+            }
+        }
+
         let listener = self.get_tag_or_attr_key(listener);
         let mut current_node_id = node_id;
         if bubbles {
@@ -658,14 +2144,11 @@ impl Dom {
                     }
                 };
 
-                let Some((element_id, ..)) = self
-                    .element_id_mapping
-                    .iter()
-                    .find(|(_, id)| **id == current_node_id)
-                else {
+                let Some(element_id) = self.node_id_mapping.get(&current_node_id) else {
                     return;
                 };
 
+                // fails when the VirtualDom thread is gone after a panic
                 self.context
                     .event_sender
                     .send(DomEvent {
@@ -674,7 +2157,7 @@ impl Dom {
                         element_id: *element_id,
                         bubbles: false,
                     })
-                    .unwrap();
+                    .ok();
                 break;
             }
         } else {
@@ -686,11 +2169,7 @@ impl Dom {
                 return;
             };
 
-            let Some((element_id, ..)) = self
-                .element_id_mapping
-                .iter()
-                .find(|(_, id)| **id == current_node_id)
-            else {
+            let Some(element_id) = self.node_id_mapping.get(&current_node_id) else {
                 return;
             };
 
@@ -702,7 +2181,7 @@ impl Dom {
                     element_id: *element_id,
                     bubbles: false,
                 })
-                .unwrap();
+                .ok();
         }
     }
 
@@ -723,121 +2202,347 @@ impl Dom {
         screen_descriptor: &ScreenDescriptor,
     ) -> bool {
         let position = Self::translate_mouse_pos(position, screen_descriptor);
+        let delta = position - self.state.cursor_state.current_position;
         self.state.cursor_state.current_position = position;
-        self.state.hovered.clear();
-        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
-            let node = dom.tree.get_node_context_mut(id).unwrap();
-            let rect = node.computed.rect;
-            let is_hovered = rect.contains(epaint::Pos2::new(
-                dom.state.cursor_state.current_position.x as f32,
-                dom.state.cursor_state.current_position.y as f32,
-            ));
-            if is_hovered {
-                dom.state.hovered.push(id);
-            }
-            true
-        });
+        let is_touch = self.state.pointer.pointer_type == PointerType::Touch;
+        self.state.hovered = self.hit_test(position, is_touch);
+        // a held scrollbar gets the pointer to itself, nothing else is dragged or selected
+        if !self.state.scrollbar_drags.is_empty() {
+            self.drag_scrollbars();
+            self.check_and_set_cursor_icon();
+            return true;
+        }
+        self.update_drag();
 
+        // pressing a draggable node never selects text
         if self.state.cursor_state.drag_start_position.is_some()
             && self.state.cursor_state.drag_end_position.is_none()
+            && self.drag.is_none()
         {
             if let Some(start_position) = self.state.cursor_state.drag_start_position {
-                let min = Pos2::new(
-                    start_position
-                        .x
-                        .min(self.state.cursor_state.current_position.x),
-                    start_position
-                        .y
-                        .min(self.state.cursor_state.current_position.y),
-                );
+                let end_position = self
+                    .state
+                    .cursor_state
+                    .drag_end_position
+                    .unwrap_or(self.state.cursor_state.current_position);
+                self.select_between(start_position, end_position);
+            }
 
-                let max = Pos2::new(
-                    start_position
-                        .x
-                        .max(self.state.cursor_state.current_position.x),
-                    start_position
-                        .y
-                        .max(self.state.cursor_state.current_position.y),
-                );
+            // send drag event to the focused node
+            if let Some(focused) = self.state.focused {
+                self.queue_move(focused.node_id, "drag", delta);
+            }
+        }
 
-                let selection_rect = epaint::Rect::from_min_max(min, max);
+        if let Some(hovered) = self.state.hovered.last() {
+            self.queue_move(*hovered, "mousemove", delta);
+        }
+        if self.move_coalescing == MoveCoalescing::None {
+            self.flush_moves();
+        }
 
-                self.state.selection.clear();
-                self.traverse_tree(self.get_root_id(), &mut |dom, id| {
-                    let node = dom.tree.get_node_context_mut(id).unwrap();
-                    if let Some(selection_mode) = node.attrs.get("global_selection_mode") {
-                        if *selection_mode == "off".into() {
-                            return false;
-                        }
-                    }
-                    if node.tag != Tag::Text {
-                        return true;
-                    }
-                    if node.computed.rect.intersects(selection_rect) {
-                        let mut start_cursor =
-                            node.get_text_cursor(start_position.to_vec2()).unwrap();
-                        let mut end_cursor = node
-                            .get_text_cursor(
-                                dom.state
-                                    .cursor_state
-                                    .drag_end_position
-                                    .unwrap_or(dom.state.cursor_state.current_position)
-                                    .to_vec2(),
-                            )
-                            .unwrap();
-
-                        // swap cursors if the selection is backwards
-                        if start_cursor.pcursor.offset > end_cursor.pcursor.offset {
-                            std::mem::swap(&mut start_cursor, &mut end_cursor);
-                        }
+        self.check_and_set_cursor_icon();
 
-                        dom.set_selection(id, start_cursor, end_cursor, false);
-                    }
+        true
+    }
 
-                    false
-                });
+    fn queue_move(&mut self, node_id: NodeId, listener: &'static str, delta: Vec2) {
+        let timestamp = self.now();
+        if let Some(pending) = self
+            .pending_moves
+            .iter_mut()
+            .find(|pending| pending.node_id == node_id && pending.listener == listener)
+        {
+            pending.delta += delta;
+            pending.timestamp = timestamp;
+            pending.coalesced += 1;
+            return;
+        }
+
+        self.pending_moves.push(PendingMove {
+            node_id,
+            listener,
+            delta,
+            timestamp,
+            coalesced: 1,
+        });
+    }
+
+    /// Sends the queued ``mousemove`` and ``drag`` events, called before every frame and before clicks so they stay in order
+    pub fn flush_moves(&mut self) {
+        for pending in std::mem::take(&mut self.pending_moves) {
+            if self.tree.get_node_context(pending.node_id).is_none() {
+                continue;
+            }
+            let state = EventState::new(self, pending.node_id);
+            let event = match pending.listener {
+                "drag" => events::Event::Drag(events::DragEvent {
+                    state,
+                    timestamp: pending.timestamp,
+                    delta: pending.delta,
+                    coalesced: pending.coalesced,
+                }),
+                _ => events::Event::MouseMove(events::MouseMoveEvent {
+                    state,
+                    timestamp: pending.timestamp,
+                    delta: pending.delta,
+                    coalesced: pending.coalesced,
+                }),
+            };
+            self.send_event_to_element(pending.node_id, pending.listener, Arc::new(event), true);
+        }
+        self.flush_drag_over();
+    }
+
+    fn drag_event(&self, node_id: NodeId, payload: &DragPayload, dropped: bool) -> Arc<events::Event> {
+        Arc::new(events::Event::DragDrop(events::DragDropEvent {
+            state: EventState::new(self, node_id),
+            payload: payload.clone(),
+            position: self.state.cursor_state.current_position,
+            dropped,
+        }))
+    }
+
+    /// Remembers a press on a draggable node, it becomes a drag in [`Dom::update_drag`]
+    fn press_draggable(&mut self) {
+        let source = self.state.hovered.iter().rev().copied().find(|id| {
+            self.tree
+                .get_node_context(*id)
+                .is_some_and(|node| node.attrs.get("draggable").is_some_and(|value| &**value == "true"))
+        });
+        self.drag = source.filter(|id| !self.is_disabled(*id)).map(|source| {
+            let payload = DragPayload::default();
+            if let Some(data) = self.tree.get_node_context(source).unwrap().attrs.get("drag_data") {
+                payload.set(data.to_string());
             }
+            DragState {
+                source,
+                origin: self.state.cursor_state.current_position,
+                active: false,
+                payload,
+                target: None,
+                moved: false,
+            }
+        });
+    }
 
-            // send drag event to the focused node
-            if let Some(focused) = self.state.focused {
-                self.send_event_to_element(
-                    focused.node_id,
-                    "drag",
-                    Arc::new(events::Event::Drag(events::DragEvent {
-                        state: EventState::new(self, focused.node_id),
-                    })),
-                    true,
-                );
+    /// Starts the drag once the pointer moved far enough and follows the drop target under it, called on every move
+    fn update_drag(&mut self) {
+        let position = self.state.cursor_state.current_position;
+        let Some(drag) = &mut self.drag else {
+            return;
+        };
+        if !drag.active {
+            if (position - drag.origin).length() < DRAG_THRESHOLD {
+                return;
             }
+            drag.active = true;
+            let (source, payload) = (drag.source, drag.payload.clone());
+            self.clear_selection();
+            let event = self.drag_event(source, &payload, false);
+            self.send_event_to_element(source, "dragstart", event, false);
         }
 
-        self.check_and_set_cursor_icon();
+        let target = self.state.hovered.iter().rev().copied().find(|id| {
+            self.tree.get_node_context(*id).is_some_and(|node| {
+                node.listeners.contains("dragover") || node.listeners.contains("drop")
+            })
+        });
+        let drag = self.drag.as_mut().unwrap();
+        drag.moved = true;
+        if drag.target != target {
+            let previous = std::mem::replace(&mut drag.target, target);
+            let payload = drag.payload.clone();
+            if let Some(previous) = previous {
+                let event = self.drag_event(previous, &payload, false);
+                self.send_event_to_element(previous, "dragleave", event, false);
+            }
+        }
+        // the ghost follows the pointer
+        self.request_redraw();
+    }
 
-        true
+    /// Sends the ``dragover`` of this frame
+    fn flush_drag_over(&mut self) {
+        let Some(drag) = self.drag.as_mut().filter(|drag| drag.active && drag.moved) else {
+            return;
+        };
+        drag.moved = false;
+        let (target, payload) = (drag.target, drag.payload.clone());
+        if let Some(target) = target {
+            let event = self.drag_event(target, &payload, false);
+            self.send_event_to_element(target, "dragover", event, false);
+        }
+    }
+
+    /// Drops on the target under the pointer when the button is released
+    fn release_drag(&mut self) {
+        self.flush_drag_over();
+        let Some(drag) = self.drag.take().filter(|drag| drag.active) else {
+            return;
+        };
+        if let Some(target) = drag.target {
+            let event = self.drag_event(target, &drag.payload, true);
+            self.send_event_to_element(target, "drop", event, false);
+        }
+        let event = self.drag_event(drag.source, &drag.payload, drag.target.is_some());
+        self.send_event_to_element(drag.source, "dragend", event, false);
+        self.request_redraw();
+    }
+
+    /// Where the ghost of the dragged node is drawn, relative to the node itself
+    pub(crate) fn drag_ghost(&self) -> Option<(NodeId, Vec2)> {
+        let drag = self.drag.as_ref().filter(|drag| drag.active)?;
+        Some((drag.source, self.state.cursor_state.current_position - drag.origin))
+    }
+
+    /// Scrolls the containers under a drag that comes close to one of their edges, called every frame.
+    /// Returns whether anything scrolled, so the next frame is requested
+    pub fn auto_scroll_drag(&mut self) -> bool {
+        if !self.drag.as_ref().is_some_and(|drag| drag.active) {
+            return false;
+        }
+        let position = self.state.cursor_state.current_position;
+        let speed = |distance: f32| AUTO_SCROLL_SPEED * (1.0 - distance / AUTO_SCROLL_EDGE).clamp(0.0, 1.0);
+
+        // the innermost container that can still scroll that way, hovered nodes removed this frame are skipped
+        for id in self.state.hovered.clone().into_iter().rev() {
+            let (Ok(style), Some(node)) = (self.tree.style(id), self.tree.get_node_context(id)) else {
+                continue;
+            };
+            let (scroll_x, scroll_y) = (
+                style.overflow.x == Overflow::Scroll,
+                style.overflow.y == Overflow::Scroll,
+            );
+            if !scroll_x && !scroll_y {
+                continue;
+            }
+            let rect = node.computed.rect;
+            let before = node.scroll;
+            let mut delta = Vec2::ZERO;
+            if scroll_x {
+                delta.x = speed(rect.max.x - position.x) - speed(position.x - rect.min.x);
+            }
+            if scroll_y {
+                delta.y = speed(rect.max.y - position.y) - speed(position.y - rect.min.y);
+            }
+            if delta == Vec2::ZERO {
+                continue;
+            }
+
+            self.set_scroll_offset(id, before + delta);
+            if self.get_scroll_offset(id) != Some(before) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Touches and pens are treated as the left mouse button, only the first finger down acts as pointer
+    pub fn on_touch(
+        &mut self,
+        renderer: &Renderer,
+        phase: TouchPhase,
+        position: &PhysicalPosition<f64>,
+        id: u64,
+        force: Option<Force>,
+    ) -> bool {
+        match self.state.pointer.touch_id {
+            Some(touch_id) if touch_id != id => return false,
+            None if phase != TouchPhase::Started => return false,
+            _ => {}
+        }
+
+        let tilt = match force {
+            Some(Force::Calibrated { altitude_angle, .. }) => altitude_angle.map(|angle| angle as f32),
+            _ => None,
+        };
+        self.state.pointer = PointerState {
+            pointer_type: if tilt.is_some() {
+                PointerType::Pen
+            } else {
+                PointerType::Touch
+            },
+            pressure: match (phase, force) {
+                (TouchPhase::Ended | TouchPhase::Cancelled, _) => 0.0,
+                (_, Some(force)) => force.normalized() as f32,
+                (_, None) => 1.0,
+            },
+            tilt,
+            touch_id: Some(id),
+        };
+
+        let mut repaint = self.on_mouse_move(position, &renderer.screen_descriptor);
+        match phase {
+            TouchPhase::Started => {
+                repaint |= self.on_mouse_input(renderer, &MouseButton::Left, &ElementState::Pressed);
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                repaint |= self.on_mouse_input(renderer, &MouseButton::Left, &ElementState::Released);
+                self.state.pointer.touch_id = None;
+            }
+            TouchPhase::Moved => {}
+        }
+
+        repaint
     }
 
     pub fn on_mouse_input(
         &mut self,
-        _renderer: &Renderer,
+        renderer: &Renderer,
         button: &winit::event::MouseButton,
         state: &winit::event::ElementState,
     ) -> bool {
+        self.flush_moves();
+
+        // presses on a scrollbar don't focus or click what's under it
+        if button == &winit::event::MouseButton::Left {
+            let handled = match state {
+                ElementState::Pressed => self.press_scrollbar(renderer),
+                ElementState::Released => !std::mem::take(&mut self.state.scrollbar_drags).is_empty(),
+            };
+            if handled {
+                // still tracked like other presses, the scrollbar shows its active colors from it
+                match state {
+                    ElementState::Pressed => {
+                        self.state.cursor_state.drag_start_position = Some(self.state.cursor_state.current_position);
+                        self.state.cursor_state.drag_end_position = None;
+                    }
+                    ElementState::Released => {
+                        self.state.cursor_state.drag_end_position = Some(self.state.cursor_state.current_position);
+                    }
+                }
+                return true;
+            }
+        }
+
         if button == &winit::event::MouseButton::Left
             && state == &winit::event::ElementState::Pressed
         {
             self.state.cursor_state.drag_start_position =
                 Some(self.state.cursor_state.current_position);
             self.state.cursor_state.drag_end_position = None;
+            self.press_draggable();
         } else if button == &winit::event::MouseButton::Left
             && state == &winit::event::ElementState::Released
         {
             self.state.cursor_state.drag_end_position =
                 Some(self.state.cursor_state.current_position);
+            self.release_drag();
         }
 
         // find first element with tabindex
         let mut focused_text_child = None;
+        let clicked_disabled = self
+            .state
+            .hovered
+            .last()
+            .is_some_and(|id| self.is_disabled(*id));
         let focused_node = self.state.hovered.clone().iter().rev().find_map(|id| {
+            if clicked_disabled {
+                return None;
+            }
+
             let Some(node) = self.tree.get_node_context(*id) else {
                 return None;
             };
@@ -846,7 +2551,11 @@ impl Dom {
                 focused_text_child = Some(*id);
             }
 
-            if node.attrs.get("tabindex").is_some() || node.listeners.contains("click") {
+            if node.tag == Tag::Button
+                || node.attrs.get("tabindex").is_some()
+                || node.listeners.contains("click")
+                || matches!(&node.tag, Tag::Custom(custom) if custom.element().focusable())
+            {
                 let node = FocusedNode {
                     node_id: *id,
                     text_child_id: focused_text_child,
@@ -868,23 +2577,30 @@ impl Dom {
         });
         self.set_focus(focused_node);
 
+        self.state.pressed = match state {
+            ElementState::Pressed => self
+                .state
+                .focused
+                .map(|focused| focused.node_id)
+                .filter(|id| self.tree.get_node_context(*id).is_some_and(|node| node.tag == Tag::Button)),
+            ElementState::Released => None,
+        };
+
         if let Some(focused) = self.state.focused {
-            let text_cursor_position = if let Some(text_child_id) = focused.text_child_id {
+            let text_cursor = focused.text_child_id.map(|text_child_id| {
                 let node = self.tree.get_node_context(text_child_id).unwrap();
-                let cursor = node
-                    .get_text_cursor(self.state.cursor_state.current_position.to_vec2())
-                    .unwrap();
-
-                Some(cursor.pcursor.offset)
-            } else {
-                None
-            };
+                node.get_text_cursor(self.state.cursor_state.current_position.to_vec2())
+                    .unwrap()
+            });
+            let text_cursor_position = text_cursor.map(|cursor| cursor.pcursor.offset);
+            let text_cursor_prefer_next_row = text_cursor.map_or(true, |cursor| cursor.pcursor.prefer_next_row);
 
             let pressed_data = Arc::new(events::Event::Click(events::ClickEvent {
                 state: EventState::new(self, focused.node_id),
                 button: button.clone(),
                 element_state: ElementState::Pressed,
                 text_cursor_position,
+                text_cursor_prefer_next_row,
             }));
 
             let not_pressed_data = Arc::new(events::Event::Click(events::ClickEvent {
@@ -892,6 +2608,7 @@ impl Dom {
                 button: button.clone(),
                 element_state: ElementState::Released,
                 text_cursor_position,
+                text_cursor_prefer_next_row,
             }));
 
             match state {
@@ -912,112 +2629,67 @@ impl Dom {
                 winit::event::ElementState::Released => {
                     self.send_event_to_element(
                         focused.node_id,
-                        "mouseup",
-                        not_pressed_data.clone(),
-                        true,
-                    );
-                }
-            }
-        }
-
-        // if we clicked on the same node as last time, then we should select the word
-        if let winit::event::ElementState::Pressed = state {
-            self.state.selection.clear();
-
-            let selected_something =
-                if let Some((time_last_clicked, last_clicked)) = self.state.last_clicked {
-                    if Instant::now() - time_last_clicked > std::time::Duration::from_millis(500) {
-                        false
-                    } else if last_clicked.is_some() {
-                        focused_text_child == last_clicked
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                };
-
-            if selected_something {
-                let node = self
-                    .tree
-                    .get_node_context(focused_text_child.unwrap())
-                    .unwrap();
-
-                let galley = node.computed.galley.as_ref().unwrap();
-                let cursor = node
-                    .get_text_cursor(self.state.cursor_state.current_position.to_vec2())
-                    .unwrap();
-
-                let mut start_cursor = cursor;
-                let mut end_cursor = cursor;
-
-                // find the start of the word
-                while start_cursor.pcursor.offset > 0 {
-                    let prev_char = galley.text().chars().nth(start_cursor.pcursor.offset - 1);
-                    if prev_char.is_none() {
-                        break;
-                    }
-                    let prev_char = prev_char.unwrap();
-
-                    if prev_char.is_whitespace() {
-                        break;
-                    }
-
-                    start_cursor.pcursor.offset -= 1;
-                }
-
-                // find the end of the word
-                while end_cursor.pcursor.offset < galley.text().len() {
-                    let next_char = galley.text().chars().nth(end_cursor.pcursor.offset);
-                    if next_char.is_none() {
-                        break;
-                    }
-                    let next_char = next_char.unwrap();
-
-                    if next_char.is_whitespace() {
-                        break;
-                    }
-
-                    end_cursor.pcursor.offset += 1;
+                        "mouseup",
+                        not_pressed_data.clone(),
+                        true,
+                    );
                 }
+            }
+        }
 
-                start_cursor.ccursor.index = start_cursor.pcursor.offset;
-                end_cursor.ccursor.index = end_cursor.pcursor.offset;
+        // presses in quick succession on the same text node select a word, the paragraph and then all of it
+        if let winit::event::ElementState::Pressed = state {
+            self.clear_selection();
 
-                start_cursor.rcursor.column = start_cursor.pcursor.offset;
-                end_cursor.rcursor.column = end_cursor.pcursor.offset;
+            let now = self.now();
+            let is_repeat = self.state.last_clicked.is_some_and(|(time_last_clicked, last_clicked)| {
+                now - time_last_clicked <= MULTI_CLICK_INTERVAL
+                    && last_clicked.is_some()
+                    && focused_text_child == last_clicked
+            });
+            self.state.click_count = if is_repeat { self.state.click_count + 1 } else { 1 };
 
-                // swap cursors if the selection is backwards
-                if start_cursor.pcursor.offset > end_cursor.pcursor.offset {
-                    std::mem::swap(&mut start_cursor, &mut end_cursor);
-                }
+            if let Some(text_child_id) = focused_text_child.filter(|_| self.state.click_count > 1) {
+                let node = self.tree.get_node_context(text_child_id).unwrap();
+                let galley = node.computed.galley.as_ref().unwrap();
+                let cursor = node
+                    .get_text_cursor(self.state.cursor_state.current_position.to_vec2())
+                    .unwrap();
 
-                self.set_selection(focused_text_child.unwrap(), start_cursor, end_cursor, true);
-            }
+                let range = match self.state.click_count {
+                    2 => text::word_at(galley.text(), cursor.ccursor.index),
+                    3 => text::paragraph_at(galley.text(), cursor.ccursor.index),
+                    _ => 0..text::char_count(galley.text()),
+                };
+                let start_cursor = galley.from_ccursor(CCursor::new(range.start));
+                let end_cursor = galley.from_ccursor(CCursor::new(range.end));
 
-            self.state.last_clicked = if selected_something {
-                None
-            } else {
-                Some((Instant::now(), focused_text_child))
+                self.set_selection(text_child_id, start_cursor, end_cursor, true);
             }
+
+            self.state.last_clicked = Some((now, focused_text_child));
         }
 
         true
     }
 
-    /// Scrolls the last node that is scrollable
+    /// Scrolls the innermost scroll container under the pointer. What it can't take goes to the containers around it,
+    /// unless it has ``overscroll-contain`` or ``overscroll-none``
     pub fn on_scroll(&mut self, delta: &MouseScrollDelta) -> bool {
-        let Some(scroll_node) = self.state.hovered.iter().rev().find_map(|id| {
-            let style = self.tree.style(*id).unwrap();
-
-            if style.overflow.x != Overflow::Scroll && style.overflow.y != Overflow::Scroll {
-                return None;
-            }
-
-            Some(id)
-        }) else {
+        let containers: Vec<NodeId> = self
+            .state
+            .hovered
+            .iter()
+            .rev()
+            .copied()
+            .filter(|id| {
+                let style = self.tree.style(*id).unwrap();
+                style.overflow.x == Overflow::Scroll || style.overflow.y == Overflow::Scroll
+            })
+            .collect();
+        if containers.is_empty() {
             return false;
-        };
+        }
 
         let tick_size = 30.0;
         let mut scroll = Vec2::ZERO;
@@ -1033,35 +2705,207 @@ impl Dom {
                 scroll += Vec2::new(pos.x as f32, pos.y as f32);
             }
         }
+        // only trackpads scroll smoothly enough for a rubber band to feel right
+        let rubber_band = self.rubber_band && matches!(delta, MouseScrollDelta::PixelDelta(_));
+
+        for (index, scroll_node) in containers.iter().copied().enumerate() {
+            let (total_scroll_width, total_scroll_height) = {
+                let layout = self.tree.layout(scroll_node).unwrap();
+
+                (layout.scroll_width(), layout.scroll_height())
+            };
+
+            self.invalidate_layers(scroll_node);
+            let node = self.tree.get_node_context_mut(scroll_node).unwrap();
+            let previous = node.scroll;
+            let wanted = previous + scroll;
+            node.scroll.x = wanted.x.max(0.0).min(total_scroll_width);
+            node.scroll.y = wanted.y.max(0.0).min(total_scroll_height);
+            let rest = wanted - node.scroll;
+            let overscroll = node.styling.overscroll;
+            self.queue_scroll(scroll_node, previous);
+
+            let is_outermost = index == containers.len() - 1;
+            if rest == Vec2::ZERO {
+                break;
+            }
+            if overscroll == Overscroll::Auto && !is_outermost {
+                scroll = rest;
+                continue;
+            }
+            if rubber_band && overscroll != Overscroll::None {
+                self.pull_overscroll(scroll_node, rest);
+            }
+            break;
+        }
+
+        true
+    }
+
+    /// Pulls a scroll container past its ends, the further it already is the less it moves
+    fn pull_overscroll(&mut self, node_id: NodeId, delta: Vec2) {
+        let node = self.tree.get_node_context_mut(node_id).unwrap();
+        let pull = |current: f32, delta: f32| {
+            let resistance = (1.0 - current.abs() / RUBBER_BAND_LIMIT).max(0.0);
+            (current + delta * 0.5 * resistance).clamp(-RUBBER_BAND_LIMIT, RUBBER_BAND_LIMIT)
+        };
+        let overscroll = &mut node.computed.overscroll;
+        overscroll.x = pull(overscroll.x, delta.x);
+        overscroll.y = pull(overscroll.y, delta.y);
+        self.overscrolled.insert(node_id);
+        self.request_redraw();
+    }
+
+    /// Lets the rubber bands of scroll containers bounce back, called every frame.
+    /// Returns whether one is still moving, so the next frame is requested
+    pub fn relax_overscroll(&mut self) -> bool {
+        for id in self.overscrolled.clone() {
+            let Some(node) = self.tree.get_node_context_mut(id) else {
+                self.overscrolled.remove(&id);
+                continue;
+            };
+            let mut overscroll = node.computed.overscroll * RUBBER_BAND_RELAX;
+            if overscroll.length() < 0.5 {
+                overscroll = Vec2::ZERO;
+                self.overscrolled.remove(&id);
+            }
+            node.computed.overscroll = overscroll;
+            self.invalidate_layers(id);
+        }
+        !self.overscrolled.is_empty()
+    }
+
+    fn queue_scroll(&mut self, node_id: NodeId, previous: Vec2) {
+        self.pending_scrolls.entry(node_id).or_insert(previous);
+    }
+
+    /// Sends one ``scroll`` event per node that scrolled since the last frame, called before every frame.
+    /// ``reachstart`` and ``reachend`` are sent when the offset comes within the threshold of either end
+    pub fn flush_scrolls(&mut self) {
+        let now = self.now();
+        for (node_id, previous) in std::mem::take(&mut self.pending_scrolls) {
+            let Some(node) = self.tree.get_node_context(node_id) else {
+                self.scroll_tracking.remove(&node_id);
+                continue;
+            };
+            let offset = node.scroll;
+            let delta = offset - previous;
+            if delta == Vec2::ZERO {
+                continue;
+            }
+            let threshold = |attr: &str| {
+                node.attrs
+                    .get(attr)
+                    .and_then(|value| value.parse::<f32>().ok())
+                    .unwrap_or(0.0)
+            };
+            let (start_threshold, end_threshold) =
+                (threshold("scroll_start_threshold"), threshold("scroll_end_threshold"));
+            let layout = self.tree.layout(node_id).unwrap();
+            let max_offset = Vec2::new(layout.scroll_width(), layout.scroll_height());
+
+            // vertical lists are the common case, only containers that just scroll sideways go by x
+            let (position, max) = if max_offset.y > 0.0 || max_offset.x <= 0.0 {
+                (offset.y, max_offset.y)
+            } else {
+                (offset.x, max_offset.x)
+            };
+            let near_start = position <= start_threshold;
+            let near_end = max - position <= end_threshold;
+
+            let tracking = self.scroll_tracking.insert(
+                node_id,
+                ScrollTracking {
+                    sent_at: now,
+                    near_start,
+                    near_end,
+                },
+            );
+            let velocity = match &tracking {
+                Some(last) if now - last.sent_at <= std::time::Duration::from_millis(100) => {
+                    delta / (now - last.sent_at).as_secs_f32().max(0.001)
+                }
+                _ => Vec2::ZERO,
+            };
+            // nodes start out scrolled to the start, each edge is only reported again after leaving it
+            let (was_near_start, was_near_end) = tracking
+                .map(|last| (last.near_start, last.near_end))
+                .unwrap_or((true, false));
 
-        let (total_scroll_width, total_scroll_height) = {
-            let layout = self.tree.layout(*scroll_node).unwrap();
+            let event = events::Event::Scroll(events::ScrollEvent {
+                state: EventState::new(self, node_id),
+                offset,
+                max_offset,
+                delta,
+                velocity,
+            });
+            self.send_event_to_element(node_id, "scroll", Arc::new(event), false);
+
+            for (listener, reached) in [
+                ("reachstart", near_start && !was_near_start),
+                ("reachend", near_end && !was_near_end),
+            ] {
+                if reached {
+                    let event = events::Event::ScrollEdge(events::ScrollEdgeEvent {
+                        state: EventState::new(self, node_id),
+                        offset,
+                        max_offset,
+                    });
+                    self.send_event_to_element(node_id, listener, Arc::new(event), false);
+                }
+            }
+        }
+    }
 
-            (layout.scroll_width(), layout.scroll_height())
+    /// Text that doesn't come from a single key press, sent to the focused node as ``input``
+    pub fn on_text_input(&mut self, text: &str) -> bool {
+        let Some(focused) = self.state.focused else {
+            return false;
         };
 
-        let node = self.tree.get_node_context_mut(*scroll_node).unwrap();
-        scroll += node.scroll;
-        node.scroll.x = scroll.x.max(0.0).min(total_scroll_width);
-        node.scroll.y = scroll.y.max(0.0).min(total_scroll_height);
+        self.send_event_to_element(
+            focused.node_id,
+            "input",
+            Arc::new(events::Event::Input(events::InputEvent {
+                state: EventState::new(self, focused.node_id),
+                logical_key: Key::Character(text.into()),
+                physical_key: PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+                text: Some(text.into()),
+            })),
+            true,
+        );
 
         true
     }
 
-    pub fn on_keyboard_input(&mut self, input: &KeyEvent) -> bool {
+    pub fn on_keyboard_input(
+        &mut self,
+        element_state: ElementState,
+        logical_key: &Key,
+        physical_key: PhysicalKey,
+        text: Option<SmolStr>,
+    ) -> bool {
+        // copies text selected by dragging over it, inputs copy their own text
+        let is_copy = matches!(logical_key, Key::Character(c) if c == "c") && self.state.command();
+        if element_state.is_pressed() && is_copy && !self.state.selection.is_empty() && !self.wants_text_input() {
+            if let Ok(mut clipboard) = ClipboardContext::new() {
+                let _ = clipboard.set_contents(self.selected_text());
+            }
+        }
+
         let Some(focused) = self.state.focused else {
             return false;
         };
 
-        if input.state.is_pressed() {
+        if element_state.is_pressed() {
             self.send_event_to_element(
                 focused.node_id,
                 "input",
                 Arc::new(events::Event::Input(events::InputEvent {
                     state: EventState::new(self, focused.node_id),
-                    logical_key: input.logical_key.clone(),
-                    physical_key: input.physical_key,
-                    text: input.text.clone(),
+                    logical_key: logical_key.clone(),
+                    physical_key,
+                    text: text.clone(),
                 })),
                 true,
             );
@@ -1069,22 +2913,54 @@ impl Dom {
 
         self.send_event_to_element(
             focused.node_id,
-            match input.state {
+            match element_state {
                 winit::event::ElementState::Pressed => "keydown",
                 winit::event::ElementState::Released => "keyup",
             },
             Arc::new(events::Event::Key(events::KeyInput {
                 state: EventState::new(self, focused.node_id),
-                element_state: input.state,
-                logical_key: input.logical_key.clone(),
-                physical_key: input.physical_key,
-                text: input.text.clone(),
+                element_state,
+                logical_key: logical_key.clone(),
+                physical_key,
+                text,
             })),
             true,
         );
 
+        if self.is_activation_key(focused.node_id, logical_key) && !self.is_disabled(focused.node_id) {
+            self.state.pressed = element_state.is_pressed().then_some(focused.node_id);
+        }
+
+        if element_state.is_pressed() && self.is_activation_key(focused.node_id, logical_key) {
+            self.send_event_to_element(
+                focused.node_id,
+                "click",
+                Arc::new(events::Event::Click(events::ClickEvent {
+                    state: EventState::new(self, focused.node_id),
+                    button: MouseButton::Left,
+                    element_state: ElementState::Pressed,
+                    text_cursor_position: None,
+                    text_cursor_prefer_next_row: true,
+                })),
+                true,
+            );
+        }
+
+        // inputs, buttons and nodes with their own key handlers keep these keys to themselves
+        let handles_keys = self
+            .tree
+            .get_node_context(focused.node_id)
+            .is_some_and(|node| node.listeners.contains("keydown") || node.listeners.contains("input"));
+        if element_state.is_pressed()
+            && !handles_keys
+            && !self.wants_text_input()
+            && !self.is_activation_key(focused.node_id, logical_key)
+        {
+            self.scroll_with_key(focused.node_id, logical_key);
+        }
+
         if let Some(text_child_id) = focused.text_child_id {
-            if let winit::keyboard::Key::Character(c) = &input.logical_key {
+            if let winit::keyboard::Key::Character(c) = logical_key {
                 // check if we need to select all
                 if *c == "a" && self.state.modifiers().state().control_key() {
                     let node = self.tree.get_node_context(text_child_id).unwrap();
@@ -1139,8 +3015,58 @@ impl Dom {
         true
     }
 
-    /// sends an event to the element that the layout has changed
+    /// Scrolls the closest container around ``node_id`` that scrolls along the axis of the key, with the steps of
+    /// [`Dom::keyboard_scroll`]. Returns whether a container took the key
+    fn scroll_with_key(&mut self, node_id: NodeId, logical_key: &Key) -> bool {
+        let shift = self.state.modifiers().state().shift_key();
+        // the offset along the axis from the current one, the size the container shows and the furthest it goes
+        let step: fn(&KeyboardScroll, f32, f32, f32) -> f32 = match logical_key {
+            Key::Named(NamedKey::ArrowUp | NamedKey::ArrowLeft) => |steps, offset, _, _| offset - steps.line,
+            Key::Named(NamedKey::ArrowDown | NamedKey::ArrowRight) => |steps, offset, _, _| offset + steps.line,
+            Key::Named(NamedKey::PageUp) => |steps, offset, page, _| offset - page * steps.page,
+            Key::Named(NamedKey::PageDown) => |steps, offset, page, _| offset + page * steps.page,
+            Key::Named(NamedKey::Space) if shift => |steps, offset, page, _| offset - page * steps.page,
+            Key::Named(NamedKey::Space) => |steps, offset, page, _| offset + page * steps.page,
+            Key::Named(NamedKey::Home) => |_, _, _, _| 0.0,
+            Key::Named(NamedKey::End) => |_, _, _, max| max,
+            _ => return false,
+        };
+        let horizontal = matches!(logical_key, Key::Named(NamedKey::ArrowLeft | NamedKey::ArrowRight));
+
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            let Some(node) = self.tree.get_node_context(id) else {
+                return false;
+            };
+            let (Ok(style), Ok(layout)) = (self.tree.style(id), self.tree.layout(id)) else {
+                return false;
+            };
+            let (overflow, max) = if horizontal {
+                (style.overflow.x, layout.scroll_width())
+            } else {
+                (style.overflow.y, layout.scroll_height())
+            };
+            if overflow == Overflow::Scroll && max > 0.0 {
+                let mut offset = node.scroll;
+                let page = node.computed.rect.size();
+                if horizontal {
+                    offset.x = step(&self.keyboard_scroll, offset.x, page.x, max);
+                } else {
+                    offset.y = step(&self.keyboard_scroll, offset.y, page.y, max);
+                }
+                self.set_scroll_offset(id, offset);
+                return true;
+            }
+            current = node.parent_id;
+        }
+        false
+    }
+
+    /// sends an event to the element that the layout has changed, and ``mounted`` to nodes laid out for the first time
     pub fn on_layout_changed(&mut self, nodes: &[NodeId]) {
+        if !nodes.is_empty() {
+            self.hit_index_dirty = true;
+        }
         for node_id in nodes {
             let rect = self.tree.get_node_context(*node_id).unwrap().computed.rect;
             let layout = self.tree.layout(*node_id).unwrap().clone();
@@ -1155,27 +3081,130 @@ impl Dom {
                 false,
             );
         }
-    }
 
-    pub fn on_window_resize(&mut self) {
-        // send all nodes a layout event
-        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
-            let rect = dom.tree.get_node_context(id).unwrap().computed.rect;
-            let layout = dom.tree.layout(id).unwrap().clone();
-            dom.send_event_to_element(
-                id,
-                "layout",
-                Arc::new(events::Event::Layout(LayoutEvent {
-                    state: EventState::new(dom, id),
+        // listeners are added right after their node is created, so it's part of this layout
+        for node_id in std::mem::take(&mut self.pending_mounts) {
+            let Some(node) = self.tree.get_node_context(node_id) else {
+                continue;
+            };
+            let rect = node.computed.rect;
+            self.send_event_to_element(
+                node_id,
+                "mounted",
+                Arc::new(events::Event::Mounted(events::MountedEvent {
+                    state: EventState::new(self, node_id),
                     rect,
-                    layout,
                 })),
                 false,
             );
+        }
+    }
+
+    /// Sends ``visibilitychange`` to the nodes whose visible share changed, runs after every layout.
+    /// Every ancestor that doesn't have ``overflow-visible`` clips what can be seen of the node
+    pub fn update_visibility(&mut self, viewport: epaint::Rect) {
+        let mut changes = vec![];
+        for (&node_id, &sent) in &self.visibility {
+            let Some(node) = self.tree.get_node_context(node_id) else {
+                continue;
+            };
+            let rect = node.computed.rect;
+            let mut visible = rect.intersect(viewport);
+            let mut parent = self.tree.parent(node_id);
+            while let Some(id) = parent {
+                let style = self.tree.style(id).unwrap();
+                if style.overflow.x != Overflow::Visible || style.overflow.y != Overflow::Visible {
+                    visible = visible.intersect(self.tree.get_node_context(id).unwrap().computed.rect);
+                }
+                parent = self.tree.parent(id);
+            }
+
+            let ratio = if !visible.is_positive() {
+                0.0
+            } else if rect.area() > 0.0 {
+                (visible.area() / rect.area()).min(1.0)
+            } else {
+                1.0
+            };
+            // fully in or out of view is always reported, so nothing gets stuck at 0.98
+            let changed = match sent {
+                None => true,
+                Some(sent) => {
+                    (ratio - sent).abs() >= VISIBILITY_STEP
+                        || (ratio != sent && (ratio == 0.0 || ratio == 1.0))
+                }
+            };
+            if changed {
+                let visible = if visible.is_positive() { visible } else { epaint::Rect::NOTHING };
+                changes.push((node_id, ratio, visible));
+            }
+        }
+
+        for (node_id, ratio, visible_rect) in changes {
+            self.visibility.insert(node_id, Some(ratio));
+            self.send_event_to_element(
+                node_id,
+                "visibilitychange",
+                Arc::new(events::Event::Visibility(events::VisibilityEvent {
+                    state: EventState::new(self, node_id),
+                    ratio,
+                    visible_rect,
+                })),
+                false,
+            );
+        }
+    }
+
+    /// Layout events for nodes that actually moved are sent after the next layout pass,
+    /// ``onresize`` listeners are notified once the size settles
+    pub fn on_window_resize(&mut self, size: PhysicalSize<u32>) {
+        self.context.window_size.send_replace(size);
+    }
+
+    /// Sends ``onresize`` to every node listening for it, regardless of focus
+    pub fn send_resize_event(&mut self, size: PhysicalSize<u32>) {
+        self.send_document_event("resize", |state| {
+            events::Event::Resize(events::ResizeEvent { state, size })
+        });
+    }
+
+    /// Sends the event to every node listening for it, for events that are about the whole window
+    fn send_document_event(&mut self, listener: &str, event: impl Fn(EventState) -> events::Event) {
+        let key = self.get_tag_or_attr_key(listener);
+        self.traverse_tree(self.get_root_id(), &mut |dom, id| {
+            let node = dom.tree.get_node_context(id).unwrap();
+            if node.listeners.contains(&key) {
+                let event = event(EventState::new(dom, id));
+                dom.send_event_to_element(id, listener, Arc::new(event), false);
+            }
             true
         });
     }
 
+    pub fn event_metrics(&self) -> EventMetrics {
+        self.context.event_sender.metrics()
+    }
+
+    pub(crate) fn context(&self) -> &DomContext {
+        &self.context
+    }
+
+    pub fn request_redraw(&self) {
+        self.context.host.request_redraw();
+    }
+
+    pub(crate) fn host(&self) -> Arc<dyn Host> {
+        self.context.host.clone()
+    }
+
+    pub fn window_size(&self) -> PhysicalSize<u32> {
+        self.context.host.inner_size()
+    }
+
+    pub fn set_window_min_size(&self, size: Option<PhysicalSize<u32>>) {
+        self.context.host.set_min_inner_size(size);
+    }
+
     pub fn on_window_moved(&mut self, position: &PhysicalPosition<i32>) {
         self.state.window_position = *position;
     }
@@ -1199,8 +3228,9 @@ impl Dom {
         }
 
         if clear {
-            self.state.selection.clear();
+            self.clear_selection();
         }
+        let node = self.tree.get_node_context(node_id).unwrap();
         self.state.selection.push(SelectedNode {
             node_id,
             parent_id: node.parent_id.unwrap(),
@@ -1208,9 +3238,11 @@ impl Dom {
             start_cursor,
             end_cursor,
         });
+        let parent_id = node.parent_id.unwrap();
+        self.invalidate_layers(node_id);
 
         self.send_event_to_element(
-            node.parent_id.unwrap(),
+            parent_id,
             "select",
             Arc::new(events::Event::Select(events::SelectEvent {
                 state: EventState::new(self, node_id),
@@ -1224,6 +3256,8 @@ impl Dom {
     pub fn set_focus(&mut self, focused_node: Option<FocusedNode>) {
         let prev_focused = self.state.focused;
         self.state.focused = focused_node;
+        // brings up the on-screen keyboard on mobile
+        self.context.host.set_ime_allowed(self.wants_text_input());
 
         if let Some(prev_focused) = prev_focused {
             if let Some(focused) = self.state.focused {
@@ -1243,7 +3277,115 @@ impl Dom {
         }
     }
 
+    /// Focuses a node as if it was clicked, without the click
+    pub fn focus_node(&mut self, node_id: NodeId) {
+        if self.state.focused.is_some_and(|focused| focused.node_id == node_id)
+            || self.tree.get_node_context(node_id).is_none()
+        {
+            return;
+        }
+
+        self.send_event_to_element(
+            node_id,
+            "focus",
+            Arc::new(events::Event::Focus(events::FocusEvent {
+                state: EventState::new(self, node_id),
+            })),
+            true,
+        );
+        self.set_focus(Some(FocusedNode {
+            node_id,
+            text_child_id: None,
+        }));
+        // clicked nodes are already in view, the others might be anywhere
+        self.scroll_into_view(node_id, self.focus_scroll_margin);
+    }
+
+    /// Scrolls every scroll container around the node, from the closest one out, just far enough to show the node
+    /// with ``margin`` around it. Nodes larger than a container show their start
+    pub fn scroll_into_view(&mut self, node_id: NodeId, margin: f32) {
+        let Some(node) = self.tree.get_node_context(node_id) else {
+            return;
+        };
+        let mut target = node.computed.rect.expand(margin);
+        let mut current = node.parent_id;
+        while let Some(id) = current {
+            let Some(container) = self.tree.get_node_context(id) else {
+                return;
+            };
+            current = container.parent_id;
+            let Ok(style) = self.tree.style(id) else {
+                return;
+            };
+            let scrolls_x = style.overflow.x == Overflow::Scroll;
+            let scrolls_y = style.overflow.y == Overflow::Scroll;
+            if !scrolls_x && !scrolls_y {
+                continue;
+            }
+
+            let viewport = container.computed.rect;
+            let distance = |start: f32, end: f32, view_start: f32, view_end: f32| {
+                if start < view_start {
+                    start - view_start
+                } else if end > view_end {
+                    // never past the start of the node
+                    (end - view_end).min(start - view_start)
+                } else {
+                    0.0
+                }
+            };
+            let delta = Vec2::new(
+                if scrolls_x { distance(target.min.x, target.max.x, viewport.min.x, viewport.max.x) } else { 0.0 },
+                if scrolls_y { distance(target.min.y, target.max.y, viewport.min.y, viewport.max.y) } else { 0.0 },
+            );
+            if delta == Vec2::ZERO {
+                continue;
+            }
+
+            let previous = container.scroll;
+            self.set_scroll_offset(id, previous + delta);
+            let Some(scrolled) = self.get_scroll_offset(id) else {
+                return;
+            };
+            // the containers further out see the node where this one moved it to
+            target = target.translate(previous - scrolled);
+        }
+    }
+
+    /// Whether the focused node takes text, like an ``Input``
+    pub fn wants_text_input(&self) -> bool {
+        self.state.focused.is_some_and(|focused| {
+            self.tree
+                .get_node_context(focused.node_id)
+                .is_some_and(|node| node.attrs.contains_key("text_cursor"))
+        })
+    }
+
+    /// ``id`` is handed out by the [`crate::DomHandle`], so it's known before the override is applied
+    pub fn push_cursor_override(&mut self, id: usize, icon: CursorIcon) {
+        self.cursor_overrides.push((id, icon));
+        self.check_and_set_cursor_icon();
+    }
+
+    pub fn remove_cursor_override(&mut self, id: usize) {
+        self.cursor_overrides.retain(|(override_id, _)| *override_id != id);
+        self.check_and_set_cursor_icon();
+    }
+
+    fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) {
+        if self.context.current_cursor_icon != cursor_icon {
+            self.context.host.set_cursor_icon(cursor_icon);
+            self.context.current_cursor_icon = cursor_icon;
+        }
+    }
+
     pub fn check_and_set_cursor_icon(&mut self) {
+        // overrides win from anything that's hovered
+        if let Some((_, cursor_icon)) = self.cursor_overrides.last() {
+            self.set_cursor_icon(*cursor_icon);
+            return;
+        }
+
         let mut new_cursor_icon = CursorIcon::Default;
 
         // check if we're hovering over a node with tabindex or click listener
@@ -1325,9 +3467,419 @@ impl Dom {
             }
         }
 
-        if self.context.current_cursor_icon != new_cursor_icon {
-            self.context.window.set_cursor_icon(new_cursor_icon);
-            self.context.current_cursor_icon = new_cursor_icon;
+        self.set_cursor_icon(new_cursor_icon);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{host::HeadlessHost, DomEventLoop, RendererDescriptor};
+    use dioxus::prelude::{Scope, Template};
+
+    const WINDOW_SIZE: PhysicalSize<u32> = PhysicalSize::new(800, 600);
+
+    /// A view that fills its parent, every node of a chain of them covers the whole window
+    const FILL: &[TemplateNode<'static>] = &[TemplateNode::Element {
+        tag: "view",
+        namespace: None,
+        attrs: &[TemplateAttribute::Static {
+            name: "class",
+            value: "w-full h-full bg-slate-200",
+            namespace: None,
+        }],
+        children: &[],
+    }];
+
+    const ROW: &[TemplateNode<'static>] = &[TemplateNode::Element {
+        tag: "view",
+        namespace: None,
+        attrs: &[TemplateAttribute::Static {
+            name: "class",
+            value: "w-full h-1 shrink-0",
+            namespace: None,
+        }],
+        children: &[],
+    }];
+
+    const COLUMN: &[TemplateNode<'static>] = &[TemplateNode::Element {
+        tag: "view",
+        namespace: None,
+        attrs: &[TemplateAttribute::Static {
+            name: "class",
+            value: "flex-col w-full",
+            namespace: None,
+        }],
+        children: &[],
+    }];
+
+    const SIDEBAR: &[TemplateNode<'static>] = &[TemplateNode::Element {
+        tag: "view",
+        namespace: None,
+        attrs: &[
+            TemplateAttribute::Static {
+                name: "class",
+                value: "w-full h-10",
+                namespace: None,
+            },
+            TemplateAttribute::Static {
+                name: "id",
+                value: "sidebar",
+                namespace: None,
+            },
+        ],
+        children: &[],
+    }];
+
+    /// A view with a placeholder for dynamic nodes as its only child
+    const SLOT: &[TemplateNode<'static>] = &[TemplateNode::Element {
+        tag: "view",
+        namespace: None,
+        attrs: &[],
+        children: &[TemplateNode::Dynamic { id: 0 }],
+    }];
+
+    fn empty(_cx: Scope) -> dioxus::prelude::Element {
+        None
+    }
+
+    /// A dom without a window and an empty tree, the tests build it with their own mutations
+    fn headless() -> DomEventLoop {
+        let mut event_loop = DomEventLoop::new_single_threaded(
+            empty,
+            Arc::new(HeadlessHost::new(WINDOW_SIZE)),
+            RendererDescriptor {
+                font_definitions: Default::default(),
+                pixels_per_point: 1.0,
+                window_size: WINDOW_SIZE,
+            },
+            (),
+        );
+        // applies the first render of ``empty`` so its element ids don't end up in the tree of the test
+        let _ = event_loop.get_paint_info();
+        event_loop.dom.reset();
+        event_loop
+    }
+
+    /// Applies ``edits`` like a render of the VirtualDom would, with the templates of the tests registered
+    fn apply(dom: &mut Dom, templates: &[(&'static str, &'static [TemplateNode<'static>])], edits: Vec<DomMutation>) {
+        dom.apply_batch(MutationBatch {
+            templates: templates
+                .iter()
+                .map(|&(name, roots)| Template {
+                    name,
+                    roots,
+                    node_paths: &[],
+                    attr_paths: &[],
+                })
+                .collect(),
+            edits,
+            hot_reloaded: false,
+        });
+    }
+
+    #[test]
+    fn deep_tree() {
+        const DEPTH: usize = 10_000;
+
+        let mut event_loop = headless();
+        let mut edits = Vec::with_capacity(DEPTH * 2);
+        for i in 1..=DEPTH {
+            edits.push(DomMutation::LoadTemplate { name: "fill", index: 0, id: ElementId(i) });
+            edits.push(DomMutation::AppendChildren { id: ElementId(i - 1), m: 1 });
+        }
+        apply(&mut event_loop.dom, &[("fill", FILL)], edits);
+
+        let root = event_loop.dom.get_root_id();
+        let top = event_loop.dom.element_id_mapping[&ElementId(1)];
+        let deepest = event_loop.dom.element_id_mapping[&ElementId(DEPTH)];
+
+        // taffy lays the tree out recursively, so every pass but its layout runs here on the default stack. The rects
+        // are filled in the way a layout of ``w-full h-full`` nodes would
+        let renderer = &mut event_loop.renderer;
+        let dom = &mut event_loop.dom;
+        renderer.compute_styles(dom);
+        renderer.compute_rects(dom);
+        let mut visited = 0;
+        dom.traverse_tree(root, &mut |dom, id| {
+            visited += 1;
+            dom.tree.get_node_context_mut(id).unwrap().computed.rect =
+                epaint::Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+            true
+        });
+        assert_eq!(visited, DEPTH + 1);
+        dom.hit_index_dirty = true;
+
+        let (primitives, _) = renderer.paint(dom);
+        assert!(!primitives.is_empty());
+        renderer.recycle(primitives);
+
+        let hits = dom.hit_test(Pos2::new(400.0, 300.0), false);
+        assert_eq!(hits.len(), DEPTH + 1);
+        assert_eq!(hits.first(), Some(&root));
+        assert_eq!(hits.last(), Some(&deepest));
+
+        dom.remove_node(top);
+        assert!(dom.tree.get_node_context(deepest).is_none());
+        assert!(dom.tree.children(root).unwrap().is_empty());
+        assert_eq!(dom.element_id_mapping.len(), 1);
+
+        dom.hit_index_dirty = true;
+        assert_eq!(dom.hit_test(Pos2::new(400.0, 300.0), false), vec![root]);
+    }
+
+    #[test]
+    fn many_siblings() {
+        const SIBLINGS: usize = 5_000;
+
+        // a column with all of the rows as static children, so instantiating the template clones every one of them
+        let list: &'static [TemplateNode<'static>] = Box::leak(Box::new([TemplateNode::Element {
+            tag: "view",
+            namespace: None,
+            attrs: &[TemplateAttribute::Static {
+                name: "class",
+                value: "flex-col w-full",
+                namespace: None,
+            }],
+            children: Box::leak(vec![ROW[0]; SIBLINGS].into_boxed_slice()),
+        }]));
+
+        let mut event_loop = headless();
+        let mut edits = vec![
+            DomMutation::LoadTemplate { name: "list", index: 0, id: ElementId(1) },
+            DomMutation::LoadTemplate { name: "list", index: 0, id: ElementId(2) },
+            DomMutation::LoadTemplate { name: "column", index: 0, id: ElementId(3) },
+            DomMutation::AppendChildren { id: ElementId(0), m: 3 },
+        ];
+        // and a column whose rows are appended in a single mutation
+        for i in 0..SIBLINGS {
+            edits.push(DomMutation::LoadTemplate { name: "row", index: 0, id: ElementId(4 + i) });
+        }
+        edits.push(DomMutation::AppendChildren { id: ElementId(3), m: SIBLINGS });
+        apply(
+            &mut event_loop.dom,
+            &[("list", list), ("column", COLUMN), ("row", ROW)],
+            edits,
+        );
+
+        let columns: Vec<NodeId> = (1..=3).map(|i| event_loop.dom.element_id_mapping[&ElementId(i)]).collect();
+        for column in &columns {
+            assert_eq!(event_loop.dom.tree.children(*column).unwrap().len(), SIBLINGS);
+        }
+
+        let (primitives, _, _) = event_loop.get_paint_info();
+        event_loop.recycle_primitives(primitives);
+
+        let dom = &event_loop.dom;
+        for column in &columns {
+            let rows = dom.tree.children(*column).unwrap();
+            assert_eq!(rows.len(), SIBLINGS);
+            assert_eq!(dom.node_info(*column).unwrap().children.len(), SIBLINGS);
+            let first = dom.tree.get_node_context(rows[0]).unwrap().computed.rect;
+            let last = dom.tree.get_node_context(rows[SIBLINGS - 1]).unwrap().computed.rect;
+            assert!(last.min.y > first.min.y);
+        }
+    }
+
+    #[test]
+    fn auto_scroll_skips_removed_hovered_nodes() {
+        let mut event_loop = headless();
+        let dom = &mut event_loop.dom;
+        apply(
+            dom,
+            &[("row", ROW)],
+            vec![
+                DomMutation::LoadTemplate { name: "row", index: 0, id: ElementId(1) },
+                DomMutation::LoadTemplate { name: "row", index: 0, id: ElementId(2) },
+                DomMutation::AppendChildren { id: ElementId(0), m: 2 },
+            ],
+        );
+        let source = dom.element_id_mapping[&ElementId(1)];
+        let hovered = dom.element_id_mapping[&ElementId(2)];
+        dom.state.hovered = vec![dom.get_root_id(), hovered];
+        dom.drag = Some(crate::drag::DragState {
+            source,
+            origin: Pos2::ZERO,
+            active: true,
+            payload: Default::default(),
+            target: None,
+            moved: false,
+        });
+
+        // e.g. an ``ondragover`` handler removed the container under the pointer
+        dom.remove_node(hovered);
+        assert!(!dom.auto_scroll_drag());
+    }
+
+    #[test]
+    fn insert_next_to_detached_node() {
+        let mut event_loop = headless();
+        let dom = &mut event_loop.dom;
+        // a placeholder that was never appended has no parent to insert into
+        apply(
+            dom,
+            &[("row", ROW)],
+            vec![
+                DomMutation::CreatePlaceholder { id: ElementId(1) },
+                DomMutation::LoadTemplate { name: "row", index: 0, id: ElementId(2) },
+                DomMutation::AppendChildren { id: ElementId(0), m: 1 },
+            ],
+        );
+        let placeholder = dom.element_id_mapping[&ElementId(1)];
+        let row = dom.element_id_mapping[&ElementId(2)];
+
+        assert!(matches!(dom.insert_node_before(placeholder, row), Err(DomError::MissingSibling(_))));
+        assert!(matches!(dom.insert_node_after(placeholder, row), Err(DomError::MissingSibling(_))));
+        // the row stays where it was
+        assert_eq!(dom.tree.parent(row), Some(dom.get_root_id()));
+
+        // mutations that insert next to it fail before popping the new nodes, which stay on the stack
+        let mut mounted = vec![];
+        dom.apply_mutation(DomMutation::LoadTemplate { name: "row", index: 0, id: ElementId(3) }, &mut mounted).unwrap();
+        let stack = dom.stack.clone();
+        let node_count = dom.tree.total_node_count();
+        for edit in [
+            DomMutation::ReplaceWith { id: ElementId(1), m: 1 },
+            DomMutation::InsertAfter { id: ElementId(1), m: 1 },
+            DomMutation::InsertBefore { id: ElementId(1), m: 1 },
+        ] {
+            assert!(matches!(dom.apply_mutation(edit, &mut mounted), Err(DomError::MissingSibling(_))));
+            assert_eq!(dom.stack, stack);
+            assert_eq!(dom.tree.total_node_count(), node_count);
         }
+        assert!(mounted.is_empty());
+    }
+
+    #[test]
+    fn id_index() {
+        let mut event_loop = headless();
+        let dom = &mut event_loop.dom;
+        let set_id = |id: ElementId, value: Option<&str>| DomMutation::SetAttribute {
+            name: "id".into(),
+            value: value.map_or(AttributeValue::None, |value| AttributeValue::Text(value.into())),
+            id,
+        };
+
+        // the id of a template is indexed when it's instantiated, in the order the nodes got it
+        apply(
+            dom,
+            &[("sidebar", SIDEBAR)],
+            vec![
+                DomMutation::LoadTemplate { name: "sidebar", index: 0, id: ElementId(1) },
+                DomMutation::LoadTemplate { name: "sidebar", index: 0, id: ElementId(2) },
+                DomMutation::AppendChildren { id: ElementId(0), m: 2 },
+            ],
+        );
+        let first = dom.element_id_mapping[&ElementId(1)];
+        let second = dom.element_id_mapping[&ElementId(2)];
+        assert_eq!(dom.get_node_by_id("sidebar"), Some(first));
+        assert_eq!(dom.ids["sidebar"], vec![first, second]);
+
+        // changing the id moves the node to the new one
+        apply(dom, &[], vec![set_id(ElementId(1), Some("main"))]);
+        assert_eq!(dom.get_node_by_id("main"), Some(first));
+        assert_eq!(dom.get_node_by_id("sidebar"), Some(second));
+
+        // removing the attribute takes it out of the index
+        apply(dom, &[], vec![set_id(ElementId(1), None)]);
+        assert_eq!(dom.get_node_by_id("main"), None);
+        assert!(!dom.ids.contains_key("main"));
+
+        apply(dom, &[], vec![set_id(ElementId(1), Some("sidebar"))]);
+        assert_eq!(dom.ids["sidebar"], vec![second, first]);
+
+        // removed nodes are forgotten, the key goes away with the last one
+        apply(dom, &[], vec![DomMutation::Remove { id: ElementId(2) }]);
+        assert_eq!(dom.get_node_by_id("sidebar"), Some(first));
+        dom.remove_node(first);
+        assert_eq!(dom.get_node_by_id("sidebar"), None);
+        assert!(dom.ids.is_empty());
+    }
+
+    #[test]
+    fn replace_placeholder_with_invalid_path() {
+        let mut event_loop = headless();
+        let dom = &mut event_loop.dom;
+        let errors = Arc::new(std::sync::Mutex::new(vec![]));
+        dom.set_error_handler({
+            let errors = errors.clone();
+            move |error| errors.lock().unwrap().push(error)
+        });
+
+        apply(
+            dom,
+            &[("slot", SLOT), ("row", ROW)],
+            vec![
+                DomMutation::LoadTemplate { name: "slot", index: 0, id: ElementId(1) },
+                DomMutation::LoadTemplate { name: "row", index: 0, id: ElementId(2) },
+                DomMutation::ReplacePlaceholder { path: &[5], m: 1 },
+                // the failed replace left the row on the stack, so this one still finds it
+                DomMutation::ReplacePlaceholder { path: &[0], m: 1 },
+                DomMutation::AppendChildren { id: ElementId(0), m: 1 },
+            ],
+        );
+
+        assert!(matches!(errors.lock().unwrap()[..], [DomError::InvalidPath(_)]));
+        let slot = dom.element_id_mapping[&ElementId(1)];
+        let row = dom.element_id_mapping[&ElementId(2)];
+        assert_eq!(dom.tree.children(slot).unwrap(), vec![row]);
+        assert_eq!(dom.tree.children(dom.get_root_id()).unwrap(), vec![slot]);
+    }
+
+    #[test]
+    fn z_layer_inside_layer() {
+        // the overlay in the ``layer`` is painted with the layer, under the cover that comes after it
+        const LAYERED: &[TemplateNode<'static>] = &[
+            TemplateNode::Element {
+                tag: "view",
+                namespace: None,
+                attrs: &[TemplateAttribute::Static {
+                    name: "class",
+                    value: "layer absolute w-full h-full",
+                    namespace: None,
+                }],
+                children: &[TemplateNode::Element {
+                    tag: "view",
+                    namespace: None,
+                    attrs: &[TemplateAttribute::Static {
+                        name: "class",
+                        value: "z-overlay w-full h-full",
+                        namespace: None,
+                    }],
+                    children: &[],
+                }],
+            },
+            TemplateNode::Element {
+                tag: "view",
+                namespace: None,
+                attrs: &[TemplateAttribute::Static {
+                    name: "class",
+                    value: "absolute w-full h-full",
+                    namespace: None,
+                }],
+                children: &[],
+            },
+        ];
+
+        let mut event_loop = headless();
+        apply(
+            &mut event_loop.dom,
+            &[("layered", LAYERED)],
+            vec![
+                DomMutation::LoadTemplate { name: "layered", index: 0, id: ElementId(1) },
+                DomMutation::LoadTemplate { name: "layered", index: 1, id: ElementId(2) },
+                DomMutation::AppendChildren { id: ElementId(0), m: 2 },
+            ],
+        );
+        let (primitives, _, _) = event_loop.get_paint_info();
+        event_loop.recycle_primitives(primitives);
+
+        let dom = &mut event_loop.dom;
+        let layer = dom.element_id_mapping[&ElementId(1)];
+        let overlay = dom.tree.children(layer).unwrap()[0];
+        let cover = dom.element_id_mapping[&ElementId(2)];
+        assert_eq!(dom.z_layer(overlay), ZLayer::Base);
+        let hits = dom.hit_test(Pos2::new(400.0, 300.0), false);
+        assert_eq!(hits[1..], [layer, overlay, cover]);
     }
 }
@@ -0,0 +1,63 @@
+//! Drag and drop between nodes. Pressing a node with ``draggable: "true"`` and moving the pointer a few points starts a
+//! drag, its subtree follows the pointer as a ghost until it's released over a node that listens for ``ondrop``.
+
+use std::{
+    any::Any,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use epaint::Pos2;
+use taffy::NodeId;
+
+/// How far in points the pointer has to move with the button down before it's a drag instead of a click
+pub(crate) const DRAG_THRESHOLD: f32 = 4.0;
+/// Scroll containers scroll by themselves while the pointer is this close to one of their edges
+pub(crate) const AUTO_SCROLL_EDGE: f32 = 40.0;
+/// Points per frame when the pointer is right at the edge
+pub(crate) const AUTO_SCROLL_SPEED: f32 = 12.0;
+
+/// What is being dragged, shared by every event of a drag. ``ondragstart`` sets it and drop targets only accept the
+/// types they know. Starts out as the ``drag_data`` attribute of the dragged node as a ``String``, if it has one
+#[derive(Clone, Default)]
+pub struct DragPayload(Arc<Mutex<Option<Arc<dyn Any + Send + Sync>>>>);
+
+impl DragPayload {
+    pub fn set<T: Any + Send + Sync>(&self, value: T) {
+        *self.0.lock().unwrap() = Some(Arc::new(value));
+    }
+
+    /// ``None`` when nothing was set or it's a different type
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.0.lock().unwrap().clone()?.downcast().ok()
+    }
+
+    pub fn is<T: Any + Send + Sync>(&self) -> bool {
+        self.0.lock().unwrap().as_ref().is_some_and(|value| value.is::<T>())
+    }
+
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+impl fmt::Debug for DragPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DragPayload")
+            .field(&self.0.lock().unwrap().is_some())
+            .finish()
+    }
+}
+
+/// A press on a draggable node, it becomes a drag once the pointer moved far enough
+pub(crate) struct DragState {
+    pub source: NodeId,
+    /// Where the pointer was pressed
+    pub origin: Pos2,
+    pub active: bool,
+    pub payload: DragPayload,
+    /// The innermost hovered node that listens for ``ondragover`` or ``ondrop``
+    pub target: Option<NodeId>,
+    /// The pointer moved since the last ``dragover``, they're sent once per frame
+    pub moved: bool,
+}
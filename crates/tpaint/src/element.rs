@@ -0,0 +1,104 @@
+//! Native elements implemented outside of the crate, e.g. a ``terminal`` or ``video`` that paints its own shapes.
+//! Register them with [`crate::DomEventLoop::register_element`] before the first frame, templates are resolved once.
+//! The app declares the tag next to the built-in ones in its own ``dioxus_elements`` module, like [`crate::prelude::dioxus_elements::view`].
+//! Registered elements are laid out and styled like a ``view``. An element whose content changes without its attributes,
+//! e.g. a terminal that got output, asks to be measured and painted again with [`crate::DomHandle::mark_dirty`]
+
+use std::{fmt, sync::Arc};
+
+use epaint::{Rect, Shape, Vec2};
+use rustc_hash::FxHashMap;
+use taffy::NodeId;
+
+use crate::{dom::NodeContext, events::Event};
+
+/// Behavior of a custom element, every method has a default so an element only overrides what it needs
+pub trait Element: Send + Sync + 'static {
+    /// Size of the content of a node without children, ``None`` sizes it like a ``view``
+    fn measure(&self, _node: ElementNode<'_>, _input: MeasureInput) -> Option<Vec2> {
+        None
+    }
+
+    /// Shapes painted over the background and border of the node, in points from the top left of the window.
+    /// They're clipped like the rest of the node and get its filters
+    fn paint(&self, _node: ElementNode<'_>, _shapes: &mut Vec<Shape>) {}
+
+    /// Sees every event sent to the node before its listeners do, e.g. ``click`` or ``keydown``.
+    /// Returning true keeps the event from the listeners and from bubbling
+    fn on_event(&self, _node: ElementNode<'_>, _name: &str, _event: &Event) -> bool {
+        false
+    }
+
+    /// Lets clicks focus the node without a ``tabindex`` or ``onclick``, so it gets the keys
+    fn focusable(&self) -> bool {
+        false
+    }
+}
+
+/// The node an [`Element`] is asked about
+#[derive(Clone, Copy)]
+pub struct ElementNode<'a> {
+    pub node_id: NodeId,
+    /// Where the node was laid out, in points from the top left of the window. Empty while it's measured the first time
+    pub rect: Rect,
+    pub attrs: &'a FxHashMap<Arc<str>, Arc<str>>,
+}
+
+impl<'a> ElementNode<'a> {
+    pub(crate) fn new(node_id: NodeId, node: &'a NodeContext) -> Self {
+        Self {
+            node_id,
+            rect: node.computed.rect,
+            attrs: &node.attrs,
+        }
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&'a str> {
+        self.attrs.get(name).map(|value| &**value)
+    }
+}
+
+/// What the layout knows when it asks an element for its size, in points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeasureInput {
+    pub known_width: Option<f32>,
+    pub known_height: Option<f32>,
+    /// ``None`` while the layout looks for the min or max content size
+    pub available_width: Option<f32>,
+    pub available_height: Option<f32>,
+}
+
+/// A registered element on a node, the tag is what tells them apart
+#[derive(Clone)]
+pub struct CustomElement {
+    name: Arc<str>,
+    element: Arc<dyn Element>,
+}
+
+impl CustomElement {
+    pub(crate) fn new(name: Arc<str>, element: Arc<dyn Element>) -> Self {
+        Self { name, element }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn element(&self) -> &dyn Element {
+        &*self.element
+    }
+}
+
+impl PartialEq for CustomElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomElement {}
+
+impl fmt::Debug for CustomElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomElement").field(&self.name).finish()
+    }
+}
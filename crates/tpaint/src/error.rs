@@ -0,0 +1,72 @@
+use std::fmt;
+
+use dioxus::core::ElementId;
+use taffy::NodeId;
+
+/// Problems that are reported through [`crate::DomEventLoop::on_error`] instead of taking the app down
+#[derive(Debug, Clone)]
+pub enum DomError {
+    /// Attribute values of type ``Any`` can't be stored on a node
+    UnsupportedAttributeValue { name: String },
+    /// A mutation referenced an element that isn't in the tree
+    MissingElement(ElementId),
+    /// A mutation referenced a template that was never registered
+    MissingTemplate { name: String, index: usize },
+    /// A mutation expected more nodes on the stack than there were
+    StackUnderflow { expected: usize, len: usize },
+    /// A template path pointed at a child that doesn't exist
+    InvalidPath(Vec<u8>),
+    /// A node was inserted next to one that isn't among the children of its parent
+    MissingSibling(NodeId),
+    /// The VirtualDom thread panicked, the UI won't update anymore
+    VirtualDomPanicked(String),
+}
+
+impl fmt::Display for DomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DomError::UnsupportedAttributeValue { name } => {
+                write!(f, "attribute {name} has a value that can't be stored on a node")
+            }
+            DomError::MissingElement(id) => write!(f, "element {id:?} isn't in the tree"),
+            DomError::MissingTemplate { name, index } => {
+                write!(f, "template {name} has no root at index {index}")
+            }
+            DomError::StackUnderflow { expected, len } => {
+                write!(f, "expected {expected} nodes on the stack, found {len}")
+            }
+            DomError::InvalidPath(path) => write!(f, "template path {path:?} doesn't exist"),
+            DomError::MissingSibling(node_id) => {
+                write!(f, "can't insert next to {node_id:?}, it isn't a child of its parent")
+            }
+            DomError::VirtualDomPanicked(message) => {
+                write!(f, "the VirtualDom thread panicked: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DomError {}
+
+/// Why [`crate::DomHandle::set_window_icon_from_bytes`] couldn't set the icon
+#[cfg(feature = "images")]
+#[derive(Debug)]
+pub enum WindowIconError {
+    /// The bytes aren't an image format that can be decoded
+    Decode(image::ImageError),
+    /// The decoded image was rejected by the platform
+    BadIcon(winit::window::BadIcon),
+}
+
+#[cfg(feature = "images")]
+impl fmt::Display for WindowIconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowIconError::Decode(err) => write!(f, "couldn't decode the icon: {err}"),
+            WindowIconError::BadIcon(err) => write!(f, "invalid icon: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "images")]
+impl std::error::Error for WindowIconError {}
@@ -1,45 +1,319 @@
-use std::{fmt::Debug, ops::Deref, path::Path, sync::{Arc, Mutex}};
+use std::{any::Any, fmt::Debug, ops::Deref, sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex}, time::Duration};
 
 use dioxus::prelude::{ScopeId, VirtualDom, Scope, Element};
-use epaint::{textures::TexturesDelta, ClippedPrimitive, TextureManager};
-use winit::{event::WindowEvent, event_loop::EventLoopProxy, window::Window};
+use epaint::{textures::TexturesDelta, ClippedPrimitive, Primitive, Rect, TextureId, TextureManager};
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, event::MouseScrollDelta, event_loop::EventLoopProxy, keyboard::SmolStr};
 
 
+#[cfg(feature = "images")]
+use crate::components::image::ImageCache;
 use crate::{
-    events::DomEvent,
+    atlas::{AtlasStats, TextureAtlas},
+    components::error_overlay::{ErrorOverlay, ErrorOverlayProps},
+    error::DomError,
+    #[cfg(feature = "export")]
+    export,
+    event_queue::{self, EventMetrics, EventReceiver, EventSender, EVENT_QUEUE_CAPACITY},
+    events::{DomEvent, WindowEvent},
+    handle::{DomHandle, DomSnapshot, HandleShared},
     renderer::{Renderer, RendererDescriptor, ScreenDescriptor},
-    dom::Dom,
+    dom::{Dom, KeyboardScroll, MoveCoalescing, PointerState, PointerType},
+    host::Host,
+    input::{InputEvent, Key, TouchPhase},
+    mutations::MutationQueue,
+    platform::{self, Instant},
+    recorder::{InputRecorder, TimedEvent},
+    snapshot::PaintSnapshot,
 };
+#[cfg(feature = "tray")]
+use crate::tray::{Tray, TrayEvent, TrayManager};
 
 pub struct DomEventLoop {
-    pub dom: Arc<Mutex<Dom>>,
+    /// Only touched by the thread of the event loop, the VirtualDom thread queues its changes and reads a snapshot
+    pub dom: Dom,
     pub update_scope_sender: tokio::sync::mpsc::UnboundedSender<ScopeId>,
     pub renderer: Renderer,
+    /// Rendered by the VirtualDom thread, applied when the next frame starts
+    mutations: Arc<MutationQueue>,
+    /// Where the snapshot [`DomHandle`] reads is published
+    handle_shared: Arc<HandleShared>,
+    /// The snapshot that was published before the current one, updated and swapped in after the next frame
+    snapshot: Arc<DomSnapshot>,
+    /// Only set in single-threaded mode, see [`DomEventLoop::new_single_threaded`]
+    local_vdom: Option<Box<LocalVirtualDom>>,
     #[cfg(feature = "libloading")]
     pub lib: Option<libloading::Library>,
+    pub recorder: Option<InputRecorder>,
+    /// Whether Ctrl+= / Ctrl+- / Ctrl+0 change the zoom
+    pub zoomable: bool,
+    /// Prevents the window from being resized smaller than the min-content size of the UI
+    pub auto_min_size: bool,
+    applied_min_size: Option<PhysicalSize<u32>>,
+    suspended: bool,
+    first_frame: FirstFrame,
+    on_first_frame: Option<Box<dyn FnOnce() + Send>>,
+    /// Counts the frames painted so far, recorded on their tracing spans
+    frame_id: u64,
+    #[cfg(feature = "tray")]
+    tray: TrayManager,
+    /// See [`DomEventLoop::set_viewport`]
+    viewport: Option<Viewport>,
+    /// The whole window, returned by [`DomEventLoop::get_paint_info`] while there's a viewport
+    window_screen: ScreenDescriptor,
+    /// Or'ed into the ids of managed textures, so roots that paint through the same renderer don't overwrite each other's
+    texture_namespace: u64,
+    /// A button was pressed inside the viewport and is still held, the pointer keeps going to this root
+    pointer_captured: bool,
+    pointer_inside_viewport: bool,
+    /// Touches that started inside the viewport
+    viewport_touches: Vec<u64>,
+    /// Keys go to the root that was pressed last
+    has_keyboard_focus: bool,
+}
+
+/// A region of the window in physical pixels, for hosts that embed several roots in one window.
+/// See [`DomEventLoop::set_viewport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x as f64 && y >= self.y as f64 && x < (self.x + self.width) as f64 && y < (self.y + self.height) as f64
+    }
+}
+
+/// Every root after the first gets its own texture namespace, in the top bits of the ids
+static NEXT_TEXTURE_NAMESPACE: AtomicU64 = AtomicU64::new(0);
+const TEXTURE_NAMESPACE_SHIFT: u32 = 48;
+
+/// How far the first frame with content is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FirstFrame {
+    Waiting,
+    /// Tessellated, it counts as rendered once its primitives are recycled or the next frame starts
+    Produced,
+    Rendered,
 }
 
 #[derive(Clone)]
 pub struct DomContext {
     pub texture_manager: Arc<Mutex<TextureManager>>,
-    pub window: Arc<Window>,
+    pub atlas: Arc<Mutex<TextureAtlas>>,
+    /// The window the dom is shown in, see [`crate::host`]
+    pub host: Arc<dyn Host>,
     #[cfg(feature = "images")]
     pub client: reqwest::Client,
-    pub event_sender: tokio::sync::mpsc::UnboundedSender<DomEvent>,
+    pub event_sender: EventSender,
     pub current_cursor_icon: winit::window::CursorIcon,
+    /// The latest size of the window, see [`crate::hooks::use_window_size`]
+    pub window_size: Arc<tokio::sync::watch::Sender<PhysicalSize<u32>>>,
+    /// Input for the whole window, see [`crate::hooks::use_window_event`]
+    pub window_events: tokio::sync::broadcast::Sender<WindowEvent>,
+    #[cfg(feature = "images")]
+    pub image_cache: Arc<Mutex<ImageCache>>,
+    /// What the tray should show, see [`crate::tray::use_tray`]
+    #[cfg(feature = "tray")]
+    pub tray: Arc<tokio::sync::watch::Sender<Option<Tray>>>,
+    #[cfg(feature = "tray")]
+    pub tray_events: tokio::sync::broadcast::Sender<TrayEvent>,
+    /// Set with [`DomEventLoop::on_error`], shared so the VirtualDom thread can report its panics without the dom
+    pub(crate) on_error: Arc<Mutex<Option<Box<dyn FnMut(DomError) + Send>>>>,
+}
+
+impl DomContext {
+    /// Logs the error and passes it on to the handler set with [`DomEventLoop::on_error`]
+    pub(crate) fn report_error(&self, error: DomError) {
+        log::error!("{error}");
+        if let Some(on_error) = &mut *self.on_error.lock().unwrap() {
+            on_error(error);
+        }
+    }
+}
+
+
+/// What happens when a component panics on the VirtualDom thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicPolicy {
+    /// Aborts the whole process
+    Abort,
+    /// Shows the panic message with a button to start the app over
+    Restart,
+    /// Keeps showing the last frame, the panic is still reported through [`DomEventLoop::on_error`]
+    #[default]
+    Freeze,
+}
+
+#[cfg(all(feature = "hot-reload", debug_assertions))]
+type HotReloadMsg = dioxus_hot_reload::HotReloadMsg;
+#[cfg(not(all(feature = "hot-reload", debug_assertions)))]
+type HotReloadMsg = ();
+
+/// Everything the VirtualDom thread listens to, kept outside of the VirtualDom so it survives a restart
+struct VdomReceivers {
+    dom_events: EventReceiver,
+    update_scope: tokio::sync::mpsc::UnboundedReceiver<ScopeId>,
+    hot_reload: tokio::sync::mpsc::UnboundedReceiver<HotReloadMsg>,
+    window_size: tokio::sync::watch::Receiver<PhysicalSize<u32>>,
+    restart: tokio::sync::mpsc::UnboundedReceiver<()>,
 }
 
+/// Drives the VirtualDom, only returns when a restart is requested from the error overlay
+async fn run_vdom(mut vdom: VirtualDom, mutations: &MutationQueue, receivers: &mut VdomReceivers, wake: &dyn Fn()) {
+    // ignore clicks on the restart button that were queued up before this run
+    while receivers.restart.try_recv().is_ok() {}
+
+    mutations.push(vdom.rebuild());
+    wake();
+
+    // set while the window is being resized, ``onresize`` is sent once the size didn't change for ``RESIZE_DEBOUNCE``
+    let mut resize_deadline: Option<Instant> = None;
+    loop {
+        let mut hot_reloaded = false;
+        tokio::select! {
+            _ = vdom.wait_for_work() => {},
+            Some(msg) = receivers.hot_reload.recv() => {
+                apply_hot_reload(&mut vdom, msg);
+                // saving a file often changes several templates at once, render them together
+                while let Some(Some(msg)) = platform::timeout(HOT_RELOAD_DEBOUNCE, receivers.hot_reload.recv()).await {
+                    apply_hot_reload(&mut vdom, msg);
+                }
+                hot_reloaded = true;
+            }
+            event = receivers.dom_events.recv() => {
+                handle_dom_event(&mut vdom, event);
+            }
+            Some(scope_id) = receivers.update_scope.recv() => {
+                if let Some(scope) = vdom.get_scope(scope_id) {
+                    scope.needs_update();
+                }
+            }
+            Ok(()) = receivers.window_size.changed() => {
+                // listeners don't get flooded while the user is dragging the window, and the VirtualDom keeps
+                // handling everything else in the meantime
+                receivers.window_size.borrow_and_update();
+                resize_deadline = Some(Instant::now() + RESIZE_DEBOUNCE);
+            }
+            _ = platform::sleep(resize_deadline.map_or(Duration::ZERO, |deadline| deadline.saturating_duration_since(Instant::now()))), if resize_deadline.is_some() => {
+                resize_deadline = None;
+                let size = *receivers.window_size.borrow();
+                mutations.push_command(move |dom| dom.send_resize_event(size));
+                wake();
+            }
+            Some(()) = receivers.restart.recv() => {
+                return;
+            }
+        }
+
+        // applied by the painting thread at the start of the next frame, rendering doesn't wait for a frame to finish
+        let changed = {
+            let _span = tracing::trace_span!("VirtualDom::render_immediate").entered();
+            if hot_reloaded {
+                mutations.push_hot_reloaded(vdom.render_immediate())
+            } else {
+                mutations.push(vdom.render_immediate())
+            }
+        };
+
+        // events that didn't change anything, like moves over nodes without handlers, leave the window idle
+        if changed {
+            wake();
+        }
+    }
+}
+
+#[tracing::instrument(skip_all, name = "handle_dom_event", fields(name = %event.name))]
+fn handle_dom_event(vdom: &mut VirtualDom, event: DomEvent) {
+    let DomEvent { name, data, element_id, bubbles } = event;
+    vdom.handle_event(&name, data.deref().clone().into_any(), element_id, bubbles);
+}
+
+fn apply_hot_reload(vdom: &mut VirtualDom, msg: HotReloadMsg) {
+    #[cfg(all(feature = "hot-reload", debug_assertions))]
+    match msg {
+        dioxus_hot_reload::HotReloadMsg::UpdateTemplate(template) => {
+            vdom.replace_template(template);
+        }
+        dioxus_hot_reload::HotReloadMsg::Shutdown => {
+            std::process::exit(0);
+        }
+    }
+    #[cfg(not(all(feature = "hot-reload", debug_assertions)))]
+    let _ = (vdom, msg);
+}
+
+/// The VirtualDom of [`DomEventLoop::new_single_threaded`], it lives on the thread of the event loop
+struct LocalVirtualDom {
+    vdom: VirtualDom,
+    receivers: VdomReceivers,
+    /// Drives the timers and io the tasks of the components wait on
+    #[cfg(not(target_arch = "wasm32"))]
+    runtime: tokio::runtime::Runtime,
+}
+
+impl LocalVirtualDom {
+    /// Gives the VirtualDom a single chance to process woken tasks, true if some scope has to render
+    fn has_work(&mut self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let vdom = &mut self.vdom;
+            self.runtime.block_on(async {
+                // lets tokio fire what the tasks are waiting on first
+                tokio::task::yield_now().await;
+                platform::poll_now(vdom.wait_for_work()).is_some()
+            })
+        }
+        #[cfg(target_arch = "wasm32")]
+        platform::poll_now(self.vdom.wait_for_work()).is_some()
+    }
+}
+
+/// How long the window size has to stay the same before ``onresize`` is sent
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(100);
+/// Window events a slow ``use_window_event`` listener can fall behind by before it skips some
+const WINDOW_EVENT_CAPACITY: usize = 256;
+/// Tray clicks a slow ``use_tray`` handler can fall behind by
+#[cfg(feature = "tray")]
+const TRAY_EVENT_CAPACITY: usize = 16;
+/// How long to wait for more template updates before rendering the ones that came in
+const HOT_RELOAD_DEBOUNCE: Duration = Duration::from_millis(50);
 
 impl DomEventLoop {
-    pub fn spawn<E: Debug + Send + Sync + Clone, T: Clone + 'static + Send + Sync>(app: fn(Scope) -> Element, window: Arc<Window>, renderer_desc: RendererDescriptor, event_proxy: EventLoopProxy<E>, redraw_event_to_send: E, root_context: T) -> DomEventLoop {
-        let (dom_event_sender, mut dom_event_receiver) = tokio::sync::mpsc::unbounded_channel::<DomEvent>();
+    pub fn spawn<E: Debug + Send + Sync + Clone + 'static, T: Clone + 'static + Send + Sync>(app: fn(Scope) -> Element, host: Arc<dyn Host>, renderer_desc: RendererDescriptor, event_proxy: EventLoopProxy<E>, redraw_event_to_send: E, root_context: T, panic_policy: PanicPolicy) -> DomEventLoop {
+        Self::spawn_with(move || VirtualDom::new(app), host, renderer_desc, proxy_waker(event_proxy, redraw_event_to_send), root_context, panic_policy)
+    }
+
+    /// Same as [`DomEventLoop::spawn`] for hosts without a winit event loop, the VirtualDom thread wakes the host
+    /// through [`Host::request_redraw`] when it rendered something
+    pub fn spawn_with_host<T: Clone + 'static + Send + Sync>(app: fn(Scope) -> Element, host: Arc<dyn Host>, renderer_desc: RendererDescriptor, root_context: T, panic_policy: PanicPolicy) -> DomEventLoop {
+        let waker = host.clone();
+        Self::spawn_with(move || VirtualDom::new(app), host, renderer_desc, move || waker.request_redraw(), root_context, panic_policy)
+    }
+
+    #[cfg(feature = "libloading")]
+    pub unsafe fn spawn_loaded_lib<E: Debug + Send + Sync + Clone + 'static, T: Clone + 'static + Send + Sync>(so_path: &'static str, host: Arc<dyn Host>, renderer_desc: RendererDescriptor, event_proxy: EventLoopProxy<E>, redraw_event_to_send: E, root_context: T, panic_policy: PanicPolicy) -> DomEventLoop {
+        // the library has to outlive the virtual dom, which lives for as long as the process does
+        let lib: &'static libloading::Library = Box::leak(Box::new(unsafe { libloading::Library::new(so_path).unwrap() }));
+        Self::spawn_with(move || {
+            let func: libloading::Symbol<fn(Scope) -> Element> = unsafe { lib.get(b"app").unwrap() };
+            VirtualDom::new(*func)
+        }, host, renderer_desc, proxy_waker(event_proxy, redraw_event_to_send), root_context, panic_policy)
+    }
+
+    /// Sets up the dom and everything the VirtualDom listens to, the caller decides where the VirtualDom runs
+    fn create<T: Clone + 'static + Send + Sync>(create_vdom: impl Fn() -> VirtualDom + Send + 'static, host: Arc<dyn Host>, renderer_desc: RendererDescriptor, root_context: T) -> (DomEventLoop, VdomReceivers, impl Fn(&Option<String>) -> VirtualDom + Send + 'static) {
+        let (dom_event_sender, dom_event_receiver) = event_queue::channel(EVENT_QUEUE_CAPACITY);
       
         #[cfg(all(feature = "hot-reload", debug_assertions))]
-        let (hot_reload_tx, mut hot_reload_rx) = tokio::sync::mpsc::unbounded_channel::<dioxus_hot_reload::HotReloadMsg>();
+        let (hot_reload_tx, hot_reload_rx) = tokio::sync::mpsc::unbounded_channel::<HotReloadMsg>();
         #[cfg(not(all(feature = "hot-reload", debug_assertions)))]
-        let (_, mut hot_reload_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let (_, hot_reload_rx) = tokio::sync::mpsc::unbounded_channel::<HotReloadMsg>();
     
-        let (update_scope_sender, mut update_scope_receiver) = tokio::sync::mpsc::unbounded_channel::<ScopeId>();
+        let (update_scope_sender, update_scope_receiver) = tokio::sync::mpsc::unbounded_channel::<ScopeId>();
         
         #[cfg(all(feature = "hot-reload", debug_assertions))]
         dioxus_hot_reload::connect(move |msg| {
@@ -48,217 +322,812 @@ impl DomEventLoop {
         let renderer = Renderer::new(renderer_desc);
         let dom_context = DomContext {
             texture_manager: renderer.tex_manager.clone(),
-            window: window.clone(),
+            atlas: renderer.atlas.clone(),
+            host: host.clone(),
             #[cfg(feature = "images")]
             client: reqwest::Client::new(),
             event_sender: dom_event_sender.clone(),
             current_cursor_icon: Default::default(),
+            window_size: Arc::new(tokio::sync::watch::channel(host.inner_size()).0),
+            window_events: tokio::sync::broadcast::channel(WINDOW_EVENT_CAPACITY).0,
+            #[cfg(feature = "images")]
+            image_cache: Default::default(),
+            #[cfg(feature = "tray")]
+            tray: Arc::new(tokio::sync::watch::channel(None).0),
+            #[cfg(feature = "tray")]
+            tray_events: tokio::sync::broadcast::channel(TRAY_EVENT_CAPACITY).0,
+            on_error: Default::default(),
         };
-        let dom = Arc::new(Mutex::new(Dom::new(dom_context.clone())));
+        let dom = Dom::new(dom_context.clone());
+        let mutations = Arc::new(MutationQueue::default());
+        let handle_shared = Arc::new(HandleShared::default());
 
-        std::thread::spawn({
-            let dom = dom.clone();
+        let (restart_sender, restart_receiver) = tokio::sync::mpsc::unbounded_channel::<()>();
+        let mut receivers = VdomReceivers {
+            dom_events: dom_event_receiver,
+            update_scope: update_scope_receiver,
+            hot_reload: hot_reload_rx,
+            window_size: dom_context.window_size.subscribe(),
+            restart: restart_receiver,
+        };
+        let build_vdom = {
+            let mutations = mutations.clone();
+            let handle_shared = handle_shared.clone();
             let context = dom_context.clone();
-            move || {
-                let mut vdom = VirtualDom::new(app).with_root_context(root_context).with_root_context(context);
-                let mutations = vdom.rebuild();
-                dom.lock().unwrap().apply_mutations(mutations);
-                event_proxy.send_event(redraw_event_to_send.clone()).unwrap();
-    
-                tokio::runtime::Builder::new_current_thread()
-                    .enable_all()
-                    .build()
-                    .unwrap()
-                    .block_on(async move {
-                        loop {
-                            tokio::select! {
-                                _ = vdom.wait_for_work() => {},
-                                Some(_msg) = hot_reload_rx.recv() => {
-                                    #[cfg(all(feature = "hot-reload", debug_assertions))]
-                                    {
-                                        match _msg {
-                                            dioxus_hot_reload::HotReloadMsg::UpdateTemplate(template) => {
-                                                vdom.replace_template(template);
-                                            }
-                                            dioxus_hot_reload::HotReloadMsg::Shutdown => {
-                                                std::process::exit(0);
-                                            }                                        
-                                        }
-                                    }
-                                }
-                                Some(event) = dom_event_receiver.recv() => {
-                                    let DomEvent { name, data, element_id, bubbles } = event;
-                                    vdom.handle_event(&name, data.deref().clone().into_any(), element_id, bubbles);
-                                }
-                                Some(scope_id) = update_scope_receiver.recv() => {
-                                    vdom.get_scope(scope_id).unwrap().needs_update();
-                                }
-                            }
-        
-                            let mutations = vdom.render_immediate();
-                            dom.lock().unwrap().apply_mutations(mutations);
-        
-                            event_proxy.send_event(redraw_event_to_send.clone()).unwrap();
-                        }
-                    });
+            move |crash_message: &Option<String>| {
+                let vdom = match crash_message {
+                    None => create_vdom().with_root_context(root_context.clone()),
+                    Some(message) => VirtualDom::new_with_props(ErrorOverlay, ErrorOverlayProps {
+                        message: message.clone(),
+                        restart: restart_sender.clone(),
+                    }),
+                };
+                vdom.with_root_context(context.clone()).with_root_context(DomHandle::new(context.clone(), mutations.clone(), handle_shared.clone()))
             }
-        });
-    
-        DomEventLoop {
+        };
+
+        let event_loop = DomEventLoop {
             dom,
             update_scope_sender,
-            renderer,
+            mutations,
+            handle_shared,
+            snapshot: Default::default(),
+            local_vdom: None,
+            #[cfg(feature = "libloading")]
             lib: None,
-        }
+            recorder: None,
+            zoomable: false,
+            auto_min_size: false,
+            applied_min_size: None,
+            suspended: false,
+            first_frame: FirstFrame::Waiting,
+            on_first_frame: None,
+            frame_id: 0,
+            #[cfg(feature = "tray")]
+            tray: TrayManager::new(dom_context.tray.subscribe(), dom_context.tray_events.clone()),
+            viewport: None,
+            window_screen: renderer.screen_descriptor.clone(),
+            texture_namespace: NEXT_TEXTURE_NAMESPACE.fetch_add(1, Ordering::Relaxed) << TEXTURE_NAMESPACE_SHIFT,
+            pointer_captured: false,
+            pointer_inside_viewport: false,
+            viewport_touches: Vec::new(),
+            has_keyboard_focus: true,
+            renderer,
+        };
+        (event_loop, receivers, build_vdom)
     }
 
-    pub unsafe fn spawn_loaded_lib<E: Debug + Send + Sync + Clone, T: Clone + 'static + Send + Sync>(so_path: &'static str, window: Arc<Window>, renderer_desc: RendererDescriptor, event_proxy: EventLoopProxy<E>, redraw_event_to_send: E, root_context: T) -> DomEventLoop {
-        let (dom_event_sender, mut dom_event_receiver) = tokio::sync::mpsc::unbounded_channel::<DomEvent>();
-      
-        #[cfg(all(feature = "hot-reload", debug_assertions))]
-        let (hot_reload_tx, mut hot_reload_rx) = tokio::sync::mpsc::unbounded_channel::<dioxus_hot_reload::HotReloadMsg>();
-        #[cfg(not(all(feature = "hot-reload", debug_assertions)))]
-        let (_, mut hot_reload_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
-    
-        let (update_scope_sender, mut update_scope_receiver) = tokio::sync::mpsc::unbounded_channel::<ScopeId>();
-        
-        #[cfg(all(feature = "hot-reload", debug_assertions))]
-        dioxus_hot_reload::connect(move |msg| {
-            let _ = hot_reload_tx.send(msg);
-        });
-        let renderer = Renderer::new(renderer_desc);
-        let dom_context = DomContext {
-            texture_manager: renderer.tex_manager.clone(),
-            window: window.clone(),
-            #[cfg(feature = "images")]
-            client: reqwest::Client::new(),
-            event_sender: dom_event_sender.clone(),
-            current_cursor_icon: Default::default(),
-        };
-        let dom = Arc::new(Mutex::new(Dom::new(dom_context.clone())));
+    fn spawn_with<T: Clone + 'static + Send + Sync>(create_vdom: impl Fn() -> VirtualDom + Send + 'static, host: Arc<dyn Host>, renderer_desc: RendererDescriptor, wake: impl Fn() + Send + 'static, root_context: T, panic_policy: PanicPolicy) -> DomEventLoop {
+        let (event_loop, mut receivers, build_vdom) = Self::create(create_vdom, host, renderer_desc, root_context);
+        let mutations = &event_loop.mutations;
 
+        #[cfg(not(target_arch = "wasm32"))]
         std::thread::spawn({
-            let dom = dom.clone();
-            let context = dom_context.clone();
-            // let func = lib.clone();
+            let context = event_loop.dom.context().clone();
+            let mutations = mutations.clone();
             move || {
-                let lib = libloading::Library::new(so_path).unwrap();
-                let func: libloading::Symbol<fn(Scope) -> Element> = unsafe { lib.get(b"app").unwrap() };
-
-                let mut vdom = VirtualDom::new(*func).with_root_context(root_context).with_root_context(context);
-                let mutations = vdom.rebuild();
-                dom.lock().unwrap().apply_mutations(mutations);
-                event_proxy.send_event(redraw_event_to_send.clone()).unwrap();
-    
-                tokio::runtime::Builder::new_current_thread()
+                let runtime = tokio::runtime::Builder::new_current_thread()
                     .enable_all()
                     .build()
-                    .unwrap()
-                    .block_on(async move {
-                        loop {
-                            tokio::select! {
-                                _ = vdom.wait_for_work() => {},
-                                Some(_msg) = hot_reload_rx.recv() => {
-                                    #[cfg(all(feature = "hot-reload", debug_assertions))]
-                                    {
-                                        match _msg {
-                                            dioxus_hot_reload::HotReloadMsg::UpdateTemplate(template) => {
-                                                vdom.replace_template(template);
-                                            }
-                                            dioxus_hot_reload::HotReloadMsg::Shutdown => {
-                                                std::process::exit(0);
-                                            }                                        
-                                        }
-                                    }
-                                }
-                                Some(event) = dom_event_receiver.recv() => {
-                                    let DomEvent { name, data, element_id, bubbles } = event;
-                                    vdom.handle_event(&name, data.deref().clone().into_any(), element_id, bubbles);
-                                }
-                                Some(scope_id) = update_scope_receiver.recv() => {
-                                    vdom.get_scope(scope_id).unwrap().needs_update();
+                    .unwrap();
+                let mut crash_message: Option<String> = None;
+
+                loop {
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        runtime.block_on(run_vdom(build_vdom(&crash_message), &mutations, &mut receivers, &wake));
+                    }));
+
+                    match result {
+                        // the error overlay asked for a restart
+                        Ok(()) => crash_message = None,
+                        Err(panic) => {
+                            let message = panic_message(&*panic);
+                            context.report_error(DomError::VirtualDomPanicked(message.clone()));
+
+                            match panic_policy {
+                                PanicPolicy::Abort => std::process::abort(),
+                                // if the overlay itself panics there's nothing left to show
+                                PanicPolicy::Restart if crash_message.is_none() => crash_message = Some(message),
+                                PanicPolicy::Restart | PanicPolicy::Freeze => {
+                                    wake();
+                                    return;
                                 }
                             }
-        
-                            let mutations = vdom.render_immediate();
-                            dom.lock().unwrap().apply_mutations(mutations);
-        
-                            event_proxy.send_event(redraw_event_to_send.clone()).unwrap();
                         }
-                    });
+                    }
+
+                    // the renders of the old VirtualDom point at elements that are gone
+                    mutations.clear();
+                    mutations.push_command(Dom::reset);
+                }
             }
         });
-    
-        DomEventLoop {
-            dom,
-            update_scope_sender,
-            renderer,
-            lib: None
+
+        // there are no threads on the web, so the VirtualDom runs on the browser's event loop.
+        // panics abort there, which leaves the panic policy with nothing to recover from
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local({
+            let mutations = mutations.clone();
+            let _ = panic_policy;
+            async move {
+                loop {
+                    run_vdom(build_vdom(&None), &mutations, &mut receivers, &wake).await;
+                    mutations.clear();
+                    mutations.push_command(Dom::reset);
+                }
+            }
+        });
+
+        event_loop
+    }
+
+    /// Runs the VirtualDom on the calling thread instead of spawning one, for hosts that can't and for tests.
+    /// Nothing happens until [`DomEventLoop::pump`] is called, so every run goes exactly the same way.
+    /// Tests and benchmarks pass a [`crate::host::HeadlessHost`] and don't need a window
+    pub fn new_single_threaded<T: Clone + 'static + Send + Sync>(app: fn(Scope) -> Element, host: Arc<dyn Host>, renderer_desc: RendererDescriptor, root_context: T) -> DomEventLoop {
+        let (mut event_loop, receivers, build_vdom) = Self::create(move || VirtualDom::new(app), host, renderer_desc, root_context);
+        let mut vdom = build_vdom(&None);
+        event_loop.mutations.push(vdom.rebuild());
+        event_loop.local_vdom = Some(Box::new(LocalVirtualDom {
+            vdom,
+            receivers,
+            #[cfg(not(target_arch = "wasm32"))]
+            runtime: tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap(),
+        }));
+        event_loop
+    }
+
+    /// Runs everything the VirtualDom of [`DomEventLoop::new_single_threaded`] has queued up: events, scope updates and
+    /// woken tasks, until there's nothing left to render. Returns true when there's something new to paint.
+    /// Unlike the threaded mode ``onresize`` isn't debounced and panics aren't caught.
+    pub fn pump(&mut self) -> bool {
+        let Some(local) = &mut self.local_vdom else {
+            return false;
+        };
+
+        let mut rendered = false;
+        let mut hot_reloaded = false;
+        loop {
+            while let Ok(msg) = local.receivers.hot_reload.try_recv() {
+                apply_hot_reload(&mut local.vdom, msg);
+                hot_reloaded = true;
+            }
+            while let Some(event) = local.receivers.dom_events.try_recv() {
+                handle_dom_event(&mut local.vdom, event);
+            }
+            while let Ok(scope_id) = local.receivers.update_scope.try_recv() {
+                if let Some(scope) = local.vdom.get_scope(scope_id) {
+                    scope.needs_update();
+                }
+            }
+            if local.receivers.window_size.has_changed().unwrap_or(false) {
+                let size = *local.receivers.window_size.borrow_and_update();
+                self.dom.send_resize_event(size);
+                // the listeners are in the event queue now
+                continue;
+            }
+
+            if !local.has_work() {
+                break;
+            }
+            rendered |= if std::mem::take(&mut hot_reloaded) {
+                self.mutations.push_hot_reloaded(local.vdom.render_immediate())
+            } else {
+                self.mutations.push(local.vdom.render_immediate())
+            };
         }
+
+        // handlers that only used the DomHandle queued commands without rendering
+        rendered || !self.mutations.is_empty()
     }
 
     pub fn get_paint_info(&mut self) -> (Vec<ClippedPrimitive>, TexturesDelta, &ScreenDescriptor) {
-        let mut vdom = self.dom.lock().unwrap();
-        self.renderer.get_paint_info(&mut vdom)
+        self.frame_id += 1;
+        let _span = tracing::trace_span!("DomEventLoop::get_paint_info", frame = self.frame_id).entered();
+        // embedders that don't recycle their primitives
+        self.finish_first_frame();
+        // nothing is locked while the frame is laid out and painted, the VirtualDom thread only queues and reads
+        // the snapshot. Events sent during the frame wait for the snapshot that has the nodes they're about
+        let event_sender = self.dom.context().event_sender.clone();
+        event_sender.hold();
+        let dom = &mut self.dom;
+        {
+            let _span = tracing::trace_span!("apply mutations").entered();
+            self.mutations.apply_to(dom);
+        }
+        #[cfg(feature = "tray")]
+        self.tray.update();
+        dom.flush_moves();
+        let is_auto_scrolling = dom.auto_scroll_drag();
+        let is_bouncing = dom.relax_overscroll();
+        if is_auto_scrolling || is_bouncing {
+            dom.request_redraw();
+        }
+        dom.flush_scrolls();
+        let (mut primitives, mut textures_delta, _) = self.renderer.get_paint_info(dom);
+        // finding the min-content size lays the tree out twice more, it only changes when the layout does
+        if self.auto_min_size && (self.renderer.layout_dirty || self.applied_min_size.is_none()) {
+            let size = Self::compute_content_min_size(&mut self.renderer, dom);
+            if self.applied_min_size != Some(size) {
+                dom.set_window_min_size(Some(size));
+                self.applied_min_size = Some(size);
+            }
+        }
+        if self.first_frame == FirstFrame::Waiting && !primitives.is_empty() {
+            self.first_frame = FirstFrame::Produced;
+        }
+
+        #[cfg(feature = "export")]
+        for (node_id, sender) in std::mem::take(&mut dom.capture_requests) {
+            // the node might have been removed since, dropping the sender lets the capture resolve to None
+            if dom.tree.get_node_context(node_id).is_some() {
+                let _ = sender.send(self.renderer.render_node_image(dom, node_id));
+            }
+        }
+        self.publish_snapshot();
+        event_sender.release();
+
+        self.place_in_viewport(&mut primitives, &mut textures_delta);
+        let screen = match self.viewport {
+            Some(_) => &self.window_screen,
+            None => &self.renderer.screen_descriptor,
+        };
+        (primitives, textures_delta, screen)
+    }
+
+    /// Updates the snapshot from the frame before the last one and hands it to the [`DomHandle`]s. It's only copied
+    /// when a handle still holds on to it, otherwise it's updated in place
+    fn publish_snapshot(&mut self) {
+        let _span = tracing::trace_span!("publish snapshot").entered();
+        self.dom.update_snapshot(Arc::make_mut(&mut self.snapshot));
+        let snapshot = std::mem::take(&mut self.snapshot);
+        self.snapshot = self.handle_shared.publish(snapshot);
+    }
+
+    /// Moves what was painted into the viewport, and gives the textures of every root after the first their own ids
+    fn place_in_viewport(&mut self, primitives: &mut [ClippedPrimitive], textures_delta: &mut TexturesDelta) {
+        let namespace = self.texture_namespace;
+        if namespace == 0 && self.viewport.is_none() {
+            return;
+        }
+        let with_namespace = |id: TextureId| match id {
+            TextureId::Managed(id) => TextureId::Managed(id | namespace),
+            id => id,
+        };
+        for (id, _) in &mut textures_delta.set {
+            *id = with_namespace(*id);
+        }
+        for id in &mut textures_delta.free {
+            *id = with_namespace(*id);
+        }
+
+        let pixels_per_point = self.renderer.screen_descriptor.pixels_per_point;
+        self.window_screen.pixels_per_point = pixels_per_point;
+        let viewport_rect = self.viewport.map(|viewport| {
+            Rect::from_min_size(
+                epaint::pos2(viewport.x as f32, viewport.y as f32) / pixels_per_point,
+                epaint::vec2(viewport.width as f32, viewport.height as f32) / pixels_per_point,
+            )
+        });
+        for primitive in primitives {
+            if let Primitive::Mesh(mesh) = &mut primitive.primitive {
+                mesh.texture_id = with_namespace(mesh.texture_id);
+            }
+            let Some(viewport_rect) = viewport_rect else {
+                continue;
+            };
+            let offset = viewport_rect.min.to_vec2();
+            primitive.clip_rect = primitive.clip_rect.translate(offset).intersect(viewport_rect);
+            match &mut primitive.primitive {
+                Primitive::Mesh(mesh) => mesh.translate(offset),
+                Primitive::Callback(callback) => callback.rect = callback.rect.translate(offset),
+            }
+        }
+    }
+
+    /// Gives the primitives of [`DomEventLoop::get_paint_info`] back once they're painted, so the next frame
+    /// reuses their allocation
+    pub fn recycle_primitives(&mut self, primitives: Vec<ClippedPrimitive>) {
+        self.renderer.recycle(primitives);
+        self.finish_first_frame();
+    }
+
+    /// The frame [`DomEventLoop::get_paint_info`] produced last, the same id is on its tracing spans
+    pub fn frame_id(&self) -> u64 {
+        self.frame_id
+    }
+
+    /// Calls ``callback`` once the first frame with content is painted, e.g. to show a window that was created
+    /// hidden without flashing an empty one. Called right away when that already happened
+    pub fn on_first_frame_rendered(&mut self, callback: impl FnOnce() + Send + 'static) {
+        if self.first_frame == FirstFrame::Rendered {
+            callback();
+        } else {
+            self.on_first_frame = Some(Box::new(callback));
+        }
+    }
+
+    /// Whether a frame with content was painted, see [`DomEventLoop::on_first_frame_rendered`]
+    pub fn first_frame_rendered(&self) -> bool {
+        self.first_frame == FirstFrame::Rendered
+    }
+
+    fn finish_first_frame(&mut self) {
+        if self.first_frame != FirstFrame::Produced {
+            return;
+        }
+        self.first_frame = FirstFrame::Rendered;
+        if let Some(callback) = self.on_first_frame.take() {
+            callback();
+        }
+    }
+
+    /// Lays out and tessellates the UI without going through one of the renderer crates, for engines with their own renderer.
+    ///
+    /// The format of the output is kept stable:
+    /// - every primitive is a [`epaint::Mesh`]. The paint callbacks of ``backdrop-blur-*`` are left out, those nodes
+    ///   only get their translucent background
+    /// - indices are ``u32`` and form a triangle list
+    /// - vertex positions and clip rects are in points, multiply them by the pixels per point to get pixels
+    /// - ``uv`` is normalized into the mesh's texture, ``TextureId::default()`` is the font atlas
+    /// - ``color`` is premultiplied sRGBA in gamma space and is multiplied with the texture sample
+    /// - textures in the delta are uploaded before painting and freed after
+    pub fn tessellate_only(&mut self) -> (Vec<ClippedPrimitive>, TexturesDelta) {
+        let (mut primitives, textures_delta, _) = self.get_paint_info();
+        primitives.retain(|primitive| matches!(primitive.primitive, Primitive::Mesh(_)));
+        (primitives, textures_delta)
+    }
+
+    /// Lays the UI out at ``size`` and paints it on the cpu, the window keeps its own size and scale
+    #[cfg(feature = "export")]
+    pub fn render_image(&mut self, size: PhysicalSize<u32>, pixels_per_point: f32) -> epaint::ColorImage {
+        let dom = &mut self.dom;
+        self.mutations.apply_to(dom);
+        let renderer = &mut self.renderer;
+        let (scale_factor, zoom, window_size) = (renderer.scale_factor, renderer.zoom, renderer.screen_descriptor.size);
+
+        renderer.scale_factor = pixels_per_point;
+        renderer.zoom = 1.0;
+        renderer.update_pixels_per_point(dom);
+        renderer.screen_descriptor.size = size;
+        let image = renderer.render_image(dom);
+
+        renderer.scale_factor = scale_factor;
+        renderer.zoom = zoom;
+        renderer.update_pixels_per_point(dom);
+        renderer.screen_descriptor.size = window_size;
+        dom.invalidate_all_layouts();
+        dom.request_redraw();
+
+        image
+    }
+
+    /// Saves the UI laid out at ``size`` as a png, see [`DomEventLoop::render_image`]
+    #[cfg(feature = "export")]
+    pub fn export_png(&mut self, path: impl AsRef<std::path::Path>, size: PhysicalSize<u32>, pixels_per_point: f32) -> std::io::Result<()> {
+        let image = self.render_image(size, pixels_per_point);
+        export::save_png(&image, path)
+    }
+
+    /// Saves the UI laid out at ``size`` as a single page pdf, the page gets the size the UI would have on a screen
+    #[cfg(feature = "export")]
+    pub fn export_pdf(&mut self, path: impl AsRef<std::path::Path>, size: PhysicalSize<u32>, pixels_per_point: f32) -> std::io::Result<()> {
+        let image = self.render_image(size, pixels_per_point);
+        export::save_pdf(&image, pixels_per_point, path)
+    }
+
+    /// The smallest size the UI can be laid out in without overflowing
+    pub fn content_min_size(&mut self) -> PhysicalSize<u32> {
+        self.mutations.apply_to(&mut self.dom);
+        self.renderer.calculate_layout(&mut self.dom);
+        Self::compute_content_min_size(&mut self.renderer, &mut self.dom)
+    }
+
+    /// Expects the styles to be computed by [`Renderer::calculate_layout`]
+    fn compute_content_min_size(renderer: &mut Renderer, dom: &mut Dom) -> PhysicalSize<u32> {
+        let size = renderer.content_min_size(dom);
+        let pixels_per_point = renderer.screen_descriptor.pixels_per_point;
+        PhysicalSize::new(
+            (size.width * pixels_per_point).ceil() as u32,
+            (size.height * pixels_per_point).ceil() as u32,
+        )
+    }
+
+    /// Same as [`DomEventLoop::get_paint_info`], but detached from the renderer so it can be sent elsewhere
+    pub fn get_paint_snapshot(&mut self) -> PaintSnapshot {
+        let (primitives, textures_delta, screen_descriptor) = self.get_paint_info();
+        PaintSnapshot::new(primitives, textures_delta, screen_descriptor)
     }
 
     pub fn on_window_event(&mut self, event: &winit::event::WindowEvent) -> bool {
+        let Some(event) = InputEvent::from_window_event(event) else {
+            return false;
+        };
+
+        self.push_input(event)
+    }
+
+    /// Drives the dom without winit, returns true if a repaint is needed
+    pub fn push_input(&mut self, event: InputEvent) -> bool {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(event.clone());
+        }
+
+        self.apply_input(&event)
+    }
+
+    /// Called for mutations that couldn't be applied and when the VirtualDom thread panics
+    pub fn on_error(&self, on_error: impl FnMut(DomError) + Send + 'static) {
+        self.dom.set_error_handler(on_error);
+    }
+
+    /// Call on ``Event::Suspended``, Android and iOS take the surface away while the app is in the background
+    pub fn on_suspended(&mut self) {
+        self.suspended = true;
+    }
+
+    /// Call on ``Event::Resumed`` once the surface is recreated, the window may have changed size in the meantime
+    pub fn on_resumed(&mut self) {
+        self.suspended = false;
+        let size = self.dom.window_size();
+        self.push_input(InputEvent::Resized { width: size.width, height: size.height });
+        self.dom.request_redraw();
+    }
+
+    /// Nothing should be painted while suspended, there's no surface to paint to
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Whether an on-screen keyboard should be shown, on platforms where winit doesn't do this by itself
+    pub fn wants_text_input(&self) -> bool {
+        self.dom.wants_text_input()
+    }
+
+    /// How many events were sent to the VirtualDom thread and how many pointer moves were dropped because it fell behind
+    pub fn event_metrics(&self) -> EventMetrics {
+        self.dom.event_metrics()
+    }
+
+    /// Whether pointer moves are merged into one ``mousemove`` / ``drag`` event per frame, the default, or all sent
+    pub fn set_move_coalescing(&mut self, move_coalescing: MoveCoalescing) {
+        self.dom.move_coalescing = move_coalescing;
+        if move_coalescing == MoveCoalescing::None {
+            self.dom.flush_moves();
+        }
+    }
+
+    /// Lets trackpad scrolling pull scroll containers past their ends and bounce back, off by default
+    pub fn set_rubber_band(&mut self, rubber_band: bool) {
+        self.dom.rubber_band = rubber_band;
+    }
+
+    /// How far the arrow keys, PageUp, PageDown and Space scroll the container around the focused node
+    pub fn set_keyboard_scroll(&mut self, keyboard_scroll: KeyboardScroll) {
+        self.dom.keyboard_scroll = keyboard_scroll;
+    }
+
+    /// The space kept around a node when focusing it scrolls it into view, 8 points by default
+    pub fn set_focus_scroll_margin(&mut self, margin: f32) {
+        self.dom.focus_scroll_margin = margin;
+    }
+
+    /// Makes ``tag`` a native element with the behavior of ``element``, see [`crate::element`].
+    /// Call it before the first frame, templates that were already built keep their tags
+    pub fn register_element(&mut self, tag: &str, element: impl crate::element::Element) {
+        self.dom.elements.insert(tag.into(), Arc::new(element));
+    }
+
+    /// Shapes text before it's laid out, see [`crate::TextShaper`]. Call it before the first frame,
+    /// text that was already measured keeps its glyphs until it changes
+    pub fn set_text_shaper(&mut self, shaper: impl crate::TextShaper) {
+        self.renderer.text_shaper = Some(Arc::new(shaper));
+    }
+
+    /// Lays the root out in a region of the window and paints it there, for hosts that show several roots in one window.
+    /// Every root gets all window events, pointer input outside of the region is ignored unless a button was pressed
+    /// inside it and keys go to the root that was pressed last. Paint the roots in one pass with ``tpaint_backend::paint_doms``.
+    /// ``None`` goes back to the whole window
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) {
+        if self.viewport == viewport {
+            return;
+        }
+        if self.viewport.is_none() {
+            self.window_screen = self.renderer.screen_descriptor.clone();
+        }
+        self.viewport = viewport;
+        let size = match viewport {
+            Some(viewport) => PhysicalSize::new(viewport.width, viewport.height),
+            None => self.window_screen.size,
+        };
+        self.renderer.screen_descriptor.size = size;
+        self.dom.on_window_resize(size);
+        self.dom.request_redraw();
+    }
+
+    pub fn viewport(&self) -> Option<Viewport> {
+        self.viewport
+    }
+
+    /// Sends the keys to this root, e.g. when the host moves between roots with a shortcut
+    pub fn set_keyboard_focus(&mut self, focused: bool) {
+        self.has_keyboard_focus = focused;
+    }
+
+    /// Drops the input that belongs to another root and moves positions into the viewport
+    fn route_to_viewport(&mut self, viewport: Viewport, event: &InputEvent) -> Option<InputEvent> {
+        let (left, top) = (viewport.x as f64, viewport.y as f64);
+        match event {
+            InputEvent::Resized { width, height } => {
+                // the host moves the viewports when the window changes size
+                self.window_screen.size = PhysicalSize::new(*width, *height);
+                None
+            }
+            InputEvent::PointerMoved { x, y } => {
+                let inside = viewport.contains(*x, *y);
+                let was_inside = std::mem::replace(&mut self.pointer_inside_viewport, inside);
+                // one more move after leaving, so nothing stays hovered
+                (inside || was_inside || self.pointer_captured).then(|| InputEvent::PointerMoved { x: x - left, y: y - top })
+            }
+            InputEvent::PointerButton { state, .. } => {
+                if state.is_pressed() {
+                    self.has_keyboard_focus = self.pointer_inside_viewport;
+                    self.pointer_captured = self.pointer_inside_viewport;
+                    self.pointer_inside_viewport.then(|| event.clone())
+                } else {
+                    std::mem::take(&mut self.pointer_captured).then(|| event.clone())
+                }
+            }
+            InputEvent::Touch { phase, x, y, id, force } => {
+                let tracked = self.viewport_touches.contains(id);
+                match phase {
+                    TouchPhase::Started if viewport.contains(*x, *y) => {
+                        self.viewport_touches.push(*id);
+                        self.has_keyboard_focus = true;
+                    }
+                    TouchPhase::Started => {
+                        self.has_keyboard_focus = false;
+                        return None;
+                    }
+                    TouchPhase::Moved if tracked => {}
+                    TouchPhase::Ended | TouchPhase::Cancelled if tracked => self.viewport_touches.retain(|touch| touch != id),
+                    _ => return None,
+                }
+                Some(InputEvent::Touch { phase: *phase, x: x - left, y: y - top, id: *id, force: *force })
+            }
+            InputEvent::ScrollLines { .. } | InputEvent::ScrollPixels { .. } => self.pointer_inside_viewport.then(|| event.clone()),
+            InputEvent::Key { .. } | InputEvent::Text { .. } => self.has_keyboard_focus.then(|| event.clone()),
+            _ => Some(event.clone()),
+        }
+    }
+
+    /// Hides the window instead of closing it while a component shows a tray, see [`DomEventLoop::on_close_requested`].
+    /// ``DomHandle::show_window`` brings it back, e.g. on a [`crate::tray::TrayEvent::Click`]
+    #[cfg(feature = "tray")]
+    pub fn set_hide_to_tray(&mut self, hide_to_tray: bool) {
+        self.tray.hide_to_tray = hide_to_tray;
+    }
+
+    /// Call on ``WindowEvent::CloseRequested``, returns true when the app should exit
+    pub fn on_close_requested(&mut self) -> bool {
+        #[cfg(feature = "tray")]
+        if self.tray.hide_to_tray && self.tray.is_shown() {
+            if let Some(window) = self.dom.host().winit_window() {
+                window.set_visible(false);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Bytes of pixels image textures may take up before the least recently used ones that aren't shown are freed,
+    /// see [`ImageCache`]
+    #[cfg(feature = "images")]
+    pub fn set_image_budget(&mut self, budget: usize) {
+        let mut cache = self.dom.context().image_cache.lock().unwrap();
+        let mut atlas = self.renderer.atlas.lock().unwrap();
+        cache.set_budget(budget, &mut atlas, &mut self.renderer.tex_manager.lock().unwrap());
+    }
+
+    /// How full the atlas small images are packed into is
+    pub fn atlas_stats(&self) -> AtlasStats {
+        self.renderer.atlas.lock().unwrap().stats()
+    }
+
+    /// Outlines nodes that have classes the parser doesn't know in red, for development
+    pub fn set_show_unknown_classes(&mut self, show: bool) {
+        let dom = &mut self.dom;
+        dom.show_unknown_classes = show;
+        // cached layers were painted without the outlines
+        let root_id = dom.get_root_id();
+        dom.traverse_tree(root_id, &mut |dom, id| {
+            dom.tree.get_node_context_mut(id).unwrap().computed.layer = None;
+            true
+        });
+        dom.request_redraw();
+    }
+
+    /// Scales the whole UI on top of the scale factor of the monitor
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.renderer.zoom = zoom.clamp(0.25, 5.0);
+        self.renderer.update_pixels_per_point(&mut self.dom);
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.renderer.zoom
+    }
+
+    fn handle_zoom_shortcut(&mut self, logical_key: &Key) -> bool {
+        if !self.dom.state.command() {
+            return false;
+        }
+
+        let Key::Character(c) = logical_key else {
+            return false;
+        };
+
+        match c.as_str() {
+            "=" | "+" => self.set_zoom(self.zoom() * 1.1),
+            "-" => self.set_zoom(self.zoom() / 1.1),
+            "0" => self.set_zoom(1.0),
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(InputRecorder::new());
+    }
+
+    pub fn stop_recording(&mut self) -> Option<InputRecorder> {
+        self.recorder.take()
+    }
+
+    /// Feeds recorded events back into the dom, the layout is re-calculated before every event
+    /// so hit testing sees the same tree as it did during the recording. The dom sees the time pass between the events
+    /// like it did then, see [`Dom::now`]
+    pub fn replay(&mut self, events: &[TimedEvent]) {
+        // the last event happens now, so the times the dom keeps aren't in the future once the replay is done
+        let duration = events.last().map_or(Duration::ZERO, |timed_event| timed_event.timestamp);
+        let started_at = Instant::now().checked_sub(duration).unwrap_or_else(Instant::now);
+        for timed_event in events {
+            self.dom.replay_time = Some(started_at + timed_event.timestamp);
+            self.mutations.apply_to(&mut self.dom);
+            self.renderer.calculate_layout(&mut self.dom);
+            self.apply_input(&timed_event.event);
+        }
+        self.dom.replay_time = None;
+    }
+
+    #[tracing::instrument(skip_all, name = "DomEventLoop::apply_input")]
+    fn apply_input(&mut self, event: &InputEvent) -> bool {
+        let routed;
+        let event = match self.viewport {
+            Some(viewport) => match self.route_to_viewport(viewport, event) {
+                Some(event) => {
+                    routed = event;
+                    &routed
+                }
+                None => return false,
+            },
+            None => event,
+        };
         let mut repaint = false;
 
         match event {
-            WindowEvent::Moved(position) => {
-                let mut dom = self.dom.lock().unwrap();
-                dom.on_window_moved(position);
+            InputEvent::WindowMoved { x, y } => {
+                self.dom.on_window_moved(&PhysicalPosition::new(*x, *y));
                 repaint = true;
             }
-            WindowEvent::Resized(size) => {
+            InputEvent::Resized { width, height } => {
                 self.renderer.screen_descriptor = ScreenDescriptor {
-                   size: *size,
+                   size: PhysicalSize::new(*width, *height),
                    pixels_per_point: self.renderer.screen_descriptor.pixels_per_point
                 };
-                let mut dom = self.dom.lock().unwrap();
-                dom.on_window_resize();
+                self.dom.on_window_resize(PhysicalSize::new(*width, *height));
                 repaint = true;
             }
-            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
-                self.renderer.screen_descriptor = ScreenDescriptor {
-                    size: self.renderer.screen_descriptor.size,
-                    pixels_per_point: *scale_factor as f32,
-                };
-                let mut dom = self.dom.lock().unwrap();
-                dom.on_window_resize();
+            InputEvent::ScaleFactorChanged { scale_factor } => {
+                // glyphs are rasterized for a specific pixels per point, so the fonts have to be rebuilt when moving between monitors
+                self.renderer.scale_factor = *scale_factor as f32;
+                self.renderer.update_pixels_per_point(&mut self.dom);
+                self.dom.on_window_resize(self.renderer.screen_descriptor.size);
                 repaint = true;
             }
-            WindowEvent::MouseInput { button, state, .. } => {
-                let mut dom = self.dom.lock().unwrap();
-                repaint = dom.on_mouse_input(&self.renderer, button, state);
+            InputEvent::PointerButton { button, state } => {
+                let dom = &mut self.dom;
+                dom.state.pointer = PointerState {
+                    pressure: if state.is_pressed() { 0.5 } else { 0.0 },
+                    ..Default::default()
+                };
+                repaint = dom.on_mouse_input(&self.renderer, &button.to_winit(), &state.to_winit());
             }
-            WindowEvent::CursorMoved { position, .. } => {
-                let mut dom = self.dom.lock().unwrap();
-                repaint = dom.on_mouse_move(position, &self.renderer.screen_descriptor);
+            InputEvent::PointerMoved { x, y } => {
+                let dom = &mut self.dom;
+                // some platforms emulate cursor events for touches
+                if dom.state.pointer.touch_id.is_some() {
+                    return false;
+                }
+                dom.state.pointer.pointer_type = PointerType::Mouse;
+                repaint = dom.on_mouse_move(&PhysicalPosition::new(*x, *y), &self.renderer.screen_descriptor);
             }
-            WindowEvent::MouseWheel { delta,  .. } => {               
-                let mut dom = self.dom.lock().unwrap();
-                repaint = dom.on_scroll(delta)
+            InputEvent::Touch { phase, x, y, id, force } => {
+                repaint = self.dom.on_touch(&self.renderer, phase.to_winit(), &PhysicalPosition::new(*x, *y), *id, force.map(|force| force.to_winit()));
             }
-            WindowEvent::KeyboardInput { event, .. } => {
-                let mut dom = self.dom.lock().unwrap();
-                repaint = dom.on_keyboard_input(event);
+            InputEvent::ScrollLines { x, y } => {
+                repaint = self.dom.on_scroll(&MouseScrollDelta::LineDelta(*x, *y))
             }
-            WindowEvent::ModifiersChanged(modifiers) => {
-                let mut dom = self.dom.lock().unwrap();
-                dom.state.keyboard_state.modifiers = *modifiers;
+            InputEvent::ScrollPixels { x, y } => {
+                repaint = self.dom.on_scroll(&MouseScrollDelta::PixelDelta(PhysicalPosition::new(*x, *y)))
             }
-            WindowEvent::Focused(focused) => {
-                let mut dom = self.dom.lock().unwrap();
+            InputEvent::Key { state, logical_key, physical_key, text } => {
+                if self.zoomable && state.is_pressed() && self.handle_zoom_shortcut(logical_key) {
+                    return true;
+                }
+
+                repaint = self.dom.on_keyboard_input(state.to_winit(), &logical_key.to_winit(), physical_key.to_winit(), text.as_deref().map(SmolStr::new));
+            }
+            InputEvent::Text { text } => {
+                repaint = self.dom.on_text_input(text);
+            }
+            InputEvent::ModifiersChanged(modifiers) => {
+                self.dom.state.keyboard_state.modifiers = modifiers.to_winit().into();
+            }
+            InputEvent::Focused(focused) => {
+                let dom = &mut self.dom;
                 dom.state.keyboard_state.modifiers = Default::default();
                 if !focused {
                     dom.set_focus(None);
                 }
             }
-            _ => {}
         }
 
+        self.send_window_event(event);
         repaint
     }
-}
\ No newline at end of file
+
+    /// Passes the input on to [`crate::hooks::use_window_event`], after the dom has seen it
+    fn send_window_event(&self, event: &InputEvent) {
+        let dom = &self.dom;
+        let position = dom.state.cursor_state.current_position;
+        let event = match event {
+            InputEvent::Key { state, logical_key, physical_key, .. } => WindowEvent::Key {
+                element_state: state.to_winit(),
+                logical_key: logical_key.to_winit(),
+                physical_key: physical_key.to_winit(),
+                modifiers: dom.state.keyboard_state.modifiers,
+            },
+            InputEvent::PointerMoved { .. } => WindowEvent::PointerMove { position },
+            InputEvent::PointerButton { button, state } => WindowEvent::PointerButton {
+                button: button.to_winit(),
+                element_state: state.to_winit(),
+                position,
+            },
+            InputEvent::Resized { width, height } => WindowEvent::Resize(PhysicalSize::new(*width, *height)),
+            InputEvent::Focused(focused) => WindowEvent::Focus(*focused),
+            _ => return,
+        };
+        // fails when no component is listening
+        let _ = dom.context().window_events.send(event);
+    }
+}
+
+/// Wakes a winit event loop with ``redraw_event_to_send`` when the VirtualDom rendered something
+fn proxy_waker<E: Debug + Send + Sync + Clone + 'static>(event_proxy: EventLoopProxy<E>, redraw_event_to_send: E) -> impl Fn() + Send + 'static {
+    // fails once the event loop has exited
+    move || {
+        let _ = event_proxy.send_event(redraw_event_to_send.clone());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -0,0 +1,152 @@
+//! The queue of events from the window to the VirtualDom thread. It's bounded so a stalled app doesn't grow it forever:
+//! once full the oldest pointer move makes room, clicks, keys and everything else are always delivered.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::{
+    events::{DomEvent, Event},
+    platform::Instant,
+};
+
+/// How many events can wait for the VirtualDom before pointer moves are dropped
+pub(crate) const EVENT_QUEUE_CAPACITY: usize = 256;
+
+/// Counters of the event queue, see [`crate::DomEventLoop::event_metrics`]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventMetrics {
+    /// Events that made it into the queue
+    pub sent: u64,
+    /// Pointer moves dropped because the queue was full
+    pub dropped: u64,
+    /// Events currently waiting for the VirtualDom
+    pub queued: usize,
+    /// The longest the queue has been
+    pub max_queued: usize,
+}
+
+struct Shared {
+    /// With the time each event was sent, to trace how long it waited
+    queue: Mutex<VecDeque<(Instant, DomEvent)>>,
+    notify: tokio::sync::Notify,
+    capacity: usize,
+    closed: AtomicBool,
+    /// See [`EventSender::hold`]
+    held: AtomicBool,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    max_queued: AtomicUsize,
+}
+
+pub(crate) fn channel(capacity: usize) -> (EventSender, EventReceiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        notify: tokio::sync::Notify::new(),
+        capacity,
+        closed: AtomicBool::new(false),
+        held: AtomicBool::new(false),
+        sent: AtomicU64::new(0),
+        dropped: AtomicU64::new(0),
+        max_queued: AtomicUsize::new(0),
+    });
+    (EventSender(shared.clone()), EventReceiver(shared))
+}
+
+/// Moves are followed by newer ones, losing one only makes the pointer skip
+fn is_droppable(event: &DomEvent) -> bool {
+    matches!(*event.data, Event::MouseMove(_) | Event::Drag(_))
+}
+
+#[derive(Clone)]
+pub struct EventSender(Arc<Shared>);
+
+impl EventSender {
+    /// Fails when the VirtualDom thread is gone, e.g. after a panic
+    pub fn send(&self, event: DomEvent) -> Result<(), DomEvent> {
+        let shared = &self.0;
+        if shared.closed.load(Ordering::Relaxed) {
+            return Err(event);
+        }
+
+        let mut queue = shared.queue.lock().unwrap();
+        if queue.len() >= shared.capacity {
+            if let Some(index) = queue.iter().position(|(_, queued)| is_droppable(queued)) {
+                queue.remove(index);
+                shared.dropped.fetch_add(1, Ordering::Relaxed);
+            } else if is_droppable(&event) {
+                shared.dropped.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            }
+        }
+        queue.push_back((Instant::now(), event));
+        shared.sent.fetch_add(1, Ordering::Relaxed);
+        shared.max_queued.fetch_max(queue.len(), Ordering::Relaxed);
+        drop(queue);
+
+        if !shared.held.load(Ordering::Acquire) {
+            shared.notify.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Keeps the events sent from now on from the VirtualDom until [`EventSender::release`], while a frame is
+    /// painted. Handlers of ``onmounted`` and ``onlayout`` read the dom through the snapshot published after it
+    pub(crate) fn hold(&self) {
+        self.0.held.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn release(&self) {
+        self.0.held.store(false, Ordering::Release);
+        self.0.notify.notify_one();
+    }
+
+    pub fn metrics(&self) -> EventMetrics {
+        let shared = &self.0;
+        EventMetrics {
+            sent: shared.sent.load(Ordering::Relaxed),
+            dropped: shared.dropped.load(Ordering::Relaxed),
+            queued: shared.queue.lock().unwrap().len(),
+            max_queued: shared.max_queued.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub(crate) struct EventReceiver(Arc<Shared>);
+
+impl EventReceiver {
+    /// Cancel safe, events stay in the queue until they are returned
+    pub async fn recv(&mut self) -> DomEvent {
+        loop {
+            if let Some(event) = self.try_recv() {
+                return event;
+            }
+            self.0.notify.notified().await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<DomEvent> {
+        if self.0.held.load(Ordering::Acquire) {
+            return None;
+        }
+        let (sent_at, event) = self.0.queue.lock().unwrap().pop_front()?;
+        tracing::trace!(
+            target: "tpaint::events",
+            name = %event.name,
+            latency_us = sent_at.elapsed().as_micros() as u64,
+            "event received"
+        );
+        Some(event)
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        self.0.closed.store(true, Ordering::Relaxed);
+        self.0.queue.lock().unwrap().clear();
+    }
+}
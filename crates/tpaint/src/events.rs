@@ -5,11 +5,15 @@ use dioxus::core::ElementId;
 use epaint::text::cursor::Cursor;
 use taffy::{Layout, NodeId};
 use winit::{
+    dpi::PhysicalSize,
     event::{ElementState, Modifiers, MouseButton},
     keyboard::{Key, PhysicalKey, SmolStr},
 };
 
-use crate::dom::{Dom, DomState};
+use crate::dom::{Dom, DomState, NodeInfo};
+pub use crate::drag::DragPayload;
+use crate::platform::Instant;
+pub use crate::dom::{PointerState, PointerType};
 
 #[derive(Debug, Clone)]
 pub enum Event {
@@ -22,6 +26,13 @@ pub enum Event {
     MouseMove(MouseMoveEvent),
     Layout(LayoutEvent),
     Select(SelectEvent),
+    Resize(ResizeEvent),
+    HotReload(HotReloadEvent),
+    Scroll(ScrollEvent),
+    ScrollEdge(ScrollEdgeEvent),
+    DragDrop(DragDropEvent),
+    Mounted(MountedEvent),
+    Visibility(VisibilityEvent),
 }
 
 impl Event {
@@ -36,6 +47,13 @@ impl Event {
             Event::MouseMove(mouse_move) => Rc::new(mouse_move),
             Event::Layout(layout) => Rc::new(layout),
             Event::Select(select) => Rc::new(select),
+            Event::Resize(resize) => Rc::new(resize),
+            Event::HotReload(hot_reload) => Rc::new(hot_reload),
+            Event::Scroll(scroll) => Rc::new(scroll),
+            Event::ScrollEdge(scroll_edge) => Rc::new(scroll_edge),
+            Event::DragDrop(drag_drop) => Rc::new(drag_drop),
+            Event::Mounted(mounted) => Rc::new(mounted),
+            Event::Visibility(visibility) => Rc::new(visibility),
         }
     }
 }
@@ -59,25 +77,55 @@ impl DomState {
     pub fn shift(&self) -> bool {
         self.modifiers().state().shift_key()
     }
+
+    /// Pressure, tilt and type of the device behind the latest pointer event
+    pub fn pointer(&self) -> PointerState {
+        self.pointer
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct EventState {
     dom_state: DomState,
+    /// The node the event was sent to, as it was when the event happened
+    target: Option<NodeInfo>,
 }
 
 impl EventState {
     pub fn new(dom: &Dom, node_id: NodeId) -> Self {
-        // let rect = dom.tree.get_node_context(node_id).unwrap().computed.rect;
         Self {
-            // rect,
             dom_state: dom.state.clone(),
+            target: dom.node_info(node_id),
         }
     }
 
     pub fn state(&self) -> &DomState {
         &self.dom_state
     }
+
+    /// Parents and children can be looked up further with [`crate::DomHandle::node_info`]
+    pub fn target(&self) -> Option<&NodeInfo> {
+        self.target.as_ref()
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.target.as_ref()?.attrs.get(name).map(|value| &**value)
+    }
+
+    pub fn rect(&self) -> Option<epaint::Rect> {
+        Some(self.target.as_ref()?.rect)
+    }
+
+    pub fn scroll_offset(&self) -> Option<epaint::Vec2> {
+        Some(self.target.as_ref()?.scroll)
+    }
+
+    /// Whether the class applies to the target right now, e.g. ``bg-red-500`` from ``hover:bg-red-500`` while hovered
+    pub fn has_class(&self, class: &str) -> bool {
+        self.target
+            .as_ref()
+            .is_some_and(|target| target.classes.iter().any(|active| &**active == class))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -101,6 +149,12 @@ pub struct BlurEvent {
 #[derive(Clone, Debug)]
 pub struct DragEvent {
     pub state: EventState,
+    /// When the latest of the coalesced moves happened
+    pub timestamp: Instant,
+    /// How far the pointer moved since the previous event, in points
+    pub delta: epaint::Vec2,
+    /// How many pointer moves this event stands for, see [`crate::MoveCoalescing`]
+    pub coalesced: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -126,11 +180,20 @@ pub struct ClickEvent {
     pub button: MouseButton,
     pub element_state: ElementState,
     pub text_cursor_position: Option<usize>,
+    /// Clicks past the end of a wrapped line put the caret at its end instead of the start of the next
+    /// line, pass it on as ``text_cursor_prefer_next_row``
+    pub text_cursor_prefer_next_row: bool,
 }
 
 #[derive(Clone, Debug)]
 pub struct MouseMoveEvent {
     pub state: EventState,
+    /// When the latest of the coalesced moves happened
+    pub timestamp: Instant,
+    /// How far the pointer moved since the previous event, in points
+    pub delta: epaint::Vec2,
+    /// How many pointer moves this event stands for, see [`crate::MoveCoalescing`]
+    pub coalesced: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -142,9 +205,107 @@ pub struct LayoutEvent {
     pub layout: Layout,
 }
 
+/// Sent once after the first layout that contains the node. There is no unmounted counterpart, the VirtualDom drops the
+/// listeners of a node before the dom hears about its removal, ``use_on_destroy`` in the component covers that
+#[derive(Clone, Debug)]
+pub struct MountedEvent {
+    pub state: EventState,
+    /// Where the node ended up, in points from the top left of the window
+    pub rect: epaint::Rect,
+}
+
+/// How much of a node can be seen through the window and the scroll containers and other clipping ancestors around it.
+/// Sent right after the node is laid out and whenever the ratio changes noticeably, at most once per frame
+#[derive(Clone, Debug)]
+pub struct VisibilityEvent {
+    pub state: EventState,
+    /// Visible area over the area of the node, from 0 to 1
+    pub ratio: f32,
+    /// The part of the node that can be seen, in points from the top left of the window
+    pub visible_rect: epaint::Rect,
+}
+
+impl VisibilityEvent {
+    pub fn is_visible(&self) -> bool {
+        self.ratio > 0.0
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SelectEvent {
     pub state: EventState,
     pub start_cursor: Cursor,
     pub end_cursor: Cursor,
 }
+
+/// Input for the whole window, see [`crate::hooks::use_window_event`]. Positions are in points from the top left
+/// of the window
+#[derive(Clone, Debug)]
+pub enum WindowEvent {
+    /// Every key press and release, before the focused node handles it
+    Key {
+        element_state: ElementState,
+        logical_key: Key,
+        physical_key: PhysicalKey,
+        modifiers: Modifiers,
+    },
+    PointerMove {
+        position: epaint::Pos2,
+    },
+    PointerButton {
+        button: MouseButton,
+        element_state: ElementState,
+        position: epaint::Pos2,
+    },
+    /// The new size of the window in physical pixels
+    Resize(PhysicalSize<u32>),
+    /// Whether the window has the keyboard focus
+    Focus(bool),
+}
+
+#[derive(Clone, Debug)]
+pub struct ResizeEvent {
+    pub state: EventState,
+    /// The new size of the window in physical pixels
+    pub size: PhysicalSize<u32>,
+}
+
+/// Sent at most once per frame to a node whose scroll offset changed, in points
+#[derive(Clone, Debug)]
+pub struct ScrollEvent {
+    pub state: EventState,
+    pub offset: epaint::Vec2,
+    /// The largest offset the content can be scrolled to
+    pub max_offset: epaint::Vec2,
+    /// How far it scrolled since the previous event
+    pub delta: epaint::Vec2,
+    /// Points per second since the previous event, zero for the first event after scrolling stood still
+    pub velocity: epaint::Vec2,
+}
+
+/// ``onreachstart`` / ``onreachend``, sent once when scrolling brings the offset within ``scroll_start_threshold`` /
+/// ``scroll_end_threshold`` points of either end, e.g. to load the next page of a list
+#[derive(Clone, Debug)]
+pub struct ScrollEdgeEvent {
+    pub state: EventState,
+    pub offset: epaint::Vec2,
+    pub max_offset: epaint::Vec2,
+}
+
+/// ``ondragstart`` and ``ondragend`` go to the dragged node, ``ondragover``, ``ondragleave`` and ``ondrop`` to the node
+/// under the pointer that listens for them. ``ondragover`` is sent at most once per frame
+#[derive(Clone, Debug)]
+pub struct DragDropEvent {
+    pub state: EventState,
+    pub payload: DragPayload,
+    /// The pointer, in points
+    pub position: epaint::Pos2,
+    /// Only for ``ondragend``, whether the drag ended over a drop target
+    pub dropped: bool,
+}
+
+/// Sent to every ``onhotreload`` listener once changed templates are applied, transient state can be restored here
+#[derive(Clone, Debug)]
+pub struct HotReloadEvent {
+    pub state: EventState,
+}
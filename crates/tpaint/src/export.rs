@@ -0,0 +1,91 @@
+//! Saves what the software rasterizer in [`crate::raster`] painted as a png or pdf.
+
+use std::path::Path;
+
+use epaint::ColorImage;
+
+pub(crate) fn to_rgba_image(image: &ColorImage) -> image::RgbaImage {
+    let bytes: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_srgba_unmultiplied())
+        .collect();
+    image::RgbaImage::from_raw(image.size[0] as u32, image.size[1] as u32, bytes).unwrap()
+}
+
+pub(crate) fn save_png(image: &ColorImage, path: impl AsRef<Path>) -> std::io::Result<()> {
+    to_rgba_image(image)
+        .save(path)
+        .map_err(std::io::Error::other)
+}
+
+/// Writes a single page pdf with the image on it, ``pixels_per_point`` decides the physical size of the page
+pub(crate) fn save_pdf(
+    image: &ColorImage,
+    pixels_per_point: f32,
+    path: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect as PdfRect, Ref};
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let page_id = Ref::new(3);
+    let image_id = Ref::new(4);
+    let mask_id = Ref::new(5);
+    let content_id = Ref::new(6);
+    let image_name = Name(b"Im1");
+
+    let [width, height] = image.size;
+    let rgb: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| {
+            let [r, g, b, _] = color.to_srgba_unmultiplied();
+            [r, g, b]
+        })
+        .collect();
+    let alpha: Vec<u8> = image.pixels.iter().map(|color| color.a()).collect();
+    let rgb = miniz_oxide::deflate::compress_to_vec_zlib(&rgb, 6);
+    let alpha = miniz_oxide::deflate::compress_to_vec_zlib(&alpha, 6);
+
+    // a point is 1/96 of an inch like on the web, pdfs measure in 1/72 of an inch
+    let page_width = width as f32 / pixels_per_point * 0.75;
+    let page_height = height as f32 / pixels_per_point * 0.75;
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id).kids([page_id]).count(1);
+
+    let mut page = pdf.page(page_id);
+    page.media_box(PdfRect::new(0.0, 0.0, page_width, page_height));
+    page.parent(page_tree_id);
+    page.contents(content_id);
+    page.resources().x_objects().pair(image_name, image_id);
+    page.finish();
+
+    let mut image_object = pdf.image_xobject(image_id, &rgb);
+    image_object.filter(Filter::FlateDecode);
+    image_object.width(width as i32);
+    image_object.height(height as i32);
+    image_object.color_space().device_rgb();
+    image_object.bits_per_component(8);
+    image_object.s_mask(mask_id);
+    image_object.finish();
+
+    let mut mask = pdf.image_xobject(mask_id, &alpha);
+    mask.filter(Filter::FlateDecode);
+    mask.width(width as i32);
+    mask.height(height as i32);
+    mask.color_space().device_gray();
+    mask.bits_per_component(8);
+    mask.finish();
+
+    let mut content = Content::new();
+    content.save_state();
+    content.transform([page_width, 0.0, 0.0, page_height, 0.0, 0.0]);
+    content.x_object(image_name);
+    content.restore_state();
+    pdf.stream(content_id, &content.finish());
+
+    std::fs::write(path, pdf.finish())
+}
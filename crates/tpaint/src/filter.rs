@@ -0,0 +1,165 @@
+//! Color filters from the ``grayscale``, ``brightness-*``, ``saturate-*`` and ``hue-rotate-*`` classes.
+//! Every filter is a 3x3 matrix over rgb like the css filter functions, so nested filters fold into one matrix.
+
+use epaint::{Color32, ColorImage, Shape, TextureId};
+
+/// The filters set on a single node through its classes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorFilter {
+    pub brightness: f32,
+    pub grayscale: f32,
+    pub saturate: f32,
+    /// In degrees
+    pub hue_rotate: f32,
+}
+
+impl Default for ColorFilter {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            grayscale: 0.0,
+            saturate: 1.0,
+            hue_rotate: 0.0,
+        }
+    }
+}
+
+impl ColorFilter {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Applied in the order tailwind puts them in ``filter``: brightness, grayscale, hue-rotate and then saturate
+    pub fn matrix(&self) -> ColorMatrix {
+        ColorMatrix::scale(self.brightness)
+            .followed_by(&ColorMatrix::saturate(1.0 - self.grayscale.clamp(0.0, 1.0)))
+            .followed_by(&ColorMatrix::hue_rotate(self.hue_rotate))
+            .followed_by(&ColorMatrix::saturate(self.saturate))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorMatrix([[f32; 3]; 3]);
+
+impl ColorMatrix {
+    fn scale(amount: f32) -> Self {
+        Self([[amount, 0.0, 0.0], [0.0, amount, 0.0], [0.0, 0.0, amount]])
+    }
+
+    fn saturate(s: f32) -> Self {
+        Self([
+            [0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s],
+            [0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s],
+            [0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s],
+        ])
+    }
+
+    fn hue_rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self([
+            [
+                0.213 + cos * 0.787 - sin * 0.213,
+                0.715 - cos * 0.715 - sin * 0.715,
+                0.072 - cos * 0.072 + sin * 0.928,
+            ],
+            [
+                0.213 - cos * 0.213 + sin * 0.143,
+                0.715 + cos * 0.285 + sin * 0.140,
+                0.072 - cos * 0.072 - sin * 0.283,
+            ],
+            [
+                0.213 - cos * 0.213 - sin * 0.787,
+                0.715 - cos * 0.715 + sin * 0.715,
+                0.072 + cos * 0.928 + sin * 0.072,
+            ],
+        ])
+    }
+
+    /// The matrix that applies ``self`` first and ``next`` after it
+    pub fn followed_by(&self, next: &ColorMatrix) -> ColorMatrix {
+        let mut out = [[0.0; 3]; 3];
+        for (row, out_row) in out.iter_mut().enumerate() {
+            for (column, out_value) in out_row.iter_mut().enumerate() {
+                *out_value = (0..3).map(|i| next.0[row][i] * self.0[i][column]).sum();
+            }
+        }
+        ColorMatrix(out)
+    }
+
+    /// Identifies the matrix when caching filtered textures
+    pub fn key(&self) -> [u32; 9] {
+        let mut key = [0; 9];
+        for (i, value) in self.0.iter().flatten().enumerate() {
+            key[i] = value.to_bits();
+        }
+        key
+    }
+
+    /// The matrix has no offsets, so it works on premultiplied colors as long as they stay below alpha
+    pub fn apply(&self, color: Color32) -> Color32 {
+        let [r, g, b, a] = color.to_array().map(|channel| channel as f32);
+        let channel = |row: [f32; 3]| (row[0] * r + row[1] * g + row[2] * b).round().clamp(0.0, a) as u8;
+        Color32::from_rgba_premultiplied(channel(self.0[0]), channel(self.0[1]), channel(self.0[2]), a as u8)
+    }
+
+    pub fn apply_image(&self, image: &ColorImage) -> ColorImage {
+        ColorImage {
+            size: image.size,
+            pixels: image.pixels.iter().map(|pixel| self.apply(*pixel)).collect(),
+        }
+    }
+
+    /// Filters the colors of the shape. Textures other than the font atlas can't be filtered through vertex colors,
+    /// ``filter_texture`` swaps them for a filtered copy and the tint is left alone so it isn't applied twice.
+    pub fn apply_to_shape(&self, shape: &mut Shape, filter_texture: &mut impl FnMut(TextureId) -> TextureId) {
+        match shape {
+            Shape::Vec(shapes) => {
+                for shape in shapes {
+                    self.apply_to_shape(shape, filter_texture);
+                }
+            }
+            Shape::Rect(rect) => {
+                if rect.fill_texture_id == TextureId::default() {
+                    rect.fill = self.apply(rect.fill);
+                } else {
+                    rect.fill_texture_id = filter_texture(rect.fill_texture_id);
+                }
+                rect.stroke.color = self.apply(rect.stroke.color);
+            }
+            Shape::Mesh(mesh) => {
+                if mesh.texture_id == TextureId::default() {
+                    for vertex in &mut mesh.vertices {
+                        vertex.color = self.apply(vertex.color);
+                    }
+                } else {
+                    mesh.texture_id = filter_texture(mesh.texture_id);
+                }
+            }
+            Shape::Text(text) => {
+                // text nodes are laid out with a single color
+                let color = text.override_text_color.unwrap_or_else(|| {
+                    text.galley
+                        .job
+                        .sections
+                        .first()
+                        .map(|section| section.format.color)
+                        .unwrap_or(text.fallback_color)
+                });
+                text.override_text_color = Some(self.apply(color));
+                text.underline.color = self.apply(text.underline.color);
+            }
+            Shape::Circle(circle) => {
+                circle.fill = self.apply(circle.fill);
+                circle.stroke.color = self.apply(circle.stroke.color);
+            }
+            Shape::LineSegment { stroke, .. } => {
+                stroke.color = self.apply(stroke.color);
+            }
+            Shape::Path(path) => {
+                path.fill = self.apply(path.fill);
+                path.stroke.color = self.apply(path.stroke.color);
+            }
+            _ => {}
+        }
+    }
+}
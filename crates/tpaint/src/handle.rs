@@ -0,0 +1,360 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use epaint::{Rect, Vec2};
+use rustc_hash::FxHashMap;
+use taffy::NodeId;
+use winit::window::{BadIcon, CursorIcon, Icon, UserAttentionType, Window};
+
+use crate::{
+    dom::{AnimatedStyle, Dom, NodeContext, NodeInfo, Tag},
+    event_loop::DomContext,
+    event_queue::EventMetrics,
+    events::{Event, EventState},
+    mutations::MutationQueue,
+    tailwind::StyleState,
+};
+
+/// What a [`DomHandle`] reads, the thread that paints publishes a new one after every frame.
+/// Reads never wait for a frame, they see the dom as it was painted last
+#[derive(Default, Clone)]
+pub(crate) struct DomSnapshot {
+    /// The first node of every ``id``
+    pub ids: FxHashMap<Arc<str>, NodeId>,
+    pub nodes: FxHashMap<NodeId, SnapshotNode>,
+    pub selected_text: String,
+    /// Bumped every time the snapshot is updated, nodes with an older one were removed
+    pub frame: u64,
+}
+
+#[derive(Clone)]
+pub(crate) struct SnapshotNode {
+    pub frame: u64,
+    tag: Tag,
+    attrs: FxHashMap<Arc<str>, Arc<str>>,
+    rect: Rect,
+    scroll: Vec2,
+    style_state: StyleState,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl SnapshotNode {
+    pub fn new(frame: u64, node: &NodeContext, children: Vec<NodeId>) -> Self {
+        Self {
+            frame,
+            tag: node.tag.clone(),
+            attrs: node.attrs.clone(),
+            rect: node.computed.rect,
+            scroll: node.scroll,
+            style_state: node.styling.cache.state,
+            parent: node.parent_id,
+            children,
+        }
+    }
+
+    /// Only copies what changed, ``clone_from`` keeps the allocations of the attributes and children
+    pub fn update(&mut self, frame: u64, node: &NodeContext, children: &[NodeId]) {
+        self.frame = frame;
+        if self.tag != node.tag {
+            self.tag = node.tag.clone();
+        }
+        if self.attrs != node.attrs {
+            self.attrs.clone_from(&node.attrs);
+        }
+        if self.children != children {
+            self.children.clear();
+            self.children.extend_from_slice(children);
+        }
+        self.rect = node.computed.rect;
+        self.scroll = node.scroll;
+        self.style_state = node.styling.cache.state;
+        self.parent = node.parent_id;
+    }
+
+    fn info(&self, node_id: NodeId) -> NodeInfo {
+        let class = self.attrs.get("class").map(|class| &**class).unwrap_or("");
+        NodeInfo {
+            node_id,
+            tag: self.tag.clone(),
+            attrs: self.attrs.clone(),
+            rect: self.rect,
+            scroll: self.scroll,
+            classes: self.style_state.active_classes(class).into_iter().map(Arc::from).collect(),
+            parent: self.parent,
+            children: self.children.clone(),
+        }
+    }
+}
+
+/// Shared by the [`crate::DomEventLoop`] and every [`DomHandle`] of it
+#[derive(Default)]
+pub(crate) struct HandleShared {
+    /// Only locked to swap or clone the ``Arc``
+    snapshot: Mutex<Arc<DomSnapshot>>,
+    next_cursor_override_id: AtomicUsize,
+}
+
+impl HandleShared {
+    /// Makes ``snapshot`` the one handles read, returns the one it replaces so its allocations can be reused
+    pub fn publish(&self, snapshot: Arc<DomSnapshot>) -> Arc<DomSnapshot> {
+        std::mem::replace(&mut *self.snapshot.lock().unwrap(), snapshot)
+    }
+
+    fn current(&self) -> Arc<DomSnapshot> {
+        self.snapshot.lock().unwrap().clone()
+    }
+}
+
+/// Gives components imperative access to the dom, available through ``use_context::<DomHandle>``.
+/// Nothing here waits for the thread that paints: getters read the dom as it was painted last, everything else
+/// is queued behind the renders before it and applied when the next frame starts, so nodes that were just mounted
+/// can be found by their ``id``
+#[derive(Clone)]
+pub struct DomHandle {
+    context: DomContext,
+    mutations: Arc<MutationQueue>,
+    shared: Arc<HandleShared>,
+}
+
+impl DomHandle {
+    pub(crate) fn new(context: DomContext, mutations: Arc<MutationQueue>, shared: Arc<HandleShared>) -> Self {
+        Self { context, mutations, shared }
+    }
+
+    /// Runs ``command`` on the dom at the start of the next frame, which is requested right away
+    fn queue(&self, command: impl FnOnce(&mut Dom) + Send + 'static) {
+        self.mutations.push_command(command);
+        self.context.host.request_redraw();
+    }
+
+    /// Calls ``f`` with the winit window the dom is shown in, hosts without one ignore the call
+    fn with_window(&self, f: impl FnOnce(&Window)) {
+        if let Some(window) = self.context.host.winit_window() {
+            f(window);
+        }
+    }
+
+    /// Forces the cursor icon regardless of what's hovered, until the returned guard is dropped.
+    /// When multiple overrides are active the most recent one wins.
+    pub fn set_cursor_override(&self, icon: CursorIcon) -> CursorOverrideGuard {
+        let id = self.shared.next_cursor_override_id.fetch_add(1, Ordering::Relaxed);
+        self.queue(move |dom| dom.push_cursor_override(id, icon));
+        CursorOverrideGuard {
+            handle: self.clone(),
+            id,
+        }
+    }
+
+    /// Shows the busy cursor, e.g. during a long-running operation
+    pub fn set_busy(&self) -> CursorOverrideGuard {
+        self.set_cursor_override(CursorIcon::Wait)
+    }
+
+    /// The node with the given ``id`` attribute, looked up in an index instead of walking the tree.
+    /// Nodes are found from the frame after they mounted, e.g. in ``onmounted``
+    pub fn get_node_by_id(&self, id: &str) -> Option<NodeHandle> {
+        let node_id = *self.shared.current().ids.get(id)?;
+        Some(NodeHandle {
+            handle: self.clone(),
+            node_id,
+        })
+    }
+
+    /// Scroll offset of the node with the given ``id`` attribute
+    pub fn get_scroll_offset(&self, id: &str) -> Option<Vec2> {
+        let snapshot = self.shared.current();
+        Some(snapshot.nodes.get(snapshot.ids.get(id)?)?.scroll)
+    }
+
+    pub fn set_scroll_offset(&self, id: &str, offset: Vec2) {
+        let id = id.to_string();
+        self.queue(move |dom| {
+            if let Some(node_id) = dom.get_node_by_id(&id) {
+                dom.set_scroll_offset(node_id, offset);
+            }
+        });
+    }
+
+    /// The text selected by dragging over it, in document order
+    pub fn selected_text(&self) -> String {
+        self.shared.current().selected_text.clone()
+    }
+
+    /// What the dom knows about a node, e.g. the parent of an event target from ``event.state.target()``
+    pub fn node_info(&self, node_id: NodeId) -> Option<NodeInfo> {
+        Some(self.shared.current().nodes.get(&node_id)?.info(node_id))
+    }
+
+    /// Sizes and moves the node with the given ``id`` attribute without re-rendering or parsing its classes, for
+    /// animations that write a new value every frame. [`AnimatedStyle::default`] hands the node back to its classes
+    pub fn set_animated_style(&self, id: &str, animated_style: AnimatedStyle) {
+        let id = id.to_string();
+        self.queue(move |dom| {
+            if let Some(node_id) = dom.get_node_by_id(&id) {
+                dom.set_animated_style(node_id, animated_style);
+            }
+        });
+    }
+
+    /// Lays out and paints the node again on the next frame, e.g. when a custom element has new content.
+    /// The node comes from [`crate::element::ElementNode::node_id`] or an event target, see [`Dom::mark_dirty`]
+    pub fn mark_dirty(&self, node_id: NodeId) {
+        self.queue(move |dom| dom.mark_dirty(node_id));
+    }
+
+    /// Moves the focus to the node with the given ``id`` attribute, e.g. for arrow keys in a list of tabs
+    pub fn focus(&self, id: &str) {
+        let id = id.to_string();
+        self.queue(move |dom| {
+            if let Some(node_id) = dom.get_node_by_id(&id) {
+                dom.focus_node(node_id);
+            }
+        });
+    }
+
+    /// Sets the icon of the window and its taskbar entry from RGBA pixels, ``None`` restores the default.
+    /// macOS ignores this, the dock shows the icon of the app bundle
+    pub fn set_window_icon_rgba(&self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+        let icon = Icon::from_rgba(rgba, width, height)?;
+        self.with_window(|window| window.set_window_icon(Some(icon)));
+        Ok(())
+    }
+
+    /// Sets the icon of the window from an encoded image, e.g. ``include_bytes!("icon.png")``
+    #[cfg(feature = "images")]
+    pub fn set_window_icon_from_bytes(&self, bytes: &[u8]) -> Result<(), crate::WindowIconError> {
+        let image = image::load_from_memory(bytes)
+            .map_err(crate::WindowIconError::Decode)?
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        self.set_window_icon_rgba(image.into_raw(), width, height)
+            .map_err(crate::WindowIconError::BadIcon)
+    }
+
+    /// Removes the icon set with [`DomHandle::set_window_icon_rgba`]
+    pub fn clear_window_icon(&self) {
+        self.with_window(|window| window.set_window_icon(None));
+    }
+
+    /// Bounces the dock icon or flashes the taskbar entry until the window is focused.
+    /// ``Critical`` keeps going until then, ``Informational`` only once on macOS. Does nothing while the window is focused
+    pub fn request_user_attention(&self, kind: UserAttentionType) {
+        self.with_window(|window| window.request_user_attention(Some(kind)));
+    }
+
+    /// Stops a request from [`DomHandle::request_user_attention`] before the window is focused
+    pub fn cancel_user_attention(&self) {
+        self.with_window(|window| window.request_user_attention(None));
+    }
+
+    /// Shows the window again after it was hidden, e.g. to the tray, and focuses it
+    pub fn show_window(&self) {
+        self.with_window(|window| {
+            window.set_visible(true);
+            window.set_minimized(false);
+            window.focus_window();
+        });
+    }
+
+    /// Lets the app notice when it can't keep up with the events, e.g. to show less while ``dropped`` keeps growing
+    pub fn event_metrics(&self) -> EventMetrics {
+        self.context.event_sender.metrics()
+    }
+
+    /// Renders the node with the given ``id`` attribute and its children against a transparent background,
+    /// at the size it's laid out at. Resolves once the next frame is painted.
+    #[cfg(feature = "export")]
+    pub async fn capture_node(&self, id: &str) -> Option<image::RgbaImage> {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let id = id.to_string();
+        // dropping the sender when there's no such node resolves the capture to None
+        self.queue(move |dom| {
+            if let Some(node_id) = dom.get_node_by_id(&id) {
+                dom.capture_requests.push((node_id, sender));
+            }
+        });
+
+        let image = receiver.await.ok()?;
+        Some(crate::export::to_rgba_image(&image))
+    }
+}
+
+/// A node from [`DomHandle::get_node_by_id`]. It only holds on to the id of the node, once the node is removed
+/// the getters return ``None`` and the rest does nothing
+#[derive(Clone)]
+pub struct NodeHandle {
+    handle: DomHandle,
+    node_id: NodeId,
+}
+
+impl NodeHandle {
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// Where the node was painted last, in points from the top left of the window
+    pub fn rect(&self) -> Option<epaint::Rect> {
+        Some(self.handle.shared.current().nodes.get(&self.node_id)?.rect)
+    }
+
+    pub fn info(&self) -> Option<NodeInfo> {
+        self.handle.node_info(self.node_id)
+    }
+
+    pub fn scroll_offset(&self) -> Option<Vec2> {
+        Some(self.handle.shared.current().nodes.get(&self.node_id)?.scroll)
+    }
+
+    /// Runs ``command`` with the node at the start of the next frame, unless it was removed by then
+    fn queue(&self, command: impl FnOnce(&mut Dom, NodeId) + Send + 'static) {
+        let node_id = self.node_id;
+        self.handle.queue(move |dom| {
+            if dom.tree.get_node_context(node_id).is_some() {
+                command(dom, node_id);
+            }
+        });
+    }
+
+    pub fn set_scroll_offset(&self, offset: Vec2) {
+        self.queue(move |dom, node_id| dom.set_scroll_offset(node_id, offset));
+    }
+
+    /// Scrolls the scroll containers around the node until it's in view, with ``margin`` points around it
+    pub fn scroll_into_view(&self, margin: f32) {
+        self.queue(move |dom, node_id| dom.scroll_into_view(node_id, margin));
+    }
+
+    pub fn focus(&self) {
+        self.queue(|dom, node_id| dom.focus_node(node_id));
+    }
+
+    /// See [`DomHandle::mark_dirty`]
+    pub fn mark_dirty(&self) {
+        self.queue(|dom, node_id| dom.mark_dirty(node_id));
+    }
+
+    /// Sends a synthetic event to the listener ``name`` of the node, e.g.
+    /// ``node.dispatch("click", true, |state| Event::Click(ClickEvent { state, .. }))``
+    pub fn dispatch(&self, name: &str, bubbles: bool, event: impl FnOnce(EventState) -> Event + Send + 'static) {
+        let name = name.to_string();
+        self.queue(move |dom, node_id| {
+            let event = event(EventState::new(dom, node_id));
+            dom.dispatch_event(node_id, &name, event, bubbles);
+        });
+    }
+}
+
+pub struct CursorOverrideGuard {
+    handle: DomHandle,
+    id: usize,
+}
+
+impl Drop for CursorOverrideGuard {
+    fn drop(&mut self) {
+        let id = self.id;
+        self.handle.queue(move |dom| dom.remove_cursor_override(id));
+    }
+}
@@ -0,0 +1,61 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use dioxus::prelude::*;
+use tokio::sync::broadcast::error::RecvError;
+use winit::dpi::PhysicalSize;
+
+use crate::{event_loop::DomContext, events::WindowEvent, platform};
+
+/// Returns the size of the window in physical pixels, the component only re-renders
+/// once the size has stayed the same for ``debounce``
+pub fn use_window_size(cx: &ScopeState, debounce: Duration) -> PhysicalSize<u32> {
+    let dom_context = use_context::<DomContext>(cx).unwrap();
+    let size = use_state(cx, || *dom_context.window_size.borrow());
+
+    use_future(cx, (), |_| {
+        to_owned![size];
+        let mut receiver = dom_context.window_size.subscribe();
+        async move {
+            while receiver.changed().await.is_ok() {
+                loop {
+                    match platform::timeout(debounce, receiver.changed()).await {
+                        Some(Ok(())) => continue,
+                        Some(Err(_)) => return,
+                        None => break,
+                    }
+                }
+
+                let new_size = *receiver.borrow_and_update();
+                if *size.get() != new_size {
+                    size.set(new_size);
+                }
+            }
+        }
+    });
+
+    *size.get()
+}
+
+/// Calls ``handler`` with the key, pointer, resize and focus input of the whole window, no matter which node is
+/// focused or hovered, e.g. for shortcuts or closing a popup with Escape. The latest ``handler`` is the one that's
+/// called, and it stops being called when the component unmounts
+pub fn use_window_event(cx: &ScopeState, handler: impl FnMut(WindowEvent) + 'static) {
+    let dom_context = use_context::<DomContext>(cx).unwrap();
+    let current: &Rc<RefCell<Box<dyn FnMut(WindowEvent)>>> = cx.use_hook(|| Rc::new(RefCell::new(Box::new(|_| {}))));
+    *current.borrow_mut() = Box::new(handler);
+
+    use_future(cx, (), |_| {
+        let handler = current.clone();
+        let mut receiver = dom_context.window_events.subscribe();
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => (handler.borrow_mut())(event),
+                    // a slow listener misses some pointer moves rather than falling further behind
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,113 @@
+//! What the dom needs from the window it's shown in. A winit [`Window`] is one, [`HeadlessHost`] runs the dom
+//! without a window for tests, benchmarks and fuzzing, and hosts with their own windowing implement [`Host`] themselves
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+pub use winit::{dpi::PhysicalSize, window::CursorIcon};
+use winit::window::Window;
+
+/// Called from the thread of the event loop and from the VirtualDom thread
+pub trait Host: Send + Sync + 'static {
+    /// Asks for [`crate::DomEventLoop::get_paint_info`] to be called again
+    fn request_redraw(&self);
+
+    /// Size of the surface the dom is painted to, in physical pixels
+    fn inner_size(&self) -> PhysicalSize<u32>;
+
+    fn scale_factor(&self) -> f64 {
+        1.0
+    }
+
+    fn set_cursor_icon(&self, _icon: CursorIcon) {}
+
+    /// Whether a text field has the focus, brings up the on-screen keyboard on mobile
+    fn set_ime_allowed(&self, _allowed: bool) {}
+
+    /// See [`crate::DomEventLoop::auto_min_size`]
+    fn set_min_inner_size(&self, _size: Option<PhysicalSize<u32>>) {}
+
+    /// The winit window behind the host, for the icon, user attention and hiding to the tray.
+    /// Those do nothing when it's ``None``
+    fn winit_window(&self) -> Option<&Window> {
+        None
+    }
+}
+
+impl Host for Window {
+    fn request_redraw(&self) {
+        Window::request_redraw(self);
+    }
+
+    fn inner_size(&self) -> PhysicalSize<u32> {
+        Window::inner_size(self)
+    }
+
+    fn scale_factor(&self) -> f64 {
+        Window::scale_factor(self)
+    }
+
+    fn set_cursor_icon(&self, icon: CursorIcon) {
+        Window::set_cursor_icon(self, icon);
+    }
+
+    fn set_ime_allowed(&self, allowed: bool) {
+        Window::set_ime_allowed(self, allowed);
+    }
+
+    fn set_min_inner_size(&self, size: Option<PhysicalSize<u32>>) {
+        Window::set_min_inner_size(self, size);
+    }
+
+    fn winit_window(&self) -> Option<&Window> {
+        Some(self)
+    }
+}
+
+/// A host without a window, its size only changes through [`HeadlessHost::set_inner_size`]
+#[derive(Debug)]
+pub struct HeadlessHost {
+    size: Mutex<PhysicalSize<u32>>,
+    scale_factor: f64,
+    redraw_requested: AtomicBool,
+}
+
+impl HeadlessHost {
+    pub fn new(size: PhysicalSize<u32>) -> Self {
+        Self::with_scale_factor(size, 1.0)
+    }
+
+    pub fn with_scale_factor(size: PhysicalSize<u32>, scale_factor: f64) -> Self {
+        Self {
+            size: Mutex::new(size),
+            scale_factor,
+            redraw_requested: AtomicBool::new(false),
+        }
+    }
+
+    /// Push an [`crate::input::InputEvent::Resized`] as well, like a window would
+    pub fn set_inner_size(&self, size: PhysicalSize<u32>) {
+        *self.size.lock().unwrap() = size;
+    }
+
+    /// Whether a redraw was requested since the last call
+    pub fn take_redraw_request(&self) -> bool {
+        self.redraw_requested.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl Host for HeadlessHost {
+    fn request_redraw(&self) {
+        self.redraw_requested.store(true, Ordering::Relaxed);
+    }
+
+    fn inner_size(&self) -> PhysicalSize<u32> {
+        *self.size.lock().unwrap()
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+}
@@ -0,0 +1,375 @@
+use winit::{
+    event::{ElementState, Force, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{self, KeyCode as WinitKeyCode, ModifiersState, NamedKey as WinitNamedKey, NativeKeyCode, SmolStr},
+};
+
+/// Input for the dom that doesn't depend on a winit event loop, hosts with their own input handling (e.g. SDL2)
+/// can translate their events into these and feed them through [`crate::DomEventLoop::push_input`].
+/// Positions and sizes are in physical pixels.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputEvent {
+    WindowMoved {
+        x: i32,
+        y: i32,
+    },
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    ScaleFactorChanged {
+        scale_factor: f64,
+    },
+    PointerMoved {
+        x: f64,
+        y: f64,
+    },
+    PointerButton {
+        button: PointerButton,
+        state: ButtonState,
+    },
+    Touch {
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+        id: u64,
+        force: Option<TouchForce>,
+    },
+    ScrollLines {
+        x: f32,
+        y: f32,
+    },
+    ScrollPixels {
+        x: f64,
+        y: f64,
+    },
+    Key {
+        state: ButtonState,
+        logical_key: Key,
+        physical_key: PhysicalKey,
+        text: Option<String>,
+    },
+    /// Text that wasn't produced by a single key press, e.g. from an IME
+    Text {
+        text: String,
+    },
+    ModifiersChanged(Modifiers),
+    Focused(bool),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u16),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub enum ButtonState {
+    Pressed,
+    Released,
+}
+
+impl ButtonState {
+    pub fn is_pressed(self) -> bool {
+        self == ButtonState::Pressed
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub enum TouchPhase {
+    Started,
+    Moved,
+    Ended,
+    Cancelled,
+}
+
+/// How hard a finger or pen presses, for devices that report it
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub struct TouchForce {
+    /// Between 0.0 and 1.0
+    pub pressure: f64,
+    /// Angle between a pen and the surface in radians, where 0 is parallel and PI/2 perpendicular. ``None`` for fingers
+    pub altitude_angle: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    /// The Windows key on Windows and Linux, Command on macOS
+    pub super_key: bool,
+}
+
+/// The meaning of a key under the current layout
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub enum Key {
+    Named(NamedKey),
+    /// What the key types, e.g. ``"a"`` or ``"A"`` with Shift held
+    Character(String),
+    /// A key that changes the next character, e.g. ``´`` before ``e``
+    Dead(Option<char>),
+    Unidentified,
+}
+
+/// Where the key is on the keyboard, independent of the layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub enum PhysicalKey {
+    Code(KeyCode),
+    Unidentified,
+}
+
+/// Declares an enum with the variants of a winit enum that share its names, the rest of winit's map to ``None``
+macro_rules! mirror_winit_enum {
+    ($(#[$attr:meta])* $name:ident => $winit:ident { $($variant:ident),* $(,)? }) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+        pub enum $name {
+            $($variant,)*
+        }
+
+        impl $name {
+            pub fn from_winit(value: $winit) -> Option<Self> {
+                match value {
+                    $($winit::$variant => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn to_winit(self) -> $winit {
+                match self {
+                    $(Self::$variant => $winit::$variant,)*
+                }
+            }
+        }
+    };
+}
+
+mirror_winit_enum! {
+    /// Keys that don't type a character
+    NamedKey => WinitNamedKey {
+        Alt, AltGraph, CapsLock, Control, Fn, NumLock, ScrollLock, Shift, Super, Meta,
+        Enter, Tab, Space, ArrowDown, ArrowLeft, ArrowRight, ArrowUp, End, Home, PageDown, PageUp,
+        Backspace, Delete, Insert, Copy, Cut, Paste, Redo, Undo, ContextMenu, Escape, Find, Help, Pause, PrintScreen,
+        MediaPlayPause, MediaStop, MediaTrackNext, MediaTrackPrevious, AudioVolumeDown, AudioVolumeUp, AudioVolumeMute,
+        BrowserBack, BrowserForward,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24,
+    }
+}
+
+mirror_winit_enum! {
+    /// Keys by their position on a US keyboard
+    KeyCode => WinitKeyCode {
+        Backquote, Backslash, BracketLeft, BracketRight, Comma, Equal, IntlBackslash, Minus, Period, Quote, Semicolon, Slash,
+        Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM,
+        KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+        AltLeft, AltRight, Backspace, CapsLock, ContextMenu, ControlLeft, ControlRight, Enter, SuperLeft, SuperRight,
+        ShiftLeft, ShiftRight, Space, Tab, Delete, End, Home, Insert, PageDown, PageUp,
+        ArrowDown, ArrowLeft, ArrowRight, ArrowUp, NumLock, ScrollLock, Escape, PrintScreen, Pause,
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+        NumpadAdd, NumpadDecimal, NumpadDivide, NumpadEnter, NumpadMultiply, NumpadSubtract,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24,
+    }
+}
+
+impl PointerButton {
+    pub fn from_winit(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => PointerButton::Left,
+            MouseButton::Right => PointerButton::Right,
+            MouseButton::Middle => PointerButton::Middle,
+            MouseButton::Back => PointerButton::Back,
+            MouseButton::Forward => PointerButton::Forward,
+            MouseButton::Other(button) => PointerButton::Other(button),
+        }
+    }
+
+    pub fn to_winit(self) -> MouseButton {
+        match self {
+            PointerButton::Left => MouseButton::Left,
+            PointerButton::Right => MouseButton::Right,
+            PointerButton::Middle => MouseButton::Middle,
+            PointerButton::Back => MouseButton::Back,
+            PointerButton::Forward => MouseButton::Forward,
+            PointerButton::Other(button) => MouseButton::Other(button),
+        }
+    }
+}
+
+impl ButtonState {
+    pub fn from_winit(state: ElementState) -> Self {
+        match state {
+            ElementState::Pressed => ButtonState::Pressed,
+            ElementState::Released => ButtonState::Released,
+        }
+    }
+
+    pub fn to_winit(self) -> ElementState {
+        match self {
+            ButtonState::Pressed => ElementState::Pressed,
+            ButtonState::Released => ElementState::Released,
+        }
+    }
+}
+
+impl TouchPhase {
+    pub fn from_winit(phase: winit::event::TouchPhase) -> Self {
+        match phase {
+            winit::event::TouchPhase::Started => TouchPhase::Started,
+            winit::event::TouchPhase::Moved => TouchPhase::Moved,
+            winit::event::TouchPhase::Ended => TouchPhase::Ended,
+            winit::event::TouchPhase::Cancelled => TouchPhase::Cancelled,
+        }
+    }
+
+    pub fn to_winit(self) -> winit::event::TouchPhase {
+        match self {
+            TouchPhase::Started => winit::event::TouchPhase::Started,
+            TouchPhase::Moved => winit::event::TouchPhase::Moved,
+            TouchPhase::Ended => winit::event::TouchPhase::Ended,
+            TouchPhase::Cancelled => winit::event::TouchPhase::Cancelled,
+        }
+    }
+}
+
+impl TouchForce {
+    pub fn from_winit(force: Force) -> Self {
+        Self {
+            pressure: force.normalized(),
+            altitude_angle: match force {
+                Force::Calibrated { altitude_angle, .. } => altitude_angle,
+                Force::Normalized(_) => None,
+            },
+        }
+    }
+
+    pub fn to_winit(self) -> Force {
+        Force::Calibrated {
+            force: self.pressure,
+            max_possible_force: 1.0,
+            altitude_angle: self.altitude_angle,
+        }
+    }
+}
+
+impl Modifiers {
+    pub fn from_winit(state: ModifiersState) -> Self {
+        Self {
+            shift: state.shift_key(),
+            control: state.control_key(),
+            alt: state.alt_key(),
+            super_key: state.super_key(),
+        }
+    }
+
+    pub fn to_winit(self) -> ModifiersState {
+        let mut state = ModifiersState::empty();
+        state.set(ModifiersState::SHIFT, self.shift);
+        state.set(ModifiersState::CONTROL, self.control);
+        state.set(ModifiersState::ALT, self.alt);
+        state.set(ModifiersState::SUPER, self.super_key);
+        state
+    }
+}
+
+impl Key {
+    /// Named keys that aren't in [`NamedKey`] become ``Unidentified``
+    pub fn from_winit(key: &keyboard::Key) -> Self {
+        match key {
+            keyboard::Key::Named(named) => NamedKey::from_winit(*named).map_or(Key::Unidentified, Key::Named),
+            keyboard::Key::Character(text) => Key::Character(text.to_string()),
+            keyboard::Key::Dead(dead) => Key::Dead(*dead),
+            keyboard::Key::Unidentified(_) => Key::Unidentified,
+        }
+    }
+
+    pub fn to_winit(&self) -> keyboard::Key {
+        match self {
+            Key::Named(named) => keyboard::Key::Named(named.to_winit()),
+            Key::Character(text) => keyboard::Key::Character(SmolStr::new(text)),
+            Key::Dead(dead) => keyboard::Key::Dead(*dead),
+            Key::Unidentified => keyboard::Key::Unidentified(keyboard::NativeKey::Unidentified),
+        }
+    }
+}
+
+impl PhysicalKey {
+    pub fn from_winit(key: keyboard::PhysicalKey) -> Self {
+        match key {
+            keyboard::PhysicalKey::Code(code) => KeyCode::from_winit(code).map_or(PhysicalKey::Unidentified, PhysicalKey::Code),
+            keyboard::PhysicalKey::Unidentified(_) => PhysicalKey::Unidentified,
+        }
+    }
+
+    pub fn to_winit(self) -> keyboard::PhysicalKey {
+        match self {
+            PhysicalKey::Code(code) => keyboard::PhysicalKey::Code(code.to_winit()),
+            PhysicalKey::Unidentified => keyboard::PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+        }
+    }
+}
+
+impl InputEvent {
+    /// Returns ``None`` for events the dom doesn't care about
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        Some(match event {
+            WindowEvent::Moved(position) => InputEvent::WindowMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::Resized(size) => InputEvent::Resized {
+                width: size.width,
+                height: size.height,
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                InputEvent::ScaleFactorChanged {
+                    scale_factor: *scale_factor,
+                }
+            }
+            WindowEvent::MouseInput { button, state, .. } => InputEvent::PointerButton {
+                button: PointerButton::from_winit(*button),
+                state: ButtonState::from_winit(*state),
+            },
+            WindowEvent::CursorMoved { position, .. } => InputEvent::PointerMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::Touch(touch) => InputEvent::Touch {
+                phase: TouchPhase::from_winit(touch.phase),
+                x: touch.location.x,
+                y: touch.location.y,
+                id: touch.id,
+                force: touch.force.map(TouchForce::from_winit),
+            },
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => InputEvent::ScrollLines { x: *x, y: *y },
+                MouseScrollDelta::PixelDelta(pos) => InputEvent::ScrollPixels { x: pos.x, y: pos.y },
+            },
+            WindowEvent::KeyboardInput { event, .. } => InputEvent::Key {
+                state: ButtonState::from_winit(event.state),
+                logical_key: Key::from_winit(&event.logical_key),
+                physical_key: PhysicalKey::from_winit(event.physical_key),
+                text: event.text.as_ref().map(|text| text.to_string()),
+            },
+            WindowEvent::Ime(winit::event::Ime::Commit(text)) => InputEvent::Text { text: text.clone() },
+            WindowEvent::ModifiersChanged(modifiers) => {
+                InputEvent::ModifiersChanged(Modifiers::from_winit(modifiers.state()))
+            }
+            WindowEvent::Focused(focused) => InputEvent::Focused(*focused),
+            _ => return None,
+        })
+    }
+}
@@ -2,14 +2,54 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+mod anchor;
+pub mod atlas;
+mod bidi;
+pub mod class;
 pub mod components;
+pub mod decoration;
+pub mod element;
 mod dom;
+mod drag;
+mod error;
 mod event_loop;
+mod event_queue;
+#[cfg(feature = "export")]
+mod export;
 pub mod events;
+mod filter;
+mod handle;
+pub mod hooks;
+pub mod host;
+pub mod input;
+mod mutations;
+mod platform;
+mod raster;
+pub mod recorder;
 mod renderer;
+pub mod snapshot;
 mod tailwind;
+pub mod text;
+#[cfg(feature = "tray")]
+pub mod tray;
 
-pub use renderer::RendererDescriptor;
+pub use anchor::Placement;
+pub use atlas::AtlasStats;
+pub use bidi::{TextDirection, TextShaper};
+pub use decoration::TextDecorations;
+pub use dom::{AnimatedStyle, KeyboardScroll, MoveCoalescing, NodeInfo};
+pub use drag::DragPayload;
+pub use error::DomError;
+#[cfg(feature = "images")]
+pub use error::WindowIconError;
+pub use event_queue::EventMetrics;
+pub use handle::{CursorOverrideGuard, DomHandle, NodeHandle};
+pub use renderer::{BackdropBlur, RendererDescriptor, ScreenDescriptor};
+pub use snapshot::PaintSnapshot;
+pub use tailwind::ZLayer;
+/// The class parser, exposed for the benchmarks
+#[doc(hidden)]
+pub use tailwind::{StyleState, Tailwind};
 
 #[doc(hidden)]
 pub trait EventReturn<P>: Sized {
@@ -55,7 +95,7 @@ macro_rules! impl_event {
     };
 }
 
-pub use event_loop::DomEventLoop;
+pub use event_loop::{DomEventLoop, PanicPolicy, Viewport};
 
 pub mod prelude {
     pub use dioxus::prelude::*;
@@ -74,8 +114,45 @@ pub mod prelude {
             pub const NAME_SPACE: Option<&'static str> = None;
             pub const class: AttributeDescription = ("class", None, false);
 
+            /// Stable identifier, scroll offsets of removed nodes are restored when a node with the same ``id`` mounts
+            pub const id: AttributeDescription = ("id", None, false);
+
+            /// Raw values over the classes, e.g. ``width: 240; height: 50%; translate: 4 -8; background-color: #ff000080``.
+            /// Also ``min-*`` / ``max-*`` sizes, for values that change every frame like a panel that is being resized
+            pub const style: AttributeDescription = ("style", None, false);
+
             /// Prefix texture ids with ``texture://``
             pub const src: AttributeDescription = ("src", None, false);
+            /// ``{width}x{height}`` in points, used for layout instead of the size of the texture in ``src``
+            pub const natural_size: AttributeDescription = ("natural_size", None, false);
+            /// ``texture://{id} {insets}`` draws the texture as a nine-patch, the insets mark the corners like css ``border-image-slice``
+            pub const border_image: AttributeDescription = ("border-image", None, false);
+            /// ``texture://{id}`` drawn behind the children, see the ``bg-cover``, ``bg-contain``, ``bg-no-repeat`` and ``bg-center`` classes
+            pub const bg_image: AttributeDescription = ("bg-image", None, false);
+
+            /// ``id`` of the node to float next to, the node is moved after every layout and should be ``absolute``
+            pub const anchor: AttributeDescription = ("anchor", None, false);
+            /// ``top``, ``bottom`` (default), ``left`` or ``right`` of the ``anchor``, flipped when it doesn't fit
+            pub const placement: AttributeDescription = ("placement", None, false);
+            /// Distance in points between the node and its ``anchor``
+            pub const anchor_gap: AttributeDescription = ("anchor_gap", None, false);
+
+            /// ``id`` of the node to lay out and paint the node in, the root when empty. Use the ``Portal`` component,
+            /// it keeps a placeholder where the node was rendered
+            pub const portal: AttributeDescription = ("portal", None, false);
+
+            /// Distance in points from the start that sends ``onreachstart``, 0 by default
+            pub const scroll_start_threshold: AttributeDescription = ("scroll_start_threshold", None, false);
+            /// Distance in points from the end that sends ``onreachend``, 0 by default
+            pub const scroll_end_threshold: AttributeDescription = ("scroll_end_threshold", None, false);
+
+            /// ``ltr``, ``rtl`` or ``auto`` (default) for the text of the node and its children, ``rtl`` also mirrors ``flex-row``
+            pub const dir: AttributeDescription = ("dir", None, false);
+
+            /// ``true`` lets the node be dragged onto nodes with ``ondrop``, see [`crate::DragPayload`]
+            pub const draggable: AttributeDescription = ("draggable", None, false);
+            /// The payload of a drag of this node as a ``String``, until ``ondragstart`` sets another one
+            pub const drag_data: AttributeDescription = ("drag_data", None, false);
 
             /// Will activate any classes prefixed with ``active:`` if this is true
             pub const is_active: AttributeDescription = ("is_active", None, false);
@@ -84,10 +161,37 @@ pub mod prelude {
             pub const text_cursor: AttributeDescription = ("text_cursor", None, false);
             pub const text_cursor_visible: AttributeDescription =
                 ("text_cursor_visible", None, false);
+            /// Where the caret goes when ``text_cursor`` is at a line wrap, at the start of the next line when true
+            /// and at the end of the wrapped one when false
+            pub const text_cursor_prefer_next_row: AttributeDescription =
+                ("text_cursor_prefer_next_row", None, false);
+            /// Underlines, squiggles and backgrounds over ranges of the text, built with [`crate::TextDecorations`]
+            pub const text_decorations: AttributeDescription = ("text_decorations", None, false);
             pub const text_selection_start: AttributeDescription =
                 ("text_selection_start", None, false);
             pub const global_selection_mode: AttributeDescription =
                 ("global_selection_mode", None, false);
+
+            /// ``button`` makes Enter and Space trigger ``onclick`` while focused
+            pub const role: AttributeDescription = ("role", None, false);
+            pub const aria_label: AttributeDescription = ("aria-label", None, false);
+            /// Suppresses pointer and keyboard events for the element and its children, and activates classes prefixed with ``disabled:``
+            pub const disabled: AttributeDescription = ("disabled", None, false);
+            pub const checked: AttributeDescription = ("checked", None, false);
+        }
+
+        /// A ``view`` that is focusable, clicks on Enter and Space, and activates classes prefixed with ``active:`` while pressed
+        pub struct button;
+        impl button {
+            pub const TAG_NAME: &'static str = "button";
+            pub const NAME_SPACE: Option<&'static str> = None;
+            pub const class: AttributeDescription = ("class", None, false);
+            pub const id: AttributeDescription = ("id", None, false);
+            pub const tabindex: AttributeDescription = ("tabindex", None, false);
+            pub const is_active: AttributeDescription = ("is_active", None, false);
+            pub const aria_label: AttributeDescription = ("aria-label", None, false);
+            pub const disabled: AttributeDescription = ("disabled", None, false);
+            pub const style: AttributeDescription = ("style", None, false);
         }
 
         pub mod events {
@@ -135,10 +239,50 @@ pub mod prelude {
                 onlayout
             ];
 
+            impl_event![
+                crate::events::MountedEvent;
+                onmounted
+            ];
+
+            impl_event![
+                crate::events::VisibilityEvent;
+                onvisibilitychange
+            ];
+
             impl_event![
                 crate::events::SelectEvent;
                 onselect
             ];
+
+            impl_event![
+                crate::events::ResizeEvent;
+                onresize
+            ];
+
+            impl_event![
+                crate::events::HotReloadEvent;
+                onhotreload
+            ];
+
+            impl_event![
+                crate::events::ScrollEvent;
+                onscroll
+            ];
+
+            impl_event![
+                crate::events::ScrollEdgeEvent;
+                onreachstart
+                onreachend
+            ];
+
+            impl_event![
+                crate::events::DragDropEvent;
+                ondragstart
+                ondragover
+                ondragleave
+                ondrop
+                ondragend
+            ];
         }
     }
 }
@@ -0,0 +1,171 @@
+//! Mutations from the VirtualDom, copied out of the borrowed dioxus ones so they can be handed to the thread that paints.
+//! The VirtualDom thread queues them without touching the dom, the painting thread swaps the queue out at the start
+//! of a frame and applies everything in one go, so neither has to wait for the other.
+//! Writes from a [`crate::DomHandle`] are queued next to them, so they see the nodes the renders before them created.
+
+use std::sync::{Arc, Mutex};
+
+use dioxus::{
+    core::{BorrowedAttributeValue, ElementId, Mutation, Mutations},
+    prelude::Template,
+};
+
+use crate::dom::Dom;
+
+pub(crate) enum AttributeValue {
+    Text(Arc<str>),
+    None,
+    /// ``Any`` values can't leave the VirtualDom, applying them reports an error
+    Unsupported,
+}
+
+/// Same as [`Mutation`], but owning its strings
+pub(crate) enum DomMutation {
+    AppendChildren { id: ElementId, m: usize },
+    AssignId { path: &'static [u8], id: ElementId },
+    CreatePlaceholder { id: ElementId },
+    CreateTextNode { value: Arc<str>, id: ElementId },
+    HydrateText { path: &'static [u8], value: Arc<str>, id: ElementId },
+    LoadTemplate { name: &'static str, index: usize, id: ElementId },
+    ReplaceWith { id: ElementId, m: usize },
+    ReplacePlaceholder { path: &'static [u8], m: usize },
+    InsertAfter { id: ElementId, m: usize },
+    InsertBefore { id: ElementId, m: usize },
+    SetAttribute { name: Box<str>, value: AttributeValue, id: ElementId },
+    SetText { value: Arc<str>, id: ElementId },
+    NewEventListener { name: Box<str>, id: ElementId },
+    RemoveEventListener { name: Box<str>, id: ElementId },
+    Remove { id: ElementId },
+    PushRoot { id: ElementId },
+}
+
+impl From<Mutation<'_>> for DomMutation {
+    fn from(mutation: Mutation<'_>) -> Self {
+        match mutation {
+            Mutation::AppendChildren { id, m } => Self::AppendChildren { id, m },
+            Mutation::AssignId { path, id } => Self::AssignId { path, id },
+            Mutation::CreatePlaceholder { id } => Self::CreatePlaceholder { id },
+            Mutation::CreateTextNode { value, id } => Self::CreateTextNode {
+                value: value.into(),
+                id,
+            },
+            Mutation::HydrateText { path, value, id } => Self::HydrateText {
+                path,
+                value: value.into(),
+                id,
+            },
+            Mutation::LoadTemplate { name, index, id } => Self::LoadTemplate { name, index, id },
+            Mutation::ReplaceWith { id, m } => Self::ReplaceWith { id, m },
+            Mutation::ReplacePlaceholder { path, m } => Self::ReplacePlaceholder { path, m },
+            Mutation::InsertAfter { id, m } => Self::InsertAfter { id, m },
+            Mutation::InsertBefore { id, m } => Self::InsertBefore { id, m },
+            Mutation::SetAttribute {
+                name, value, id, ..
+            } => Self::SetAttribute {
+                name: name.into(),
+                value: match value {
+                    BorrowedAttributeValue::Int(val) => AttributeValue::Text(val.to_string().into()),
+                    BorrowedAttributeValue::Bool(val) => AttributeValue::Text(val.to_string().into()),
+                    BorrowedAttributeValue::Float(val) => AttributeValue::Text(val.to_string().into()),
+                    BorrowedAttributeValue::Text(val) => AttributeValue::Text(val.into()),
+                    BorrowedAttributeValue::None => AttributeValue::None,
+                    BorrowedAttributeValue::Any(_) => AttributeValue::Unsupported,
+                },
+                id,
+            },
+            Mutation::SetText { value, id } => Self::SetText {
+                value: value.into(),
+                id,
+            },
+            Mutation::NewEventListener { name, id } => Self::NewEventListener {
+                name: name.into(),
+                id,
+            },
+            Mutation::RemoveEventListener { name, id } => Self::RemoveEventListener {
+                name: name.into(),
+                id,
+            },
+            Mutation::Remove { id } => Self::Remove { id },
+            Mutation::PushRoot { id } => Self::PushRoot { id },
+        }
+    }
+}
+
+/// Everything a single render of the VirtualDom changed
+pub(crate) struct MutationBatch {
+    pub templates: Vec<Template<'static>>,
+    pub edits: Vec<DomMutation>,
+    /// The render after templates were replaced by hot reloading, ``onhotreload`` is sent once it's applied
+    pub hot_reloaded: bool,
+}
+
+impl From<Mutations<'_>> for MutationBatch {
+    fn from(mutations: Mutations<'_>) -> Self {
+        Self {
+            templates: mutations.templates,
+            edits: mutations.edits.into_iter().map(DomMutation::from).collect(),
+            hot_reloaded: false,
+        }
+    }
+}
+
+/// Something the VirtualDom thread wants done to the dom, e.g. focusing a node from a [`crate::DomHandle`]
+pub(crate) type DomCommand = Box<dyn FnOnce(&mut Dom) + Send>;
+
+enum Pending {
+    Batch(MutationBatch),
+    Command(DomCommand),
+}
+
+/// Batches and commands waiting for the next frame
+#[derive(Default)]
+pub(crate) struct MutationQueue {
+    pending: Mutex<Vec<Pending>>,
+}
+
+impl MutationQueue {
+    /// Renders that changed nothing are dropped, returns whether there's something new to paint
+    pub fn push(&self, mutations: Mutations<'_>) -> bool {
+        let batch = MutationBatch::from(mutations);
+        if batch.templates.is_empty() && batch.edits.is_empty() {
+            return false;
+        }
+        self.pending.lock().unwrap().push(Pending::Batch(batch));
+        true
+    }
+
+    /// Always queued, even without changes ``onhotreload`` has to be sent
+    pub fn push_hot_reloaded(&self, mutations: Mutations<'_>) -> bool {
+        let batch = MutationBatch {
+            hot_reloaded: true,
+            ..MutationBatch::from(mutations)
+        };
+        self.pending.lock().unwrap().push(Pending::Batch(batch));
+        true
+    }
+
+    /// Runs ``command`` on the thread that paints, after the batches that were queued before it
+    pub fn push_command(&self, command: impl FnOnce(&mut Dom) + Send + 'static) {
+        self.pending.lock().unwrap().push(Pending::Command(Box::new(command)));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.lock().unwrap().is_empty()
+    }
+
+    /// Batches of a VirtualDom that is being replaced would point at elements that are gone
+    pub fn clear(&self) {
+        self.pending.lock().unwrap().clear();
+    }
+
+    /// Swaps the pending batches out for an empty queue and applies them, in the order they were queued
+    pub fn apply_to(&self, dom: &mut Dom) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for pending in pending {
+            match pending {
+                Pending::Batch(batch) => dom.apply_batch(batch),
+                Pending::Command(command) => command(dom),
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! The bits that differ between native and the web, on ``wasm32`` there are no threads or tokio timers
+//! so the VirtualDom runs on the browser's event loop instead.
+
+use std::{
+    future::Future,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    time::Duration,
+};
+
+pub use web_time::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Returns ``None`` when ``future`` didn't finish within ``duration``
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    tokio::select! {
+        output = future => Some(output),
+        _ = sleep(duration) => None,
+    }
+}
+
+/// Runs ``f`` on tokio's blocking pool, returns ``None`` if it panicked
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn spawn_blocking<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> Option<R> {
+    tokio::task::spawn_blocking(f).await.ok()
+}
+
+/// There are no threads to hand the work to on the web, so it runs in place
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn spawn_blocking<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> Option<R> {
+    Some(f())
+}
+
+/// Polls ``future`` once without waiting, for the single-threaded mode where the caller decides when to poll again
+pub(crate) fn poll_now<F: Future>(future: F) -> Option<F::Output> {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // SAFETY: the vtable never touches the data pointer
+    let waker = unsafe { Waker::from_raw(clone(std::ptr::null())) };
+    let mut future = std::pin::pin!(future);
+    match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
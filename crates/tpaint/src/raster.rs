@@ -0,0 +1,162 @@
+//! A software rasterizer for the tessellated output, so the UI can be saved without a gpu and ``layer`` subtrees
+//! can be painted to a texture once.
+
+use epaint::{
+    textures::TexturesDelta, ClippedPrimitive, Color32, ColorImage, ImageData, Primitive, Rect,
+    TextureId, Vertex,
+};
+use rustc_hash::FxHashMap;
+
+/// A cpu copy of every texture the renderer handed out, kept up to date from the textures deltas
+#[derive(Default)]
+pub(crate) struct SoftwareTextures {
+    images: FxHashMap<TextureId, ColorImage>,
+}
+
+impl SoftwareTextures {
+    pub fn apply(&mut self, delta: &TexturesDelta) {
+        for (id, image_delta) in &delta.set {
+            let image = match &image_delta.image {
+                ImageData::Color(image) => (**image).clone(),
+                ImageData::Font(image) => ColorImage {
+                    size: image.size,
+                    pixels: image.srgba_pixels(None).collect(),
+                },
+            };
+
+            match image_delta.pos {
+                None => {
+                    self.images.insert(*id, image);
+                }
+                Some([x, y]) => {
+                    let Some(target) = self.images.get_mut(id) else {
+                        continue;
+                    };
+                    for row in 0..image.size[1] {
+                        let from = row * image.size[0];
+                        let to = (y + row) * target.size[0] + x;
+                        target.pixels[to..to + image.size[0]]
+                            .copy_from_slice(&image.pixels[from..from + image.size[0]]);
+                    }
+                }
+            }
+        }
+
+        for id in &delta.free {
+            self.images.remove(id);
+        }
+    }
+
+    fn sample(&self, texture_id: TextureId, uv: epaint::Pos2) -> [f32; 4] {
+        let Some(image) = self.images.get(&texture_id) else {
+            return [1.0; 4];
+        };
+        let x = ((uv.x * image.size[0] as f32) as usize).min(image.size[0] - 1);
+        let y = ((uv.y * image.size[1] as f32) as usize).min(image.size[1] - 1);
+        to_rgba(image.pixels[y * image.size[0] + x])
+    }
+}
+
+fn to_rgba(color: Color32) -> [f32; 4] {
+    let [r, g, b, a] = color.to_array();
+    [r, g, b, a].map(|channel| channel as f32 / 255.0)
+}
+
+/// Paints the primitives onto a transparent image of ``size`` pixels, colors stay premultiplied
+pub(crate) fn rasterize(
+    primitives: &[ClippedPrimitive],
+    textures: &SoftwareTextures,
+    size: [usize; 2],
+    pixels_per_point: f32,
+) -> ColorImage {
+    let mut pixels = vec![[0.0f32; 4]; size[0] * size[1]];
+    let screen = Rect::from_min_size(epaint::Pos2::ZERO, epaint::vec2(size[0] as f32, size[1] as f32));
+
+    for ClippedPrimitive { clip_rect, primitive } in primitives {
+        let Primitive::Mesh(mesh) = primitive else {
+            continue;
+        };
+        let clip = Rect::from_min_max(
+            (clip_rect.min * pixels_per_point).round(),
+            (clip_rect.max * pixels_per_point).round(),
+        )
+        .intersect(screen);
+        if !clip.is_positive() {
+            continue;
+        }
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+            rasterize_triangle(&mut pixels, size[0], clip, pixels_per_point, [a, b, c], |uv| {
+                textures.sample(mesh.texture_id, uv)
+            });
+        }
+    }
+
+    ColorImage {
+        size,
+        pixels: pixels
+            .into_iter()
+            .map(|[r, g, b, a]| {
+                Color32::from_rgba_premultiplied(
+                    (r * 255.0).round() as u8,
+                    (g * 255.0).round() as u8,
+                    (b * 255.0).round() as u8,
+                    (a * 255.0).round() as u8,
+                )
+            })
+            .collect(),
+    }
+}
+
+fn rasterize_triangle(
+    pixels: &mut [[f32; 4]],
+    width: usize,
+    clip: Rect,
+    pixels_per_point: f32,
+    vertices: [&Vertex; 3],
+    sample: impl Fn(epaint::Pos2) -> [f32; 4],
+) {
+    let [p0, p1, p2] = vertices.map(|vertex| vertex.pos * pixels_per_point);
+    let area = (p1 - p0).x * (p2 - p0).y - (p1 - p0).y * (p2 - p0).x;
+    if area.abs() < f32::EPSILON {
+        return;
+    }
+
+    let bounds = Rect::from_points(&[p0, p1, p2]).intersect(clip);
+    if !bounds.is_positive() {
+        return;
+    }
+
+    let edge = |from: epaint::Pos2, to: epaint::Pos2, point: epaint::Pos2| {
+        ((to - from).x * (point - from).y - (to - from).y * (point - from).x) / area
+    };
+    let colors = vertices.map(|vertex| to_rgba(vertex.color));
+
+    for y in bounds.min.y.floor() as usize..bounds.max.y.ceil() as usize {
+        for x in bounds.min.x.floor() as usize..bounds.max.x.ceil() as usize {
+            // sample at the pixel center, like a gpu does
+            let point = epaint::pos2(x as f32 + 0.5, y as f32 + 0.5);
+            if !clip.contains(point) {
+                continue;
+            }
+            let weights = [edge(p1, p2, point), edge(p2, p0, point), edge(p0, p1, point)];
+            if weights.iter().any(|weight| *weight < 0.0) {
+                continue;
+            }
+
+            let uv = vertices[0].uv.to_vec2() * weights[0]
+                + vertices[1].uv.to_vec2() * weights[1]
+                + vertices[2].uv.to_vec2() * weights[2];
+            let texel = sample(uv.to_pos2());
+
+            let pixel = &mut pixels[y * width + x];
+            let src_alpha = (0..3).map(|i| colors[i][3] * weights[i]).sum::<f32>() * texel[3];
+            for channel in 0..4 {
+                let src = (0..3).map(|i| colors[i][channel] * weights[i]).sum::<f32>() * texel[channel];
+                // premultiplied "over"
+                pixel[channel] = src + pixel[channel] * (1.0 - src_alpha);
+            }
+        }
+    }
+}
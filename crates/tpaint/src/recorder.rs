@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use crate::{input::InputEvent, platform::Instant};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedEvent {
+    /// Time since the recording started
+    pub timestamp: Duration,
+    pub event: InputEvent,
+}
+
+/// Captures the events going into the dom, so interactions can be replayed with [`crate::DomEventLoop::replay`]
+pub struct InputRecorder {
+    started_at: Instant,
+    events: Vec<TimedEvent>,
+}
+
+impl Default for InputRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            events: vec![],
+        }
+    }
+
+    pub fn record(&mut self, event: InputEvent) {
+        self.events.push(TimedEvent {
+            timestamp: self.started_at.elapsed(),
+            event,
+        });
+    }
+
+    pub fn events(&self) -> &[TimedEvent] {
+        &self.events
+    }
+
+    pub fn into_events(self) -> Vec<TimedEvent> {
+        self.events
+    }
+
+    /// Writes the recording as one json object per line
+    #[cfg(feature = "record")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        for event in self.events.iter() {
+            serde_json::to_writer(&mut writer, event)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+}
+
+/// Reads a recording written by [`InputRecorder::save`]
+#[cfg(feature = "record")]
+pub fn load_recording(path: impl AsRef<std::path::Path>) -> std::io::Result<Vec<TimedEvent>> {
+    use std::io::BufRead;
+
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut events = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(serde_json::from_str(&line)?);
+    }
+    Ok(events)
+}
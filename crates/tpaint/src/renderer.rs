@@ -1,23 +1,82 @@
-use std::{
-    sync::{Arc, Mutex},
-    time::Instant,
-};
+use std::sync::{Arc, Mutex};
 
 use epaint::{
     text::FontDefinitions,
     textures::{TextureOptions, TexturesDelta},
-    vec2, ClippedPrimitive, ClippedShape, Color32, Fonts, Pos2, Primitive, Rect, Shape,
-    TessellationOptions, Tessellator, TextureId, TextureManager, Vec2, WHITE_UV,
+    vec2, ClippedPrimitive, ClippedShape, Color32, ColorImage, Fonts, Galley, ImageData, ImageDelta, Mesh,
+    Pos2, Primitive, Rect, Shape, Stroke, TessellationOptions, Tessellator, TextureId, TextureManager, Vec2,
+    WHITE_UV,
 };
-
+use rustc_hash::{FxHashMap, FxHashSet};
 use taffy::{AvailableSpace, Layout, NodeId, Overflow, Size};
 use winit::dpi::PhysicalSize;
 
 use crate::{
-    dom::{CursorState, Dom, NodeContext, SelectedNode, Tag},
-    tailwind::{StyleState, TailwindCache},
+    atlas::TextureAtlas,
+    bidi::{self, TextDirection, TextShaper},
+    decoration::{self, TextDecorations},
+    element::{ElementNode, MeasureInput},
+    dom::{CursorState, Dom, LayerCache, NodeContext, SelectedNode, Tag},
+    filter::ColorMatrix,
+    platform::Instant,
+    raster::{self, SoftwareTextures},
+    tailwind::{
+        parse_texture_id, Animation, BackgroundSize, BorderImage, IntrinsicSize, StyleState, TailwindCache, ZLayer,
+    },
 };
 
+/// A subtree in a higher [`ZLayer`] than the one being painted, it's painted once everything under it is
+struct DeferredSubtree {
+    z_layer: ZLayer,
+    node_id: NodeId,
+    parent_id: Option<NodeId>,
+    /// The clip of its parent, only ``z-sticky`` keeps it
+    parent_clip: Option<Rect>,
+    parent_color_matrix: Option<ColorMatrix>,
+}
+
+/// What a node hands down to its children in the style pass, instead of every node walking its ancestors
+#[derive(Clone, Copy, Default)]
+struct StyleInherited {
+    /// See [`Dom::is_disabled`]
+    disabled: bool,
+    /// See [`Dom::is_group_hovered`]
+    group_hovered: bool,
+    /// See [`Dom::text_direction`]
+    direction: TextDirection,
+}
+
+/// What a node hands down to its children while painting, so they don't have to look at their ancestors
+#[derive(Clone, Copy, Default)]
+struct PaintInherited {
+    clip: Option<Rect>,
+    /// The color filters of the node and its ancestors folded together, ``None`` when there are none
+    color_matrix: Option<ColorMatrix>,
+}
+
+impl PaintInherited {
+    /// For a subtree painted on its own, the filters of the ancestors of its root still apply
+    fn from_ancestors(dom: &Dom, parent_id: Option<NodeId>) -> Self {
+        Self {
+            clip: None,
+            color_matrix: parent_id.and_then(|parent_id| dom.color_matrix(parent_id)),
+        }
+    }
+}
+
+/// The filter of the node folded into the ones of its ancestors, see [`Dom::color_matrix`]
+fn inherit_color_matrix(node: &NodeContext, parent: Option<ColorMatrix>) -> Option<ColorMatrix> {
+    if node.styling.filter.is_identity() {
+        return parent;
+    }
+    let own = node.styling.filter.matrix();
+    // the filter of a child runs before the one of its parent
+    Some(match parent {
+        Some(outer) => own.followed_by(&outer),
+        None => own,
+    })
+}
+
 #[derive(Clone, Debug)]
 pub struct ScreenDescriptor {
     pub pixels_per_point: f32,
@@ -27,8 +86,65 @@ pub struct Renderer {
     pub screen_descriptor: ScreenDescriptor,
     pub fonts: Fonts,
     pub tex_manager: Arc<Mutex<TextureManager>>,
+    /// Small images packed into shared textures, see [`TextureAtlas`]
+    pub atlas: Arc<Mutex<TextureAtlas>>,
     pub shapes: Vec<ClippedShape>,
     pub tessellator: Tessellator,
+    /// The scale factor of the monitor the window is on
+    pub scale_factor: f32,
+    /// User controlled zoom, multiplied with the scale factor
+    pub zoom: f32,
+    /// See [`crate::DomEventLoop::set_text_shaper`]
+    pub(crate) text_shaper: Option<Arc<dyn TextShaper>>,
+    /// Texture changes that were taken for an offscreen render and still have to reach the backend
+    pending_textures_delta: TexturesDelta,
+    /// The pixels of every user texture, filtered copies are made from these
+    texture_pixels: FxHashMap<TextureId, Arc<ColorImage>>,
+    /// Keyed by the source texture and the key of the color matrix
+    filtered_textures: FxHashMap<FilterKey, FilteredTexture>,
+    /// The ids of the copies in ``filtered_textures`` and of the textures in ``layer_textures``, their pixels aren't kept
+    derived_texture_ids: FxHashSet<TextureId>,
+    /// The copies every node was painted with last, a copy is freed once no node uses it anymore
+    node_filtered_textures: FxHashMap<NodeId, Vec<FilterKey>>,
+    /// What the subtree of every ``layer`` node was painted to, see [`LayerCache`]
+    layer_textures: FxHashMap<NodeId, TextureId>,
+    /// Every ``animate-*`` class plays relative to this, so all spinners turn in sync
+    animation_clock: Instant,
+    /// A node with an ``animate-*`` class got painted this frame, so the next one has to follow
+    is_animating: bool,
+    /// Whether the last [`Renderer::calculate_layout`] had anything to lay out again
+    pub(crate) layout_dirty: bool,
+    /// The viewport ``w-min`` and friends were last resolved in, see [`Renderer::resolve_intrinsic_sizes`]
+    intrinsic_viewport: Rect,
+    /// The primitives of a painted frame handed back through [`Renderer::recycle`], the next frame fills them again
+    /// instead of growing a new vector
+    recycled_primitives: Vec<ClippedPrimitive>,
+    /// Scratch space for the drag ghost and layers, which are tessellated apart from the frame
+    scratch_primitives: Vec<ClippedPrimitive>,
+    /// Emptied meshes of recycled primitives, new meshes are tessellated into their vertices and indices
+    mesh_pool: Vec<Mesh>,
+    /// The selection of the dom for the frame being painted
+    frame_selection: Vec<SelectedNode>,
+    /// Cpu copies of the textures, ``layer`` subtrees and exports are rasterized with them
+    software_textures: SoftwareTextures,
+}
+
+/// The source texture of a filtered copy and the key of its color matrix
+type FilterKey = (TextureId, [u32; 9]);
+
+struct FilteredTexture {
+    id: TextureId,
+    /// How many nodes were painted with it last
+    users: usize,
+}
+
+/// Emitted as a paint callback right before the shapes of a node with a ``backdrop-blur-*`` class, with the rect of the node.
+/// Backends that support it blur what's already painted there, the others skip it and only the background of the node shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BackdropBlur {
+    /// Standard deviation of the gaussian in points, like css ``blur()``
+    pub radius: f32,
+    pub rounding: epaint::Rounding,
 }
 
 pub struct RendererDescriptor {
@@ -37,11 +153,21 @@ pub struct RendererDescriptor {
     pub font_definitions: FontDefinitions,
 }
 
+const MAX_TEXTURE_SIDE: usize = 4096;
+/// How opaque the copy of a dragged node that follows the pointer is
+const DRAG_GHOST_OPACITY: f32 = 0.7;
+/// Seconds for one turn of ``animate-spin``
+const SPIN_PERIOD: f32 = 0.8;
+/// Seconds for the bar of ``animate-indeterminate`` to cross the node once
+const INDETERMINATE_PERIOD: f32 = 1.4;
+/// Width of the bar of ``animate-indeterminate`` relative to the node
+const INDETERMINATE_WIDTH: f32 = 0.4;
+
 impl Renderer {
     pub fn new(
         desc: RendererDescriptor
     ) -> Renderer {
-        let fonts = Fonts::new(desc.pixels_per_point, 4096, desc.font_definitions);
+        let fonts = Fonts::new(desc.pixels_per_point, MAX_TEXTURE_SIDE, desc.font_definitions);
         let mut tex_manager = TextureManager::default();
         let font_image_delta: Option<_> = fonts.font_image_delta();
         if let Some(font_image_delta) = font_image_delta {
@@ -52,35 +178,71 @@ impl Renderer {
             );
         }
 
+        let tessellator = Self::create_tessellator(&fonts);
+
+        Renderer {
+            screen_descriptor: ScreenDescriptor {
+                pixels_per_point: desc.pixels_per_point,
+                size: desc.window_size,
+            },
+            fonts,
+            tex_manager: Arc::new(Mutex::new(tex_manager)),
+            atlas: Default::default(),
+            shapes: Vec::new(),
+            tessellator,
+            scale_factor: desc.pixels_per_point,
+            zoom: 1.0,
+            text_shaper: None,
+            pending_textures_delta: TexturesDelta::default(),
+            texture_pixels: FxHashMap::default(),
+            filtered_textures: FxHashMap::default(),
+            derived_texture_ids: FxHashSet::default(),
+            layer_textures: FxHashMap::default(),
+            node_filtered_textures: FxHashMap::default(),
+            animation_clock: Instant::now(),
+            is_animating: false,
+            layout_dirty: true,
+            intrinsic_viewport: Rect::NOTHING,
+            recycled_primitives: Vec::new(),
+            scratch_primitives: Vec::new(),
+            mesh_pool: Vec::new(),
+            frame_selection: Vec::new(),
+            software_textures: SoftwareTextures::default(),
+        }
+    }
+
+    fn create_tessellator(fonts: &Fonts) -> Tessellator {
         let (font_tex_size, prepared_discs) = {
             let atlas = fonts.texture_atlas();
             let atlas = atlas.lock();
             (atlas.size(), atlas.prepared_discs())
         };
 
-        let tessellator = Tessellator::new(
+        Tessellator::new(
             fonts.pixels_per_point(),
             TessellationOptions::default(),
             font_tex_size,
             prepared_discs,
-        );
+        )
+    }
 
-        Renderer {
-            screen_descriptor: ScreenDescriptor {
-                pixels_per_point: desc.pixels_per_point,
-                size: desc.window_size,
-            },
-            fonts,
-            tex_manager: Arc::new(Mutex::new(tex_manager)),
-            shapes: Vec::new(),
-            tessellator,
+    /// Re-rasterizes the fonts and re-layouts the dom if the scale factor or zoom changed
+    pub fn update_pixels_per_point(&mut self, dom: &mut Dom) {
+        let pixels_per_point = self.scale_factor * self.zoom;
+        if self.screen_descriptor.pixels_per_point == pixels_per_point {
+            return;
         }
+
+        self.screen_descriptor.pixels_per_point = pixels_per_point;
+        self.fonts.begin_frame(pixels_per_point, MAX_TEXTURE_SIDE);
+        self.tessellator = Self::create_tessellator(&self.fonts);
+
+        // galleys were laid out for the old pixels per point
+        dom.invalidate_all_layouts();
     }
 
-    #[tracing::instrument(skip_all, name = "Renderer::calculate_layout")]
-    pub fn calculate_layout(&mut self, dom: &mut Dom) {
-        let root_id = dom.get_root_id();
-        let available_space = Size {
+    fn available_space(&self) -> Size<AvailableSpace> {
+        Size {
             width: taffy::style::AvailableSpace::Definite(
                 (self.screen_descriptor.size.width as f32
                     / self.screen_descriptor.pixels_per_point)
@@ -91,198 +253,379 @@ impl Renderer {
                     / self.screen_descriptor.pixels_per_point)
                     .ceil(),
             ),
-        };
+        }
+    }
 
-        // rect layout pass
-        {
-            let _guard =
-                tracing::trace_span!("Renderer::calculate_layout rect layout pass").entered();
+    fn compute_layout(&mut self, dom: &mut Dom, available_space: Size<AvailableSpace>, store_galleys: bool) {
+        let root_id = dom.get_root_id();
+        self.compute_node_layout(dom, root_id, available_space, store_galleys);
+    }
 
-            dom.tree
-                .get_node_context_mut(root_id)
-                .unwrap()
-                .attrs
-                .insert("class".into(), "w-full h-full".into());
+    fn compute_node_layout(
+        &mut self,
+        dom: &mut Dom,
+        node_id: NodeId,
+        available_space: Size<AvailableSpace>,
+        store_galleys: bool,
+    ) {
+        let mut measure_cache = MeasureCache::default();
+        dom.tree
+            .compute_layout_with_measure(
+                node_id,
+                available_space,
+                // Note: this closure is a FnMut closure and can be used to borrow external context for the duration of layout
+                // For example, you may wish to borrow a global font registry and pass it into your text measuring function
+                |known_dimensions, available_space, node_id, node_context| {
+                    measure_function(
+                        known_dimensions,
+                        available_space,
+                        node_id,
+                        node_context,
+                        &self.fonts,
+                        self.text_shaper.as_deref(),
+                        &self.tex_manager.lock().unwrap(),
+                        &mut measure_cache,
+                        store_galleys,
+                    )
+                },
+            )
+            .unwrap();
+    }
 
-            dom.traverse_tree_with_parent(root_id, None, &mut |dom, id, parent| {
-                let node = dom.tree.get_node_context_mut(id).unwrap();
+    /// The min-content size of the root node in points, expects the styles to be computed by [`Renderer::calculate_layout`]
+    #[tracing::instrument(skip_all, name = "Renderer::content_min_size")]
+    pub fn content_min_size(&mut self, dom: &mut Dom) -> Size<f32> {
+        self.compute_layout(
+            dom,
+            Size {
+                width: AvailableSpace::MinContent,
+                height: AvailableSpace::MinContent,
+            },
+            false,
+        );
+        let size = dom.tree.layout(dom.get_root_id()).unwrap().size;
 
-                let style_state = StyleState {
-                    hovered: dom.state.hovered.contains(&id),
-                    focused: dom
-                        .state
-                        .focused
-                        .as_ref()
-                        .map(|id2| id2.node_id == id)
-                        .unwrap_or(false),
-                    active: *node.attrs.get("is_active").unwrap_or(&"".into()) == "true".into(),
-                };
+        // restore the actual layout
+        self.compute_layout(dom, self.available_space(), true);
 
-                let class = node.attrs.get("class");
-                let styling_hash = TailwindCache {
-                    class: class.cloned(),
-                    state: style_state.clone(),
-                    texture_id: node.styling.texture_id,
-                };
+        size
+    }
 
-                if node.styling.cache == styling_hash {
-                    return true;
-                }
-                node.styling.cache = styling_hash;
+    /// Resolves the classes of every node into its taffy style and styling, the first pass of [`Renderer::calculate_layout`]
+    pub(crate) fn compute_styles(&mut self, dom: &mut Dom) {
+        let root_id = dom.get_root_id();
+        let _guard =
+            tracing::trace_span!("Renderer::calculate_layout rect layout pass").entered();
+
+        dom.tree
+            .get_node_context_mut(root_id)
+            .unwrap()
+            .attrs
+            .insert("class".into(), "w-full h-full".into());
+
+        let inherited = StyleInherited::default();
+        dom.traverse_tree_mut_with_parent_and_data(root_id, None, &inherited, &mut |dom, id, parent, inherited| {
+            let node = dom.tree.get_node_context(id).unwrap();
+            let hovered = dom.state.hovered.contains(&id);
+            let disabled = inherited.disabled || node.attrs.get("disabled").is_some_and(|value| &**value == "true");
+            let group_hovered = inherited.group_hovered;
+            let direction = node
+                .attrs
+                .get("dir")
+                .and_then(|dir| TextDirection::parse(dir))
+                .unwrap_or(inherited.direction);
+            let is_group = node
+                .attrs
+                .get("class")
+                .is_some_and(|class| class.split_whitespace().any(|class| class == "group"));
+            let for_children = StyleInherited {
+                disabled,
+                group_hovered: group_hovered || (is_group && hovered),
+                direction,
+            };
+            // ``space-*`` on the parent gives every child but the first a margin on its leading side
+            let spacing = parent
+                .filter(|parent| dom.tree.child_at_index(*parent, 0).ok() != Some(id))
+                .map(|parent| dom.tree.get_node_context(parent).unwrap().styling.space)
+                .unwrap_or(Vec2::ZERO);
+            let node = dom.tree.get_node_context_mut(id).unwrap();
+
+            let style_state = StyleState {
+                hovered,
+                focused: dom
+                    .state
+                    .focused
+                    .as_ref()
+                    .map(|id2| id2.node_id == id)
+                    .unwrap_or(false),
+                active: *node.attrs.get("is_active").unwrap_or(&"".into()) == "true".into()
+                    || dom.state.pressed == Some(id),
+                disabled,
+                group_hovered,
+            };
 
-                let style = match node.tag {
-                    Tag::View => {
-                        if let Some(src) = node.attrs.get("src") {
-                            node.styling.set_texture(src);
-                        }
+            let class = node.attrs.get("class");
+            let styling_hash = TailwindCache {
+                class: class.cloned(),
+                state: style_state.clone(),
+                src: node.attrs.get("src").cloned(),
+                spacing,
+                direction,
+                inline_style: node.attrs.get("style").cloned(),
+            };
 
-                        node.styling
-                            .set_styling(class.unwrap_or(&"".into()), &style_state)
-                    }
-                    Tag::Text => {
-                        let [node, parent] = dom
-                            .tree
-                            .get_disjoint_node_context_mut([id, parent.unwrap()])
-                            .unwrap();
-
-                        let class = node.attrs.get("class");
-                        let style = node
-                            .styling
-                            .set_styling(class.unwrap_or(&"".into()), &style_state);
-                        node.styling.text = parent.styling.text.clone();
-                        style
+            if node.styling.cache == styling_hash {
+                return (true, for_children);
+            }
+            let class_changed = node.styling.cache.class != styling_hash.class;
+            node.styling.cache = styling_hash;
+            let old_styling = node.styling.clone();
+
+            let mut style = match node.tag {
+                Tag::View | Tag::Button | Tag::Custom(_) => {
+                    if let Some(src) = node.attrs.get("src") {
+                        node.styling.set_texture(src);
                     }
-                };
 
-                let old_style = dom.tree.style(id).unwrap();
-                if old_style != &style {
-                    dom.tree.set_style(id, style).unwrap();
+                    let mut style = node
+                        .styling
+                        .set_styling(class.unwrap_or(&"".into()), &style_state);
+                    if let Some(inline_style) = node.attrs.get("style") {
+                        node.styling.apply_inline_style(inline_style, &mut style);
+                    }
+                    node.computed.animated_style.apply(&mut style);
+                    node.styling.text.direction = direction;
+                    // the main axis of a row starts on the right
+                    if direction == TextDirection::Rtl {
+                        style.flex_direction = match style.flex_direction {
+                            taffy::FlexDirection::Row => taffy::FlexDirection::RowReverse,
+                            taffy::FlexDirection::RowReverse => taffy::FlexDirection::Row,
+                            flex_direction => flex_direction,
+                        };
+                    }
+                    style
+                }
+                Tag::Text => {
+                    let [node, parent] = dom
+                        .tree
+                        .get_disjoint_node_context_mut([id, parent.unwrap()])
+                        .unwrap();
+
+                    let class = node.attrs.get("class");
+                    let style = node
+                        .styling
+                        .set_styling(class.unwrap_or(&"".into()), &style_state);
+                    node.styling.text = parent.styling.text.clone();
+                    style
                 }
+            };
+            if spacing.x != 0.0 {
+                style.margin.left = taffy::LengthPercentageAuto::Length(spacing.x);
+            }
+            if spacing.y != 0.0 {
+                style.margin.top = taffy::LengthPercentageAuto::Length(spacing.y);
+            }
 
-                true
-            });
-        }
+            // a state change without a variant for it, e.g. hovering a node without ``hover:``, doesn't change anything
+            let styling = &dom.tree.get_node_context(id).unwrap().styling;
+            let (styling_changed, order_changed) = (*styling != old_styling, styling.order != old_styling.order);
+            // the hit index keeps the layer of every node, e.g. ``hover:z-10`` changes it without a new batch
+            if styling.z_layer != old_styling.z_layer {
+                dom.hit_index_dirty = true;
+            }
+            let old_style = dom.tree.style(id).unwrap();
+            let style_changed = old_style != &style;
+            if style_changed {
+                dom.tree.set_style(id, style).unwrap();
+            }
+            if style_changed || styling_changed {
+                dom.invalidate_layers(id);
+            }
+            if class_changed {
+                dom.report_unknown_classes(id);
+            }
 
-        fn measure_function(
-            known_dimensions: taffy::geometry::Size<Option<f32>>,
-            available_space: taffy::geometry::Size<taffy::style::AvailableSpace>,
-            node_context: Option<&mut NodeContext>,
-            fonts: &Fonts,
-            texture_manager: &TextureManager,
-        ) -> Size<f32> {
-            if let Size {
-                width: Some(width),
-                height: Some(height),
-            } = known_dimensions
-            {
-                return Size { width, height };
+            if order_changed {
+                if let Some(parent) = parent {
+                    dom.reorder_queue.insert(parent);
+                }
             }
 
-            match node_context {
-                None => Size::ZERO,
-                Some(node_context) => match node_context.tag {
-                    Tag::View => {
-                        let Some(texture_id) = node_context.styling.texture_id else {
-                            return Size::ZERO;
-                        };
-                        let meta = texture_manager.meta(texture_id).unwrap();
+            (true, for_children)
+        });
 
-                        let image_width = meta.size[0] as f32;
-                        let image_height = meta.size[1] as f32;
-
-                        match (known_dimensions.width, known_dimensions.height) {
-                            (Some(width), Some(height)) => Size { width, height },
-                            (Some(width), None) => Size {
-                                width,
-                                height: (width / image_width) * image_height,
-                            },
-                            (None, Some(height)) => Size {
-                                width: (height / image_height) * image_width,
-                                height,
-                            },
-                            (None, None) => Size {
-                                width: image_width,
-                                height: image_height,
-                            },
-                        }
-                    }
-                    Tag::Text => {
-                        let galley = if let AvailableSpace::Definite(space) = available_space.width
-                        {
-                            fonts.layout(
-                                node_context
-                                    .attrs
-                                    .get("value")
-                                    .unwrap_or(&"".into())
-                                    .to_string(),
-                                node_context.styling.text.font.clone(),
-                                node_context.styling.text.color,
-                                space,
-                            )
-                        } else {
-                            fonts.layout_no_wrap(
-                                node_context
-                                    .attrs
-                                    .get("value")
-                                    .unwrap_or(&"".into())
-                                    .to_string(),
-                                node_context.styling.text.font.clone(),
-                                node_context.styling.text.color,
-                            )
-                        };
+        // taffy gets the children in their ``order-*`` before laying them out
+        dom.apply_child_order();
+    }
 
-                        let size = galley.size();
-                        node_context.computed.galley = Some(galley);
+    #[tracing::instrument(skip_all, name = "Renderer::calculate_layout")]
+    pub fn calculate_layout(&mut self, dom: &mut Dom) {
+        let root_id = dom.get_root_id();
 
-                        Size {
-                            width: size.x,
-                            height: size.y,
-                        }
-                    }
-                },
-            }
-        }
+        self.compute_styles(dom);
 
-        // send event on dirty nodes
+        // send event on dirty nodes, in the order of the tree
         let mut dirty_nodes = vec![];
+        let mut is_dirty_node = FxHashSet::default();
         dom.traverse_tree(root_id, &mut |dom, id| {
             let is_dirty = dom.tree.dirty(id).unwrap_or(false);
             if is_dirty {
                 dirty_nodes.push(id);
+                is_dirty_node.insert(id);
             }
             true
         });
 
         {
             let _guard = tracing::trace_span!("taffy compute layout").entered();
-            dom.tree
-                .compute_layout_with_measure(
-                    root_id,
-                    available_space,
-                    // Note: this closure is a FnMut closure and can be used to borrow external context for the duration of layout
-                    // For example, you may wish to borrow a global font registry and pass it into your text measuring function
-                    |known_dimensions, available_space, _node_id, node_context| {
-                        measure_function(
-                            known_dimensions,
-                            available_space,
-                            node_context,
-                            &self.fonts,
-                            &self.tex_manager.lock().unwrap(),
-                        )
-                    },
-                )
-                .unwrap();
-            self.compute_rects(dom);
+            let scroll_anchors = dom.capture_scroll_anchors();
+            self.resolve_intrinsic_sizes(dom);
+            // taffy marks every ancestor of a dirty node, so the root is dirty when anything is
+            self.layout_dirty = dom.tree.dirty(root_id).unwrap_or(true);
+            self.compute_layout(dom, self.available_space(), true);
+            dom.apply_scroll_anchors(scroll_anchors);
+            let changed_nodes = self.compute_rects(dom);
+            // anchored nodes are placed once everything else has its rect
+            let anchored_nodes = dom.apply_anchors(self.viewport());
+            let (transitioning_nodes, is_animating) = dom.apply_layout_transitions(dom.now());
+            if is_animating {
+                dom.request_redraw();
+            }
+            // nodes that moved without being dirty themselves, e.g. after a resize, need to know as well
+            for id in changed_nodes
+                .into_iter()
+                .chain(anchored_nodes)
+                .chain(transitioning_nodes)
+            {
+                if is_dirty_node.insert(id) {
+                    dirty_nodes.push(id);
+                }
+            }
         }
 
         dom.on_layout_changed(&dirty_nodes);
+        dom.update_visibility(self.viewport());
+    }
+
+    /// The window in points
+    fn viewport(&self) -> Rect {
+        Rect::from_min_size(
+            Pos2::ZERO,
+            vec2(
+                self.screen_descriptor.size.width as f32,
+                self.screen_descriptor.size.height as f32,
+            ) / self.screen_descriptor.pixels_per_point,
+        )
+    }
+
+    /// ``w-min``, ``w-max``, ``w-fit`` and their heights aren't taffy dimensions. Every such node is laid out on its own
+    /// under min-content or max-content space and the result becomes its size, nested ones are resolved first.
+    /// Only nodes in dirty subtrees are measured again, the others keep the size they were resolved to
+    fn resolve_intrinsic_sizes(&mut self, dom: &mut Dom) {
+        // an axis that isn't intrinsic is measured at its last size, which can change with the window
+        let viewport = self.viewport();
+        let resized = std::mem::replace(&mut self.intrinsic_viewport, viewport) != viewport;
+
+        let mut nodes = vec![];
+        dom.traverse_tree(dom.get_root_id(), &mut |dom, id| {
+            // taffy marks the ancestors of a dirty node as well, so a clean node has a clean subtree
+            let dirty = resized || dom.tree.dirty(id).unwrap_or(true);
+            let styling = &dom.tree.get_node_context(id).unwrap().styling;
+            if dirty && (styling.intrinsic_width.is_some() || styling.intrinsic_height.is_some()) {
+                nodes.push((id, styling.intrinsic_width, styling.intrinsic_height));
+            }
+            dirty
+        });
+
+        for (id, width, height) in nodes.into_iter().rev() {
+            let resolved = dom.tree.style(id).unwrap().clone();
+            // measure the content, not the size resolved last time
+            let mut style = resolved.clone();
+            for (intrinsic, size, min_size, max_size) in [
+                (width, &mut style.size.width, &mut style.min_size.width, &mut style.max_size.width),
+                (height, &mut style.size.height, &mut style.min_size.height, &mut style.max_size.height),
+            ] {
+                match intrinsic {
+                    Some(IntrinsicSize::Fit) => {
+                        *min_size = taffy::Dimension::AUTO;
+                        *max_size = taffy::Dimension::AUTO;
+                    }
+                    Some(_) => *size = taffy::Dimension::AUTO,
+                    None => {}
+                }
+            }
+            if style != resolved {
+                dom.tree.set_style(id, style.clone()).unwrap();
+            }
+
+            // an axis that isn't intrinsic keeps the size it got last time, text wraps the same way
+            let last_size = dom.tree.layout(id).unwrap().size;
+            let mut measure = |content: AvailableSpace| {
+                let available_space = Size {
+                    width: match width {
+                        Some(_) => content,
+                        None if last_size.width > 0.0 => AvailableSpace::Definite(last_size.width),
+                        None => AvailableSpace::MaxContent,
+                    },
+                    height: match height {
+                        Some(_) => content,
+                        None if last_size.height > 0.0 => AvailableSpace::Definite(last_size.height),
+                        None => AvailableSpace::MaxContent,
+                    },
+                };
+                self.compute_node_layout(dom, id, available_space, false);
+                dom.tree.layout(id).unwrap().size
+            };
+            let needs = |kind| width == Some(kind) || height == Some(kind);
+            let min_content = (needs(IntrinsicSize::Min) || needs(IntrinsicSize::Fit))
+                .then(|| measure(AvailableSpace::MinContent))
+                .unwrap_or(Size::ZERO);
+            let max_content = (needs(IntrinsicSize::Max) || needs(IntrinsicSize::Fit))
+                .then(|| measure(AvailableSpace::MaxContent))
+                .unwrap_or(Size::ZERO);
+
+            let mut resolved = style.clone();
+            for (intrinsic, min_content, max_content, size, min_size, max_size) in [
+                (
+                    width,
+                    min_content.width,
+                    max_content.width,
+                    &mut resolved.size.width,
+                    &mut resolved.min_size.width,
+                    &mut resolved.max_size.width,
+                ),
+                (
+                    height,
+                    min_content.height,
+                    max_content.height,
+                    &mut resolved.size.height,
+                    &mut resolved.min_size.height,
+                    &mut resolved.max_size.height,
+                ),
+            ] {
+                match intrinsic {
+                    Some(IntrinsicSize::Min) => *size = taffy::Dimension::Length(min_content),
+                    Some(IntrinsicSize::Max) => *size = taffy::Dimension::Length(max_content),
+                    // stays auto so it stretches, just never past the content
+                    Some(IntrinsicSize::Fit) => {
+                        *min_size = taffy::Dimension::Length(min_content);
+                        *max_size = taffy::Dimension::Length(max_content);
+                    }
+                    None => {}
+                }
+            }
+            if resolved != style {
+                dom.tree.set_style(id, resolved).unwrap();
+            }
+        }
     }
 
-    /// will compute the rects for all the nodes using the final computed layout
+    /// will compute the rects for all the nodes using the final computed layout, returns the nodes whose rect changed
     #[tracing::instrument(skip_all, name = "Renderer::compute_rects")]
-    pub fn compute_rects(&mut self, dom: &mut Dom) {
+    pub fn compute_rects(&mut self, dom: &mut Dom) -> Vec<NodeId> {
         // Now we do a pass so we cache the computed layout in our VDom tree
         let root_id = dom.get_root_id();
+        let mut changed_nodes = vec![];
         dom.traverse_tree_mut_with_parent_and_data(
             root_id,
             None,
@@ -296,15 +639,21 @@ impl Renderer {
                         let parent = dom.tree.get_node_context(parent_id).unwrap();
                         let scroll = parent.scroll;
 
+                        // the rubber band pulls the content past the ends
                         Vec2::new(
                             scroll.x.min(parent_layout.scroll_width()).max(0.0),
                             scroll.y.min(parent_layout.scroll_height()).max(0.0),
-                        )
+                        ) + parent.computed.overscroll
                     })
                     .unwrap_or_default();
 
+                // anchored nodes start from where they were placed last time, so they only move when something changed
+                let node = dom.tree.get_node_context(id).unwrap();
                 let location = *parent_location_offset - parent_scroll_offset
-                    + epaint::Vec2::new(layout.location.x, layout.location.y);
+                    + epaint::Vec2::new(layout.location.x, layout.location.y)
+                    + node.computed.anchor_offset
+                    + node.styling.translate
+                    + node.computed.animated_style.translate.unwrap_or_default();
 
                 let rect = epaint::Rect {
                     min: location.to_pos2(),
@@ -315,13 +664,30 @@ impl Renderer {
                 };
 
                 let node = dom.tree.get_node_context_mut(id).unwrap();
+                if node.computed.rect != rect {
+                    changed_nodes.push(id);
+                }
                 node.computed.rect = rect;
                 (true, location)
             },
         );
+
+        changed_nodes
     }
 
     fn get_rect_shape(&self, node: &NodeContext, parent_clip: Rect) -> ClippedShape {
+        if let Some(shape) = node
+            .attrs
+            .get("border-image")
+            .and_then(|value| BorderImage::parse(value))
+            .and_then(|border_image| self.get_border_image_shape(node.computed.rect, &border_image))
+        {
+            return ClippedShape {
+                clip_rect: parent_clip,
+                shape,
+            };
+        }
+
         let styling = &node.styling;
         let rounding = styling.border.radius;
         let rect = epaint::Rect {
@@ -350,7 +716,9 @@ impl Renderer {
                 TextureId::default()
             },
             uv: if styling.texture_id.is_some() {
-                epaint::Rect::from_min_max(epaint::pos2(0.0, 0.0), epaint::pos2(1.0, 1.0))
+                styling
+                    .texture_uv
+                    .unwrap_or(epaint::Rect::from_min_max(epaint::pos2(0.0, 0.0), epaint::pos2(1.0, 1.0)))
             } else {
                 epaint::Rect::from_min_max(WHITE_UV, WHITE_UV)
             },
@@ -362,6 +730,163 @@ impl Renderer {
         }
     }
 
+    /// The texture of the ``bg-image`` attribute, sized, positioned and tiled inside the border of the node
+    fn get_background_image_shape(&self, node: &NodeContext, parent_clip: Rect) -> Option<ClippedShape> {
+        let texture_id = parse_texture_id(node.attrs.get("bg-image")?)?;
+        let texture_size = self.tex_manager.lock().unwrap().meta(texture_id)?.size;
+        let texture_size = vec2(texture_size[0] as f32, texture_size[1] as f32);
+        let background = &node.styling.background;
+        let rect = node.computed.rect.shrink(node.styling.border.width);
+        if !rect.is_positive() {
+            return None;
+        }
+
+        let tile = match background.size {
+            BackgroundSize::Auto => texture_size,
+            BackgroundSize::Cover => {
+                texture_size * (rect.width() / texture_size.x).max(rect.height() / texture_size.y)
+            }
+            BackgroundSize::Contain => {
+                texture_size * (rect.width() / texture_size.x).min(rect.height() / texture_size.y)
+            }
+        };
+        if tile.x < 1.0 || tile.y < 1.0 {
+            return None;
+        }
+        let origin = rect.min + (rect.size() - tile) * background.position;
+
+        let (columns, rows) = if background.repeat {
+            // start at the first tile that reaches into the node
+            let start = origin
+                - vec2(
+                    ((origin.x - rect.min.x) / tile.x).ceil() * tile.x,
+                    ((origin.y - rect.min.y) / tile.y).ceil() * tile.y,
+                );
+            let xs = (0..)
+                .map(|i| start.x + i as f32 * tile.x)
+                .take_while(|x| *x < rect.max.x)
+                .collect::<Vec<_>>();
+            let ys = (0..)
+                .map(|i| start.y + i as f32 * tile.y)
+                .take_while(|y| *y < rect.max.y)
+                .collect::<Vec<_>>();
+            (xs, ys)
+        } else {
+            (vec![origin.x], vec![origin.y])
+        };
+
+        let mut mesh = epaint::Mesh::with_texture(texture_id);
+        for y in &rows {
+            for x in &columns {
+                mesh.add_rect_with_uv(
+                    Rect::from_min_size(epaint::pos2(*x, *y), tile),
+                    Rect::from_min_max(epaint::pos2(0.0, 0.0), epaint::pos2(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+            }
+        }
+
+        Some(ClippedShape {
+            clip_rect: parent_clip.intersect(rect),
+            shape: Shape::mesh(mesh),
+        })
+    }
+
+    /// Nine quads: the corners keep their size, the edges stretch along one axis and the center along both
+    fn get_border_image_shape(&self, rect: Rect, border_image: &BorderImage) -> Option<Shape> {
+        let texture_size = self
+            .tex_manager
+            .lock()
+            .unwrap()
+            .meta(border_image.texture_id)?
+            .size;
+        let [top, right, bottom, left] = border_image.slice;
+
+        // shrink the corners when they don't fit, like browsers do
+        let fit = (rect.width() / (left + right))
+            .min(rect.height() / (top + bottom))
+            .min(1.0);
+        let xs = [rect.min.x, rect.min.x + left * fit, rect.max.x - right * fit, rect.max.x];
+        let ys = [rect.min.y, rect.min.y + top * fit, rect.max.y - bottom * fit, rect.max.y];
+        let us = [
+            0.0,
+            left / texture_size[0] as f32,
+            1.0 - right / texture_size[0] as f32,
+            1.0,
+        ];
+        let vs = [
+            0.0,
+            top / texture_size[1] as f32,
+            1.0 - bottom / texture_size[1] as f32,
+            1.0,
+        ];
+
+        let mut mesh = epaint::Mesh::with_texture(border_image.texture_id);
+        for row in 0..3 {
+            for column in 0..3 {
+                mesh.add_rect_with_uv(
+                    Rect::from_min_max(
+                        epaint::pos2(xs[column], ys[row]),
+                        epaint::pos2(xs[column + 1], ys[row + 1]),
+                    ),
+                    Rect::from_min_max(
+                        epaint::pos2(us[column], vs[row]),
+                        epaint::pos2(us[column + 1], vs[row + 1]),
+                    ),
+                    Color32::WHITE,
+                );
+            }
+        }
+
+        Some(Shape::mesh(mesh))
+    }
+
+    /// The frame of an ``animate-*`` class at the current time of the animation clock
+    fn get_animation_shape(&self, node: &NodeContext, animation: Animation, clip: Rect) -> Option<ClippedShape> {
+        let rect = node.computed.rect;
+        let color = node.styling.text.color;
+        let elapsed = self.animation_clock.elapsed().as_secs_f32();
+
+        let shape = match animation {
+            Animation::Spin => {
+                let width = (rect.width().min(rect.height()) * 0.12).max(2.0);
+                let radius = rect.width().min(rect.height()) / 2.0 - width / 2.0;
+                if radius <= 0.0 {
+                    return None;
+                }
+                // three quarters of a circle, starting wherever the turn is at
+                const SEGMENTS: usize = 32;
+                let start = (elapsed / SPIN_PERIOD).fract() * std::f32::consts::TAU;
+                let sweep = std::f32::consts::TAU * 0.75;
+                let points = (0..=SEGMENTS)
+                    .map(|index| {
+                        let angle = start + sweep * index as f32 / SEGMENTS as f32;
+                        rect.center() + vec2(angle.cos(), angle.sin()) * radius
+                    })
+                    .collect();
+                Shape::line(points, Stroke::new(width, color))
+            }
+            Animation::Indeterminate => {
+                // eased, so the bar speeds up in the middle like the usual indeterminate progress bars
+                let t = (elapsed / INDETERMINATE_PERIOD).fract();
+                let t = t * t * (3.0 - 2.0 * t);
+                let bar = rect.width() * INDETERMINATE_WIDTH;
+                let left = rect.min.x - bar + t * (rect.width() + bar);
+                let bar = Rect::from_min_max(epaint::pos2(left, rect.min.y), epaint::pos2(left + bar, rect.max.y))
+                    .intersect(rect);
+                if !bar.is_positive() {
+                    return None;
+                }
+                Shape::rect_filled(bar, node.styling.border.radius, color)
+            }
+        };
+
+        Some(ClippedShape {
+            clip_rect: clip.intersect(rect),
+            shape,
+        })
+    }
+
     #[tracing::instrument(skip_all, name = "Renderer::get_paint_info")]
     pub fn get_paint_info(
         &mut self,
@@ -371,192 +896,768 @@ impl Renderer {
         self.calculate_layout(dom);
         log::debug!("layout took: {:?}", now.elapsed());
 
+        let (clipped_primitives, texture_delta) = self.paint(dom);
+        (clipped_primitives, texture_delta, &self.screen_descriptor)
+    }
+
+    /// Collects and tessellates the shapes of the dom as it was laid out last
+    pub(crate) fn paint(&mut self, dom: &mut Dom) -> (Vec<ClippedPrimitive>, TexturesDelta) {
         // get all computed rects
         let now = Instant::now();
         let root_id = dom.get_root_id();
+        let cursor_state = dom.state.cursor_state.clone();
+        let mut selection = std::mem::take(&mut self.frame_selection);
+        selection.clear();
+        selection.extend_from_slice(&dom.state.selection);
+
+        // new textures have to be known before painting, filtered copies are made from their pixels
+        let mut texture_delta = std::mem::take(&mut self.pending_textures_delta);
+        texture_delta.append(self.take_textures_delta());
+
+        let mut clipped_primitives = std::mem::take(&mut self.recycled_primitives);
+        clipped_primitives.clear();
+        clipped_primitives.reserve(self.shapes.len());
+        self.is_animating = false;
+        let collect_span = tracing::trace_span!("Renderer::collect_shapes").entered();
+        let mut deferred = vec![];
+        self.collect_z_layer_shapes(
+            dom,
+            DeferredSubtree {
+                z_layer: ZLayer::Base,
+                node_id: root_id,
+                parent_id: None,
+                parent_clip: None,
+                parent_color_matrix: None,
+            },
+            &cursor_state,
+            &selection,
+            &mut deferred,
+        );
+        // the lowest layer first, subtrees of the same layer in the order of the tree
+        while let Some(index) = (0..deferred.len()).min_by_key(|index| deferred[*index].z_layer) {
+            let subtree = deferred.remove(index);
+            self.collect_z_layer_shapes(dom, subtree, &cursor_state, &selection, &mut deferred);
+        }
+
+        drop(collect_span);
+        self.release_removed_filtered_textures(dom);
+        self.free_removed_layer_textures(dom);
+        texture_delta.append(self.take_textures_delta());
+
+        self.tessellate_shapes(&mut clipped_primitives);
+
+        if let Some((source, offset)) = dom.drag_ghost() {
+            self.paint_drag_ghost(dom, source, offset, &cursor_state, &selection, &mut clipped_primitives);
+        }
+        self.frame_selection = selection;
+
+        // animations keep going without anything else changing, the app doesn't have to wake up for them
+        if self.is_animating {
+            dom.request_redraw();
+        }
+
+        let mesh_pool = &mut self.mesh_pool;
+        clipped_primitives.retain_mut(|p| match &mut p.primitive {
+            Primitive::Mesh(mesh) if mesh.is_empty() => {
+                mesh_pool.push(std::mem::take(mesh));
+                false
+            }
+            _ => p.clip_rect.is_positive(),
+        });
+
+        log::debug!(
+            "paint info took: {:?} - primitives {}",
+            now.elapsed(),
+            clipped_primitives.len()
+        );
+
+        (clipped_primitives, texture_delta)
+    }
+
+    #[tracing::instrument(skip_all, name = "Renderer::take_textures_delta")]
+    fn take_textures_delta(&mut self) -> TexturesDelta {
+        let font_image_delta = self.fonts.font_image_delta();
+        let mut tex_manager = self.tex_manager.lock().unwrap();
+        if let Some(font_image_delta) = font_image_delta {
+            tex_manager.set(epaint::TextureId::default(), font_image_delta);
+        }
+
+        let delta = tex_manager.take_delta();
+        for (id, image_delta) in &delta.set {
+            let is_derived = self.derived_texture_ids.contains(id);
+            match (&image_delta.image, image_delta.pos) {
+                (ImageData::Color(image), None) if !is_derived && *id != TextureId::default() => {
+                    self.texture_pixels.insert(*id, image.clone());
+                }
+                _ => {
+                    self.texture_pixels.remove(id);
+                }
+            }
+        }
+        for id in &delta.free {
+            self.texture_pixels.remove(id);
+            let derived_texture_ids = &mut self.derived_texture_ids;
+            self.filtered_textures.retain(|(source, _), filtered| {
+                if source == id {
+                    tex_manager.free(filtered.id);
+                    derived_texture_ids.remove(&filtered.id);
+                }
+                source != id
+            });
+        }
+
+        self.software_textures.apply(&delta);
+        delta
+    }
+
+    /// A copy of the texture with the color matrix applied, made on the cpu the first time it's asked for.
+    /// ``None`` when the pixels of the texture aren't known
+    fn filtered_texture(&mut self, key: FilterKey, matrix: &ColorMatrix) -> Option<TextureId> {
+        if let Some(filtered) = self.filtered_textures.get(&key) {
+            return Some(filtered.id);
+        }
+        let pixels = self.texture_pixels.get(&key.0)?;
+
+        let id = self.tex_manager.lock().unwrap().alloc(
+            "filtered".into(),
+            ImageData::Color(Arc::new(matrix.apply_image(pixels))),
+            TextureOptions::LINEAR,
+        );
+        self.filtered_textures.insert(key, FilteredTexture { id, users: 0 });
+        self.derived_texture_ids.insert(id);
+        Some(id)
+    }
+
+    /// Runs the shapes of the node pushed since ``start`` through the color matrix
+    fn apply_color_matrix(&mut self, node_id: NodeId, start: usize, matrix: &ColorMatrix) {
+        let mut shapes = std::mem::take(&mut self.shapes);
+        let mut used = vec![];
+        for clipped_shape in &mut shapes[start..] {
+            matrix.apply_to_shape(&mut clipped_shape.shape, &mut |texture_id| {
+                let key = (texture_id, matrix.key());
+                let Some(filtered) = self.filtered_texture(key, matrix) else {
+                    return texture_id;
+                };
+                if !used.contains(&key) {
+                    used.push(key);
+                }
+                filtered
+            });
+        }
+        self.shapes = shapes;
+        self.set_node_filtered_textures(node_id, used);
+    }
+
+    /// Swaps the copies the node uses, the ones it used before are freed when it was their last user.
+    /// A node whose filter keeps changing, e.g. in a transition, holds on to one copy per texture instead of one per value
+    fn set_node_filtered_textures(&mut self, node_id: NodeId, used: Vec<FilterKey>) {
+        for key in &used {
+            if let Some(filtered) = self.filtered_textures.get_mut(key) {
+                filtered.users += 1;
+            }
+        }
+        let previous = if used.is_empty() {
+            self.node_filtered_textures.remove(&node_id)
+        } else {
+            self.node_filtered_textures.insert(node_id, used)
+        };
+        for key in previous.into_iter().flatten() {
+            self.release_filtered_texture(key);
+        }
+    }
+
+    fn release_filtered_texture(&mut self, key: FilterKey) {
+        // the source might have been freed already, which took its copies with it
+        let Some(filtered) = self.filtered_textures.get_mut(&key) else {
+            return;
+        };
+        filtered.users -= 1;
+        if filtered.users == 0 {
+            let id = filtered.id;
+            self.filtered_textures.remove(&key);
+            self.derived_texture_ids.remove(&id);
+            self.tex_manager.lock().unwrap().free(id);
+        }
+    }
+
+    /// Releases the copies of nodes that were removed since the last frame
+    fn release_removed_filtered_textures(&mut self, dom: &Dom) {
+        if self.node_filtered_textures.is_empty() {
+            return;
+        }
+        let removed: Vec<NodeId> = self
+            .node_filtered_textures
+            .keys()
+            .filter(|node_id| dom.tree.get_node_context(**node_id).is_none())
+            .copied()
+            .collect();
+        for node_id in removed {
+            self.set_node_filtered_textures(node_id, vec![]);
+        }
+    }
+
+    /// Paints the dom on the cpu at the current screen descriptor, the backend still gets the texture changes on the next frame
+    #[cfg(feature = "export")]
+    pub fn render_image(&mut self, dom: &mut Dom) -> epaint::ColorImage {
+        let (primitives, textures_delta, _) = self.get_paint_info(dom);
+        self.pending_textures_delta = textures_delta;
+
+        let size = [
+            self.screen_descriptor.size.width as usize,
+            self.screen_descriptor.size.height as usize,
+        ];
+        raster::rasterize(&primitives, &self.software_textures, size, self.screen_descriptor.pixels_per_point)
+    }
+
+    /// Paints a node and its children on the cpu against a transparent background, at the size it was laid out at
+    #[cfg(feature = "export")]
+    pub fn render_node_image(&mut self, dom: &mut Dom, node_id: NodeId) -> epaint::ColorImage {
         let cursor_state = dom.state.cursor_state.clone();
         let selection = dom.state.selection.clone();
+        let rect = dom.tree.get_node_context(node_id).unwrap().computed.rect;
+        let parent_id = dom.tree.parent(node_id);
 
+        let inherited = PaintInherited::from_ancestors(dom, parent_id);
         dom.traverse_tree_mut_with_parent_and_data(
-            root_id,
-            None,
-            &None,
-            &mut |dom, id, parent_id, parent_clip| {
-                let node = dom.tree.get_node_context(id).unwrap();
-                let style = dom.tree.style(id).unwrap();
-
-                // we need to make sure the scrollbar doesnt get overwritten
-                let node_clip = {
-                    epaint::Rect {
-                        min: node.computed.rect.min,
-                        max: epaint::Pos2 {
-                            x: if style.overflow.y == Overflow::Scroll
-                                && style.scrollbar_width != 0.0
-                            {
-                                node.computed.rect.max.x - style.scrollbar_width
-                            } else {
-                                node.computed.rect.max.x
-                            },
-                            y: if style.overflow.x == Overflow::Scroll
-                                && style.scrollbar_width != 0.0
-                            {
-                                node.computed.rect.max.y - style.scrollbar_width
-                            } else {
-                                node.computed.rect.max.y
-                            },
-                        },
-                    }
-                };
+            node_id,
+            parent_id,
+            &inherited,
+            &mut |dom, id, parent_id, parent| {
+                self.collect_node_shapes(dom, id, parent_id, parent, &cursor_state, &selection)
+            },
+        );
+        // filtered copies of textures might've been made, the cpu textures need them too
+        let textures_delta = self.take_textures_delta();
+        self.pending_textures_delta.append(textures_delta);
+        let mut primitives = Vec::with_capacity(self.shapes.len());
+        self.tessellate_shapes(&mut primitives);
+
+        // move the node to the top left of the image
+        let offset = -rect.min.to_vec2();
+        for primitive in primitives.iter_mut() {
+            primitive.clip_rect = primitive.clip_rect.translate(offset);
+            if let Primitive::Mesh(mesh) = &mut primitive.primitive {
+                mesh.translate(offset);
+            }
+        }
 
-                let mut clip = node_clip;
-                match style.overflow.y {
-                    Overflow::Scroll | Overflow::Hidden => {
-                        if let Some(current_clip) = parent_clip {
-                            clip = node_clip.intersect(*current_clip);
-                        }
-                    }
-                    Overflow::Visible => {
-                        if let Some(parent_clip_rect) = parent_clip {
-                            clip = *parent_clip_rect;
-                        }
-                    }
-                    _ => {}
+        let pixels_per_point = self.screen_descriptor.pixels_per_point;
+        let size = [
+            (rect.width() * pixels_per_point).round() as usize,
+            (rect.height() * pixels_per_point).round() as usize,
+        ];
+        raster::rasterize(&primitives, &self.software_textures, size, pixels_per_point)
+    }
+
+    /// Pushes the shapes of a single node, returns whether its children should be visited and what they inherit
+    fn collect_node_shapes(
+        &mut self,
+        dom: &Dom,
+        id: NodeId,
+        parent_id: Option<NodeId>,
+        parent: &PaintInherited,
+        cursor_state: &CursorState,
+        selection: &Vec<SelectedNode>,
+    ) -> (bool, PaintInherited) {
+        let node = dom.tree.get_node_context(id).unwrap();
+        let style = dom.tree.style(id).unwrap();
+        let shapes_start = self.shapes.len();
+        let parent_clip = parent.clip;
+        let color_matrix = inherit_color_matrix(node, parent.color_matrix);
+
+        // we need to make sure the scrollbar doesnt get overwritten
+        let node_clip = {
+            epaint::Rect {
+                min: node.computed.rect.min,
+                max: epaint::Pos2 {
+                    x: if style.overflow.y == Overflow::Scroll
+                        && style.scrollbar_width != 0.0
+                    {
+                        node.computed.rect.max.x - style.scrollbar_width
+                    } else {
+                        node.computed.rect.max.x
+                    },
+                    y: if style.overflow.x == Overflow::Scroll
+                        && style.scrollbar_width != 0.0
+                    {
+                        node.computed.rect.max.y - style.scrollbar_width
+                    } else {
+                        node.computed.rect.max.y
+                    },
+                },
+            }
+        };
+
+        let mut clip = node_clip;
+        match style.overflow.y {
+            Overflow::Scroll | Overflow::Hidden => {
+                if let Some(current_clip) = parent_clip {
+                    clip = node_clip.intersect(current_clip);
                 }
+            }
+            Overflow::Visible => {
+                if let Some(parent_clip_rect) = parent_clip {
+                    clip = parent_clip_rect;
+                }
+            }
+            _ => {}
+        }
+
+        // ``hidden`` and ``invisible`` subtrees keep their nodes and state, they're just not painted
+        if node.styling.invisible || style.display == taffy::Display::None {
+            return (false, PaintInherited { clip: Some(clip), color_matrix });
+        }
+
+        // a node that clips its children and ends up with an empty clip can't show anything,
+        // so there's no need to go through its subtree
+        if style.overflow.y != Overflow::Visible && !clip.is_positive() {
+            return (false, PaintInherited { clip: Some(clip), color_matrix });
+        }
+
+        // nodes outside of the visible area don't need any shapes, their children might still be visible though
+        if !node.computed.rect.intersects(clip) {
+            return (true, PaintInherited { clip: Some(clip), color_matrix });
+        }
 
-                match node.tag {
-                    Tag::Text => {
-                        let shape = Shape::galley(
-                            node.computed.rect.min,
-                            node.computed
-                                .galley
-                                .clone()
-                                .expect("Galley should've been set in the calculate_layout step"),
-                            Color32::BLACK,
-                        );
-                        let parent = dom.tree.get_node_context(parent_id.unwrap()).unwrap();
-
-                        if let Some(cursor) = parent.attrs.get("text_cursor") {
-                            let epaint::Shape::Text(text_shape) = &shape else {
-                                unreachable!();
-                            };
-
-                            if let Ok(cursor) = str::parse::<isize>(cursor) {
-                                if cursor >= 0 {
-                                    if *parent
-                                        .attrs
-                                        .get("text_cursor_visible")
-                                        .unwrap_or(&"true".into())
-                                        == "true".into()
-                                    {
-                                        self.shapes.push(self.get_cursor_shape(
-                                            parent,
-                                            text_shape,
-                                            cursor as usize,
-                                        ));
-                                    }
-                                }
+        match node.tag {
+            Tag::Text => {
+                let shape = Shape::galley(
+                    node.computed.rect.min,
+                    node.computed
+                        .galley
+                        .clone()
+                        .expect("Galley should've been set in the calculate_layout step"),
+                    Color32::BLACK,
+                );
+                let parent = dom.tree.get_node_context(parent_id.unwrap()).unwrap();
+
+                if let Some(cursor) = parent.attrs.get("text_cursor") {
+                    let epaint::Shape::Text(text_shape) = &shape else {
+                        unreachable!();
+                    };
+
+                    if let Ok(cursor) = str::parse::<isize>(cursor) {
+                        if cursor >= 0 {
+                            if *parent
+                                .attrs
+                                .get("text_cursor_visible")
+                                .unwrap_or(&"true".into())
+                                == "true".into()
+                            {
+                                let prefer_next_row = parent
+                                    .attrs
+                                    .get("text_cursor_prefer_next_row")
+                                    .map_or(true, |value| &**value == "true");
+                                self.shapes.push(self.get_cursor_shape(
+                                    parent,
+                                    text_shape,
+                                    cursor as usize,
+                                    prefer_next_row,
+                                ));
                             }
                         }
+                    }
+                }
 
-                        let parent = parent_id
-                            .map(|parent_id| dom.tree.get_node_context(parent_id).unwrap());
-                        let selection_shapes = self.get_selection_shape(
-                            &cursor_state,
-                            &selection,
-                            &id,
-                            node,
-                            parent.unwrap(),
-                        );
-                        self.shapes.extend(selection_shapes);
-                        self.shapes.push(ClippedShape {
-                            clip_rect: clip,
-                            shape,
-                        });
+                let parent = parent_id
+                    .map(|parent_id| dom.tree.get_node_context(parent_id).unwrap());
+                let selection_shapes = self.get_selection_shape(
+                    cursor_state,
+                    selection,
+                    &id,
+                    node,
+                    parent.unwrap(),
+                );
+                self.shapes.extend(selection_shapes);
+
+                let (backgrounds, lines) = match parent.unwrap().attrs.get("text_decorations") {
+                    Some(decorations) => decoration::shapes(
+                        node.computed.galley.as_ref().unwrap(),
+                        parent.unwrap().styling.text.direction,
+                        node.computed.rect.min,
+                        &TextDecorations::parse(decorations),
+                    ),
+                    None => (vec![], vec![]),
+                };
+                let clipped = |shape| ClippedShape {
+                    clip_rect: clip,
+                    shape,
+                };
+                self.shapes.extend(backgrounds.into_iter().map(clipped));
+                self.shapes.push(clipped(shape));
+                self.shapes.extend(lines.into_iter().map(clipped));
+            }
+            Tag::View | Tag::Button | Tag::Custom(_) => {
+                #[cfg(feature = "backdrop-blur")]
+                if node.styling.backdrop_blur > 0.0 {
+                    self.shapes.push(ClippedShape {
+                        clip_rect: clip,
+                        shape: Shape::Callback(epaint::PaintCallback {
+                            rect: node.computed.rect,
+                            callback: Arc::new(BackdropBlur {
+                                radius: node.styling.backdrop_blur,
+                                rounding: node.styling.border.radius,
+                            }),
+                        }),
+                    });
+                }
+                self.shapes.push(self.get_rect_shape(node, clip));
+                if let Some(background) = self.get_background_image_shape(node, clip) {
+                    self.shapes.push(background);
+                }
+                if let Some(animation) = node.styling.animation {
+                    self.is_animating = true;
+                    if let Some(shape) = self.get_animation_shape(node, animation, clip) {
+                        self.shapes.push(shape);
                     }
-                    Tag::View => {
-                        self.shapes.push(self.get_rect_shape(node, clip));
-
-                        let are_both_scrollbars_visible = style.overflow.x == Overflow::Scroll
-                            && style.overflow.y == Overflow::Scroll;
-
-                        if style.scrollbar_width > 0.0 && style.overflow.y == Overflow::Scroll {
-                            let layout = dom.tree.layout(id).unwrap();
-                            let (container_shape, button_shape) = self.get_scrollbar_shape(
-                                node,
-                                &layout,
-                                style.scrollbar_width,
-                                false,
-                                are_both_scrollbars_visible,
-                                false,
-                                false,
-                            );
-
-                            self.shapes.push(container_shape);
-                            self.shapes.push(button_shape);
-                        }
+                }
 
-                        if style.scrollbar_width > 0.0 && style.overflow.x == Overflow::Scroll {
-                            let layout = dom.tree.layout(id).unwrap();
-                            let (container_shape, button_shape) = self.get_scrollbar_shape(
-                                node,
-                                &layout,
-                                style.scrollbar_width,
-                                true,
-                                are_both_scrollbars_visible,
-                                false,
-                                false,
-                            );
-
-                            self.shapes.push(container_shape);
-                            self.shapes.push(button_shape);
-                        }
+                let are_both_scrollbars_visible = style.overflow.x == Overflow::Scroll
+                    && style.overflow.y == Overflow::Scroll;
+
+                if style.scrollbar_width > 0.0 && style.overflow.y == Overflow::Scroll {
+                    let layout = dom.tree.layout(id).unwrap();
+                    let (container_shape, button_shape) = self.get_scrollbar_shape(
+                        node,
+                        &layout,
+                        style.scrollbar_width,
+                        false,
+                        are_both_scrollbars_visible,
+                        cursor_state,
+                    );
+
+                    self.shapes.push(container_shape);
+                    self.shapes.push(button_shape);
+                }
 
-                        if are_both_scrollbars_visible {
-                            self.shapes.push(self.get_scrollbar_bottom_right_prop(
-                                node,
-                                &self.shapes[self.shapes.len() - 4],
-                                &self.shapes[self.shapes.len() - 2],
-                                style.scrollbar_width,
-                            ))
-                        }
-                    }
+                if style.scrollbar_width > 0.0 && style.overflow.x == Overflow::Scroll {
+                    let layout = dom.tree.layout(id).unwrap();
+                    let (container_shape, button_shape) = self.get_scrollbar_shape(
+                        node,
+                        &layout,
+                        style.scrollbar_width,
+                        true,
+                        are_both_scrollbars_visible,
+                        cursor_state,
+                    );
+
+                    self.shapes.push(container_shape);
+                    self.shapes.push(button_shape);
+                }
+
+                if are_both_scrollbars_visible {
+                    self.shapes.push(self.get_scrollbar_bottom_right_prop(
+                        node,
+                        &self.shapes[self.shapes.len() - 4],
+                        &self.shapes[self.shapes.len() - 2],
+                        style.scrollbar_width,
+                    ))
+                }
+            }
+        }
+
+        if let Tag::Custom(custom) = &node.tag {
+            let mut shapes = Vec::new();
+            custom.element().paint(ElementNode::new(id, node), &mut shapes);
+            self.shapes.extend(shapes.into_iter().map(|shape| ClippedShape { clip_rect: clip, shape }));
+        }
+
+        match color_matrix {
+            Some(matrix) => self.apply_color_matrix(id, shapes_start, &matrix),
+            None if self.node_filtered_textures.contains_key(&id) => self.set_node_filtered_textures(id, vec![]),
+            None => {}
+        }
+
+        if dom.show_unknown_classes && !node.styling.unknown_classes.is_empty() {
+            self.shapes.push(ClippedShape {
+                clip_rect: clip,
+                shape: Shape::rect_stroke(node.computed.rect, 0.0, Stroke::new(2.0, Color32::RED)),
+            });
+        }
+
+        (true, PaintInherited { clip: Some(clip), color_matrix })
+    }
+
+    /// Collects the shapes of a subtree in its [`ZLayer`], the nodes in it that go in a higher layer are left for later
+    fn collect_z_layer_shapes(
+        &mut self,
+        dom: &mut Dom,
+        subtree: DeferredSubtree,
+        cursor_state: &CursorState,
+        selection: &Vec<SelectedNode>,
+        deferred: &mut Vec<DeferredSubtree>,
+    ) {
+        let DeferredSubtree { z_layer, node_id, parent_id, parent_clip, parent_color_matrix } = subtree;
+        dom.traverse_tree_mut_with_parent_and_data(
+            node_id,
+            parent_id,
+            &PaintInherited { clip: parent_clip, color_matrix: parent_color_matrix },
+            &mut |dom, id, parent_id, parent| {
+                let node = dom.tree.get_node_context(id).unwrap();
+                let higher_z_layer = node.styling.z_layer.filter(|node_z_layer| *node_z_layer > z_layer && id != node_id);
+                if let Some(node_z_layer) = higher_z_layer {
+                    deferred.push(DeferredSubtree {
+                        z_layer: node_z_layer,
+                        node_id: id,
+                        parent_id,
+                        parent_clip: if node_z_layer == ZLayer::Sticky { parent.clip } else { None },
+                        parent_color_matrix: parent.color_matrix,
+                    });
+                    return (false, *parent);
+                }
+
+                if node.styling.layer {
+                    self.paint_layer(dom, id, parent_id, parent, cursor_state, selection);
+                    return (false, *parent);
                 }
 
-                (true, Some(clip))
+                self.collect_node_shapes(dom, id, parent_id, parent, cursor_state, selection)
             },
         );
+    }
 
-        let texture_delta = {
-            let font_image_delta = self.fonts.font_image_delta();
-            let mut tex_manager = self.tex_manager.lock().unwrap();
-            if let Some(font_image_delta) = font_image_delta {
-                tex_manager.set(epaint::TextureId::default(), font_image_delta);
-            }
+    /// Paints a node marked with ``layer`` and its subtree. Nodes in it keep the [`ZLayer`] of the ``layer`` node. The subtree is
+    /// rasterized into a texture of its own, which is drawn every frame until the subtree gets invalidated, see
+    /// [`Dom::invalidate_layers`]. Paint callbacks in it, like [`BackdropBlur`], are left out
+    fn paint_layer(
+        &mut self,
+        dom: &mut Dom,
+        id: NodeId,
+        parent_id: Option<NodeId>,
+        parent: &PaintInherited,
+        cursor_state: &CursorState,
+        selection: &Vec<SelectedNode>,
+    ) {
+        let node = dom.tree.get_node_context(id).unwrap();
+        let rect = node.computed.rect;
+        let pixels_per_point = self.screen_descriptor.pixels_per_point;
+
+        // filters of ancestors end up in the layer too, so changing them has to invalidate it
+        let color_matrix = inherit_color_matrix(node, parent.color_matrix);
+        let is_cache_valid = self.layer_textures.contains_key(&id)
+            && node.computed.layer.as_ref().is_some_and(|layer| {
+                layer.rect.size() == rect.size()
+                    && layer.color_matrix == color_matrix
+                    && layer.pixels_per_point == pixels_per_point
+            });
+
+        if !is_cache_valid {
+            // the shapes collected so far belong to the frame, the layer is tessellated on its own
+            let frame_shapes = std::mem::take(&mut self.shapes);
+            // the layer is only clipped by itself
+            dom.traverse_tree_mut_with_parent_and_data(
+                id,
+                parent_id,
+                &PaintInherited { clip: None, color_matrix: parent.color_matrix },
+                &mut |dom, id, parent_id, parent| {
+                    self.collect_node_shapes(dom, id, parent_id, parent, cursor_state, selection)
+                },
+            );
+            let mut primitives = std::mem::take(&mut self.scratch_primitives);
+            self.tessellate_shapes(&mut primitives);
+            self.shapes = frame_shapes;
+
+            let bounds = self.rasterize_layer(id, &mut primitives);
+            self.recycle_meshes(&mut primitives);
+            self.scratch_primitives = primitives;
+            dom.tree.get_node_context_mut(id).unwrap().computed.layer = Some(LayerCache {
+                rect,
+                bounds,
+                pixels_per_point,
+                color_matrix,
+            });
+        }
 
-            tex_manager.take_delta()
+        let layer = dom.tree.get_node_context(id).unwrap().computed.layer.as_ref().unwrap();
+        let Some(texture_id) = self.layer_textures.get(&id).copied().filter(|_| layer.bounds.is_positive()) else {
+            return;
         };
+        // the layer might've moved, e.g. because an ancestor got scrolled
+        let bounds = layer.bounds.translate(rect.min - layer.rect.min);
+        self.shapes.push(ClippedShape {
+            clip_rect: parent.clip.map_or(bounds, |parent_clip| bounds.intersect(parent_clip)),
+            shape: Shape::image(
+                texture_id,
+                bounds,
+                Rect::from_min_max(epaint::pos2(0.0, 0.0), epaint::pos2(1.0, 1.0)),
+                Color32::WHITE,
+            ),
+        });
+    }
 
-        let mut clipped_primitives: Vec<ClippedPrimitive> = Vec::with_capacity(self.shapes.len());
-        for clipped_shape in std::mem::take(&mut self.shapes) {
-            self.tessellator
-                .tessellate_clipped_shape(clipped_shape, &mut clipped_primitives);
+    /// Rasterizes the tessellated subtree of a ``layer`` node into its texture, which is made the first time.
+    /// Returns the area the texture covers, in points
+    fn rasterize_layer(&mut self, id: NodeId, primitives: &mut [ClippedPrimitive]) -> Rect {
+        let pixels_per_point = self.screen_descriptor.pixels_per_point;
+        // children and shadows can reach past the node, the texture covers everything that was painted
+        let bounds = primitives
+            .iter()
+            .filter_map(|primitive| match &primitive.primitive {
+                Primitive::Mesh(mesh) => Some(mesh.calc_bounds().intersect(primitive.clip_rect)),
+                Primitive::Callback(_) => None,
+            })
+            .filter(|bounds| bounds.is_positive())
+            .fold(Rect::NOTHING, |bounds, mesh_bounds| bounds.union(mesh_bounds));
+        if !bounds.is_positive() {
+            return Rect::NOTHING;
         }
 
-        clipped_primitives.retain(|p| {
-            p.clip_rect.is_positive()
-                && match &p.primitive {
-                    Primitive::Mesh(mesh) => !mesh.is_empty(),
-                    Primitive::Callback(_) => true,
-                }
+        // whole pixels, so the texture isn't resampled while the layer stays put
+        let min = (bounds.min * pixels_per_point).floor();
+        let size = [
+            (((bounds.max.x * pixels_per_point).ceil() - min.x) as usize).min(MAX_TEXTURE_SIDE),
+            (((bounds.max.y * pixels_per_point).ceil() - min.y) as usize).min(MAX_TEXTURE_SIDE),
+        ];
+        let bounds = Rect::from_min_size(
+            min / pixels_per_point,
+            vec2(size[0] as f32, size[1] as f32) / pixels_per_point,
+        );
+
+        // move the subtree to the top left of the texture
+        let offset = -bounds.min.to_vec2();
+        for primitive in primitives.iter_mut() {
+            primitive.clip_rect = primitive.clip_rect.translate(offset);
+            if let Primitive::Mesh(mesh) = &mut primitive.primitive {
+                mesh.translate(offset);
+            }
+        }
+        let image = ImageData::Color(Arc::new(raster::rasterize(
+            primitives,
+            &self.software_textures,
+            size,
+            pixels_per_point,
+        )));
+
+        let mut tex_manager = self.tex_manager.lock().unwrap();
+        match self.layer_textures.get(&id) {
+            Some(texture_id) => tex_manager.set(*texture_id, ImageDelta::full(image, TextureOptions::LINEAR)),
+            None => {
+                let texture_id = tex_manager.alloc("layer".into(), image, TextureOptions::LINEAR);
+                self.layer_textures.insert(id, texture_id);
+                self.derived_texture_ids.insert(texture_id);
+            }
+        }
+        bounds
+    }
+
+    /// Frees the textures of ``layer`` nodes that were removed or aren't a ``layer`` anymore
+    fn free_removed_layer_textures(&mut self, dom: &Dom) {
+        let mut tex_manager = self.tex_manager.lock().unwrap();
+        let derived_texture_ids = &mut self.derived_texture_ids;
+        self.layer_textures.retain(|node_id, texture_id| {
+            let is_layer = dom.tree.get_node_context(*node_id).is_some_and(|node| node.styling.layer);
+            if !is_layer {
+                tex_manager.free(*texture_id);
+                derived_texture_ids.remove(texture_id);
+            }
+            is_layer
         });
+    }
 
-        log::debug!(
-            "paint info took: {:?} - primitives {}",
-            now.elapsed(),
-            clipped_primitives.len()
+    /// The dragged node and its children drawn again on top of everything, moved along with the pointer
+    fn paint_drag_ghost(
+        &mut self,
+        dom: &mut Dom,
+        source: NodeId,
+        offset: Vec2,
+        cursor_state: &CursorState,
+        selection: &Vec<SelectedNode>,
+        clipped_primitives: &mut Vec<ClippedPrimitive>,
+    ) {
+        let parent_id = dom.tree.get_node_context(source).and_then(|node| node.parent_id);
+        let inherited = PaintInherited::from_ancestors(dom, parent_id);
+        dom.traverse_tree_mut_with_parent_and_data(
+            source,
+            parent_id,
+            &inherited,
+            &mut |dom, id, parent_id, parent| {
+                self.collect_node_shapes(dom, id, parent_id, parent, cursor_state, selection)
+            },
         );
 
-        (clipped_primitives, texture_delta, &self.screen_descriptor)
+        let mut primitives = std::mem::take(&mut self.scratch_primitives);
+        self.tessellate_shapes(&mut primitives);
+        for mut primitive in primitives.drain(..) {
+            // paint callbacks draw at a fixed rect
+            let Primitive::Mesh(mesh) = &mut primitive.primitive else {
+                continue;
+            };
+            mesh.translate(offset);
+            for vertex in &mut mesh.vertices {
+                vertex.color = vertex.color.gamma_multiply(DRAG_GHOST_OPACITY);
+            }
+            primitive.clip_rect = primitive.clip_rect.translate(offset);
+            clipped_primitives.push(primitive);
+        }
+        self.scratch_primitives = primitives;
+    }
+
+    /// Hands the primitives of a painted frame back, so the next frame reuses their allocation and the vertices and
+    /// indices of their meshes. Optional, without it every frame allocates them again
+    pub fn recycle(&mut self, mut primitives: Vec<ClippedPrimitive>) {
+        let meshes = primitives.len();
+        self.recycle_meshes(&mut primitives);
+        // a frame worth of meshes is enough, the pool doesn't keep the largest frame ever painted
+        self.mesh_pool.truncate(meshes);
+        if primitives.capacity() > self.recycled_primitives.capacity() {
+            self.recycled_primitives = primitives;
+        }
+    }
+
+    /// Empties ``primitives``, their meshes go to the pool
+    fn recycle_meshes(&mut self, primitives: &mut Vec<ClippedPrimitive>) {
+        for primitive in primitives.drain(..) {
+            if let Primitive::Mesh(mut mesh) = primitive.primitive {
+                mesh.vertices.clear();
+                mesh.indices.clear();
+                self.mesh_pool.push(mesh);
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all, name = "Renderer::tessellate_shapes")]
+    fn tessellate_shapes(&mut self, clipped_primitives: &mut Vec<ClippedPrimitive>) {
+        let mut shapes = std::mem::take(&mut self.shapes);
+        for ClippedShape { clip_rect, shape } in shapes.drain(..) {
+            self.tessellate_clipped_shape(clip_rect, shape, clipped_primitives);
+        }
+        self.shapes = shapes;
+    }
+
+    /// Like [`Tessellator::tessellate_clipped_shape`], except that new meshes are taken from the pool
+    fn tessellate_clipped_shape(&mut self, clip_rect: Rect, shape: Shape, out: &mut Vec<ClippedPrimitive>) {
+        if !clip_rect.is_positive() {
+            return;
+        }
+        let shape = match shape {
+            Shape::Vec(shapes) => {
+                for shape in shapes {
+                    self.tessellate_clipped_shape(clip_rect, shape, out);
+                }
+                return;
+            }
+            Shape::Callback(callback) => {
+                out.push(ClippedPrimitive { clip_rect, primitive: Primitive::Callback(callback) });
+                return;
+            }
+            shape => shape,
+        };
+
+        // shapes with the same clip and texture end up in the same mesh
+        let texture_id = shape.texture_id();
+        let continues_mesh = out.last().is_some_and(|last| {
+            last.clip_rect == clip_rect && matches!(&last.primitive, Primitive::Mesh(mesh) if mesh.texture_id == texture_id)
+        });
+        if !continues_mesh {
+            let mut mesh = self.mesh_pool.pop().unwrap_or_default();
+            mesh.texture_id = texture_id;
+            out.push(ClippedPrimitive { clip_rect, primitive: Primitive::Mesh(mesh) });
+        }
+        let Some(ClippedPrimitive { primitive: Primitive::Mesh(mesh), .. }) = out.last_mut() else {
+            unreachable!("a mesh was pushed above");
+        };
+        self.tessellator.set_clip_rect(clip_rect);
+        self.tessellator.tessellate_shape(shape, mesh);
     }
 
     pub fn get_scrollbar_rect(
@@ -692,43 +1793,48 @@ impl Renderer {
         bar_width: f32,
         horizontal: bool,
         are_both_scrollbars_visible: bool,
-        hovered: bool,
-        thumb_hovered: bool,
+        cursor_state: &CursorState,
     ) -> (ClippedShape, ClippedShape) {
-        let styling = &node.styling;
+        let scrollbar = &node.styling.scrollbar;
+        let track_rect = self.get_scrollbar_rect(node, bar_width, horizontal, are_both_scrollbars_visible);
+        let thumb_rect = self.get_scroll_thumb_rect(node, layout, bar_width, horizontal, are_both_scrollbars_visible);
+
+        // active while the press that is still held started on it
+        let is_pressed = cursor_state.drag_end_position.is_none();
+        let pressed_in = |rect: Rect| {
+            is_pressed && cursor_state.drag_start_position.is_some_and(|start| rect.contains(start))
+        };
+        let state_color = |rect: Rect, color: Color32, hovered: Option<Color32>, active: Option<Color32>| {
+            match (pressed_in(rect), rect.contains(cursor_state.current_position)) {
+                (true, _) => active.or(hovered).unwrap_or(color),
+                (false, true) => hovered.unwrap_or(color),
+                (false, false) => color,
+            }
+        };
 
         let container_shape = epaint::Shape::Rect(epaint::RectShape {
-            rect: self.get_scrollbar_rect(node, bar_width, horizontal, are_both_scrollbars_visible),
+            rect: track_rect,
             rounding: epaint::Rounding::ZERO,
-            fill: if hovered {
-                styling.scrollbar.background_color_hovered
-            } else {
-                styling.scrollbar.background_color
-            },
+            fill: state_color(
+                track_rect,
+                scrollbar.background_color,
+                scrollbar.background_color_hovered,
+                scrollbar.background_color_active,
+            ),
             stroke: epaint::Stroke::NONE,
             fill_texture_id: TextureId::default(),
             uv: epaint::Rect::from_min_max(WHITE_UV, WHITE_UV),
         });
 
         let button_shape = epaint::Shape::Rect(epaint::RectShape {
-            rect: self.get_scroll_thumb_rect(
-                node,
-                layout,
-                bar_width,
-                horizontal,
-                are_both_scrollbars_visible,
+            rect: thumb_rect,
+            rounding: epaint::Rounding::same(scrollbar.thumb_radius),
+            fill: state_color(
+                thumb_rect,
+                scrollbar.thumb_color,
+                scrollbar.thumb_color_hovered,
+                scrollbar.thumb_color_active,
             ),
-            rounding: epaint::Rounding {
-                ne: 100.0,
-                nw: 100.0,
-                se: 100.0,
-                sw: 100.0,
-            },
-            fill: if thumb_hovered {
-                styling.scrollbar.thumb_color_hovered
-            } else {
-                styling.scrollbar.thumb_color
-            },
             stroke: epaint::Stroke::NONE,
             fill_texture_id: TextureId::default(),
             uv: epaint::Rect::from_min_max(WHITE_UV, WHITE_UV),
@@ -800,19 +1906,51 @@ impl Renderer {
 
         let parent_clip: Rect = parent.computed.rect;
 
-        let galley = self.fonts.layout(
-            node.attrs.get("value").unwrap().clone().to_string(),
-            parent.styling.text.font.clone(),
-            parent.styling.text.color,
-            selected_node.computed_rect_when_selected.size().x + 1.0,
+        let direction = parent.styling.text.direction;
+        let text = bidi::shape(
+            self.text_shaper.as_deref(),
+            node.attrs.get("value").unwrap().to_string(),
+            direction,
+        );
+        let galley = bidi::reorder(
+            self.fonts.layout(
+                text,
+                parent.styling.text.font.clone(),
+                parent.styling.text.color,
+                selected_node.computed_rect_when_selected.size().x + 1.0,
+            ),
+            direction,
         );
 
         let min = selected_node.start_cursor.rcursor;
         let max = selected_node.end_cursor.rcursor;
 
+        let selection_rect = |left: f32, right: f32, row: &epaint::text::Row| ClippedShape {
+            clip_rect: parent_clip,
+            shape: epaint::Shape::Rect(epaint::RectShape {
+                rect: Rect::from_min_max(
+                    node.computed.rect.min + vec2(left, row.min_y()),
+                    node.computed.rect.min + vec2(right, row.max_y()),
+                ),
+                rounding: epaint::Rounding::ZERO,
+                fill: parent.styling.text.selection_color,
+                stroke: epaint::Stroke::default(),
+                fill_texture_id: TextureId::default(),
+                uv: epaint::Rect::from_min_max(WHITE_UV, WHITE_UV),
+            }),
+        };
+
         let mut shapes = vec![];
         for ri in min.row..=max.row {
             let row = &galley.rows[ri];
+            let start = if ri == min.row { min.column } else { 0 };
+            let end = if ri == max.row { max.column } else { row.glyphs.len() };
+            // runs in both directions can split the selection of a row into several rects
+            if let Some(spans) = bidi::selection_spans(&galley, direction, ri, start..end) {
+                shapes.extend(spans.into_iter().map(|span| selection_rect(span.start, span.end, row)));
+                continue;
+            }
+
             let left = if ri == min.row {
                 row.x_offset(min.column)
             } else {
@@ -828,21 +1966,7 @@ impl Renderer {
                 };
                 row.rect.right() + newline_size
             };
-            let rect = Rect::from_min_max(
-                node.computed.rect.min + vec2(left, row.min_y()),
-                node.computed.rect.min + vec2(right, row.max_y()),
-            );
-            shapes.push(ClippedShape {
-                clip_rect: parent_clip,
-                shape: epaint::Shape::Rect(epaint::RectShape {
-                    rect,
-                    rounding: epaint::Rounding::ZERO,
-                    fill: parent.styling.text.selection_color,
-                    stroke: epaint::Stroke::default(),
-                    fill_texture_id: TextureId::default(),
-                    uv: epaint::Rect::from_min_max(WHITE_UV, WHITE_UV),
-                }),
-            });
+            shapes.push(selection_rect(left, right, row));
         }
 
         shapes
@@ -853,17 +1977,20 @@ impl Renderer {
         node: &NodeContext,
         text_shape: &epaint::TextShape,
         cursor_pos: usize,
+        prefer_next_row: bool,
     ) -> ClippedShape {
-        let rect = text_shape
-            .galley
-            .pos_from_cursor(&epaint::text::cursor::Cursor {
+        let rect = bidi::pos_from_cursor(
+            &text_shape.galley,
+            node.styling.text.direction,
+            &epaint::text::cursor::Cursor {
                 pcursor: epaint::text::cursor::PCursor {
                     paragraph: 0,
                     offset: cursor_pos,
-                    prefer_next_row: false,
+                    prefer_next_row,
                 },
                 ..Default::default()
-            });
+            },
+        );
 
         let mut rect = rect;
 
@@ -888,3 +2015,136 @@ impl Renderer {
         }
     }
 }
+
+/// Galleys built during a single layout pass, taffy measures the same text with the same constraints many times
+#[derive(Default)]
+struct MeasureCache {
+    no_wrap: FxHashMap<NodeId, Arc<Galley>>,
+    /// Keyed by the bits of the wrap width
+    wrapped: FxHashMap<(NodeId, u32), Arc<Galley>>,
+}
+
+/// Parses the ``{width}x{height}`` of the ``natural_size`` attribute
+fn parse_natural_size(size: &str) -> Option<[f32; 2]> {
+    let (width, height) = size.split_once('x')?;
+    let size = [width.trim().parse().ok()?, height.trim().parse().ok()?];
+    (size[0] > 0.0 && size[1] > 0.0).then_some(size)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn measure_function(
+    known_dimensions: taffy::geometry::Size<Option<f32>>,
+    available_space: taffy::geometry::Size<taffy::style::AvailableSpace>,
+    node_id: NodeId,
+    node_context: Option<&mut NodeContext>,
+    fonts: &Fonts,
+    text_shaper: Option<&dyn TextShaper>,
+    texture_manager: &TextureManager,
+    cache: &mut MeasureCache,
+    store_galley: bool,
+) -> Size<f32> {
+    if let Size {
+        width: Some(width),
+        height: Some(height),
+    } = known_dimensions
+    {
+        return Size { width, height };
+    }
+
+    if let Some(node) = &node_context {
+        if let Tag::Custom(custom) = &node.tag {
+            let definite = |space: taffy::style::AvailableSpace| match space {
+                taffy::style::AvailableSpace::Definite(size) => Some(size),
+                _ => None,
+            };
+            let input = MeasureInput {
+                known_width: known_dimensions.width,
+                known_height: known_dimensions.height,
+                available_width: definite(available_space.width),
+                available_height: definite(available_space.height),
+            };
+            if let Some(size) = custom.element().measure(ElementNode::new(node_id, node), input) {
+                return Size {
+                    width: known_dimensions.width.unwrap_or(size.x),
+                    height: known_dimensions.height.unwrap_or(size.y),
+                };
+            }
+        }
+    }
+
+    match node_context {
+        None => Size::ZERO,
+        Some(node_context) => match node_context.tag {
+            Tag::View | Tag::Button | Tag::Custom(_) => {
+                let natural_size = node_context
+                    .attrs
+                    .get("natural_size")
+                    .and_then(|size| parse_natural_size(size));
+                let [image_width, image_height] = match (natural_size, node_context.styling.texture_id) {
+                    (Some(size), _) => size,
+                    (None, Some(texture_id)) => {
+                        let meta = texture_manager.meta(texture_id).unwrap();
+                        // an atlas region only covers part of its page
+                        let uv = node_context.styling.texture_uv.map_or(epaint::vec2(1.0, 1.0), |uv| uv.size());
+                        [meta.size[0] as f32 * uv.x, meta.size[1] as f32 * uv.y]
+                    }
+                    (None, None) => return Size::ZERO,
+                };
+
+                match (known_dimensions.width, known_dimensions.height) {
+                    (Some(width), Some(height)) => Size { width, height },
+                    (Some(width), None) => Size {
+                        width,
+                        height: (width / image_width) * image_height,
+                    },
+                    (None, Some(height)) => Size {
+                        width: (height / image_height) * image_width,
+                        height,
+                    },
+                    (None, None) => Size {
+                        width: image_width,
+                        height: image_height,
+                    },
+                }
+            }
+            Tag::Text => {
+                let direction = node_context.styling.text.direction;
+                let text = node_context
+                    .attrs
+                    .get("value")
+                    .map(|value| value.to_string())
+                    .unwrap_or_default();
+                let text = bidi::shape(text_shaper, text, direction);
+                let font = &node_context.styling.text.font;
+                let color = node_context.styling.text.color;
+
+                let no_wrap = cache
+                    .no_wrap
+                    .entry(node_id)
+                    .or_insert_with(|| {
+                        bidi::reorder(fonts.layout_no_wrap(text.clone(), font.clone(), color), direction)
+                    })
+                    .clone();
+                let galley = match available_space.width {
+                    // wrapping only changes the galley when the text doesn't fit on one line
+                    AvailableSpace::Definite(space) if no_wrap.size().x > space => cache
+                        .wrapped
+                        .entry((node_id, space.to_bits()))
+                        .or_insert_with(|| bidi::reorder(fonts.layout(text, font.clone(), color, space), direction))
+                        .clone(),
+                    _ => no_wrap,
+                };
+
+                let size = galley.size();
+                if store_galley {
+                    node_context.computed.galley = Some(galley);
+                }
+
+                Size {
+                    width: size.x,
+                    height: size.y,
+                }
+            }
+        },
+    }
+}
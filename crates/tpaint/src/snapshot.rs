@@ -0,0 +1,71 @@
+use epaint::{textures::TexturesDelta, ClippedPrimitive, Mesh, Primitive, Rect, TextureId, Vertex};
+
+use crate::renderer::ScreenDescriptor;
+
+/// A mesh together with the rect it should be clipped to
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClippedMesh {
+    pub clip_rect: Rect,
+    pub texture_id: TextureId,
+    pub indices: Vec<u32>,
+    pub vertices: Vec<Vertex>,
+}
+
+/// Everything a backend needs to paint a single frame, detached from the renderer.
+/// With the ``serialize`` feature it can be sent to another process and replayed by any backend.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PaintSnapshot {
+    /// Size of the window in physical pixels
+    pub size_in_pixels: [u32; 2],
+    pub pixels_per_point: f32,
+    pub meshes: Vec<ClippedMesh>,
+    pub textures_delta: TexturesDelta,
+}
+
+impl PaintSnapshot {
+    pub fn new(
+        primitives: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        screen_descriptor: &ScreenDescriptor,
+    ) -> Self {
+        let meshes = primitives
+            .into_iter()
+            .filter_map(|clipped| match clipped.primitive {
+                Primitive::Mesh(mesh) => Some(ClippedMesh {
+                    clip_rect: clipped.clip_rect,
+                    texture_id: mesh.texture_id,
+                    indices: mesh.indices,
+                    vertices: mesh.vertices,
+                }),
+                Primitive::Callback(_) => {
+                    log::warn!("Paint callbacks can't be part of a snapshot, skipping");
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            size_in_pixels: [screen_descriptor.size.width, screen_descriptor.size.height],
+            pixels_per_point: screen_descriptor.pixels_per_point,
+            meshes,
+            textures_delta,
+        }
+    }
+
+    /// Turns the snapshot back into primitives that can be handed to a backend
+    pub fn to_clipped_primitives(&self) -> Vec<ClippedPrimitive> {
+        self.meshes
+            .iter()
+            .map(|mesh| ClippedPrimitive {
+                clip_rect: mesh.clip_rect,
+                primitive: Primitive::Mesh(Mesh {
+                    indices: mesh.indices.clone(),
+                    vertices: mesh.vertices.clone(),
+                    texture_id: mesh.texture_id,
+                }),
+            })
+            .collect()
+    }
+}
@@ -8,6 +8,9 @@ use taffy::geometry::Point;
 use taffy::prelude::*;
 use taffy::style::{Overflow, Style};
 
+use crate::bidi::TextDirection;
+use crate::filter::ColorFilter;
+
 type Colors = HashMap<&'static str, HashMap<&'static str, [u8; 4]>>;
 
 lazy_static! {
@@ -30,6 +33,8 @@ pub struct TextStyling {
     pub color: Color32,
     pub font: FontId,
     pub selection_color: Color32,
+    /// From the ``dir`` attribute of the node or its ancestors
+    pub direction: TextDirection,
 }
 
 impl Default for TextStyling {
@@ -41,44 +46,275 @@ impl Default for TextStyling {
                 family: FontFamily::default(),
             },
             selection_color: Color32::from_rgb(191, 219, 254),
+            direction: TextDirection::Auto,
         }
     }
 }
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct ScrollbarStyling {
+    /// ``scrollbar-track-{color}``
     pub background_color: Color32,
-    pub background_color_hovered: Color32,
+    /// ``scrollbar-track-hover-{color}``, falls back to the normal color
+    pub background_color_hovered: Option<Color32>,
+    /// ``scrollbar-track-active-{color}``, while the track is pressed
+    pub background_color_active: Option<Color32>,
+    /// ``scrollbar-thumb-{color}``
     pub thumb_color: Color32,
-    pub thumb_color_hovered: Color32,
+    /// ``scrollbar-thumb-hover-{color}``
+    pub thumb_color_hovered: Option<Color32>,
+    /// ``scrollbar-thumb-active-{color}``, while the thumb is pressed
+    pub thumb_color_active: Option<Color32>,
+    /// ``scrollbar-thumb-rounded-{n}``, fully round by default
+    pub thumb_radius: f32,
 }
 
 impl Default for ScrollbarStyling {
     fn default() -> Self {
         Self {
             background_color: Color32::BLACK,
-            background_color_hovered: Color32::BLACK,
+            background_color_hovered: None,
+            background_color_active: None,
             thumb_color: Color32::DARK_GRAY,
-            thumb_color_hovered: Color32::GRAY,
+            thumb_color_hovered: Some(Color32::GRAY),
+            thumb_color_active: None,
+            thumb_radius: 100.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BackgroundSize {
+    /// The size of the texture in pixels, as points
+    #[default]
+    Auto,
+    /// Scaled to fill the whole node, parts of the texture may be cut off
+    Cover,
+    /// Scaled to fit inside the node
+    Contain,
+}
+
+/// Sizes taffy doesn't know about, resolved by measuring the node before every layout
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IntrinsicSize {
+    /// ``w-min``, as small as the content can get, e.g. the longest word
+    Min,
+    /// ``w-max``, the content without any wrapping
+    Max,
+    /// ``w-fit``, the available space clamped between the min and max content sizes
+    Fit,
+}
+
+/// What happens when a scroll container is scrolled past one of its ends
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Overscroll {
+    /// ``overscroll-auto``, the rest of the scroll goes to the scroll container around it
+    #[default]
+    Auto,
+    /// ``overscroll-contain``, the scroll stops here, the rubber band still shows
+    Contain,
+    /// ``overscroll-none``, the scroll stops here without a rubber band
+    None,
+}
+
+/// Where a node and its children are painted, every layer is painted over the ones before it and hit tested
+/// before them. Within a layer nodes keep the order of the tree
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum ZLayer {
+    /// ``z-base``, the tree as it is
+    #[default]
+    Base,
+    /// ``z-sticky``, headers and toolbars over the content they scroll with, still clipped by their scroll container
+    Sticky,
+    /// ``z-overlay``, modals, menus and popovers, no longer clipped by scroll containers
+    Overlay,
+    /// ``z-toast``, notifications over the overlays
+    Toast,
+    /// ``z-tooltip``
+    Tooltip,
+    /// The ghost of a drag is painted here, over everything else, see [`crate::DragPayload`]
+    DragGhost,
+}
+
+impl ZLayer {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Base => "base",
+            Self::Sticky => "sticky",
+            Self::Overlay => "overlay",
+            Self::Toast => "toast",
+            Self::Tooltip => "tooltip",
+            Self::DragGhost => "drag-ghost",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "base" => Some(Self::Base),
+            "sticky" => Some(Self::Sticky),
+            "overlay" => Some(Self::Overlay),
+            "toast" => Some(Self::Toast),
+            "tooltip" => Some(Self::Tooltip),
+            // reserved for the renderer
+            _ => None,
         }
     }
 }
 
+/// Animations the renderer plays by itself every frame, so they don't need the app to re-render.
+/// Inside of a ``layer`` they only move when the layer gets repainted
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Animation {
+    /// ``animate-spin``, an arc in the text color that turns around the center of the node
+    Spin,
+    /// ``animate-indeterminate``, a bar in the text color that slides across the node over and over
+    Indeterminate,
+}
+
+impl IntrinsicSize {
+    fn parse(class: &str) -> Option<Self> {
+        match class {
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "fit" => Some(Self::Fit),
+            _ => None,
+        }
+    }
+}
+
+/// How the ``bg-image`` attribute is drawn
+#[derive(Clone, PartialEq, Debug)]
+pub struct BackgroundStyling {
+    pub size: BackgroundSize,
+    pub repeat: bool,
+    /// Where the image is anchored, from 0.0 (left / top) to 1.0 (right / bottom)
+    pub position: epaint::Vec2,
+}
+
+impl Default for BackgroundStyling {
+    fn default() -> Self {
+        Self {
+            size: BackgroundSize::Auto,
+            repeat: true,
+            position: epaint::Vec2::ZERO,
+        }
+    }
+}
+
+/// Same as the default of tailwind's ``transition`` classes
+const DEFAULT_TRANSITION_DURATION: f32 = 0.15;
+
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct TailwindCache {
     pub class: Option<Arc<str>>,
     pub state: StyleState,
-    pub texture_id: Option<epaint::TextureId>,
+    /// The texture and atlas region are parsed from it
+    pub src: Option<Arc<str>>,
+    /// The margin the ``space-*`` classes of the parent give this node
+    pub spacing: epaint::Vec2,
+    /// The ``style`` attribute, applied over the classes
+    pub inline_style: Option<Arc<str>>,
+    pub direction: TextDirection,
 }
 
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct Tailwind {
     pub cache: TailwindCache,
     pub texture_id: Option<epaint::TextureId>,
+    /// The atlas region of the texture, all of it when ``None``
+    pub texture_uv: Option<epaint::Rect>,
     pub background_color: Color32,
+    pub background: BackgroundStyling,
     pub border: Border,
     pub text: TextStyling,
     pub scrollbar: ScrollbarStyling,
+    /// Whether the subtree should be painted to a texture once and re-used until it changes
+    pub layer: bool,
+    /// Applies to the node and all of its children
+    pub filter: ColorFilter,
+    /// Standard deviation in points of the blur behind the node, see [`crate::BackdropBlur`]
+    pub backdrop_blur: f32,
+    /// Where the node goes between its siblings, see [`crate::dom::Dom::apply_child_order`]
+    pub order: i32,
+    /// Margin put between the children by ``space-x-*`` and ``space-y-*``, negative values make them overlap
+    pub space: epaint::Vec2,
+    /// ``w-min``, ``w-max`` or ``w-fit``
+    pub intrinsic_width: Option<IntrinsicSize>,
+    /// ``h-min``, ``h-max`` or ``h-fit``
+    pub intrinsic_height: Option<IntrinsicSize>,
+    /// ``transition-layout`` animates the node and its children from their old rect to the new one
+    pub layout_transition: bool,
+    /// In seconds, from ``duration-{ms}``
+    pub transition_duration: f32,
+    pub animation: Option<Animation>,
+    pub overscroll: Overscroll,
+    /// ``overflow-anchor-none``, the scroll offset stays put when content above the viewport changes size
+    pub scroll_anchor_disabled: bool,
+    /// ``pointer-events-none`` when false, ``pointer-events-auto`` when true, inherited from the parent when ``None``
+    pub pointer_events: Option<bool>,
+    /// ``invisible``, the node keeps its place in the layout but it and its children aren't painted or hit
+    pub invisible: bool,
+    /// ``z-overlay`` and the other layers, the node and its children are painted in the layer of their parent when ``None``
+    pub z_layer: Option<ZLayer>,
+    /// Moves the node and its children after layout, from ``translate`` in the ``style`` attribute
+    pub translate: epaint::Vec2,
+    /// Classes the parser didn't understand, see [`crate::dom::Dom::show_unknown_classes`]
+    pub unknown_classes: Vec<Arc<str>>,
+}
+
+/// A texture stretched as a 3x3 grid so the corners keep their size, from the ``border-image`` attribute
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BorderImage {
+    pub texture_id: epaint::TextureId,
+    /// Insets of the corners as top, right, bottom and left, in pixels of the texture and points on screen
+    pub slice: [f32; 4],
+}
+
+impl BorderImage {
+    /// Parses ``texture://{id} {insets}``, where the insets follow the css shorthand of one to four values
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split_whitespace();
+        let texture_id = parse_texture_id(parts.next()?)?;
+        let insets = parts
+            .map(|inset| inset.parse::<f32>().ok().filter(|inset| *inset >= 0.0))
+            .collect::<Option<Vec<_>>>()?;
+        let slice = match insets[..] {
+            [all] => [all; 4],
+            [vertical, horizontal] => [vertical, horizontal, vertical, horizontal],
+            [top, horizontal, bottom] => [top, horizontal, bottom, horizontal],
+            [top, right, bottom, left] => [top, right, bottom, left],
+            _ => {
+                log::error!("border-image takes one to four insets: {}", value);
+                return None;
+            }
+        };
+        Some(Self { texture_id, slice })
+    }
+}
+
+/// Texture ids are passed around as ``texture://{id}``, regions of an atlas page add their uv as
+/// ``texture://{id}#{min_u},{min_v},{max_u},{max_v}``, see [`crate::atlas::AtlasRegion::src`]
+pub(crate) fn parse_texture_id(src: &str) -> Option<epaint::TextureId> {
+    let id = src.strip_prefix("texture://")?;
+    let id = id.split_once('#').map_or(id, |(id, _)| id);
+    let Ok(id) = id.parse::<u64>() else {
+        log::error!("Failed to parse texture id: {}", id);
+        return None;
+    };
+    Some(epaint::TextureId::Managed(id))
+}
+
+/// The part of the texture an atlas region covers, ``None`` for all of it
+pub(crate) fn parse_texture_uv(src: &str) -> Option<epaint::Rect> {
+    let (_, uv) = src.split_once('#')?;
+    let uv: Vec<f32> = uv.split(',').map(|value| value.parse().ok()).collect::<Option<_>>()?;
+    match uv[..] {
+        [min_u, min_v, max_u, max_v] => Some(epaint::Rect::from_min_max(
+            epaint::pos2(min_u, min_v),
+            epaint::pos2(max_u, max_v),
+        )),
+        _ => None,
+    }
 }
 
 #[derive(Default, Clone, Copy, PartialEq, Debug)]
@@ -86,23 +322,76 @@ pub struct StyleState {
     pub hovered: bool,
     pub focused: bool,
     pub active: bool,
+    pub disabled: bool,
+    /// An ancestor with the ``group`` class is hovered
+    pub group_hovered: bool,
+}
+
+impl StyleState {
+    /// The classes of ``class`` that apply in this state, variants like ``hover:`` lose their prefix
+    pub fn active_classes<'c>(&self, class: &'c str) -> Vec<&'c str> {
+        class
+            .split_whitespace()
+            .filter_map(|class| {
+                let variants = [
+                    ("hover:", self.hovered),
+                    ("focus:", self.focused),
+                    ("active:", self.active),
+                    ("disabled:", self.disabled),
+                    ("group-hover:", self.group_hovered),
+                ];
+                match variants.into_iter().find_map(|(variant, on)| Some((class.strip_prefix(variant)?, on))) {
+                    Some((class, true)) => Some(class),
+                    Some((_, false)) => None,
+                    None => Some(class),
+                }
+            })
+            .collect()
+    }
 }
 
 impl Tailwind {
     pub fn set_styling(&mut self, class: &str, state: &StyleState) -> Style {
         // todo: perhaps find a way to this lazily
         self.background_color = Default::default();
+        self.background = Default::default();
         self.border = Default::default();
         self.text = Default::default();
+        self.scrollbar = Default::default();
+        self.layer = false;
+        self.filter = Default::default();
+        self.backdrop_blur = 0.0;
+        self.order = 0;
+        self.space = epaint::Vec2::ZERO;
+        self.intrinsic_width = None;
+        self.intrinsic_height = None;
+        self.layout_transition = false;
+        self.transition_duration = DEFAULT_TRANSITION_DURATION;
+        self.animation = None;
+        self.overscroll = Overscroll::Auto;
+        self.scroll_anchor_disabled = false;
+        self.pointer_events = None;
+        self.invisible = false;
+        self.z_layer = None;
+        self.translate = epaint::Vec2::ZERO;
 
         self.get_style(class, state)
     }
 
     pub fn get_style(&mut self, class: &str, state: &StyleState) -> Style {
         let mut layout_style = Style::default();
+        self.unknown_classes.clear();
 
         for class in class.split_whitespace() {
-            self.handle_class(&mut layout_style, &COLORS, class);
+            let known = self.handle_class(&mut layout_style, &COLORS, class);
+            // variants only apply in some states, they're checked on a scratch copy so typos show up right away
+            let known = match strip_variant(class) {
+                Some(class) => Tailwind::default().handle_class(&mut Style::default(), &COLORS, class),
+                None => known,
+            };
+            if !known {
+                self.unknown_classes.push(class.into());
+            }
         }
 
         for class in class.split_whitespace() {
@@ -121,39 +410,83 @@ impl Tailwind {
                     self.handle_class(&mut layout_style, &COLORS, class);
                 }
             }
+            if state.disabled {
+                if let Some(class) = class.strip_prefix("disabled:") {
+                    self.handle_class(&mut layout_style, &COLORS, class);
+                }
+            }
+            if state.group_hovered {
+                if let Some(class) = class.strip_prefix("group-hover:") {
+                    self.handle_class(&mut layout_style, &COLORS, class);
+                }
+            }
         }
 
         layout_style
     }
 
+    /// Applies the ``style`` attribute over what the classes set, e.g. ``width: 120; translate: 4 -8``.
+    /// Meant for values that change continuously, like the size of a panel that is being dragged
+    pub fn apply_inline_style(&mut self, inline_style: &str, style: &mut Style) {
+        for declaration in inline_style.split(';').map(str::trim).filter(|declaration| !declaration.is_empty()) {
+            let applied = declaration.split_once(':').and_then(|(property, value)| {
+                let value = value.trim();
+                match property.trim() {
+                    "width" => style.size.width = parse_inline_dimension(value)?,
+                    "height" => style.size.height = parse_inline_dimension(value)?,
+                    "min-width" => style.min_size.width = parse_inline_dimension(value)?,
+                    "min-height" => style.min_size.height = parse_inline_dimension(value)?,
+                    "max-width" => style.max_size.width = parse_inline_dimension(value)?,
+                    "max-height" => style.max_size.height = parse_inline_dimension(value)?,
+                    "translate" => {
+                        let mut parts = value.split_whitespace().map(parse_inline_length);
+                        let x = parts.next()??;
+                        let y = parts.next().unwrap_or(Some(0.0))?;
+                        self.translate = epaint::vec2(x, y);
+                    }
+                    "background-color" => {
+                        self.background_color = parse_hex_color(value).or_else(|| handle_color(value, &COLORS))?
+                    }
+                    _ => return None,
+                }
+                Some(())
+            });
+            if applied.is_none() {
+                log::warn!(target: "tpaint::class", "Invalid style declaration: {}", declaration);
+            }
+        }
+    }
+
     pub fn set_texture(&mut self, src: &str) {
-        // check texture:// prefix, meaning it's a texture id
-        if let Some(src) = src.strip_prefix("texture://") {
-            let Ok(id) = src.parse::<u64>() else {
-                log::error!("Failed to parse texture id: {}", src);
-                return;
-            };
-            self.texture_id = Some(epaint::TextureId::Managed(id));
-            return;
+        if let Some(texture_id) = parse_texture_id(src) {
+            self.texture_id = Some(texture_id);
+            self.texture_uv = parse_texture_uv(src);
         }
     }
 
-    fn handle_class(&mut self, style: &mut Style, colors: &Colors, class: &str) {
+    /// Returns whether the class is one the parser knows, classes are allowed to match more than one branch
+    fn handle_class(&mut self, style: &mut Style, colors: &Colors, class: &str) -> bool {
+        let mut known = false;
+
         if class == "flex-col" {
+            known = true;
             style.display = Display::Flex;
             style.flex_direction = FlexDirection::Column;
         }
 
         if class == "flex-row" {
+            known = true;
             style.display = Display::Flex;
             style.flex_direction = FlexDirection::Row;
         }
 
         if class == "grid" {
+            known = true;
             style.display = Display::Grid;
         }
 
         if let Some(class) = class.strip_prefix("grid-cols-") {
+            known |= class.parse::<usize>().is_ok();
             style.grid_template_columns = Vec::new();
             for _ in 0..class.parse::<usize>().unwrap_or(0) {
                 style.grid_template_columns.push(fr(1.0));
@@ -161,6 +494,7 @@ impl Tailwind {
         }
 
         if let Some(class) = class.strip_prefix("col-span-") {
+            known |= class.parse::<u16>().is_ok();
             let span = class.parse::<u16>().unwrap_or(0);
 
             style.grid_column = Line {
@@ -170,6 +504,7 @@ impl Tailwind {
         }
 
         if let Some(class) = class.strip_prefix("row-span-") {
+            known |= class.parse::<u16>().is_ok();
             let span = class.parse::<u16>().unwrap_or(0);
 
             style.grid_row = Line {
@@ -190,67 +525,106 @@ impl Tailwind {
                 }
                 _ => {}
             }
+            known |= matches!(class, "wrap" | "wrap-reverse" | "nowrap" | "none");
         }
 
         if class == "shrink" {
+            known = true;
             style.flex_shrink = 1.0;
         } else if class == "shrink-0" {
+            known = true;
             style.flex_shrink = 0.0;
         }
 
         if class == "grow" {
+            known = true;
             style.flex_grow = 1.0;
         } else if class == "grow-0" {
+            known = true;
             style.flex_grow = 0.0;
         }
 
         if let Some(class) = class.strip_prefix("basis-") {
+            known |= is_size(class);
             style.flex_basis = handle_size(class);
         }
 
         if let Some(class) = class.strip_prefix("w-") {
-            style.size.width = handle_size(class);
+            self.intrinsic_width = IntrinsicSize::parse(class);
+            known |= self.intrinsic_width.is_some() || is_size(class);
+            style.size.width = match self.intrinsic_width {
+                Some(_) => Dimension::AUTO,
+                None => handle_size(class),
+            };
         }
 
         if let Some(class) = class.strip_prefix("h-") {
-            style.size.height = handle_size(class);
+            self.intrinsic_height = IntrinsicSize::parse(class);
+            known |= self.intrinsic_height.is_some() || is_size(class);
+            style.size.height = match self.intrinsic_height {
+                Some(_) => Dimension::AUTO,
+                None => handle_size(class),
+            };
         }
 
         if let Some(class) = class.strip_prefix("min-w-") {
+            known |= is_size(class);
             style.min_size.width = handle_size(class);
         }
 
         if let Some(class) = class.strip_prefix("min-h-") {
+            known |= is_size(class);
             style.min_size.height = handle_size(class);
         }
 
         if let Some(class) = class.strip_prefix("max-w-") {
+            known |= is_size(class);
             style.max_size.width = handle_size(class);
         }
 
         if let Some(class) = class.strip_prefix("max-h-") {
+            known |= is_size(class);
             style.max_size.height = handle_size(class);
         }
 
         if let Some(class) = class.strip_prefix("bg-") {
             if let Some(color) = handle_color(class, colors) {
                 self.background_color = color;
+                known = true;
+            }
+
+            match class {
+                "auto" => self.background.size = BackgroundSize::Auto,
+                "cover" => self.background.size = BackgroundSize::Cover,
+                "contain" => self.background.size = BackgroundSize::Contain,
+                "repeat" => self.background.repeat = true,
+                "no-repeat" => self.background.repeat = false,
+                _ => {}
+            }
+            known |= matches!(class, "auto" | "cover" | "contain" | "repeat" | "no-repeat");
+
+            if let Some(position) = handle_background_position(class) {
+                self.background.position = position;
+                known = true;
             }
         }
 
         if let Some(class) = class.strip_prefix("text-") {
             if let Some(color) = handle_color(class, colors) {
                 self.text.color = color;
+                known = true;
             }
 
             if let Ok(size) = class.parse::<f32>() {
                 self.text.font.size = size;
+                known = true;
             }
         }
 
         if let Some(class) = class.strip_prefix("selection-") {
             if let Some(color) = handle_color(class, colors) {
                 self.text.selection_color = color;
+                known = true;
             }
         }
 
@@ -259,10 +633,12 @@ impl Tailwind {
                 "sans" => FontFamily::Proportional,
                 "mono" => FontFamily::Monospace,
                 _ => FontFamily::default(),
-            }
+            };
+            known |= matches!(class, "sans" | "mono");
         }
 
         if let Some(class) = class.strip_prefix("p-") {
+            known |= class.parse::<f32>().is_ok();
             let padding = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.padding = Rect {
                 top: padding,
@@ -273,38 +649,45 @@ impl Tailwind {
         }
 
         if let Some(class) = class.strip_prefix("py-") {
+            known |= class.parse::<f32>().is_ok();
             let padding = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.padding.top = padding;
             style.padding.bottom = padding;
         }
 
         if let Some(class) = class.strip_prefix("px-") {
+            known |= class.parse::<f32>().is_ok();
             let padding = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.padding.left = padding;
             style.padding.right = padding;
         }
 
         if let Some(class) = class.strip_prefix("pt-") {
+            known |= class.parse::<f32>().is_ok();
             let padding = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.padding.top = padding;
         }
 
         if let Some(class) = class.strip_prefix("pb-") {
+            known |= class.parse::<f32>().is_ok();
             let padding = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.padding.bottom = padding;
         }
 
         if let Some(class) = class.strip_prefix("pl-") {
+            known |= class.parse::<f32>().is_ok();
             let padding = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.padding.left = padding;
         }
 
         if let Some(class) = class.strip_prefix("pr-") {
+            known |= class.parse::<f32>().is_ok();
             let padding = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.padding.right = padding;
         }
 
         if let Some(class) = class.strip_prefix("m-") {
+            known |= class.parse::<f32>().is_ok();
             let margin = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
             style.margin = Rect {
                 top: margin,
@@ -315,67 +698,85 @@ impl Tailwind {
         }
 
         if let Some(class) = class.strip_prefix("my-") {
+            known |= class.parse::<f32>().is_ok();
             let margin = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
             style.margin.top = margin;
             style.margin.bottom = margin;
         }
 
         if let Some(class) = class.strip_prefix("mx-") {
+            known |= class.parse::<f32>().is_ok();
             let margin = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
             style.margin.left = margin;
             style.margin.right = margin;
         }
 
         if let Some(class) = class.strip_prefix("mt-") {
+            known |= class.parse::<f32>().is_ok();
             let margin = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
             style.margin.top = margin;
         }
 
         if let Some(class) = class.strip_prefix("mb-") {
+            known |= class.parse::<f32>().is_ok();
             let margin = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
             style.margin.bottom = margin;
         }
 
         if let Some(class) = class.strip_prefix("ml-") {
+            known |= class.parse::<f32>().is_ok();
             let margin = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
             style.margin.left = margin;
         }
 
         if let Some(class) = class.strip_prefix("mr-") {
+            known |= class.parse::<f32>().is_ok();
             let margin = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
             style.margin.right = margin;
         }
 
+        // ``-mt-4`` pulls the node over whatever is above it
+        if let Some(class) = class.strip_prefix('-') {
+            known |= handle_negative_margin(style, class);
+        }
+
         if let Some(class) = class.strip_prefix("rounded-") {
             if let Ok(value) = class.parse::<f32>() {
+                known = true;
                 self.border.radius.ne = value;
                 self.border.radius.nw = value;
                 self.border.radius.se = value;
                 self.border.radius.sw = value;
             } else {
                 if let Some(class) = class.strip_prefix("tl-") {
+                    known |= class.parse::<f32>().is_ok();
                     self.border.radius.nw = class.parse::<f32>().unwrap_or(0.0);
                 }
 
                 if let Some(class) = class.strip_prefix("tr-") {
+                    known |= class.parse::<f32>().is_ok();
                     self.border.radius.ne = class.parse::<f32>().unwrap_or(0.0);
                 }
 
                 if let Some(class) = class.strip_prefix("bl-") {
+                    known |= class.parse::<f32>().is_ok();
                     self.border.radius.sw = class.parse::<f32>().unwrap_or(0.0);
                 }
 
                 if let Some(class) = class.strip_prefix("br-") {
+                    known |= class.parse::<f32>().is_ok();
                     self.border.radius.se = class.parse::<f32>().unwrap_or(0.0);
                 }
 
                 // t and b
                 if let Some(class) = class.strip_prefix("t-") {
+                    known |= class.parse::<f32>().is_ok();
                     self.border.radius.ne = class.parse::<f32>().unwrap_or(0.0);
                     self.border.radius.nw = class.parse::<f32>().unwrap_or(0.0);
                 }
 
                 if let Some(class) = class.strip_prefix("b-") {
+                    known |= class.parse::<f32>().is_ok();
                     self.border.radius.se = class.parse::<f32>().unwrap_or(0.0);
                     self.border.radius.sw = class.parse::<f32>().unwrap_or(0.0);
                 }
@@ -385,23 +786,54 @@ impl Tailwind {
         if let Some(class) = class.strip_prefix("border-") {
             if let Some(color) = handle_color(class, colors) {
                 self.border.color = color;
+                known = true;
             } else {
-                let value = class.parse::<f32>().unwrap_or(0.0);
-                self.border.width = value;
+                let value = class.parse::<f32>();
+                known |= value.is_ok();
+                self.border.width = value.unwrap_or(0.0);
             }
         }
 
-        if let Some(class) = class.strip_prefix("justify-") {
-            style.justify_content = Some(match class {
-                "start" => JustifyContent::Start,
-                "end" => JustifyContent::End,
-                "center" => JustifyContent::Center,
-                "between" => JustifyContent::SpaceBetween,
-                "around" => JustifyContent::SpaceAround,
-                "evenly" => JustifyContent::SpaceEvenly,
-                "stretch" => JustifyContent::Stretch,
-                _ => panic!("Unknown justify content {class}"),
-            })
+        if let Some(class) = class.strip_prefix("justify-items-") {
+            match handle_grid_align(class) {
+                Some(align) => {
+                    style.justify_items = Some(align);
+                    known = true;
+                }
+                None => debug!("Unknown justify items {class}"),
+            }
+        } else if let Some(class) = class.strip_prefix("justify-self-") {
+            match class {
+                "auto" => {
+                    style.justify_self = None;
+                    known = true;
+                }
+                _ => match handle_grid_align(class) {
+                    Some(align) => {
+                        style.justify_self = Some(align);
+                        known = true;
+                    }
+                    None => debug!("Unknown justify self {class}"),
+                },
+            }
+        } else if let Some(class) = class.strip_prefix("justify-") {
+            let justify = match class {
+                "start" => Some(JustifyContent::Start),
+                "end" => Some(JustifyContent::End),
+                "center" => Some(JustifyContent::Center),
+                "between" => Some(JustifyContent::SpaceBetween),
+                "around" => Some(JustifyContent::SpaceAround),
+                "evenly" => Some(JustifyContent::SpaceEvenly),
+                "stretch" => Some(JustifyContent::Stretch),
+                _ => None,
+            };
+            match justify {
+                Some(justify) => {
+                    style.justify_content = Some(justify);
+                    known = true;
+                }
+                None => debug!("Unknown justify content {class}"),
+            }
         }
 
         if let Some(class) = class.strip_prefix("items-") {
@@ -413,6 +845,7 @@ impl Tailwind {
                 "stretch" => style.align_items = Some(AlignItems::Stretch),
                 _ => debug!("Unknown align items {class}"),
             }
+            known |= matches!(class, "start" | "end" | "center" | "baseline" | "stretch");
         }
 
         if let Some(class) = class.strip_prefix("self-") {
@@ -422,11 +855,87 @@ impl Tailwind {
                 "center" => style.align_self = Some(AlignItems::Center),
                 "baseline" => style.align_self = Some(AlignItems::Baseline),
                 "stretch" => style.align_self = Some(AlignItems::Stretch),
+                "auto" => style.align_self = None,
                 _ => debug!("Unknown align self {class}"),
             }
+            known |= matches!(class, "start" | "end" | "center" | "baseline" | "stretch" | "auto");
+        }
+
+        if let Some(class) = class.strip_prefix("place-content-") {
+            match handle_align_content(class) {
+                Some(align) => {
+                    style.align_content = Some(align);
+                    style.justify_content = Some(align);
+                    known = true;
+                }
+                None => debug!("Unknown place content {class}"),
+            }
+        }
+
+        if let Some(class) = class.strip_prefix("place-items-") {
+            match handle_grid_align(class) {
+                Some(align) => {
+                    style.align_items = Some(align);
+                    style.justify_items = Some(align);
+                    known = true;
+                }
+                None => debug!("Unknown place items {class}"),
+            }
+        }
+
+        if let Some(class) = class.strip_prefix("place-self-") {
+            match class {
+                "auto" => {
+                    style.align_self = None;
+                    style.justify_self = None;
+                    known = true;
+                }
+                _ => match handle_grid_align(class) {
+                    Some(align) => {
+                        style.align_self = Some(align);
+                        style.justify_self = Some(align);
+                        known = true;
+                    }
+                    None => debug!("Unknown place self {class}"),
+                },
+            }
+        }
+
+        if let Some(class) = class.strip_prefix("order-") {
+            match class {
+                "first" => self.order = -9999,
+                "last" => self.order = 9999,
+                "none" => self.order = 0,
+                _ => match class.parse::<i32>() {
+                    Ok(order) => self.order = order,
+                    Err(_) => debug!("Unknown order {class}"),
+                },
+            }
+            known |= matches!(class, "first" | "last" | "none") || class.parse::<i32>().is_ok();
+        }
+        if let Some(order) = class.strip_prefix("-order-").and_then(|order| order.parse::<i32>().ok()) {
+            known = true;
+            self.order = -order;
+        }
+
+        if let Some(class) = class.strip_prefix("aspect-") {
+            known |= matches!(class, "auto" | "square" | "video");
+            match class {
+                "auto" => style.aspect_ratio = None,
+                "square" => style.aspect_ratio = Some(1.0),
+                "video" => style.aspect_ratio = Some(16.0 / 9.0),
+                _ => match handle_aspect_ratio(class) {
+                    Some(ratio) => {
+                        style.aspect_ratio = Some(ratio);
+                        known = true;
+                    }
+                    None => debug!("Unknown aspect ratio {class}"),
+                },
+            }
         }
 
         if let Some(class) = class.strip_prefix("gap-") {
+            known |= class.parse::<f32>().is_ok();
             let gap = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.gap = Size {
                 width: gap,
@@ -435,49 +944,175 @@ impl Tailwind {
         }
 
         if let Some(class) = class.strip_prefix("gap-x-") {
+            known |= class.parse::<f32>().is_ok();
             let gap = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.gap.width = gap;
         }
 
         if let Some(class) = class.strip_prefix("gap-y-") {
+            known |= class.parse::<f32>().is_ok();
             let gap = LengthPercentage::Length(class.parse::<f32>().unwrap_or(0.0));
             style.gap.height = gap;
         }
 
+        if let Some(space) = class.strip_prefix("space-x-").and_then(|space| space.parse::<f32>().ok()) {
+            known = true;
+            self.space.x = space;
+        }
+
+        if let Some(space) = class.strip_prefix("space-y-").and_then(|space| space.parse::<f32>().ok()) {
+            known = true;
+            self.space.y = space;
+        }
+
+        if let Some(space) = class.strip_prefix("-space-x-").and_then(|space| space.parse::<f32>().ok()) {
+            known = true;
+            self.space.x = -space;
+        }
+
+        if let Some(space) = class.strip_prefix("-space-y-").and_then(|space| space.parse::<f32>().ok()) {
+            known = true;
+            self.space.y = -space;
+        }
+
         if class == "relative" {
+            known = true;
             style.position = Position::Relative;
         }
 
         if class == "absolute" {
+            known = true;
             style.position = Position::Absolute;
         }
 
         if class == "hidden" {
+            known = true;
             style.display = Display::None;
         }
 
+        if let Some(z_layer) = class.strip_prefix("z-").and_then(ZLayer::parse) {
+            known = true;
+            self.z_layer = Some(z_layer);
+        }
+
+        if class == "invisible" {
+            known = true;
+            self.invisible = true;
+        }
+
+        // undoes ``invisible``, e.g. for ``group-hover:visible``
+        if class == "visible" {
+            known = true;
+            self.invisible = false;
+        }
+
+        if class == "layer" {
+            known = true;
+            self.layer = true;
+        }
+
+        if class == "transition-layout" {
+            known = true;
+            self.layout_transition = true;
+        }
+
+        if class == "animate-spin" {
+            known = true;
+            self.animation = Some(Animation::Spin);
+        }
+
+        if class == "animate-indeterminate" {
+            known = true;
+            self.animation = Some(Animation::Indeterminate);
+        }
+
+        if let Some(duration) = class.strip_prefix("duration-").and_then(|duration| duration.parse::<f32>().ok()) {
+            known = true;
+            self.transition_duration = duration / 1000.0;
+        }
+
+        if class == "grayscale" {
+            known = true;
+            self.filter.grayscale = 1.0;
+        }
+        if class == "grayscale-0" {
+            known = true;
+            self.filter.grayscale = 0.0;
+        }
+        if let Some(amount) = class.strip_prefix("brightness-").and_then(|amount| amount.parse::<f32>().ok()) {
+            known = true;
+            self.filter.brightness = amount / 100.0;
+        }
+        if let Some(amount) = class.strip_prefix("saturate-").and_then(|amount| amount.parse::<f32>().ok()) {
+            known = true;
+            self.filter.saturate = amount / 100.0;
+        }
+        if let Some(degrees) = class.strip_prefix("hue-rotate-").and_then(|degrees| degrees.parse::<f32>().ok()) {
+            known = true;
+            self.filter.hue_rotate = degrees;
+        }
+        if let Some(degrees) = class.strip_prefix("-hue-rotate-").and_then(|degrees| degrees.parse::<f32>().ok()) {
+            known = true;
+            self.filter.hue_rotate = -degrees;
+        }
+
+        if class == "backdrop-blur" {
+            known = true;
+            self.backdrop_blur = 8.0;
+        }
+        if let Some(size) = class.strip_prefix("backdrop-blur-") {
+            let blur = match size {
+                "none" => Some(0.0),
+                "sm" => Some(4.0),
+                "md" => Some(12.0),
+                "lg" => Some(16.0),
+                "xl" => Some(24.0),
+                "2xl" => Some(40.0),
+                "3xl" => Some(64.0),
+                size => size.parse::<f32>().ok(),
+            };
+            if let Some(blur) = blur {
+                self.backdrop_blur = blur;
+                known = true;
+            }
+        }
+
         if let Some(class) = class.strip_prefix("left-") {
+            known |= class.parse::<f32>().is_ok();
             style.inset.left = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
         }
         if let Some(class) = class.strip_prefix("right-") {
+            known |= class.parse::<f32>().is_ok();
             style.inset.right = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
         }
 
         if let Some(class) = class.strip_prefix("top-") {
+            known |= class.parse::<f32>().is_ok();
             style.inset.top = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
         }
 
         if let Some(class) = class.strip_prefix("bottom-") {
+            known |= class.parse::<f32>().is_ok();
             style.inset.bottom = LengthPercentageAuto::Length(class.parse::<f32>().unwrap_or(0.0));
         }
 
-        style.scrollbar_width = match class {
-            "scrollbar-default" => 10.0,
-            "scrollbar-none" => 0.0,
-            _ => 0.0,
-        };
+        if let Some(width) = match class {
+            "scrollbar-default" => Some(10.0),
+            "scrollbar-thin" => Some(6.0),
+            "scrollbar-none" => Some(0.0),
+            _ => None,
+        } {
+            known = true;
+            style.scrollbar_width = width;
+        }
+
+        if let Some(width) = class.strip_prefix("scrollbar-w-").and_then(|width| width.parse::<f32>().ok()) {
+            known = true;
+            style.scrollbar_width = width;
+        }
 
         if let Some(class) = class.strip_prefix("overflow-") {
+            known |= matches!(class, "scroll" | "hidden" | "visible");
             match class {
                 "scroll" => {
                     style.overflow = Point {
@@ -504,7 +1139,37 @@ impl Tailwind {
             }
         }
 
+        if let Some(overscroll) = match class {
+            "overscroll-auto" => Some(Overscroll::Auto),
+            "overscroll-contain" => Some(Overscroll::Contain),
+            "overscroll-none" => Some(Overscroll::None),
+            _ => None,
+        } {
+            known = true;
+            self.overscroll = overscroll;
+        }
+
+        if class == "overflow-anchor-none" {
+            known = true;
+            self.scroll_anchor_disabled = true;
+        }
+
+        if class == "overflow-anchor-auto" {
+            known = true;
+            self.scroll_anchor_disabled = false;
+        }
+
+        if let Some(pointer_events) = match class {
+            "pointer-events-none" => Some(false),
+            "pointer-events-auto" => Some(true),
+            _ => None,
+        } {
+            known = true;
+            self.pointer_events = Some(pointer_events);
+        }
+
         if let Some(class) = class.strip_prefix("overflow-x-") {
+            known |= matches!(class, "scroll" | "hidden" | "visible");
             match class {
                 "scroll" => {
                     style.overflow.x = Overflow::Scroll;
@@ -523,6 +1188,7 @@ impl Tailwind {
         }
 
         if let Some(class) = class.strip_prefix("overflow-y-") {
+            known |= matches!(class, "scroll" | "hidden" | "visible");
             match class {
                 "scroll" => {
                     style.overflow.y = Overflow::Scroll;
@@ -543,14 +1209,138 @@ impl Tailwind {
         if let Some(class) = class.strip_prefix("scrollbar-bg-") {
             if let Some(color) = handle_color(class, colors) {
                 self.scrollbar.background_color = color;
+                known = true;
             }
         }
 
         if let Some(class) = class.strip_prefix("scrollbar-thumb-bg-") {
             if let Some(color) = handle_color(class, colors) {
                 self.scrollbar.thumb_color = color;
+                known = true;
+            }
+        }
+
+        if let Some(class) = class.strip_prefix("scrollbar-track-") {
+            if let Some(color) = class.strip_prefix("hover-").and_then(|class| handle_color(class, colors)) {
+                self.scrollbar.background_color_hovered = Some(color);
+                known = true;
+            } else if let Some(color) = class.strip_prefix("active-").and_then(|class| handle_color(class, colors)) {
+                self.scrollbar.background_color_active = Some(color);
+                known = true;
+            } else if let Some(color) = handle_color(class, colors) {
+                self.scrollbar.background_color = color;
+                known = true;
+            }
+        }
+
+        if let Some(class) = class.strip_prefix("scrollbar-thumb-") {
+            if let Some(color) = class.strip_prefix("hover-").and_then(|class| handle_color(class, colors)) {
+                self.scrollbar.thumb_color_hovered = Some(color);
+                known = true;
+            } else if let Some(color) = class.strip_prefix("active-").and_then(|class| handle_color(class, colors)) {
+                self.scrollbar.thumb_color_active = Some(color);
+                known = true;
+            } else if let Some(radius) = class.strip_prefix("rounded-").and_then(|radius| radius.parse::<f32>().ok()) {
+                self.scrollbar.thumb_radius = radius;
+                known = true;
+            } else if let Some(color) = handle_color(class, colors) {
+                self.scrollbar.thumb_color = color;
+                known = true;
             }
         }
+
+        // read by the dom to find the ancestor of ``group-hover:`` classes
+        if class == "group" {
+            known = true;
+        }
+
+        known
+    }
+}
+
+/// Points with an optional ``px`` suffix
+fn parse_inline_length(value: &str) -> Option<f32> {
+    value.strip_suffix("px").unwrap_or(value).parse().ok()
+}
+
+/// Points, percentages of the parent or ``auto``
+fn parse_inline_dimension(value: &str) -> Option<Dimension> {
+    if value == "auto" {
+        return Some(Dimension::AUTO);
+    }
+    if let Some(percent) = value.strip_suffix('%') {
+        return Some(Dimension::Percent(percent.parse::<f32>().ok()? / 100.0));
+    }
+    Some(Dimension::Length(parse_inline_length(value)?))
+}
+
+/// ``#rgb``, ``#rrggbb`` or ``#rrggbbaa``
+fn parse_hex_color(value: &str) -> Option<Color32> {
+    let hex = value.strip_prefix('#')?;
+    let channel = |index: usize, len: usize| {
+        let digits = hex.get(index * len..index * len + len)?;
+        let value = u8::from_str_radix(digits, 16).ok()?;
+        Some(if len == 1 { value * 17 } else { value })
+    };
+    match hex.len() {
+        3 => Some(Color32::from_rgb(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+        6 => Some(Color32::from_rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+        8 => Some(Color32::from_rgba_unmultiplied(
+            channel(0, 2)?,
+            channel(1, 2)?,
+            channel(2, 2)?,
+            channel(3, 2)?,
+        )),
+        _ => None,
+    }
+}
+
+/// ``hover:bg-red-500`` to ``bg-red-500``
+fn strip_variant(class: &str) -> Option<&str> {
+    ["hover:", "focus:", "active:", "disabled:", "group-hover:"]
+        .into_iter()
+        .find_map(|variant| class.strip_prefix(variant))
+}
+
+fn handle_negative_margin(style: &mut Style, class: &str) -> bool {
+    let Some((side, value)) = class.split_once('-') else {
+        return false;
+    };
+    let Ok(value) = value.parse::<f32>() else {
+        return false;
+    };
+    let margin = LengthPercentageAuto::Length(-value);
+    match side {
+        "m" => {
+            style.margin = Rect {
+                top: margin,
+                bottom: margin,
+                left: margin,
+                right: margin,
+            }
+        }
+        "my" => {
+            style.margin.top = margin;
+            style.margin.bottom = margin;
+        }
+        "mx" => {
+            style.margin.left = margin;
+            style.margin.right = margin;
+        }
+        "mt" => style.margin.top = margin,
+        "mb" => style.margin.bottom = margin,
+        "ml" => style.margin.left = margin,
+        "mr" => style.margin.right = margin,
+        _ => return false,
+    }
+    true
+}
+
+/// Whether [`handle_size`] understands the class, it falls back to zero otherwise
+fn is_size(class: &str) -> bool {
+    match class {
+        "full" | "auto" => true,
+        class => class.strip_suffix('%').unwrap_or(class).parse::<f32>().is_ok(),
     }
 }
 
@@ -575,6 +1365,56 @@ fn handle_size(class: &str) -> Dimension {
     }
 }
 
+/// The values of ``justify-items-*``, ``justify-self-*``, ``place-items-*`` and ``place-self-*``
+fn handle_grid_align(class: &str) -> Option<AlignItems> {
+    Some(match class {
+        "start" => AlignItems::Start,
+        "end" => AlignItems::End,
+        "center" => AlignItems::Center,
+        "baseline" => AlignItems::Baseline,
+        "stretch" => AlignItems::Stretch,
+        _ => return None,
+    })
+}
+
+fn handle_align_content(class: &str) -> Option<AlignContent> {
+    Some(match class {
+        "start" => AlignContent::Start,
+        "end" => AlignContent::End,
+        "center" => AlignContent::Center,
+        "between" => AlignContent::SpaceBetween,
+        "around" => AlignContent::SpaceAround,
+        "evenly" => AlignContent::SpaceEvenly,
+        "stretch" => AlignContent::Stretch,
+        _ => return None,
+    })
+}
+
+/// ``[4/3]``, the width divided by the height
+fn handle_aspect_ratio(class: &str) -> Option<f32> {
+    let (width, height) = class.strip_prefix('[')?.strip_suffix(']')?.split_once('/')?;
+    let ratio = width.trim().parse::<f32>().ok()? / height.trim().parse::<f32>().ok()?;
+    (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+}
+
+/// ``bg-center``, ``bg-left-top`` and friends, also spelled ``bg-position-center``
+fn handle_background_position(class: &str) -> Option<epaint::Vec2> {
+    let class = class.strip_prefix("position-").unwrap_or(class);
+    let (x, y) = match class {
+        "center" => (0.5, 0.5),
+        "top" => (0.5, 0.0),
+        "bottom" => (0.5, 1.0),
+        "left" => (0.0, 0.5),
+        "right" => (1.0, 0.5),
+        "left-top" => (0.0, 0.0),
+        "right-top" => (1.0, 0.0),
+        "left-bottom" => (0.0, 1.0),
+        "right-bottom" => (1.0, 1.0),
+        _ => return None,
+    };
+    Some(epaint::vec2(x, y))
+}
+
 fn handle_color(class: &str, colors: &Colors) -> Option<Color32> {
     // Split the class into components
     let components: Vec<&str> = class.split('/').collect();
@@ -582,10 +1422,10 @@ fn handle_color(class: &str, colors: &Colors) -> Option<Color32> {
 
     // If there's an alpha channel specified, get it
     let alpha = if components.len() > 1 {
-        match components[1].parse::<u16>() {
-            // convert from 100 to 255k
-            Ok(a) => (a * 255 / 100) as u8,
-            Err(_) => return None, // Invalid alpha
+        match components[1].parse::<u32>() {
+            // convert from 100 to 255
+            Ok(a) if a <= 100 => (a * 255 / 100) as u8,
+            _ => return None, // Invalid alpha
         }
     } else {
         255 // Default alpha
@@ -1013,3 +1853,156 @@ pub fn insert_default_colors(colors: &mut Colors) {
         .collect(),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The taffy style of ``class`` and the parser, which holds what taffy has no style for
+    fn parse(class: &str) -> (Style, Tailwind) {
+        let mut tailwind = Tailwind::default();
+        let style = tailwind.get_style(class, &StyleState::default());
+        assert!(tailwind.unknown_classes.is_empty(), "{class} isn't known");
+        (style, tailwind)
+    }
+
+    #[test]
+    fn order() {
+        for (class, order) in [
+            ("order-3", 3),
+            ("order-12", 12),
+            ("-order-2", -2),
+            ("order-first", -9999),
+            ("order-last", 9999),
+            ("order-none", 0),
+        ] {
+            assert_eq!(parse(class).1.order, order, "{class}");
+        }
+    }
+
+    #[test]
+    fn align_self() {
+        for (class, align) in [
+            ("self-start", Some(AlignItems::FlexStart)),
+            ("self-center", Some(AlignItems::Center)),
+            ("self-end", Some(AlignItems::FlexEnd)),
+            ("self-stretch", Some(AlignItems::Stretch)),
+            ("self-baseline", Some(AlignItems::Baseline)),
+            ("self-auto", None),
+        ] {
+            assert_eq!(parse(class).0.align_self, align, "{class}");
+        }
+    }
+
+    #[test]
+    fn justify_items_and_self() {
+        for (suffix, align) in [
+            ("start", AlignItems::Start),
+            ("center", AlignItems::Center),
+            ("end", AlignItems::End),
+            ("stretch", AlignItems::Stretch),
+        ] {
+            let (style, _) = parse(&format!("justify-items-{suffix}"));
+            assert_eq!(style.justify_items, Some(align), "justify-items-{suffix}");
+            // justify-content is left alone
+            assert_eq!(style.justify_content, None, "justify-items-{suffix}");
+
+            let (style, _) = parse(&format!("justify-self-{suffix}"));
+            assert_eq!(style.justify_self, Some(align), "justify-self-{suffix}");
+        }
+        assert_eq!(parse("justify-self-auto").0.justify_self, None);
+    }
+
+    #[test]
+    fn place_content_and_items() {
+        for (suffix, align) in [
+            ("start", AlignContent::Start),
+            ("center", AlignContent::Center),
+            ("end", AlignContent::End),
+            ("between", AlignContent::SpaceBetween),
+            ("around", AlignContent::SpaceAround),
+            ("evenly", AlignContent::SpaceEvenly),
+            ("stretch", AlignContent::Stretch),
+        ] {
+            let (style, _) = parse(&format!("place-content-{suffix}"));
+            assert_eq!(style.align_content, Some(align), "place-content-{suffix}");
+            assert_eq!(style.justify_content, Some(align), "place-content-{suffix}");
+        }
+
+        for (suffix, align) in [
+            ("start", AlignItems::Start),
+            ("center", AlignItems::Center),
+            ("end", AlignItems::End),
+            ("baseline", AlignItems::Baseline),
+            ("stretch", AlignItems::Stretch),
+        ] {
+            let (style, _) = parse(&format!("place-items-{suffix}"));
+            assert_eq!(style.align_items, Some(align), "place-items-{suffix}");
+            assert_eq!(style.justify_items, Some(align), "place-items-{suffix}");
+        }
+    }
+
+    #[test]
+    fn color_alpha() {
+        let colors = &*COLORS;
+        assert_eq!(handle_color("black/100", colors), Some(Color32::from_rgba_unmultiplied(0, 0, 0, 255)));
+        assert_eq!(handle_color("black/0", colors), Some(Color32::from_rgba_unmultiplied(0, 0, 0, 0)));
+        for class in ["black/101", "black/300", "black/70000", "black/-1"] {
+            assert_eq!(handle_color(class, colors), None, "{class}");
+        }
+    }
+
+    #[test]
+    fn unknown_alignment() {
+        for class in [
+            "self-sideways",
+            "justify-self-between",
+            "justify-sideways",
+            "place-items-around",
+            "order-soon",
+        ] {
+            let mut tailwind = Tailwind::default();
+            tailwind.get_style(class, &StyleState::default());
+            assert_eq!(tailwind.unknown_classes, vec![Arc::<str>::from(class)], "{class}");
+        }
+    }
+
+    #[test]
+    fn class_builder_is_known() {
+        use crate::class::{Align, Class, Color, Justify, Length, Overflow, Palette, Shade, Size};
+        use crate::tw;
+
+        let mut classes = vec![
+            tw![flex_col, gap(4.0), items(Align::Center), bg(Palette::Red.shade(Shade::S500))],
+            tw![flex_row, flex_wrap, grow, shrink_0, order(-2), aspect_ratio(16.0, 9.0)],
+            tw![grid, grid_cols(3), col_span(2), row_span(1), gap_x(2.0), gap_y(0.5)],
+            tw![p(8.0), px(1.5), mt(-4.0), mx(2.0), space_x(-1.0), space_y(3.0)],
+            tw![rounded(4.0), rounded_tl(2.0), border(1.0), border_color(Color::BLACK.opacity(250))],
+            tw![text(Color::WHITE), text_size(14.0), font_mono, selection(Palette::Sky.shade(Shade::S200))],
+            tw![absolute, left(0.0), top(-8.0), z(ZLayer::Overlay), layer, overflow_y(Overflow::Scroll)],
+            tw![transition_layout, duration(150), grayscale(true), brightness(50.0), backdrop_blur(4.0)],
+            tw![scrollbar_none, scrollbar_thumb_bg(Palette::Zinc.shade(Shade::S700).opacity(50))],
+            tw![place_content(Justify::Evenly), place_items(Align::End), justify_self(Align::Stretch)],
+            tw![bg_size(BackgroundSize::Cover), bg_repeat(false), pointer_events_none, invisible],
+            Class::new()
+                .hover(tw![bg(Color::TRANSPARENT)])
+                .group_hover(tw![text(Palette::Rose.shade(Shade::S950))])
+                .disabled(tw![hidden]),
+        ];
+        for size in [Size::Points(12.5), Size::Percent(50.0), Size::Full, Size::Auto, Size::Min, Size::Max, Size::Fit] {
+            classes.push(tw![w(size), h(size)]);
+        }
+        for length in [Length::Points(0.0), Length::Percent(33.0), Length::Full, Length::Auto] {
+            classes.push(tw![basis(length), min_w(length), min_h(length), max_w(length), max_h(length)]);
+        }
+        for justify in [Justify::Start, Justify::Between, Justify::Around, Justify::Stretch] {
+            classes.push(tw![justify(justify)]);
+        }
+
+        for class in classes {
+            let mut tailwind = Tailwind::default();
+            tailwind.get_style(class.as_str(), &StyleState::default());
+            assert!(tailwind.unknown_classes.is_empty(), "{:?} in {class:?}", tailwind.unknown_classes);
+        }
+    }
+}
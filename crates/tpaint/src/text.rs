@@ -0,0 +1,126 @@
+//! Grapheme and word boundaries for editing text. Cursors count chars, like the ones of epaint, but should never end
+//! up between the chars of a single grapheme, e.g. an emoji with a skin tone or a letter with a combining accent.
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The byte offset of the char at ``char_index``, or the length of the text past the end
+pub fn byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(index, _)| index)
+        .unwrap_or(text.len())
+}
+
+/// Same as [`byte_index`] for both ends, swapped when the range is backwards
+pub fn byte_range(text: &str, chars: Range<usize>) -> Range<usize> {
+    let (start, end) = if chars.start <= chars.end {
+        (chars.start, chars.end)
+    } else {
+        (chars.end, chars.start)
+    };
+    byte_index(text, start)..byte_index(text, end)
+}
+
+pub fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Char offsets where a grapheme starts, and the end of the text
+fn grapheme_boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut chars = 0;
+    text.graphemes(true)
+        .map(move |grapheme| {
+            let start = chars;
+            chars += grapheme.chars().count();
+            start
+        })
+        .chain(std::iter::once(char_count(text)))
+}
+
+/// The start of the grapheme before the cursor
+pub fn prev_grapheme(text: &str, char_index: usize) -> usize {
+    grapheme_boundaries(text)
+        .take_while(|boundary| *boundary < char_index)
+        .last()
+        .unwrap_or(0)
+}
+
+/// The end of the grapheme after the cursor
+pub fn next_grapheme(text: &str, char_index: usize) -> usize {
+    grapheme_boundaries(text)
+        .find(|boundary| *boundary > char_index)
+        .unwrap_or_else(|| char_count(text))
+}
+
+pub fn is_grapheme_boundary(text: &str, char_index: usize) -> bool {
+    grapheme_boundaries(text).any(|boundary| boundary == char_index)
+}
+
+/// Moves a cursor that is inside a grapheme to the start of it
+pub fn snap_to_grapheme(text: &str, char_index: usize) -> usize {
+    grapheme_boundaries(text)
+        .take_while(|boundary| *boundary <= char_index)
+        .last()
+        .unwrap_or(0)
+}
+
+/// The word, run of whitespace or punctuation the cursor is in, as char offsets. What a double click selects
+pub fn word_at(text: &str, char_index: usize) -> Range<usize> {
+    let mut chars = 0;
+    let mut last = 0..0;
+    for word in text.split_word_bounds() {
+        let len = word.chars().count();
+        let range = chars..chars + len;
+        if range.contains(&char_index) {
+            return range;
+        }
+        chars += len;
+        last = range;
+    }
+    // past the end of the text, the last word
+    last
+}
+
+/// The paragraph the cursor is in without its line break, as char offsets. What a triple click selects
+pub fn paragraph_at(text: &str, char_index: usize) -> Range<usize> {
+    let mut start = 0;
+    for (index, char) in text.chars().enumerate() {
+        if char == '\n' {
+            if index >= char_index {
+                return start..index;
+            }
+            start = index + 1;
+        }
+    }
+    start..char_count(text)
+}
+
+/// Where Ctrl + Left goes, the start of the word before the cursor
+pub fn prev_word(text: &str, char_index: usize) -> usize {
+    let mut chars = 0;
+    let mut start = 0;
+    for word in text.split_word_bounds() {
+        if chars >= char_index {
+            break;
+        }
+        if !word.trim().is_empty() {
+            start = chars;
+        }
+        chars += word.chars().count();
+    }
+    start
+}
+
+/// Where Ctrl + Right goes, the end of the word after the cursor
+pub fn next_word(text: &str, char_index: usize) -> usize {
+    let mut chars = 0;
+    for word in text.split_word_bounds() {
+        chars += word.chars().count();
+        if chars > char_index && !word.trim().is_empty() {
+            return chars;
+        }
+    }
+    chars
+}
@@ -0,0 +1,267 @@
+//! System tray icon, described by a component with [`use_tray`] and created on the thread of the [`crate::DomEventLoop`].
+//! On Linux the tray is a gtk widget, the app has to run the gtk event loop as described by the ``tray-icon`` crate
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use dioxus::prelude::*;
+use rustc_hash::FxHashMap;
+use tokio::sync::{broadcast, watch};
+use tray_icon::{
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    Icon, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent, TrayIconId,
+};
+use winit::event::MouseButton;
+
+use crate::event_loop::DomContext;
+
+/// What the tray shows, compared with the previous render so the tray is only updated when it changes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tray {
+    pub tooltip: Option<String>,
+    /// RGBA pixels with their width and height
+    pub icon: Option<(Vec<u8>, u32, u32)>,
+    pub items: Vec<TrayMenuItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayMenuItem {
+    /// ``id`` is sent back in [`TrayEvent::MenuItem`] when the item is clicked
+    Item { id: String, label: String, enabled: bool },
+    Separator,
+}
+
+impl Tray {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    pub fn icon_rgba(mut self, rgba: Vec<u8>, width: u32, height: u32) -> Self {
+        self.icon = Some((rgba, width, height));
+        self
+    }
+
+    pub fn item(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.items.push(TrayMenuItem::Item { id: id.into(), label: label.into(), enabled: true });
+        self
+    }
+
+    pub fn disabled_item(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.items.push(TrayMenuItem::Item { id: id.into(), label: label.into(), enabled: false });
+        self
+    }
+
+    pub fn separator(mut self) -> Self {
+        self.items.push(TrayMenuItem::Separator);
+        self
+    }
+}
+
+/// Sent to the handler of [`use_tray`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayEvent {
+    /// The icon was clicked, the menu opens on a right click on most platforms
+    Click(MouseButton),
+    DoubleClick(MouseButton),
+    /// The ``id`` of the [`TrayMenuItem::Item`] that was clicked
+    MenuItem(String),
+}
+
+/// Shows ``tray`` in the system tray while the component is mounted and calls ``handler`` with its clicks.
+/// Only one tray is shown, when multiple components call this the one that rendered last wins
+pub fn use_tray(cx: &ScopeState, tray: Tray, handler: impl FnMut(TrayEvent) + 'static) {
+    let dom_context = use_context::<DomContext>(cx).unwrap();
+    let current: &Rc<RefCell<Box<dyn FnMut(TrayEvent)>>> = cx.use_hook(|| Rc::new(RefCell::new(Box::new(|_| {}))));
+    *current.borrow_mut() = Box::new(handler);
+
+    cx.use_hook(|| TrayGuard(dom_context.clone()));
+    let changed = dom_context.tray.send_if_modified(|shown| {
+        if shown.as_ref() == Some(&tray) {
+            return false;
+        }
+        *shown = Some(tray);
+        true
+    });
+    if changed {
+        // the tray is updated on the thread of the event loop when the next frame starts
+        dom_context.host.request_redraw();
+    }
+
+    use_future(cx, (), |_| {
+        let handler = current.clone();
+        let mut receiver = dom_context.tray_events.subscribe();
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => (handler.borrow_mut())(event),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        }
+    });
+}
+
+/// Removes the tray when the component that shows it unmounts
+struct TrayGuard(DomContext);
+
+impl Drop for TrayGuard {
+    fn drop(&mut self) {
+        self.0.tray.send_replace(None);
+        self.0.host.request_redraw();
+    }
+}
+
+/// Where the events of the tray of every event loop go, by the id of its icon. The handlers of ``tray-icon`` are global,
+/// they're installed along with this the first time a tray is made and called on the thread of the event loop
+fn routes() -> &'static Mutex<FxHashMap<String, broadcast::Sender<TrayEvent>>> {
+    static ROUTES: OnceLock<Mutex<FxHashMap<String, broadcast::Sender<TrayEvent>>>> = OnceLock::new();
+    ROUTES.get_or_init(|| {
+        TrayIconEvent::set_event_handler(Some(|event: TrayIconEvent| {
+            let tray_event = match &event {
+                TrayIconEvent::Click { button, button_state: MouseButtonState::Up, .. } => {
+                    TrayEvent::Click(mouse_button(*button))
+                }
+                TrayIconEvent::DoubleClick { button, .. } => TrayEvent::DoubleClick(mouse_button(*button)),
+                _ => return,
+            };
+            send(&event.id().0, tray_event);
+        }));
+        MenuEvent::set_event_handler(Some(|event: MenuEvent| {
+            // the ids of the items start with the id of their tray, see ``build_menu``
+            if let Some((tray_id, item_id)) = event.id.0.split_once('/') {
+                send(tray_id, TrayEvent::MenuItem(item_id.to_string()));
+            }
+        }));
+        Mutex::default()
+    })
+}
+
+fn send(tray_id: &str, event: TrayEvent) {
+    if let Some(events) = routes().lock().unwrap().get(tray_id) {
+        // fails when no component is listening
+        let _ = events.send(event);
+    }
+}
+
+/// Owns the tray icon, which has to live on the thread of the event loop
+pub(crate) struct TrayManager {
+    /// Tells the events of this tray apart from the ones of other event loops
+    id: String,
+    receiver: watch::Receiver<Option<Tray>>,
+    icon: Option<TrayIcon>,
+    /// See [`crate::DomEventLoop::set_hide_to_tray`]
+    pub hide_to_tray: bool,
+}
+
+impl TrayManager {
+    pub fn new(receiver: watch::Receiver<Option<Tray>>, events: broadcast::Sender<TrayEvent>) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = format!("tpaint-tray-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        routes().lock().unwrap().insert(id.clone(), events);
+
+        Self {
+            id,
+            receiver,
+            icon: None,
+            hide_to_tray: false,
+        }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.icon.is_some()
+    }
+
+    /// Creates, updates or removes the tray icon after a component changed it
+    pub fn update(&mut self) {
+        if !self.receiver.has_changed().unwrap_or(false) {
+            return;
+        }
+        let Some(tray) = self.receiver.borrow_and_update().clone() else {
+            self.icon = None;
+            return;
+        };
+
+        let icon = tray.icon.as_ref().and_then(|(rgba, width, height)| match Icon::from_rgba(rgba.clone(), *width, *height) {
+            Ok(icon) => Some(icon),
+            Err(err) => {
+                log::warn!("Invalid tray icon: {}", err);
+                None
+            }
+        });
+        let menu = build_menu(&self.id, &tray.items);
+
+        match &self.icon {
+            Some(tray_icon) => {
+                if let Err(err) = tray_icon.set_tooltip(tray.tooltip.as_deref()) {
+                    log::warn!("Failed to update the tray tooltip: {}", err);
+                }
+                if let Err(err) = tray_icon.set_icon(icon) {
+                    log::warn!("Failed to update the tray icon: {}", err);
+                }
+                tray_icon.set_menu(menu.map(|menu| Box::new(menu) as _));
+            }
+            None => {
+                let mut builder = TrayIconBuilder::new().with_id(TrayIconId(self.id.clone()));
+                if let Some(tooltip) = &tray.tooltip {
+                    builder = builder.with_tooltip(tooltip);
+                }
+                if let Some(icon) = icon {
+                    builder = builder.with_icon(icon);
+                }
+                if let Some(menu) = menu {
+                    builder = builder.with_menu(Box::new(menu));
+                }
+                match builder.build() {
+                    Ok(tray_icon) => self.icon = Some(tray_icon),
+                    Err(err) => log::error!("Failed to create the tray icon: {}", err),
+                }
+            }
+        }
+    }
+}
+
+impl Drop for TrayManager {
+    fn drop(&mut self) {
+        routes().lock().unwrap().remove(&self.id);
+    }
+}
+
+/// The ids of the items get the id of the tray in front, so a click goes to the event loop the menu belongs to
+fn build_menu(tray_id: &str, items: &[TrayMenuItem]) -> Option<Menu> {
+    if items.is_empty() {
+        return None;
+    }
+    let menu = Menu::new();
+    for item in items {
+        let result = match item {
+            TrayMenuItem::Item { id, label, enabled } => {
+                menu.append(&MenuItem::with_id(format!("{tray_id}/{id}"), label, *enabled, None))
+            }
+            TrayMenuItem::Separator => menu.append(&PredefinedMenuItem::separator()),
+        };
+        if let Err(err) = result {
+            log::warn!("Failed to add a tray menu item: {}", err);
+        }
+    }
+    Some(menu)
+}
+
+fn mouse_button(button: tray_icon::MouseButton) -> MouseButton {
+    match button {
+        tray_icon::MouseButton::Left => MouseButton::Left,
+        tray_icon::MouseButton::Right => MouseButton::Right,
+        tray_icon::MouseButton::Middle => MouseButton::Middle,
+    }
+}
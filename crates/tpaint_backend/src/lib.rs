@@ -0,0 +1,110 @@
+//! The interface every tpaint renderer crate implements, so examples and apps can be generic over the backend.
+
+use std::fmt;
+
+use tpaint::{
+    epaint::{textures::TexturesDelta, ClippedPrimitive, ImageDelta, TextureId},
+    DomEventLoop, ScreenDescriptor,
+};
+
+/// Failures a backend can't recover from by itself, like a surface that can't be acquired after reconfiguring it.
+/// The embedder has to recreate the renderer or shut down
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RendererError {
+    OutOfMemory,
+    /// The GPU was reset or removed, every resource of the renderer is gone
+    DeviceLost,
+    /// Anything else the graphics api reports
+    Other(String),
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RendererError::OutOfMemory => write!(f, "the GPU ran out of memory"),
+            RendererError::DeviceLost => write!(f, "the GPU device was lost"),
+            RendererError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for RendererError {}
+
+pub trait TpaintRenderer {
+    /// What the backend records into for a single frame, e.g. the wgpu command encoder and target view
+    type Frame<'a>;
+
+    fn set_texture(&mut self, frame: &mut Self::Frame<'_>, id: TextureId, delta: &ImageDelta);
+
+    fn free_texture(&mut self, id: TextureId);
+
+    fn paint(
+        &mut self,
+        frame: &mut Self::Frame<'_>,
+        primitives: &[ClippedPrimitive],
+        screen: &ScreenDescriptor,
+    );
+
+    /// Uploads new textures, paints, then frees the textures that are no longer used
+    fn paint_with_textures(
+        &mut self,
+        frame: &mut Self::Frame<'_>,
+        primitives: &[ClippedPrimitive],
+        textures_delta: &TexturesDelta,
+        screen: &ScreenDescriptor,
+    ) {
+        for (id, delta) in &textures_delta.set {
+            self.set_texture(frame, *id, delta);
+        }
+
+        self.paint(frame, primitives, screen);
+
+        for id in &textures_delta.free {
+            self.free_texture(*id);
+        }
+    }
+}
+
+/// Lays out and paints the current state of the dom
+pub fn paint_dom<R: TpaintRenderer>(renderer: &mut R, frame: &mut R::Frame<'_>, dom: &mut DomEventLoop) {
+    let (primitives, textures_delta, screen) = dom.get_paint_info();
+    let screen = screen.clone();
+    let _span = tracing::trace_span!("paint_dom", frame = dom.frame_id()).entered();
+    renderer.paint_with_textures(frame, &primitives, &textures_delta, &screen);
+    dom.recycle_primitives(primitives);
+}
+
+/// Paints roots that share the window in one pass, see ``DomEventLoop::set_viewport``. Later roots are painted over earlier ones
+pub fn paint_doms<R: TpaintRenderer>(renderer: &mut R, frame: &mut R::Frame<'_>, doms: &mut [&mut DomEventLoop]) {
+    // the primitives of the other roots are moved behind the ones of the first, their emptied vectors wait to get them back
+    let mut all_primitives: Option<Vec<ClippedPrimitive>> = None;
+    let mut others = Vec::with_capacity(doms.len().saturating_sub(1));
+    let mut all_textures_delta = TexturesDelta::default();
+    let mut screen = None;
+    for dom in doms.iter_mut() {
+        let (mut primitives, textures_delta, dom_screen) = dom.get_paint_info();
+        screen.get_or_insert_with(|| dom_screen.clone());
+        all_textures_delta.append(textures_delta);
+        match &mut all_primitives {
+            None => all_primitives = Some(primitives),
+            Some(all_primitives) => {
+                let count = primitives.len();
+                all_primitives.append(&mut primitives);
+                others.push((primitives, count));
+            }
+        }
+    }
+    let (Some(screen), Some(mut all_primitives)) = (screen, all_primitives) else {
+        return;
+    };
+
+    let _span = tracing::trace_span!("paint_doms", roots = doms.len()).entered();
+    renderer.paint_with_textures(frame, &all_primitives, &all_textures_delta, &screen);
+
+    // from the back, so every root gets its own primitives and allocations back
+    for (dom, (mut primitives, count)) in doms.iter_mut().skip(1).zip(others).rev() {
+        primitives.extend(all_primitives.drain(all_primitives.len() - count..));
+        dom.recycle_primitives(primitives);
+    }
+    doms[0].recycle_primitives(all_primitives);
+}
@@ -1,5 +1,9 @@
 use std::{borrow::Cow, collections::HashMap};
 
+mod swapchain;
+pub use swapchain::*;
+pub use tpaint_backend::RendererError;
+
 use beuk::{
     ash::vk::{
         self, BufferUsageFlags, CullModeFlags, DescriptorImageInfo, FrontFace, PolygonMode,
@@ -70,6 +74,8 @@ pub struct Renderer {
 
     textures_to_index: HashMap<TextureId, usize>,
     textures: Slab<ResourceHandle<Texture>>,
+    /// Paint callbacks other than backdrop blurs are skipped, this keeps the warning to once
+    warned_about_callbacks: bool,
 }
 
 struct SlicedBuffer {
@@ -97,9 +103,11 @@ fn create_index_buffer(ctx: &RenderContext, capacity: u64) -> ResourceHandle<Buf
 }
 
 impl Renderer {
-    pub fn new(ctx: &RenderContext, color_format: vk::Format, depth_format: vk::Format) -> Self {
-        let swapchain = ctx.get_swapchain();
-
+    /// ``depth_format`` is only needed when the render pass tpaint is drawn in has a depth attachment,
+    /// tpaint never writes or tests depth and relies on paint order, so it can target a swapchain without a depth image.
+    /// The shaders are compiled from GLSL every time, beuk's ``create_shader`` and ``create_graphics_pipeline`` take
+    /// neither SPIR-V nor a ``VkPipelineCache`` yet, so precompiling and caching them has to start in beuk
+    pub fn new(ctx: &RenderContext, color_format: vk::Format, depth_format: Option<vk::Format>) -> Self {
         let graphics_pipeline = ctx.create_graphics_pipeline(
             "tpaint",
             GraphicsPipelineDescriptor {
@@ -136,7 +144,7 @@ impl Renderer {
                 },
                 fragment: FragmentState {
                     color_attachment_formats: smallvec![color_format],
-                    depth_attachment_format: depth_format,
+                    depth_attachment_format: depth_format.unwrap_or(vk::Format::UNDEFINED),
                     shader: ctx.create_shader(ShaderDescriptor {
                         label: "tpaint_fragment",
                         kind: beuk::shaders::ShaderKind::Fragment,
@@ -154,8 +162,8 @@ impl Renderer {
                     front_face: FrontFace::COUNTER_CLOCKWISE,
                     polygon_mode: PolygonMode::FILL,
                 },
-                depth_stencil: Some(DepthStencilState {
-                    format: swapchain.depth_image_format,
+                depth_stencil: depth_format.map(|format| DepthStencilState {
+                    format,
                     depth_write_enabled: false,
                     depth_compare: beuk::graphics_pipeline::CompareFunction::Always,
                     bias: DepthBiasState::default(),
@@ -205,9 +213,11 @@ impl Renderer {
             },
             textures_to_index: HashMap::default(),
             textures: Slab::default(),
+            warned_about_callbacks: false,
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Renderer::update_texture"))]
     pub fn update_texture(&mut self, ctx: &RenderContext, id: TextureId, image_delta: &ImageDelta) {
         let width = image_delta.image.width() as u32;
         let height = image_delta.image.height() as u32;
@@ -355,6 +365,7 @@ impl Renderer {
         self.textures.get(*index)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Renderer::update_buffers"))]
     pub fn update_buffers(&mut self, ctx: &RenderContext, paint_jobs: &[epaint::ClippedPrimitive]) {
         // Determine how many vertices & indices need to be rendered, and gather prepare callbacks
         let (vertex_count, index_count) = {
@@ -363,14 +374,13 @@ impl Renderer {
                     Primitive::Mesh(mesh) => {
                         (acc.0 + mesh.vertices.len(), acc.1 + mesh.indices.len())
                     }
-                    Primitive::Callback(_) => {
-                        unimplemented!();
-                        // if let Some(c) = callback.callback.downcast_ref::<Callback>() {
-                        //     callbacks.push(c.0.as_ref());
-                        // } else {
-                        //     log::warn!("Unknown paint callback: expected `egui_wgpu::Callback`");
-                        // };
-                        // acc
+                    Primitive::Callback(callback) => {
+                        // backdrop blurs aren't supported, the node still paints its background
+                        if !callback.callback.is::<tpaint::BackdropBlur>() && !self.warned_about_callbacks {
+                            log::warn!("Skipping paint callbacks, the beuk renderer only draws meshes");
+                            self.warned_about_callbacks = true;
+                        }
+                        acc
                     }
                 }
             })
@@ -437,6 +447,7 @@ impl Renderer {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Renderer::render"))]
     pub fn render(
         &self,
         ctx: &RenderContext,
@@ -553,9 +564,8 @@ impl Renderer {
                             log::warn!("Missing texture: {:?}", mesh.texture_id);
                         }
                     }
-                    Primitive::Callback(_) => {
-                        unimplemented!();
-                    }
+                    // skipped, see update_buffers
+                    Primitive::Callback(_) => {}
                 }
             }
 
@@ -582,6 +592,38 @@ struct ScissorRect {
     height: u32,
 }
 
+/// What [`Renderer`] paints into through [`tpaint_backend::TpaintRenderer`], rendering has to be begun on the command buffer already
+pub struct BeukFrame<'a> {
+    pub ctx: &'a RenderContext,
+    pub command_buffer: vk::CommandBuffer,
+}
+
+impl tpaint_backend::TpaintRenderer for Renderer {
+    type Frame<'a> = BeukFrame<'a>;
+
+    fn set_texture(&mut self, frame: &mut BeukFrame<'_>, id: TextureId, delta: &ImageDelta) {
+        self.update_texture(frame.ctx, id, delta);
+    }
+
+    fn free_texture(&mut self, id: TextureId) {
+        Renderer::free_texture(self, &id);
+    }
+
+    fn paint(
+        &mut self,
+        frame: &mut BeukFrame<'_>,
+        primitives: &[epaint::ClippedPrimitive],
+        screen: &tpaint::ScreenDescriptor,
+    ) {
+        let screen = ScreenDescriptor {
+            size_in_pixels: screen.size.into(),
+            pixels_per_point: screen.pixels_per_point,
+        };
+        self.update_buffers(frame.ctx, primitives);
+        self.render(frame.ctx, primitives, &screen, frame.command_buffer);
+    }
+}
+
 impl ScissorRect {
     fn new(clip_rect: &epaint::Rect, pixels_per_point: f32, target_size: [u32; 2]) -> Self {
         // Transform clip rect to physical pixels:
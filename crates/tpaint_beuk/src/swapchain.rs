@@ -0,0 +1,28 @@
+use beuk::ash::{prelude::VkResult, vk};
+use tpaint_backend::RendererError;
+
+/// What to do with the swapchain after acquiring or presenting an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapchainStatus {
+    Ready,
+    /// Out of date or suboptimal, recreate the swapchain with the size of the window, e.g. with
+    /// ``RenderContext::recreate_swapchain``, and acquire again
+    Recreate,
+    /// Nothing was acquired in time, skip the frame
+    Skip,
+}
+
+/// Sorts the result of ``acquire_next_image`` or ``queue_present`` of ash, the ``bool`` is whether the swapchain is
+/// suboptimal. Errors the swapchain can't recover from, like a lost device, are returned for the embedder to handle
+pub fn swapchain_status(result: VkResult<bool>) -> Result<SwapchainStatus, RendererError> {
+    match result {
+        Ok(false) => Ok(SwapchainStatus::Ready),
+        Ok(true) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => Ok(SwapchainStatus::Recreate),
+        Err(vk::Result::TIMEOUT | vk::Result::NOT_READY) => Ok(SwapchainStatus::Skip),
+        Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY | vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
+            Err(RendererError::OutOfMemory)
+        }
+        Err(vk::Result::ERROR_DEVICE_LOST) => Err(RendererError::DeviceLost),
+        Err(err) => Err(RendererError::Other(format!("presenting failed: {err}"))),
+    }
+}
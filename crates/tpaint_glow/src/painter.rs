@@ -59,6 +59,13 @@ pub struct Painter {
     /// Stores outdated OpenGL textures that are yet to be deleted
     textures_to_destroy: Vec<glow::Texture>,
 
+    /// Adjacent meshes with the same texture and clip rect, drawn with a single call. Kept between frames so its
+    /// buffers are reused
+    batch: Mesh,
+
+    /// Draw calls of the last [`Self::paint_primitives`], to see how well meshes are batched
+    draw_calls: usize,
+
     /// Used to make sure we are destroyed correctly.
     destroyed: bool,
 }
@@ -221,6 +228,8 @@ impl Painter {
                 textures: Default::default(),
                 next_native_tex_id: 1 << 32,
                 textures_to_destroy: Vec::new(),
+                batch: Mesh::default(),
+                draw_calls: 0,
                 destroyed: false,
             })
         }
@@ -235,6 +244,11 @@ impl Painter {
         self.max_texture_side
     }
 
+    /// Draw calls the last frame took, meshes that share a texture and clip rect are merged into one
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
     /// The framebuffer we use as an intermediate render target,
     /// or `None` if we are painting to the screen framebuffer directly.
     ///
@@ -341,6 +355,7 @@ impl Painter {
     ///
     /// Please be mindful of these effects when integrating into your program, and also be mindful
     /// of the effects your program might have on this code. Look at the source if in doubt.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Painter::paint_primitives"))]
     pub fn paint_primitives(
         &mut self,
         screen_size_px: [u32; 2],
@@ -350,19 +365,38 @@ impl Painter {
         self.assert_not_destroyed();
 
         let size_in_pixels = unsafe { self.prepare_painting(screen_size_px, pixels_per_point) };
+        self.draw_calls = 0;
+
+        let mut batch = std::mem::take(&mut self.batch);
+        let mut batch_clip_rect = Rect::NOTHING;
 
         for ClippedPrimitive {
             clip_rect,
             primitive,
         } in clipped_primitives
         {
-            set_clip_rect(&self.gl, size_in_pixels, pixels_per_point, *clip_rect);
-
             match primitive {
                 Primitive::Mesh(mesh) => {
-                    self.paint_mesh(mesh);
+                    if mesh.is_empty() {
+                        continue;
+                    }
+                    let fits = batch.texture_id == mesh.texture_id && batch_clip_rect == *clip_rect;
+                    if !batch.is_empty() && !fits {
+                        self.paint_batch(&mut batch, batch_clip_rect, size_in_pixels, pixels_per_point);
+                    }
+                    batch_clip_rect = *clip_rect;
+                    batch.append_ref(mesh);
                 }
                 Primitive::Callback(callback) => {
+                    // callbacks draw in between, so everything before them has to be drawn first
+                    self.paint_batch(&mut batch, batch_clip_rect, size_in_pixels, pixels_per_point);
+                    set_clip_rect(&self.gl, size_in_pixels, pixels_per_point, *clip_rect);
+
+                    // there's no blur here, nodes with a backdrop blur just show their translucent background
+                    if callback.callback.is::<tpaint::BackdropBlur>() {
+                        continue;
+                    }
+
                     if callback.rect.is_positive() {
                         // Transform callback rect to physical pixels:
                         let rect_min_x = pixels_per_point * callback.rect.min.x;
@@ -406,6 +440,9 @@ impl Painter {
             }
         }
 
+        self.paint_batch(&mut batch, batch_clip_rect, size_in_pixels, pixels_per_point);
+        self.batch = batch;
+
         unsafe {
             self.vao.unbind(&self.gl);
             self.gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
@@ -416,6 +453,19 @@ impl Painter {
         }
     }
 
+    /// Draws the meshes gathered so far and empties the batch, keeping its buffers
+    fn paint_batch(&mut self, batch: &mut Mesh, clip_rect: Rect, size_in_pixels: (u32, u32), pixels_per_point: f32) {
+        if batch.is_empty() {
+            return;
+        }
+        set_clip_rect(&self.gl, size_in_pixels, pixels_per_point, clip_rect);
+        self.paint_mesh(batch);
+        self.draw_calls += 1;
+        // ``Mesh::clear`` drops the vertex buffer
+        batch.vertices.clear();
+        batch.indices.clear();
+    }
+
     #[inline(never)] // Easier profiling
     fn paint_mesh(&mut self, mesh: &Mesh) {
         debug_assert!(mesh.is_valid());
@@ -456,6 +506,7 @@ impl Painter {
 
     // ------------------------------------------------------------------------
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Painter::set_texture"))]
     pub fn set_texture(&mut self, tex_id: TextureId, delta: &ImageDelta) {
         self.assert_not_destroyed();
 
@@ -702,6 +753,32 @@ pub fn clear(gl: &glow::Context, screen_size_in_pixels: [u32; 2], clear_color: [
     }
 }
 
+/// The gl context lives in the painter, so there's nothing to pass along per frame
+impl tpaint_backend::TpaintRenderer for Painter {
+    type Frame<'a> = ();
+
+    fn set_texture(&mut self, _frame: &mut (), id: TextureId, delta: &ImageDelta) {
+        Painter::set_texture(self, id, delta);
+    }
+
+    fn free_texture(&mut self, id: TextureId) {
+        Painter::free_texture(self, id);
+    }
+
+    fn paint(
+        &mut self,
+        _frame: &mut (),
+        primitives: &[ClippedPrimitive],
+        screen: &tpaint::ScreenDescriptor,
+    ) {
+        self.paint_primitives(
+            [screen.size.width, screen.size.height],
+            screen.pixels_per_point,
+            primitives,
+        );
+    }
+}
+
 impl Drop for Painter {
     fn drop(&mut self) {
         if !self.destroyed {
@@ -0,0 +1,28 @@
+// Fragment shader bindings, every texture is in one bind group and the push constants pick one per draw
+
+struct PushConstants {
+    texture_index: u32,
+    sampler_index: u32,
+};
+var<push_constant> r_push: PushConstants;
+
+@group(1) @binding(0) var r_textures: binding_array<texture_2d<f32>>;
+@group(1) @binding(1) var r_samplers: binding_array<sampler>;
+
+@fragment
+fn fs_main_linear_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
+    // We always have an sRGB aware texture at the moment.
+    let tex_linear = textureSample(r_textures[r_push.texture_index], r_samplers[r_push.sampler_index], in.tex_coord);
+    let tex_gamma = gamma_from_linear_rgba(tex_linear);
+    let out_color_gamma = in.color * tex_gamma;
+    return vec4<f32>(linear_from_gamma_rgb(out_color_gamma.rgb), out_color_gamma.a);
+}
+
+@fragment
+fn fs_main_gamma_framebuffer(in: VertexOutput) -> @location(0) vec4<f32> {
+    // We always have an sRGB aware texture at the moment.
+    let tex_linear = textureSample(r_textures[r_push.texture_index], r_samplers[r_push.sampler_index], in.tex_coord);
+    let tex_gamma = gamma_from_linear_rgba(tex_linear);
+    let out_color_gamma = in.color * tex_gamma;
+    return out_color_gamma;
+}
\ No newline at end of file
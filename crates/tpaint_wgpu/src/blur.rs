@@ -0,0 +1,339 @@
+//! ``backdrop-blur-*``: blurs what's already painted under a node before the node itself is painted on top
+
+use std::{borrow::Cow, num::NonZeroU64};
+
+use tpaint::{epaint, BackdropBlur};
+use wgpu::util::DeviceExt as _;
+
+/// Taps on each side of the center, bigger blurs skip pixels instead
+const MAX_TAPS: i32 = 32;
+
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct Params {
+    region_origin: [i32; 2],
+    region_size: [i32; 2],
+    direction: [i32; 2],
+    taps: i32,
+    step: f32,
+    rect_min: [f32; 2],
+    rect_max: [f32; 2],
+    sigma: f32,
+    rounding: f32,
+    _padding: [f32; 2],
+}
+
+struct Pipelines {
+    format: wgpu::TextureFormat,
+    blur: wgpu::RenderPipeline,
+    composite: wgpu::RenderPipeline,
+}
+
+/// Scratch textures the size of the target, the backdrop is copied into the first and ping-pongs between the two
+struct Scratch {
+    size: [u32; 2],
+    format: wgpu::TextureFormat,
+    views: [wgpu::TextureView; 2],
+    textures: [wgpu::Texture; 2],
+}
+
+pub(crate) struct BackdropBlurRenderer {
+    module: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: Option<Pipelines>,
+    scratch: Option<Scratch>,
+}
+
+impl BackdropBlurRenderer {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("tpaint_backdrop_blur"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("blur.wgsl"))),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("tpaint_backdrop_blur_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: NonZeroU64::new(std::mem::size_of::<Params>() as _),
+                        ty: wgpu::BufferBindingType::Uniform,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("tpaint_backdrop_blur_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            module,
+            bind_group_layout,
+            pipeline_layout,
+            pipelines: None,
+            scratch: None,
+        }
+    }
+
+    fn create_pipeline(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        entry_point: &str,
+        blend: Option<wgpu::BlendState>,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(entry_point),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &self.module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &self.module,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        })
+    }
+
+    fn prepare(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat, size: [u32; 2]) {
+        if self.pipelines.as_ref().map(|pipelines| pipelines.format) != Some(format) {
+            self.pipelines = Some(Pipelines {
+                format,
+                blur: self.create_pipeline(device, format, "fs_blur", None),
+                composite: self.create_pipeline(
+                    device,
+                    format,
+                    "fs_composite",
+                    Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                ),
+            });
+        }
+
+        let is_scratch_valid = self
+            .scratch
+            .as_ref()
+            .map(|scratch| scratch.size == size && scratch.format == format)
+            .unwrap_or(false);
+        if !is_scratch_valid {
+            let textures = [0, 1].map(|i| {
+                device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(&format!("tpaint_backdrop_blur_scratch_{i}")),
+                    size: wgpu::Extent3d {
+                        width: size[0],
+                        height: size[1],
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::COPY_DST
+                        | wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+            });
+            let views = [0, 1].map(|i| textures[i].create_view(&wgpu::TextureViewDescriptor::default()));
+            self.scratch = Some(Scratch {
+                size,
+                format,
+                views,
+                textures,
+            });
+        }
+    }
+
+    fn bind_group(&self, device: &wgpu::Device, params: Params, source: &wgpu::TextureView) -> wgpu::BindGroup {
+        // every pass gets its own buffer, writes through the queue would all land before the encoder runs
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tpaint_backdrop_blur_params"),
+            contents: bytemuck::cast_slice(&[params]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("tpaint_backdrop_blur_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+            ],
+        })
+    }
+
+    /// Records the copy, the two blur passes and the composite into ``view``, which has to show ``target``
+    #[allow(clippy::too_many_arguments)]
+    pub fn blur(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        view: &wgpu::TextureView,
+        blur: &BackdropBlur,
+        rect: epaint::Rect,
+        clip_rect: epaint::Rect,
+        pixels_per_point: f32,
+    ) {
+        let size = [target.width(), target.height()];
+        let sigma = blur.radius * pixels_per_point;
+        if sigma <= 0.0 || size[0] == 0 || size[1] == 0 {
+            return;
+        }
+
+        let rect = epaint::Rect::from_min_max(
+            (rect.min * pixels_per_point).round(),
+            (rect.max * pixels_per_point).round(),
+        );
+        let screen = epaint::Rect::from_min_size(
+            epaint::Pos2::ZERO,
+            epaint::vec2(size[0] as f32, size[1] as f32),
+        );
+        let visible = epaint::Rect::from_min_max(
+            (clip_rect.min * pixels_per_point).round(),
+            (clip_rect.max * pixels_per_point).round(),
+        )
+        .intersect(rect)
+        .intersect(screen);
+        if !visible.is_positive() {
+            return;
+        }
+        // the gaussian reaches three sigmas out, pixels that far away still bleed into the node
+        let region = rect.expand(sigma * 3.0).intersect(screen);
+        let region_origin = [region.min.x as u32, region.min.y as u32];
+        let region_size = [
+            (region.max.x as u32 - region_origin[0]).max(1),
+            (region.max.y as u32 - region_origin[1]).max(1),
+        ];
+
+        self.prepare(device, target.format(), size);
+        let pipelines = self.pipelines.as_ref().unwrap();
+        let scratch = self.scratch.as_ref().unwrap();
+
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: target,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: region_origin[0],
+                    y: region_origin[1],
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: &scratch.textures[0],
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: region_size[0],
+                height: region_size[1],
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let taps = ((sigma * 3.0).ceil() as i32).clamp(1, MAX_TAPS);
+        let params = Params {
+            region_origin: [region_origin[0] as i32, region_origin[1] as i32],
+            region_size: [region_size[0] as i32, region_size[1] as i32],
+            direction: [1, 0],
+            taps,
+            step: (sigma * 3.0 / taps as f32).max(1.0),
+            rect_min: rect.min.into(),
+            rect_max: rect.max.into(),
+            sigma,
+            rounding: blur.rounding.nw.max(blur.rounding.ne).max(blur.rounding.sw).max(blur.rounding.se)
+                * pixels_per_point,
+            _padding: [0.0; 2],
+        };
+
+        // horizontal from the copy into the second texture, vertical back into the first
+        for (direction, source, destination) in [([1, 0], 0, 1), ([0, 1], 1, 0)] {
+            let bind_group = self.bind_group(
+                device,
+                Params { direction, ..params },
+                &scratch.views[source],
+            );
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("tpaint_backdrop_blur_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &scratch.views[destination],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&pipelines.blur);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.set_viewport(0.0, 0.0, region_size[0] as f32, region_size[1] as f32, 0.0, 1.0);
+            pass.set_scissor_rect(0, 0, region_size[0], region_size[1]);
+            pass.draw(0..3, 0..1);
+        }
+
+        let bind_group = self.bind_group(device, params, &scratch.views[0]);
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("tpaint_backdrop_composite_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&pipelines.composite);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.set_viewport(0.0, 0.0, size[0] as f32, size[1] as f32, 0.0, 1.0);
+        pass.set_scissor_rect(
+            visible.min.x as u32,
+            visible.min.y as u32,
+            (visible.width() as u32).max(1),
+            (visible.height() as u32).max(1),
+        );
+        pass.draw(0..3, 0..1);
+    }
+}
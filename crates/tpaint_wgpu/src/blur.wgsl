@@ -0,0 +1,66 @@
+// Backdrop blur: the region under a node is copied out of the target, blurred horizontally and vertically,
+// then composited back inside the (rounded) rect of the node.
+
+struct Params {
+    // where the copied region starts in the target, in pixels
+    region_origin: vec2<i32>,
+    region_size: vec2<i32>,
+    // (1, 0) for the horizontal pass, (0, 1) for the vertical one
+    direction: vec2<i32>,
+    taps: i32,
+    step: f32,
+    // the node in target pixels
+    rect_min: vec2<f32>,
+    rect_max: vec2<f32>,
+    sigma: f32,
+    rounding: f32,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var source: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // a single triangle covering the whole viewport
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_blur(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let center = vec2<i32>(position.xy);
+    var sum = vec4<f32>(0.0);
+    var weights = 0.0;
+    for (var i = -params.taps; i <= params.taps; i++) {
+        let offset = f32(i) * params.step;
+        let weight = exp(-(offset * offset) / (2.0 * params.sigma * params.sigma));
+        let coords = clamp(
+            center + params.direction * i32(round(offset)),
+            vec2<i32>(0),
+            params.region_size - vec2<i32>(1),
+        );
+        sum += textureLoad(source, coords, 0) * weight;
+        weights += weight;
+    }
+    return sum / weights;
+}
+
+@fragment
+fn fs_composite(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let coords = clamp(
+        vec2<i32>(position.xy) - params.region_origin,
+        vec2<i32>(0),
+        params.region_size - vec2<i32>(1),
+    );
+    let color = textureLoad(source, coords, 0);
+
+    // signed distance to the rounded rect, for anti-aliased corners
+    let half_size = (params.rect_max - params.rect_min) * 0.5;
+    let p = abs(position.xy - (params.rect_min + half_size)) - half_size + params.rounding;
+    let distance = length(max(p, vec2<f32>(0.0))) + min(max(p.x, p.y), 0.0) - params.rounding;
+    let mask = clamp(0.5 - distance, 0.0, 1.0);
+
+    // premultiplied, blended with One / OneMinusSrcAlpha
+    return color * mask;
+}
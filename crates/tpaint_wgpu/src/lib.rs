@@ -1,2 +1,7 @@
+#[cfg(feature = "backdrop-blur")]
+mod blur;
 mod renderer;
-pub use renderer::*;
\ No newline at end of file
+mod surface;
+pub use renderer::*;
+pub use surface::*;
+pub use tpaint_backend::RendererError;
@@ -1,8 +1,13 @@
 use std::collections::HashMap;
-use std::{borrow::Cow, num::NonZeroU64, ops::Range};
+use std::{
+    borrow::Cow,
+    num::{NonZeroU32, NonZeroU64},
+    ops::Range,
+};
 
 use tpaint::epaint;
 use tpaint::epaint::{emath::NumExt, PaintCallbackInfo, Primitive, Vertex};
+use tpaint::BackdropBlur;
 
 use wgpu::util::DeviceExt as _;
 
@@ -140,6 +145,259 @@ impl PartialEq for UniformBuffer {
     }
 }
 
+/// Features the device needs for [`Renderer`] to bind every texture at once and pick one per draw with push
+/// constants, like the beuk backend does. Without them each texture gets a bind group of its own
+pub const BINDLESS_FEATURES: wgpu::Features =
+    wgpu::Features::TEXTURE_BINDING_ARRAY.union(wgpu::Features::PUSH_CONSTANTS);
+
+/// Textures in the shared bind group at most, textures past it get a bind group of their own
+const MAX_BINDLESS_TEXTURES: u32 = 1024;
+
+/// A sampler per combination of magnification and minification filter
+const BINDLESS_SAMPLERS: u32 = 4;
+
+/// The features and limits to request from ``adapter`` so the renderer binds every texture at once, only the
+/// defaults when the adapter can't
+pub fn required_features_and_limits(adapter: &wgpu::Adapter) -> (wgpu::Features, wgpu::Limits) {
+    let limits = adapter.limits();
+    if !adapter.features().contains(BINDLESS_FEATURES) {
+        return (wgpu::Features::empty(), wgpu::Limits::default());
+    }
+    (
+        BINDLESS_FEATURES,
+        wgpu::Limits {
+            max_push_constant_size: std::mem::size_of::<PushConstants>() as u32,
+            max_sampled_textures_per_shader_stage: limits
+                .max_sampled_textures_per_shader_stage
+                .min(MAX_BINDLESS_TEXTURES),
+            ..wgpu::Limits::default()
+        },
+    )
+}
+
+/// Which texture and sampler of the shared bind group a draw uses
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct PushConstants {
+    texture_index: u32,
+    sampler_index: u32,
+}
+
+/// What the render pass has bound, so draws with the same texture don't bind it again
+#[derive(Clone, Copy, PartialEq)]
+enum Binding {
+    Texture(epaint::TextureId),
+    Bindless,
+}
+
+/// Where the pipelines render to
+struct OutputTarget {
+    color_format: wgpu::TextureFormat,
+    depth_format: Option<wgpu::TextureFormat>,
+    msaa_samples: u32,
+}
+
+/// Every managed texture in one bind group, so switching textures between draws only changes the push constants
+struct Bindless {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    samplers: Vec<wgpu::Sampler>,
+    /// Fills the slots without a texture, the bind group needs a view for every one of them
+    placeholder: wgpu::TextureView,
+    /// The index is what the shader gets
+    slots: Vec<Option<wgpu::TextureView>>,
+    slot_of: HashMap<epaint::TextureId, PushConstants>,
+    /// ``None`` when a texture was added or freed since it was created
+    bind_group: Option<wgpu::BindGroup>,
+    capacity: u32,
+}
+
+impl Bindless {
+    /// ``None`` when the device lacks [`BINDLESS_FEATURES`] or the limits for it
+    fn new(
+        device: &wgpu::Device,
+        uniform_bind_group_layout: &wgpu::BindGroupLayout,
+        target: &OutputTarget,
+    ) -> Option<Self> {
+        let limits = device.limits();
+        let capacity = limits
+            .max_sampled_textures_per_shader_stage
+            .min(MAX_BINDLESS_TEXTURES);
+        if !device.features().contains(BINDLESS_FEATURES)
+            || limits.max_push_constant_size < std::mem::size_of::<PushConstants>() as u32
+            || limits.max_samplers_per_shader_stage < BINDLESS_SAMPLERS
+            || capacity == 0
+        {
+            return None;
+        }
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("tpaint_bindless"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                include_str!("egui.wgsl"),
+                include_str!("bindless.wgsl")
+            ))),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("tpaint_bindless_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: NonZeroU32::new(capacity),
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: NonZeroU32::new(BINDLESS_SAMPLERS),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("tpaint_bindless_pipeline_layout"),
+            bind_group_layouts: &[uniform_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::FRAGMENT,
+                range: 0..std::mem::size_of::<PushConstants>() as u32,
+            }],
+        });
+
+        // ordered like ``sampler_index``
+        let filters = [wgpu::FilterMode::Nearest, wgpu::FilterMode::Linear];
+        let samplers = filters
+            .iter()
+            .flat_map(|&mag_filter| {
+                filters.iter().map(move |&min_filter| {
+                    device.create_sampler(&wgpu::SamplerDescriptor {
+                        label: Some(&format!(
+                            "tpaint bindless sampler (mag: {mag_filter:?}, min {min_filter:?})"
+                        )),
+                        mag_filter,
+                        min_filter,
+                        ..Default::default()
+                    })
+                })
+            })
+            .collect();
+
+        let placeholder = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("tpaint_bindless_placeholder"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        Some(Self {
+            pipeline: create_pipeline(device, "tpaint_bindless_pipeline", &module, &pipeline_layout, target),
+            bind_group_layout,
+            samplers,
+            placeholder,
+            slots: Vec::new(),
+            slot_of: HashMap::default(),
+            bind_group: None,
+            capacity,
+        })
+    }
+
+    /// Puts the texture in a free slot, or replaces the view when it already has one.
+    /// Returns false when every slot is taken
+    fn insert(
+        &mut self,
+        id: epaint::TextureId,
+        view: wgpu::TextureView,
+        options: epaint::textures::TextureOptions,
+    ) -> bool {
+        let texture_index = match self.slot_of.get(&id) {
+            Some(existing) => existing.texture_index as usize,
+            None => match self.slots.iter().position(Option::is_none) {
+                Some(free) => free,
+                None if (self.slots.len() as u32) < self.capacity => {
+                    self.slots.push(None);
+                    self.slots.len() - 1
+                }
+                None => return false,
+            },
+        };
+        self.slots[texture_index] = Some(view);
+        self.slot_of.insert(
+            id,
+            PushConstants {
+                texture_index: texture_index as u32,
+                sampler_index: sampler_index(options),
+            },
+        );
+        self.bind_group = None;
+        true
+    }
+
+    fn remove(&mut self, id: &epaint::TextureId) {
+        if let Some(slot) = self.slot_of.remove(id) {
+            self.slots[slot.texture_index as usize] = None;
+            self.bind_group = None;
+        }
+    }
+
+    /// Recreates the bind group when textures were added or freed
+    fn prepare(&mut self, device: &wgpu::Device) {
+        if self.bind_group.is_some() || self.slot_of.is_empty() {
+            return;
+        }
+        let views: Vec<&wgpu::TextureView> = self
+            .slots
+            .iter()
+            .map(|slot| slot.as_ref().unwrap_or(&self.placeholder))
+            .chain(std::iter::repeat(&self.placeholder))
+            .take(self.capacity as usize)
+            .collect();
+        let samplers: Vec<&wgpu::Sampler> = self.samplers.iter().collect();
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("tpaint_bindless_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureViewArray(&views),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::SamplerArray(&samplers),
+                },
+            ],
+        }));
+    }
+
+    /// The bind group and push constants for a texture in it
+    fn binding(&self, id: &epaint::TextureId) -> Option<(&wgpu::BindGroup, PushConstants)> {
+        Some((self.bind_group.as_ref()?, *self.slot_of.get(id)?))
+    }
+}
+
+fn sampler_index(options: epaint::textures::TextureOptions) -> u32 {
+    let index = |filter| match filter {
+        epaint::textures::TextureFilter::Nearest => 0,
+        epaint::textures::TextureFilter::Linear => 1,
+    };
+    index(options.magnification) * 2 + index(options.minification)
+}
+
 struct SlicedBuffer {
     buffer: wgpu::Buffer,
     slices: Vec<Range<usize>>,
@@ -157,6 +415,8 @@ pub struct Renderer {
     previous_uniform_buffer_content: UniformBuffer,
     uniform_bind_group: wgpu::BindGroup,
     texture_bind_group_layout: wgpu::BindGroupLayout,
+    /// ``None`` when the device lacks [`BINDLESS_FEATURES`], every texture is bound on its own then
+    bindless: Option<Bindless>,
 
     /// Map of egui texture IDs to textures and their associated bindgroups (texture view +
     /// sampler). The texture may be None if the TextureId is just a handle to a user-provided
@@ -169,6 +429,9 @@ pub struct Renderer {
     ///
     /// See also [`CallbackTrait`].
     pub callback_resources: CallbackResources,
+
+    #[cfg(feature = "backdrop-blur")]
+    backdrop_blur: crate::blur::BackdropBlurRenderer,
 }
 
 impl Renderer {
@@ -184,7 +447,10 @@ impl Renderer {
     ) -> Self {
         let shader = wgpu::ShaderModuleDescriptor {
             label: Some("egui"),
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("egui.wgsl"))),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                include_str!("egui.wgsl"),
+                include_str!("texture.wgsl")
+            ))),
         };
         let module = device.create_shader_module(shader);
 
@@ -254,72 +520,16 @@ impl Renderer {
             push_constant_ranges: &[],
         });
 
-        let depth_stencil = output_depth_format.map(|format| wgpu::DepthStencilState {
-            format,
-            depth_write_enabled: false,
-            depth_compare: wgpu::CompareFunction::Always,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-        });
-
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("egui_pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                entry_point: "vs_main",
-                module: &module,
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: 5 * 4,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    // 0: vec2 position
-                    // 1: vec2 texture coordinates
-                    // 2: uint color
-                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Uint32],
-                }],
-            },
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                unclipped_depth: false,
-                conservative: false,
-                cull_mode: None,
-                front_face: wgpu::FrontFace::default(),
-                polygon_mode: wgpu::PolygonMode::default(),
-                strip_index_format: None,
-            },
-            depth_stencil,
-            multisample: wgpu::MultisampleState {
-                alpha_to_coverage_enabled: false,
-                count: msaa_samples,
-                mask: !0,
-            },
-
-            fragment: Some(wgpu::FragmentState {
-                module: &module,
-                entry_point: if output_color_format.is_srgb() {
-                    log::warn!("Detected a linear (sRGBA aware) framebuffer {:?}. egui prefers Rgba8Unorm or Bgra8Unorm", output_color_format);
-                    "fs_main_linear_framebuffer"
-                } else {
-                    "fs_main_gamma_framebuffer" // this is what we prefer
-                },
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: output_color_format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::One,
-                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                        alpha: wgpu::BlendComponent {
-                            src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
-                            dst_factor: wgpu::BlendFactor::One,
-                            operation: wgpu::BlendOperation::Add,
-                        },
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-        });
+        if output_color_format.is_srgb() {
+            log::warn!("Detected a linear (sRGBA aware) framebuffer {:?}. egui prefers Rgba8Unorm or Bgra8Unorm", output_color_format);
+        }
+        let target = OutputTarget {
+            color_format: output_color_format,
+            depth_format: output_depth_format,
+            msaa_samples,
+        };
+        let pipeline = create_pipeline(device, "egui_pipeline", &module, &pipeline_layout, &target);
+        let bindless = Bindless::new(device, &uniform_bind_group_layout, &target);
 
         const VERTEX_BUFFER_START_CAPACITY: wgpu::BufferAddress =
             (std::mem::size_of::<Vertex>() * 1024) as _;
@@ -346,19 +556,34 @@ impl Renderer {
             },
             uniform_bind_group,
             texture_bind_group_layout,
+            bindless,
             textures: HashMap::default(),
             next_user_texture_id: 0,
             samplers: HashMap::default(),
             callback_resources: CallbackResources::default(),
+            #[cfg(feature = "backdrop-blur")]
+            backdrop_blur: crate::blur::BackdropBlurRenderer::new(device),
         }
     }
 
     /// Executes the egui renderer onto an existing wgpu renderpass.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Renderer::render"))]
     pub fn render<'rp>(
         &'rp self,
         render_pass: &mut wgpu::RenderPass<'rp>,
         paint_jobs: &'rp [epaint::ClippedPrimitive],
         screen_descriptor: &ScreenDescriptor,
+    ) {
+        self.render_from(render_pass, paint_jobs, 0, screen_descriptor);
+    }
+
+    /// Renders a part of the paint jobs given to [`Renderer::update_buffers`], ``first_mesh`` is the number of meshes before it
+    fn render_from<'rp>(
+        &'rp self,
+        render_pass: &mut wgpu::RenderPass<'rp>,
+        paint_jobs: &'rp [epaint::ClippedPrimitive],
+        first_mesh: usize,
+        screen_descriptor: &ScreenDescriptor,
     ) {
         let pixels_per_point = screen_descriptor.pixels_per_point;
         let size_in_pixels = screen_descriptor.size_in_pixels;
@@ -366,9 +591,11 @@ impl Renderer {
         // Whether or not we need to reset the render pass because a paint callback has just
         // run.
         let mut needs_reset = true;
+        // the pipeline and bind groups are set by the first draw after a reset
+        let mut bound = None;
 
-        let mut index_buffer_slices = self.index_buffer.slices.iter();
-        let mut vertex_buffer_slices = self.vertex_buffer.slices.iter();
+        let mut index_buffer_slices = self.index_buffer.slices[first_mesh..].iter();
+        let mut vertex_buffer_slices = self.vertex_buffer.slices[first_mesh..].iter();
 
         for epaint::ClippedPrimitive {
             clip_rect,
@@ -384,8 +611,7 @@ impl Renderer {
                     0.0,
                     1.0,
                 );
-                render_pass.set_pipeline(&self.pipeline);
-                render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                bound = None;
                 needs_reset = false;
             }
 
@@ -410,24 +636,51 @@ impl Renderer {
                     let index_buffer_slice = index_buffer_slices.next().unwrap();
                     let vertex_buffer_slice = vertex_buffer_slices.next().unwrap();
 
-                    if let Some((_texture, bind_group)) = self.textures.get(&mesh.texture_id) {
-                        render_pass.set_bind_group(1, bind_group, &[]);
-                        render_pass.set_index_buffer(
-                            self.index_buffer.buffer.slice(
-                                index_buffer_slice.start as u64..index_buffer_slice.end as u64,
-                            ),
-                            wgpu::IndexFormat::Uint32,
-                        );
-                        render_pass.set_vertex_buffer(
+                    let bindless = self
+                        .bindless
+                        .as_ref()
+                        .and_then(|bindless| Some((bindless, bindless.binding(&mesh.texture_id)?)));
+                    if let Some((bindless, (bind_group, push_constants))) = bindless {
+                        if bound != Some(Binding::Bindless) {
+                            render_pass.set_pipeline(&bindless.pipeline);
+                            render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                            render_pass.set_bind_group(1, bind_group, &[]);
+                            bound = Some(Binding::Bindless);
+                        }
+                        render_pass.set_push_constants(
+                            wgpu::ShaderStages::FRAGMENT,
                             0,
-                            self.vertex_buffer.buffer.slice(
-                                vertex_buffer_slice.start as u64..vertex_buffer_slice.end as u64,
-                            ),
+                            bytemuck::bytes_of(&push_constants),
                         );
-                        render_pass.draw_indexed(0..mesh.indices.len() as u32, 0, 0..1);
+                    } else if let Some((_texture, bind_group)) = self.textures.get(&mesh.texture_id) {
+                        match bound {
+                            Some(Binding::Texture(id)) if id == mesh.texture_id => {}
+                            Some(Binding::Texture(_)) => render_pass.set_bind_group(1, bind_group, &[]),
+                            _ => {
+                                render_pass.set_pipeline(&self.pipeline);
+                                render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+                                render_pass.set_bind_group(1, bind_group, &[]);
+                            }
+                        }
+                        bound = Some(Binding::Texture(mesh.texture_id));
                     } else {
                         log::warn!("Missing texture: {:?}", mesh.texture_id);
+                        continue;
                     }
+
+                    render_pass.set_index_buffer(
+                        self.index_buffer.buffer.slice(
+                            index_buffer_slice.start as u64..index_buffer_slice.end as u64,
+                        ),
+                        wgpu::IndexFormat::Uint32,
+                    );
+                    render_pass.set_vertex_buffer(
+                        0,
+                        self.vertex_buffer.buffer.slice(
+                            vertex_buffer_slice.start as u64..vertex_buffer_slice.end as u64,
+                        ),
+                    );
+                    render_pass.draw_indexed(0..mesh.indices.len() as u32, 0, 0..1);
                 }
                 Primitive::Callback(callback) => {
                     let cbfn = if let Some(c) = callback.callback.downcast_ref::<Callback>() {
@@ -482,6 +735,7 @@ impl Renderer {
     }
 
     /// Should be called before `render()`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Renderer::update_texture"))]
     pub fn update_texture(
         &mut self,
         device: &wgpu::Device,
@@ -570,15 +824,14 @@ impl Renderer {
                 .samplers
                 .entry(image_delta.options)
                 .or_insert_with(|| create_sampler(image_delta.options, device));
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
             let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
                 label,
                 layout: &self.texture_bind_group_layout,
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
-                        resource: wgpu::BindingResource::TextureView(
-                            &texture.create_view(&wgpu::TextureViewDescriptor::default()),
-                        ),
+                        resource: wgpu::BindingResource::TextureView(&view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
@@ -589,11 +842,24 @@ impl Renderer {
             let origin = wgpu::Origin3d::ZERO;
             queue_write_data_to_texture(&texture, origin);
             self.textures.insert(id, (Some(texture), bind_group));
+            if let Some(bindless) = &mut self.bindless {
+                if !bindless.insert(id, view, image_delta.options) {
+                    log::debug!("Every bindless slot is taken, {id:?} gets a bind group of its own");
+                }
+            }
         };
     }
 
     pub fn free_texture(&mut self, id: &epaint::TextureId) {
         self.textures.remove(id);
+        if let Some(bindless) = &mut self.bindless {
+            bindless.remove(id);
+        }
+    }
+
+    /// Whether managed textures share one bind group, see [`BINDLESS_FEATURES`]
+    pub fn is_bindless(&self) -> bool {
+        self.bindless.is_some()
     }
 
     /// Get the WGPU texture and bind group associated to a texture that has been allocated by egui.
@@ -742,6 +1008,7 @@ impl Renderer {
     /// Should be called before `render()`.
     ///
     /// Returns all user-defined command buffers gathered from [`CallbackTrait::prepare`] & [`CallbackTrait::finish_prepare`] callbacks.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, name = "Renderer::update_buffers"))]
     pub fn update_buffers(
         &mut self,
         device: &wgpu::Device,
@@ -752,6 +1019,10 @@ impl Renderer {
     ) -> Vec<wgpu::CommandBuffer> {
         let screen_size_in_points = screen_descriptor.screen_size_in_points();
 
+        if let Some(bindless) = &mut self.bindless {
+            bindless.prepare(device);
+        }
+
         let uniform_buffer_content = UniformBuffer {
             screen_size_in_points,
             _padding: Default::default(),
@@ -776,7 +1047,7 @@ impl Renderer {
                     Primitive::Callback(callback) => {
                         if let Some(c) = callback.callback.downcast_ref::<Callback>() {
                             callbacks.push(c.0.as_ref());
-                        } else {
+                        } else if !callback.callback.is::<BackdropBlur>() {
                             log::warn!("Unknown paint callback: expected `egui_wgpu::Callback`");
                         };
                         acc
@@ -877,6 +1148,81 @@ impl Renderer {
     }
 }
 
+/// The pipeline both texture bindings share, only the fragment shader and layout differ
+fn create_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    module: &wgpu::ShaderModule,
+    pipeline_layout: &wgpu::PipelineLayout,
+    target: &OutputTarget,
+) -> wgpu::RenderPipeline {
+    let depth_stencil = target.depth_format.map(|format| wgpu::DepthStencilState {
+        format,
+        depth_write_enabled: false,
+        depth_compare: wgpu::CompareFunction::Always,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(pipeline_layout),
+        vertex: wgpu::VertexState {
+            entry_point: "vs_main",
+            module,
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: 5 * 4,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                // 0: vec2 position
+                // 1: vec2 texture coordinates
+                // 2: uint color
+                attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Uint32],
+            }],
+        },
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            unclipped_depth: false,
+            conservative: false,
+            cull_mode: None,
+            front_face: wgpu::FrontFace::default(),
+            polygon_mode: wgpu::PolygonMode::default(),
+            strip_index_format: None,
+        },
+        depth_stencil,
+        multisample: wgpu::MultisampleState {
+            alpha_to_coverage_enabled: false,
+            count: target.msaa_samples,
+            mask: !0,
+        },
+
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: if target.color_format.is_srgb() {
+                "fs_main_linear_framebuffer"
+            } else {
+                "fs_main_gamma_framebuffer" // this is what we prefer
+            },
+            targets: &[Some(wgpu::ColorTargetState {
+                format: target.color_format,
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview: None,
+    })
+}
+
 fn create_sampler(
     options: epaint::textures::TextureOptions,
     device: &wgpu::Device,
@@ -962,3 +1308,137 @@ fn renderer_impl_send_sync() {
     fn assert_send_sync<T: Send + Sync>() {}
     assert_send_sync::<Renderer>();
 }
+
+/// What [`Renderer`] paints into through [`tpaint_backend::TpaintRenderer`]
+pub struct WgpuFrame<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub view: &'a wgpu::TextureView,
+    /// The view is loaded as-is when ``None``
+    pub clear_color: Option<wgpu::Color>,
+    /// The texture behind ``view``, ``backdrop-blur-*`` copies out of it so it needs ``TextureUsages::COPY_SRC``.
+    /// Without it, or without the ``backdrop-blur`` feature, nodes only get their translucent background.
+    pub backdrop: Option<&'a wgpu::Texture>,
+}
+
+fn begin_render_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    view: &'a wgpu::TextureView,
+    load: wgpu::LoadOp<wgpu::Color>,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("tpaint_render_pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    })
+}
+
+impl Renderer {
+    /// Paints up to every backdrop blur, blurs what's painted so far under it and carries on in a new render pass
+    #[cfg(feature = "backdrop-blur")]
+    fn paint_with_backdrop_blurs(
+        &mut self,
+        frame: &mut WgpuFrame<'_>,
+        target: &wgpu::Texture,
+        primitives: &[epaint::ClippedPrimitive],
+        screen: &ScreenDescriptor,
+    ) {
+        let mut load = frame
+            .clear_color
+            .map(wgpu::LoadOp::Clear)
+            .unwrap_or(wgpu::LoadOp::Load);
+        let mut start = 0;
+        let mut first_mesh = 0;
+        loop {
+            let end = primitives[start..]
+                .iter()
+                .position(|primitive| match &primitive.primitive {
+                    Primitive::Callback(callback) => callback.callback.is::<BackdropBlur>(),
+                    Primitive::Mesh(_) => false,
+                })
+                .map_or(primitives.len(), |i| start + i);
+
+            {
+                let mut render_pass = begin_render_pass(frame.encoder, frame.view, load);
+                self.render_from(&mut render_pass, &primitives[start..end], first_mesh, screen);
+            }
+            load = wgpu::LoadOp::Load;
+            first_mesh += primitives[start..end]
+                .iter()
+                .filter(|primitive| matches!(primitive.primitive, Primitive::Mesh(_)))
+                .count();
+
+            let Some(primitive) = primitives.get(end) else {
+                break;
+            };
+            let Primitive::Callback(callback) = &primitive.primitive else {
+                unreachable!();
+            };
+            self.backdrop_blur.blur(
+                frame.device,
+                frame.encoder,
+                target,
+                frame.view,
+                callback.callback.downcast_ref::<BackdropBlur>().unwrap(),
+                callback.rect,
+                primitive.clip_rect,
+                screen.pixels_per_point,
+            );
+            start = end + 1;
+        }
+    }
+}
+
+impl tpaint_backend::TpaintRenderer for Renderer {
+    type Frame<'a> = WgpuFrame<'a>;
+
+    fn set_texture(
+        &mut self,
+        frame: &mut WgpuFrame<'_>,
+        id: epaint::TextureId,
+        delta: &epaint::ImageDelta,
+    ) {
+        self.update_texture(frame.device, frame.queue, id, delta);
+    }
+
+    fn free_texture(&mut self, id: epaint::TextureId) {
+        Renderer::free_texture(self, &id);
+    }
+
+    fn paint(
+        &mut self,
+        frame: &mut WgpuFrame<'_>,
+        primitives: &[epaint::ClippedPrimitive],
+        screen: &tpaint::ScreenDescriptor,
+    ) {
+        let screen = ScreenDescriptor {
+            size_in_pixels: screen.size.into(),
+            pixels_per_point: screen.pixels_per_point,
+        };
+        // the only paint callbacks tpaint emits are backdrop blurs, so there are no command buffers to submit from them
+        self.update_buffers(frame.device, frame.queue, frame.encoder, primitives, &screen);
+
+        #[cfg(feature = "backdrop-blur")]
+        if let Some(target) = frame.backdrop {
+            self.paint_with_backdrop_blurs(frame, target, primitives, &screen);
+            return;
+        }
+
+        let load = match frame.clear_color {
+            Some(color) => wgpu::LoadOp::Clear(color),
+            None => wgpu::LoadOp::Load,
+        };
+        let mut render_pass = begin_render_pass(frame.encoder, frame.view, load);
+        self.render(&mut render_pass, primitives, &screen);
+    }
+}
@@ -0,0 +1,56 @@
+use tpaint_backend::RendererError;
+
+/// Sets the size of ``config`` and reconfigures the surface with it. A size of 0, like a minimized window, only
+/// updates ``config`` since a surface can't be configured that small, [`acquire_frame`] skips frames until it grows
+pub fn resize_surface(
+    surface: &wgpu::Surface<'_>,
+    device: &wgpu::Device,
+    config: &mut wgpu::SurfaceConfiguration,
+    width: u32,
+    height: u32,
+) {
+    config.width = width;
+    config.height = height;
+    if width > 0 && height > 0 {
+        surface.configure(device, config);
+    }
+}
+
+/// The next texture of ``surface`` to paint into. Outdated, lost and suboptimal surfaces are reconfigured with
+/// ``config`` and acquired again, ``Ok(None)`` means the frame should be skipped, e.g. after a timeout or while the
+/// window is minimized. A surface that stays lost is an error, the surface has to be recreated
+pub fn acquire_frame(
+    surface: &wgpu::Surface<'_>,
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+) -> Result<Option<wgpu::SurfaceTexture>, RendererError> {
+    if config.width == 0 || config.height == 0 {
+        return Ok(None);
+    }
+
+    // a suboptimal texture is still usable, it's only taken when reconfiguring didn't help
+    let mut retried = false;
+    loop {
+        match surface.get_current_texture() {
+            Ok(frame) if frame.suboptimal && !retried => {
+                drop(frame);
+                surface.configure(device, config);
+            }
+            Ok(frame) => return Ok(Some(frame)),
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) if !retried => {
+                surface.configure(device, config);
+            }
+            Err(wgpu::SurfaceError::Outdated) => {
+                log::warn!("Surface is still outdated after reconfiguring it, skipping the frame");
+                return Ok(None);
+            }
+            // the window behind it is gone, a new surface has to be made for it
+            Err(wgpu::SurfaceError::Lost) => {
+                return Err(RendererError::Other("the surface is still lost after reconfiguring it".into()))
+            }
+            Err(wgpu::SurfaceError::Timeout) => return Ok(None),
+            Err(wgpu::SurfaceError::OutOfMemory) => return Err(RendererError::OutOfMemory),
+        }
+        retried = true;
+    }
+}
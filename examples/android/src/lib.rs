@@ -0,0 +1,192 @@
+//! Build with ``cargo apk run -p example_android``
+#![cfg(target_os = "android")]
+
+use std::sync::Arc;
+
+use tpaint::{
+    epaint::{
+        text::{FontData, FontDefinitions},
+        FontFamily,
+    },
+    DomEventLoop, PanicPolicy, RendererDescriptor,
+};
+use tpaint_wgpu::{Renderer, WgpuFrame};
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::EventLoopBuilder,
+    platform::android::{activity::AndroidApp, EventLoopBuilderExtAndroid},
+};
+
+mod app;
+
+type UserEvent = ();
+
+#[no_mangle]
+fn android_main(android_app: AndroidApp) {
+    android_logger::init_once(android_logger::Config::default().with_max_level(log::LevelFilter::Warn));
+
+    let event_loop = EventLoopBuilder::<UserEvent>::with_user_event()
+        .with_android_app(android_app.clone())
+        .build()
+        .unwrap();
+    let window = Arc::new(winit::window::WindowBuilder::new().build(&event_loop).unwrap());
+
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::LowPower,
+        compatible_surface: None,
+        force_fallback_adapter: false,
+    }))
+    .unwrap();
+
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            required_features: wgpu::Features::default(),
+            required_limits: wgpu::Limits::downlevel_defaults(),
+            label: None,
+        },
+        None,
+    ))
+    .unwrap();
+
+    let mut fonts = FontDefinitions::default();
+    fonts.font_data.insert(
+        "Inter-Regular".to_owned(),
+        FontData::from_static(include_bytes!("../../example_ui/assets/Inter-Regular.ttf")),
+    );
+    fonts
+        .families
+        .get_mut(&FontFamily::Proportional)
+        .unwrap()
+        .insert(0, "Inter-Regular".to_owned());
+
+    // the swapchain format is only known once there's a surface, which android hands out on resume
+    let swapchain_format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let mut renderer = Renderer::new(&device, swapchain_format, None, 1);
+    let mut app = DomEventLoop::spawn(
+        app::app,
+        window.clone(),
+        RendererDescriptor {
+            window_size: window.inner_size(),
+            pixels_per_point: window.scale_factor() as f32,
+            font_definitions: fonts,
+        },
+        event_loop.create_proxy(),
+        (),
+        (),
+        PanicPolicy::Restart,
+    );
+
+    let mut surface: Option<(wgpu::Surface<'static>, wgpu::SurfaceConfiguration)> = None;
+    let mut keyboard_shown = false;
+
+    event_loop
+        .run(move |event, target| {
+            let _ = (&instance, &adapter);
+
+            match event {
+                Event::Resumed => {
+                    let size = window.inner_size();
+                    let new_surface = instance.create_surface(window.clone()).unwrap();
+                    let config = wgpu::SurfaceConfiguration {
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                        format: swapchain_format,
+                        width: size.width,
+                        height: size.height,
+                        present_mode: wgpu::PresentMode::Fifo,
+                        alpha_mode: wgpu::CompositeAlphaMode::Auto,
+                        view_formats: vec![],
+                        desired_maximum_frame_latency: 2,
+                    };
+                    new_surface.configure(&device, &config);
+                    surface = Some((new_surface, config));
+                    app.on_resumed();
+                }
+
+                Event::Suspended => {
+                    surface = None;
+                    app.on_suspended();
+                }
+
+                Event::WindowEvent {
+                    event: ref window_event,
+                    ..
+                } => {
+                    match window_event {
+                        WindowEvent::Resized(size) => {
+                            if let Some((surface, config)) = &mut surface {
+                                tpaint_wgpu::resize_surface(surface, &device, config, size.width, size.height);
+                            }
+                            window.request_redraw();
+                        }
+
+                        WindowEvent::RedrawRequested => {
+                            let Some((surface, config)) = &surface else {
+                                return;
+                            };
+                            if app.is_suspended() {
+                                return;
+                            }
+
+                            target.set_control_flow(winit::event_loop::ControlFlow::Wait);
+                            let frame = match tpaint_wgpu::acquire_frame(surface, &device, config) {
+                                Ok(Some(frame)) => frame,
+                                Ok(None) => return,
+                                Err(err) => {
+                                    log::error!("Failed to acquire next swap chain texture, exiting: {err}");
+                                    target.exit();
+                                    return;
+                                }
+                            };
+                            let view = frame
+                                .texture
+                                .create_view(&wgpu::TextureViewDescriptor::default());
+                            let mut encoder = device
+                                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                            tpaint_backend::paint_dom(
+                                &mut renderer,
+                                &mut WgpuFrame {
+                                    device: &device,
+                                    queue: &queue,
+                                    encoder: &mut encoder,
+                                    view: &view,
+                                    clear_color: Some(wgpu::Color::TRANSPARENT),
+                                    backdrop: None,
+                                },
+                                &mut app,
+                            );
+
+                            queue.submit(Some(encoder.finish()));
+                            frame.present();
+                        }
+
+                        _ => {}
+                    }
+
+                    let repaint = app.on_window_event(window_event);
+
+                    // winit doesn't bring up the on-screen keyboard on android by itself
+                    let wants_keyboard = app.wants_text_input();
+                    if wants_keyboard != keyboard_shown {
+                        if wants_keyboard {
+                            android_app.show_soft_input(true);
+                        } else {
+                            android_app.hide_soft_input(true);
+                        }
+                        keyboard_shown = wants_keyboard;
+                    }
+
+                    if repaint {
+                        window.request_redraw();
+                    }
+                }
+
+                Event::UserEvent(_) => {
+                    window.request_redraw();
+                }
+                _ => {}
+            }
+        })
+        .unwrap();
+}
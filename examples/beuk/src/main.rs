@@ -45,7 +45,7 @@ fn main() {
     let mut renderer = Renderer::new(
         &ctx,
         swapchain.surface_format.format,
-        swapchain.depth_image_format,
+        Some(swapchain.depth_image_format),
     );
     drop(swapchain);
 
@@ -0,0 +1,64 @@
+//! The event loop every desktop example runs, only the surface a backend paints into differs between them
+
+use std::sync::Arc;
+
+use tpaint::DomEventLoop;
+use tpaint_backend::{RendererError, TpaintRenderer};
+use winit::{
+    dpi::PhysicalSize,
+    error::EventLoopError,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::Window,
+};
+
+/// What a backend presents into, e.g. a wgpu surface or a glutin window
+pub trait ExampleSurface<R: TpaintRenderer> {
+    fn resize(&mut self, size: PhysicalSize<u32>);
+
+    /// Acquires a frame, paints ``app`` into it with [`tpaint_backend::paint_dom`] and presents it
+    fn redraw(&mut self, renderer: &mut R, app: &mut DomEventLoop) -> Result<(), RendererError>;
+}
+
+/// Runs ``app`` until its window closes. A frame that can't be painted ends the example with the error logged
+pub fn run<R: TpaintRenderer + 'static, S: ExampleSurface<R> + 'static>(
+    event_loop: EventLoop<()>,
+    window: Arc<Window>,
+    mut renderer: R,
+    mut surface: S,
+    mut app: DomEventLoop,
+) -> Result<(), EventLoopError> {
+    event_loop.run(move |event, target| match event {
+        Event::WindowEvent {
+            event: ref window_event,
+            ..
+        } => {
+            match window_event {
+                WindowEvent::Resized(size) => {
+                    surface.resize(*size);
+                    window.request_redraw();
+                }
+                WindowEvent::CloseRequested => {
+                    if app.on_close_requested() {
+                        target.exit();
+                    }
+                }
+                WindowEvent::Destroyed => target.exit(),
+                WindowEvent::RedrawRequested => {
+                    target.set_control_flow(ControlFlow::Wait);
+                    if let Err(err) = surface.redraw(&mut renderer, &mut app) {
+                        log::error!("Failed to paint a frame, exiting: {err}");
+                        target.exit();
+                    }
+                }
+                _ => {}
+            }
+
+            if app.on_window_event(window_event) {
+                window.request_redraw();
+            }
+        }
+        Event::UserEvent(()) => window.request_redraw(),
+        _ => {}
+    })
+}
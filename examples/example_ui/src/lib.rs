@@ -1,3 +1,5 @@
+pub mod harness;
+
 use tpaint::{
     components::{
         image::Image,
@@ -10,10 +10,11 @@ use tpaint::{
         text::{FontData, FontDefinitions},
         FontFamily,
     },
-    DomEventLoop, RendererDescriptor,
+    DomEventLoop, PanicPolicy, RendererDescriptor,
 };
+use example_ui::harness::ExampleSurface;
+use tpaint_backend::RendererError;
 use tpaint_glow::painter::Painter;
-use winit::event::WindowEvent;
 
 #[cfg(feature = "hot-reload")]
 use tpaint::prelude::dioxus_hot_reload;
@@ -155,6 +156,33 @@ impl GlutinWindowContext {
     }
 }
 
+struct GlowSurface {
+    gl_window: GlutinWindowContext,
+    gl: Arc<glow::Context>,
+    clear_color: [f32; 3],
+}
+
+impl ExampleSurface<Painter> for GlowSurface {
+    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        self.gl_window.resize(size);
+    }
+
+    fn redraw(&mut self, painter: &mut Painter, app: &mut DomEventLoop) -> Result<(), RendererError> {
+        unsafe {
+            use glow::HasContext as _;
+            let [r, g, b] = self.clear_color;
+            self.gl.clear_color(r, g, b, 1.0);
+            self.gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+
+        tpaint_backend::paint_dom(painter, &mut (), app);
+
+        self.gl_window
+            .swap_buffers()
+            .map_err(|err| RendererError::Other(format!("swapping the buffers failed: {err}")))
+    }
+}
+
 fn main() {
     #[cfg(feature = "hot-reload")]
     dioxus_hot_reload::hot_reload_init!();
@@ -176,7 +204,7 @@ fn main() {
         .build()
         .unwrap();
     let (gl_window, gl) = create_display(&event_loop);
-    let gl = std::sync::Arc::new(gl);
+    let gl = Arc::new(gl);
 
     let mut fonts = FontDefinitions::default();
     // Install my own font (maybe supporting non-latin characters):
@@ -203,78 +231,24 @@ fn main() {
         event_loop.create_proxy(),
         (),
         (),
+        PanicPolicy::Restart,
     );
-    let mut painter = Painter::new(gl.clone(), "", None)
+    // the window was created hidden so it doesn't flash white before the UI is painted
+    let window = gl_window.window.clone();
+    app.on_first_frame_rendered(move || window.set_visible(true));
+    let painter = Painter::new(gl.clone(), "", None)
         .map_err(|err| {
             log::error!("error occurred in initializing painter:\n{err}");
         })
         .unwrap();
 
-    event_loop
-        .run(move |event, target| {
-            let mut redraw = || {
-                target.set_control_flow(winit::event_loop::ControlFlow::Wait);
-                unsafe {
-                    use glow::HasContext as _;
-                    gl.clear_color(clear_color[0], clear_color[1], clear_color[2], 1.0);
-                    gl.clear(glow::COLOR_BUFFER_BIT);
-                }
-
-                let (primitives, delta, screen_descriptor) = app.get_paint_info();
-
-                for (id, image_delta) in delta.set {
-                    painter.set_texture(id, &image_delta);
-                }
-
-                painter.paint_primitives(
-                    screen_descriptor.size.into(),
-                    screen_descriptor.pixels_per_point,
-                    &primitives,
-                );
-
-                for id in delta.free {
-                    painter.free_texture(id);
-                }
-
-                gl_window.swap_buffers().unwrap();
-                gl_window.window().set_visible(true);
-            };
-
-            match event {
-                // winit::event::Event::RedrawRequested if !cfg!(target_os = "windows") => redraw(),
-                winit::event::Event::WindowEvent {
-                    event: ref window_event,
-                    ..
-                } => {
-                    match window_event {
-                        WindowEvent::Resized(size) => {
-                            gl_window.resize(*size);
-                        }
-
-                        WindowEvent::CloseRequested | WindowEvent::Destroyed => {
-                            target.exit();
-                        }
-
-                        WindowEvent::RedrawRequested => {
-                            redraw();
-                        }
-
-                        _ => {}
-                    }
-
-                    let repaint = app.on_window_event(window_event);
-                    if repaint {
-                        gl_window.window().request_redraw();
-                    }
-                }
-
-                winit::event::Event::UserEvent(_) => {
-                    gl_window.window().request_redraw();
-                }
-                _ => {}
-            }
-        })
-        .unwrap();
+    let window = gl_window.window.clone();
+    let surface = GlowSurface {
+        gl_window,
+        gl,
+        clear_color,
+    };
+    example_ui::harness::run(event_loop, window, painter, surface, app).unwrap();
 }
 
 fn create_display(
@@ -0,0 +1,7 @@
+use tpaint::prelude::*;
+
+pub fn app(cx: Scope) -> Element {
+    render! {
+      example_ui::app {}
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(target_arch = "wasm32")]
+mod app;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    eprintln!("the web example only runs in the browser, build it with `trunk serve` from examples/web");
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    console_log::init_with_level(log::Level::Warn).unwrap();
+
+    wasm_bindgen_futures::spawn_local(web::run());
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::sync::Arc;
+
+    use tpaint::{
+        epaint::{
+            text::{FontData, FontDefinitions},
+            FontFamily,
+        },
+        DomEventLoop, PanicPolicy, RendererDescriptor,
+    };
+    use tpaint_wgpu::{Renderer, WgpuFrame};
+    use winit::{
+        event::WindowEvent,
+        platform::web::{EventLoopExtWebSys, WindowBuilderExtWebSys},
+    };
+
+    use crate::app;
+
+    type UserEvent = ();
+
+    pub async fn run() {
+        let event_loop = winit::event_loop::EventLoopBuilder::<UserEvent>::with_user_event()
+            .build()
+            .unwrap();
+
+        let browser_window = web_sys::window().unwrap();
+        let window = Arc::new(
+            winit::window::WindowBuilder::new()
+                .with_title("tpaint web example")
+                .with_inner_size(winit::dpi::LogicalSize {
+                    width: browser_window.inner_width().unwrap().as_f64().unwrap(),
+                    height: browser_window.inner_height().unwrap().as_f64().unwrap(),
+                })
+                .with_append(true)
+                .build(&event_loop)
+                .unwrap(),
+        );
+
+        // WebGPU isn't available in most browsers yet, so go through WebGL
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::GL,
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::default(),
+                    required_limits: wgpu::Limits::downlevel_webgl2_defaults()
+                        .using_resolution(adapter.limits()),
+                    label: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let swapchain_capabilities = surface.get_capabilities(&adapter);
+        let swapchain_format = swapchain_capabilities.formats[0];
+
+        let mut config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: swapchain_format,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: swapchain_capabilities.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        let mut fonts = FontDefinitions::default();
+        fonts.font_data.insert(
+            "Inter-Regular".to_owned(),
+            FontData::from_static(include_bytes!("../../example_ui/assets/Inter-Regular.ttf")),
+        );
+        fonts
+            .families
+            .get_mut(&FontFamily::Proportional)
+            .unwrap()
+            .insert(0, "Inter-Regular".to_owned());
+
+        let mut renderer = Renderer::new(&device, swapchain_format, None, 1);
+        let mut app = DomEventLoop::spawn(
+            app::app,
+            window.clone(),
+            RendererDescriptor {
+                window_size: window.inner_size(),
+                pixels_per_point: window.scale_factor() as f32,
+                font_definitions: fonts,
+            },
+            event_loop.create_proxy(),
+            (),
+            (),
+            PanicPolicy::Freeze,
+        );
+
+        event_loop.spawn(move |event, target| {
+            let _ = (&instance, &adapter);
+
+            let mut redraw = || {
+                target.set_control_flow(winit::event_loop::ControlFlow::Wait);
+                let frame = match tpaint_wgpu::acquire_frame(&surface, &device, &config) {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => return,
+                    Err(err) => {
+                        log::error!("Failed to acquire next swap chain texture, exiting: {err}");
+                        target.exit();
+                        return;
+                    }
+                };
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder =
+                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+                tpaint_backend::paint_dom(
+                    &mut renderer,
+                    &mut WgpuFrame {
+                        device: &device,
+                        queue: &queue,
+                        encoder: &mut encoder,
+                        view: &view,
+                        clear_color: Some(wgpu::Color::TRANSPARENT),
+                        backdrop: None,
+                    },
+                    &mut app,
+                );
+
+                queue.submit(Some(encoder.finish()));
+                frame.present();
+            };
+
+            match event {
+                winit::event::Event::WindowEvent {
+                    event: ref window_event,
+                    ..
+                } => {
+                    match window_event {
+                        WindowEvent::Resized(size) => {
+                            tpaint_wgpu::resize_surface(&surface, &device, &mut config, size.width, size.height);
+                            window.request_redraw();
+                        }
+
+                        WindowEvent::RedrawRequested => {
+                            redraw();
+                        }
+
+                        _ => {}
+                    }
+
+                    let repaint = app.on_window_event(window_event);
+                    if repaint {
+                        window.request_redraw();
+                    }
+                }
+
+                winit::event::Event::UserEvent(_) => {
+                    window.request_redraw();
+                }
+                _ => {}
+            }
+        });
+    }
+}
@@ -9,10 +9,11 @@ use tpaint::{
         text::{FontData, FontDefinitions},
         FontFamily,
     },
-    DomEventLoop, RendererDescriptor,
+    DomEventLoop, PanicPolicy, RendererDescriptor,
 };
-use tpaint_wgpu::{Renderer, ScreenDescriptor};
-use winit::event::WindowEvent;
+use example_ui::harness::ExampleSurface;
+use tpaint_backend::RendererError;
+use tpaint_wgpu::{Renderer, WgpuFrame};
 
 #[cfg(feature = "hot-reload")]
 use tpaint::prelude::dioxus_hot_reload;
@@ -21,6 +22,51 @@ mod app;
 
 type UserEvent = ();
 
+struct WgpuSurface {
+    surface: wgpu::Surface<'static>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    /// Backdrop blurs copy out of the swapchain texture
+    can_copy_from_frame: bool,
+    /// The surface and device are only valid while these live
+    _keep_alive: (wgpu::Instance, wgpu::Adapter),
+}
+
+impl ExampleSurface<Renderer> for WgpuSurface {
+    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        tpaint_wgpu::resize_surface(&self.surface, &self.device, &mut self.config, size.width, size.height);
+    }
+
+    fn redraw(&mut self, renderer: &mut Renderer, app: &mut DomEventLoop) -> Result<(), RendererError> {
+        let Some(frame) = tpaint_wgpu::acquire_frame(&self.surface, &self.device, &self.config)? else {
+            return Ok(());
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder =
+            self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        tpaint_backend::paint_dom(
+            renderer,
+            &mut WgpuFrame {
+                device: &self.device,
+                queue: &self.queue,
+                encoder: &mut encoder,
+                view: &view,
+                clear_color: Some(wgpu::Color::TRANSPARENT),
+                backdrop: self.can_copy_from_frame.then_some(&frame.texture),
+            },
+            app,
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+        frame.present();
+        Ok(())
+    }
+}
+
 fn main() {
     #[cfg(feature = "hot-reload")]
     dioxus_hot_reload::hot_reload_init!();
@@ -47,6 +93,8 @@ fn main() {
             .with_resizable(true)
             .with_transparent(false)
             .with_title("tpaint wgpu example")
+            // shown once the UI is painted, see ``on_first_frame_rendered``
+            .with_visible(false)
             .with_inner_size(winit::dpi::PhysicalSize {
                 width: 800,
                 height: 600,
@@ -65,10 +113,12 @@ fn main() {
     }))
     .unwrap();
 
+    // lets the renderer bind every texture at once where the adapter supports it
+    let (required_features, required_limits) = tpaint_wgpu::required_features_and_limits(&adapter);
     let (device, queue) = pollster::block_on(adapter.request_device(
         &wgpu::DeviceDescriptor {
-            required_features: wgpu::Features::default(),
-            required_limits: wgpu::Limits::default(),
+            required_features,
+            required_limits,
             label: None,
         },
         None,
@@ -80,8 +130,16 @@ fn main() {
     let swapchain_capabilities = surface.get_capabilities(&adapter);
     let swapchain_format = swapchain_capabilities.formats[0];
 
-    let mut config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+    // backdrop blurs copy out of the swapchain texture
+    let can_copy_from_frame = swapchain_capabilities
+        .usages
+        .contains(wgpu::TextureUsages::COPY_SRC);
+    let config = wgpu::SurfaceConfiguration {
+        usage: if can_copy_from_frame {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+        } else {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        },
         format: swapchain_format,
         width: size.width,
         height: size.height,
@@ -106,7 +164,7 @@ fn main() {
         .unwrap()
         .insert(0, "Inter-Regular".to_owned());
 
-    let mut renderer = Renderer::new(&device, swapchain_format, None, 1);
+    let renderer = Renderer::new(&device, swapchain_format, None, 1);
     let mut app = DomEventLoop::spawn(
         app::app,
         window.clone(),
@@ -118,100 +176,20 @@ fn main() {
         event_loop.create_proxy(),
         (),
         (),
+        PanicPolicy::Restart,
     );
-
-    event_loop
-        .run(move |event, target| {
-            // Have the closure take ownership of the resources.
-            // `event_loop.run` never returns, therefore we must do this to ensure
-            // the resources are properly cleaned up.
-            let _ = (&instance, &adapter);
-
-            let mut redraw = || {
-                target.set_control_flow(winit::event_loop::ControlFlow::Wait);
-                let frame = surface
-                    .get_current_texture()
-                    .expect("Failed to acquire next swap chain texture");
-                let view = frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default());
-                let mut encoder =
-                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-
-                let (primitives, delta, screen_descriptor) = app.get_paint_info();
-
-                for (id, texture) in delta.set {
-                    renderer.update_texture(&device, &queue, id, &texture);
-                }
-
-                for id in delta.free {
-                    renderer.free_texture(&id);
-                }
-
-                let screen = &ScreenDescriptor {
-                    size_in_pixels: screen_descriptor.size.into(),
-                    pixels_per_point: screen_descriptor.pixels_per_point,
-                };
-                renderer.update_buffers(&device, &queue, &mut encoder, &primitives, screen);
-                {
-                    let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        label: None,
-                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
-                            resolve_target: None,
-                            ops: wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                                store: wgpu::StoreOp::Store,
-                            },
-                        })],
-                        depth_stencil_attachment: None,
-                        occlusion_query_set: None,
-                        timestamp_writes: None,
-                    });
-
-                    renderer.render(&mut rpass, &primitives, screen)
-                }
-
-                queue.submit(Some(encoder.finish()));
-                frame.present();
-            };
-
-            match event {
-                // winit::event::Event::RedrawRequested if !cfg!(target_os = "windows") => redraw(),
-                winit::event::Event::WindowEvent {
-                    event: ref window_event,
-                    ..
-                } => {
-                    match window_event {
-                        WindowEvent::Resized(size) => {
-                            config.width = size.width;
-                            config.height = size.height;
-                            surface.configure(&device, &config);
-                            window.request_redraw();
-                        }
-
-                        WindowEvent::CloseRequested | WindowEvent::Destroyed => {
-                            target.exit();
-                        }
-
-                        WindowEvent::RedrawRequested => {
-                            redraw();
-                        }
-
-                        _ => {}
-                    }
-
-                    let repaint = app.on_window_event(window_event);
-                    if repaint {
-                        window.request_redraw();
-                    }
-                }
-
-                winit::event::Event::UserEvent(_) => {
-                    window.request_redraw();
-                }
-                _ => {}
-            }
-        })
-        .unwrap();
+    app.on_first_frame_rendered({
+        let window = window.clone();
+        move || window.set_visible(true)
+    });
+
+    let surface = WgpuSurface {
+        surface,
+        device,
+        queue,
+        config,
+        can_copy_from_frame,
+        _keep_alive: (instance, adapter),
+    };
+    example_ui::harness::run(event_loop, window, renderer, surface, app).unwrap();
 }